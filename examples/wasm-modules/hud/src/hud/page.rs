@@ -73,6 +73,7 @@ impl HudPage {
                 pipe.cur_time,
                 &mut client_ui::hud::user_data::UserData {
                     race_round_timer_counter: &456156,
+                    race_split: None,
                     ticks_per_second: &50.try_into().unwrap(),
                     /*game: Some(&GameRenderInfo::Match {
                         standings: MatchStandings::Solo {
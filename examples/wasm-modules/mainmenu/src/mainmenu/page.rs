@@ -56,7 +56,7 @@ use super::{create_theme_container, profiles::Profiles};
 struct MenuImpl {}
 
 impl MainMenuInterface for MenuImpl {
-    fn refresh(&mut self) {}
+    fn refresh(&mut self, _master_servers: &[String]) {}
 
     fn refresh_demo_list(&mut self, _path: &Path) {}
     fn refresh_demo_info(&mut self, _file: Option<&Path>) {}
@@ -195,7 +195,7 @@ impl MainMenu {
             location: "default".try_into().unwrap(),
         });
 
-        let servers_task = MainMenuUi::req_server_list(&io);
+        let servers_task = MainMenuUi::req_server_list(&io, &[]);
 
         let mut demos: DemoList = Default::default();
         demos.push(DemoListEntry::Directory {
@@ -5,6 +5,12 @@ pub use api_render_game::*;
 use client_render_game::render_game::{RenderGame, RenderGameCreateOptions, RenderGameInterface};
 use config::config::ConfigDebug;
 
+/// The capabilities this module requires from the host.
+#[used]
+#[link_section = "ddnet_manifest"]
+static MANIFEST: [u8; include_bytes!("../manifest.json").len()] =
+    *include_bytes!("../manifest.json");
+
 #[no_mangle]
 fn mod_render_game_new(
     map_file: Vec<u8>,
@@ -43,6 +43,12 @@ pub mod reusable {
 pub use api::{DB, IO_RUNTIME};
 pub use api_state::*;
 
+/// The capabilities this module requires from the host.
+#[used]
+#[link_section = "ddnet_manifest"]
+static MANIFEST: [u8; include_bytes!("../manifest.json").len()] =
+    *include_bytes!("../manifest.json");
+
 #[no_mangle]
 fn mod_state_new(
     map: Vec<u8>,
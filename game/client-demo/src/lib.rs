@@ -27,8 +27,8 @@ use client_ui::demo_player::user_data::{DemoViewerEvent, DemoViewerUiState, User
 use config::config::ConfigEngine;
 use demo::{
     recorder::{DemoRecorder, DemoRecorderCreateProps, DemoRecorderCreatePropsBase},
-    utils::{decomp, deser, deser_ex},
-    ChunkHeader, DemoEvent, DemoEvents, DemoHeader, DemoHeaderExt, DemoSnapshot, DemoTail,
+    utils::{decomp, deser, deser_ex, read_chunks},
+    DemoEvent, DemoEvents, DemoHeader, DemoHeaderExt, DemoSnapshot, DemoTail,
 };
 use egui::{FontDefinitions, Rect};
 use game_config::config::{ConfigGame, ConfigMap, ConfigRender, ConfigSoundRender};
@@ -100,66 +100,6 @@ impl DemoViewerInner {
         self.cur_events.clear();
     }
 
-    fn read_chunks<A: DeserializeOwned>(
-        demo: &DemoContainer,
-        offset: usize,
-    ) -> anyhow::Result<BTreeMap<u64, A>> {
-        let file = &demo.demo_chunks[offset..];
-
-        // unpack all chunks
-        let mut data: Vec<u8> = Default::default();
-
-        anyhow::ensure!(
-            file.len() >= std::mem::size_of::<u64>(),
-            "file not huge enough to read u64 for compressed size"
-        );
-        let chunks_size = u64::from_le_bytes(file[0..std::mem::size_of::<u64>()].try_into()?);
-        let file = &file[std::mem::size_of::<u64>()..];
-
-        decomp(&file[0..chunks_size as usize], &mut data)?;
-        let file = data.as_slice();
-
-        // read item count in this chunk
-        let (len, read_size) = deser::<u64>(file)?;
-
-        let mut file = &file[read_size..];
-
-        let mut res: BTreeMap<u64, A> = Default::default();
-
-        let mut last_data: Option<Vec<u8>> = None;
-
-        let mut writer: Vec<u8> = Default::default();
-
-        for _ in 0..len {
-            let (header, read_size) = deser::<ChunkHeader>(file)?;
-            file = &file[read_size..];
-
-            let data = if header.size > 0 {
-                let data_slice = &file[..header.size as usize];
-                let res = if let Some(last_data) = &last_data {
-                    writer.clear();
-                    bin_patch::patch(last_data, data_slice, &mut writer)?;
-                    writer.as_slice()
-                } else {
-                    decomp(data_slice, &mut writer)?;
-                    writer.as_slice()
-                };
-                last_data = Some(res.to_vec());
-                let (data, _) = deser(res)?;
-                file = &file[header.size as usize..];
-                Some(data)
-            } else {
-                None
-            };
-
-            if let Some(data) = data {
-                res.insert(header.monotonic_tick, data);
-            }
-        }
-
-        Ok(res)
-    }
-
     fn time_to_tick_impl(&self, time: Duration) -> u64 {
         // IMPORTANT: We intentionally ignore the events index here
         // without snapshots the game does not work at all, events
@@ -211,7 +151,8 @@ impl DemoViewerInner {
     ) {
         let mut it = index.range(tick_range);
         if let Some((_, chunk_byte_offset)) = if rev { it.next_back() } else { it.next() } {
-            if let Ok(mut chunks) = Self::read_chunks::<A>(demo, *chunk_byte_offset as usize) {
+            if let Ok(mut chunks) = read_chunks::<A>(&demo.demo_chunks, *chunk_byte_offset as usize)
+            {
                 cur_data.append(&mut chunks);
             }
         }
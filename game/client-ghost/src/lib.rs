@@ -194,6 +194,7 @@ impl GhostViewer {
                                         lasers: PoolFxLinkedHashMap::new_without_pool(),
                                         pickups: PoolFxLinkedHashMap::new_without_pool(),
                                         characters: PoolFxLinkedHashMap::new_without_pool(),
+                                        switches: PoolFxLinkedHashMap::new_without_pool(),
                                     },
                                     game: GameRenderInfo::Race {},
                                     game_ticks_passed: 0,
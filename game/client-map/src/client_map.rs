@@ -26,6 +26,7 @@ use map::map::Map;
 use rayon::ThreadPool;
 pub use render_game_wasm::render::render_wasm_manager::RenderGameWasmManager;
 use render_game_wasm::render::render_wasm_manager::{RenderGameMod, RENDER_MODS_PATH};
+use wasm_runtime::{WasmLimits, WasmManifest};
 
 use game_base::network::messages::GameModification;
 use sound::sound::SoundManager;
@@ -52,6 +53,8 @@ impl ClientGameStateModTask {
             ClientGameStateModTask::Ddnet => GameStateMod::Ddnet,
             ClientGameStateModTask::Wasm { file } => GameStateMod::Wasm {
                 file: file.get_storage().unwrap(),
+                // the client only has a dummy database
+                manifest: WasmManifest::default(),
             },
         }
     }
@@ -217,7 +220,9 @@ impl ClientMapLoadingFile {
                                 }
                             }?;
 
-                            let wasm_module = GameStateWasmManager::load_module(&fs, file).await?;
+                            let wasm_module =
+                                GameStateWasmManager::load_module(&fs, file, WasmLimits::client())
+                                    .await?;
 
                             Ok(wasm_module)
                         })
@@ -306,8 +311,10 @@ impl ClientMapComponentLoading {
                         };
                         match render_mod {
                             RenderModTy::Native => Ok(RenderGameMod::Native),
-                            RenderModTy::Try { name, hash, .. }
-                            | RenderModTy::Required { name, hash } => {
+                            RenderModTy::Try {
+                                name, hash, grants, ..
+                            }
+                            | RenderModTy::Required { name, hash, grants } => {
                                 // load the wasm file
                                 let path_str = if let Some(hash) = hash {
                                     format!(
@@ -358,16 +365,42 @@ impl ClientMapComponentLoading {
                                     file
                                 };
 
-                                let module = match file {
-                                    Ok(file) => RenderGameWasmManager::load_module(&fs, file).await,
+                                // the manifest of the module only requests capabilities,
+                                // the module gets the ones the user granted.
+                                // Modules without a manifest are not allowed
+                                // to use any capability.
+                                let grants = WasmManifest {
+                                    http_hosts: grants.http_hosts,
+                                    fs_paths: grants.fs_paths.into_iter().map(Into::into).collect(),
+                                    db_kinds: Vec::new(),
+                                };
+                                let module = match file.and_then(|file| {
+                                    WasmManifest::from_module(&file).map(|manifest| {
+                                        let manifest = manifest.unwrap_or_default();
+                                        let granted = manifest.granted(&grants);
+                                        if granted != manifest {
+                                            log::info!(
+                                                "render mod {} requested {manifest:?}, \
+                                                but only {granted:?} was granted",
+                                                name.as_str()
+                                            );
+                                        }
+                                        (file, granted)
+                                    })
+                                }) {
+                                    Ok((file, manifest)) => {
+                                        RenderGameWasmManager::load_module(&fs, file)
+                                            .await
+                                            .map(|file| RenderGameMod::Wasm { file, manifest })
+                                    }
                                     Err(err) => Err(err),
                                 };
 
                                 if required {
-                                    module.map(|module| RenderGameMod::Wasm { file: module })
+                                    module
                                 } else {
                                     match module {
-                                        Ok(module) => Ok(RenderGameMod::Wasm { file: module }),
+                                        Ok(module) => Ok(module),
                                         Err(err) => {
                                             log::info!("Failed to load optional render mod: {err}");
                                             Ok(RenderGameMod::Native)
@@ -637,6 +670,7 @@ impl ClientMapLoading {
                                                     game_options.clone(),
                                                     &render.io,
                                                     Arc::new(DummyDb),
+                                                    WasmLimits::client(),
                                                 ),
                                                 GameStateWasmManager::new(
                                                     game_mod,
@@ -645,6 +679,7 @@ impl ClientMapLoading {
                                                     game_options,
                                                     &render.io,
                                                     Arc::new(DummyDb),
+                                                    WasmLimits::client(),
                                                 ),
                                             ) {
                                                 (Ok(game), Ok(unpredicted_game)) => {
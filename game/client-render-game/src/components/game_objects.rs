@@ -16,12 +16,13 @@ use client_render_base::{
 use game_interface::types::{
     emoticons::EnumCount,
     flag::FlagType,
+    game::NonZeroGameTickType,
     id_types::{CharacterId, CtfFlagId, LaserId, PickupId, ProjectileId},
     laser::LaserType,
     pickup::PickupType,
     render::{
         character::CharacterInfo, flag::FlagRenderInfo, laser::LaserRenderInfo,
-        pickup::PickupRenderInfo, projectiles::ProjectileRenderInfo,
+        pickup::PickupRenderInfo, projectiles::ProjectileRenderInfo, switch::SwitchRenderInfo,
     },
     weapons::WeaponType,
 };
@@ -44,6 +45,7 @@ use math::math::{
 };
 use num_traits::FromPrimitive;
 use game_base::game_types::intra_tick_time_to_ratio;
+use vanilla::collision::collision::DoorLaser;
 
 pub struct GameObjectsRender {
     items_quad_container: QuadContainer,
@@ -70,6 +72,9 @@ pub struct GameObjectsRenderPipe<'a> {
     pub flags: &'a FxLinkedHashMap<CtfFlagId, FlagRenderInfo>,
     pub lasers: &'a FxLinkedHashMap<LaserId, LaserRenderInfo>,
     pub pickups: &'a FxLinkedHashMap<PickupId, PickupRenderInfo>,
+    /// The doors of the map, rendered while their switch is active.
+    pub doors: &'a [DoorLaser],
+    pub switches: &'a FxLinkedHashMap<u8, SwitchRenderInfo>,
 
     pub ctf_container: &'a mut CtfContainer,
     pub game_container: &'a mut GameContainer,
@@ -172,6 +177,26 @@ impl GameObjectsRender {
         pipe.flags.values().for_each(|flag| {
             self.render_flag(pipe, flag, pipe.character_infos, &base_state);
         });
+        let (doors, switches) = (pipe.doors, pipe.switches);
+        doors
+            .iter()
+            .filter(|door| {
+                switches
+                    .get(&door.number)
+                    .is_none_or(|switch| switch.active)
+            })
+            .for_each(|door| {
+                let laser = LaserRenderInfo {
+                    ty: LaserType::Door,
+                    from: door.from / 32.0,
+                    pos: door.to / 32.0,
+                    // doors never fade out
+                    eval_tick_ratio: Some((0, NonZeroGameTickType::MAX)),
+                    owner_id: None,
+                    phased: false,
+                };
+                self.render_laser(pipe, &laser, pipe.character_infos, &base_state);
+            });
         pipe.lasers.values().for_each(|laser| {
             self.render_laser(pipe, laser, pipe.character_infos, &base_state);
         });
@@ -789,9 +789,15 @@ impl RenderHud {
     }
 
     pub fn render(&mut self, pipe: &mut RenderHudPipe) {
+        let race_split = match pipe.local_player_render_info {
+            LocalCharacterRenderInfo::Vanilla(info) => info.race_split.as_ref(),
+            LocalCharacterRenderInfo::Ddrace(info) => info.race_split.as_ref(),
+            LocalCharacterRenderInfo::Unavailable => None,
+        };
         self.ui.render(&mut HudRenderPipe {
             cur_time: pipe.cur_time,
             race_timer_counter: pipe.race_timer_counter,
+            race_split,
             ticks_per_second: pipe.ticks_per_second,
             game: pipe.game,
             skin_container: pipe.skin_container,
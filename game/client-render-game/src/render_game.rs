@@ -55,6 +55,7 @@ use game_base::network::{
 };
 use game_config::config::{
     ConfigDummyScreenAnchor, ConfigGame, ConfigMap, ConfigRender, ConfigSoundRender,
+    ConfigWasmModGrants,
 };
 use game_interface::{
     chat_commands::ChatCommands,
@@ -125,12 +126,16 @@ pub enum RenderModTy {
         hash: Option<Hash>,
         /// Client local loaded render mod name
         local_name: Option<ReducedAsciiString>,
+        /// The capabilities the user granted to render mods.
+        grants: ConfigWasmModGrants,
     },
     /// Load the given mod or abort the whole loading process.
     Required {
         name: ReducedAsciiString,
         /// If the hash is `Some`, the hash must match the WASM module.
         hash: Option<Hash>,
+        /// The capabilities the user granted to render mods.
+        grants: ConfigWasmModGrants,
     },
 }
 
@@ -147,6 +152,7 @@ impl RenderModTy {
                             name: name.clone(),
                             hash: None,
                             local_name: None,
+                            grants: config_game.cl.render_mod_grants.clone(),
                         },
                         Some(name),
                     )
@@ -158,10 +164,12 @@ impl RenderModTy {
                 name: name.clone().into(),
                 hash: Some(*hash),
                 local_name: local_mod_name,
+                grants: config_game.cl.render_mod_grants.clone(),
             },
             RenderModification::RequiresWasm { name, hash } => Self::Required {
                 name: name.clone().into(),
                 hash: Some(*hash),
+                grants: config_game.cl.render_mod_grants.clone(),
             },
         }
     }
@@ -674,6 +682,8 @@ impl RenderGame {
                 flags: &stage.world.ctf_flags,
                 pickups: &stage.world.pickups,
                 lasers: &stage.world.lasers,
+                doors: render_map.data.collision.doors(),
+                switches: &stage.world.switches,
                 character_infos: &render_info.character_infos,
 
                 ctf_container: &mut self.containers.ctf_container,
@@ -938,7 +948,7 @@ impl RenderGame {
                 local_player_render_info: &local_render_info.local_player_info,
                 cur_weapon: p.map(|c| c.cur_weapon).unwrap_or_default(),
                 race_timer_counter: &p
-                    .map(|p| p.game_ticks_passed)
+                    .map(|p| p.race_ticks_passed.unwrap_or(p.game_ticks_passed))
                     .or_else(|| stage.map(|s| s.game_ticks_passed))
                     .or_else(|| render_info.stages.front().map(|(_, s)| s.game_ticks_passed))
                     .unwrap_or_default(),
@@ -8,7 +8,10 @@ use egui::Color32;
 use game_interface::types::{
     game::{GameTickType, NonZeroGameTickType},
     id_types::CharacterId,
-    render::{character::CharacterInfo, game::GameRenderInfo},
+    render::{
+        character::{CharacterInfo, LocalCharacterRaceSplit},
+        game::GameRenderInfo,
+    },
 };
 use graphics::{
     graphics::graphics::Graphics,
@@ -26,6 +29,7 @@ use ui_generic::traits::UiPageInterface;
 
 pub struct HudRenderPipe<'a> {
     pub race_timer_counter: &'a GameTickType,
+    pub race_split: Option<&'a LocalCharacterRaceSplit>,
     pub ticks_per_second: &'a NonZeroGameTickType,
     pub cur_time: &'a Duration,
     pub game: Option<&'a GameRenderInfo>,
@@ -67,6 +71,7 @@ impl HudRender {
 
         let mut user_data = UserData {
             race_round_timer_counter: pipe.race_timer_counter,
+            race_split: pipe.race_split,
             ticks_per_second: pipe.ticks_per_second,
             game: pipe.game,
             skin_container: pipe.skin_container,
@@ -1,4 +1,6 @@
-use egui::{Frame, Layout, Pos2, Rect, UiBuilder, Vec2};
+use egui::{Frame, Layout, Pos2, Rect, TextEdit, UiBuilder, Vec2};
+
+use game_config::config::MAX_SERVER_PASSWORD_LEN;
 
 use ui_base::{
     style::bg_frame_color,
@@ -53,6 +55,46 @@ pub fn render_modes(ui: &mut egui::Ui, pipe: &mut UiRenderPipe<UserData>) {
                 }
             });
         }
+        ConnectModes::Password { addr, wrong } => {
+            let config = &mut *pipe.user_data.config;
+            let mut connect = false;
+            ui.vertical(|ui| {
+                ui.label(if wrong {
+                    format!("Wrong password for {}", addr)
+                } else {
+                    format!("{} requires a password", addr)
+                });
+                let password = config
+                    .game
+                    .cl
+                    .server_passwords
+                    .entry(addr.to_string())
+                    .or_default();
+                let res = ui.add(
+                    TextEdit::singleline(password)
+                        .password(true)
+                        .char_limit(MAX_SERVER_PASSWORD_LEN),
+                );
+                connect = res.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            });
+            ui.with_layout(Layout::bottom_up(egui::Align::Min), |ui| {
+                ui.horizontal(|ui| {
+                    if ui.button("Return").clicked() {
+                        pipe.user_data.events.push(UiEvent::Disconnect);
+                        config.engine.ui.path.route("");
+                    }
+                    connect |= ui.button("Connect").clicked();
+                });
+            });
+            if connect {
+                pipe.user_data.events.push(UiEvent::Connect {
+                    addr,
+                    cert_hash: config.storage("server-cert"),
+                    rcon_secret: config.storage("rcon-secret"),
+                    can_start_local_server: false,
+                });
+            }
+        }
         ConnectModes::DisconnectErr { msg } => {
             ui.vertical(|ui| {
                 ui.label(format!(
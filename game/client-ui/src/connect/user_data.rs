@@ -11,6 +11,7 @@ pub enum ConnectModes {
     Queue { msg: String },
     ConnectingErr { msg: String },
     DisconnectErr { msg: String },
+    Password { addr: SocketAddr, wrong: bool },
 }
 
 #[hiarc_safer_rc_refcell]
@@ -96,6 +96,34 @@ pub fn render(ui: &mut egui::Ui, pipe: &mut UiRenderPipe<UserData>, ui_state: &m
         None => (time_str, Color32::WHITE, None, None),
     };
 
+    let split_msg = pipe
+        .user_data
+        .race_split
+        .filter(|_| is_game_over.is_none())
+        .map(|split| {
+            let split_str = format!(
+                "checkpoint {}: {}",
+                split.checkpoint,
+                split.time.to_race_string()
+            );
+            match split.diff_to_best_millis {
+                Some(diff) => (
+                    format!(
+                        "{split_str} ({}{}.{:0>2})",
+                        if diff < 0 { "-" } else { "+" },
+                        diff.unsigned_abs() / 1000,
+                        diff.unsigned_abs() % 1000 / 10
+                    ),
+                    match diff.cmp(&0) {
+                        std::cmp::Ordering::Less => Color32::LIGHT_GREEN,
+                        std::cmp::Ordering::Equal => Color32::WHITE,
+                        std::cmp::Ordering::Greater => Color32::LIGHT_RED,
+                    },
+                ),
+                None => (split_str, Color32::WHITE),
+            }
+        });
+
     let color_a =
         |color: Color32, a: u8| Color32::from_rgba_unmultiplied(color.r(), color.g(), color.b(), a);
 
@@ -527,7 +555,7 @@ pub fn render(ui: &mut egui::Ui, pipe: &mut UiRenderPipe<UserData>, ui_state: &m
             render_side(pipe, ui, ui_state, Side::Bottom(rect));
         });
 
-    if let Some((balance_msg, color)) = balance_msg {
+    if balance_msg.is_some() || split_msg.is_some() {
         ui.allocate_new_ui(
             UiBuilder::default().max_rect(
                 res.map(|r| {
@@ -538,14 +566,11 @@ pub fn render(ui: &mut egui::Ui, pipe: &mut UiRenderPipe<UserData>, ui_state: &m
                 .unwrap_or_else(|| ui.ctx().screen_rect()),
             ),
             |ui| {
-                ui.with_layout(
-                    Layout::left_to_right(egui::Align::Min)
-                        .with_main_justify(true)
-                        .with_main_align(egui::Align::Center),
-                    |ui| {
-                        ui.label(RichText::new(balance_msg).color(color));
-                    },
-                );
+                ui.with_layout(Layout::top_down(egui::Align::Center), |ui| {
+                    for (msg, color) in balance_msg.into_iter().chain(split_msg) {
+                        ui.label(RichText::new(msg).color(color));
+                    }
+                });
             },
         );
     }
@@ -4,7 +4,10 @@ use client_render_base::render::tee::RenderTee;
 use game_interface::types::{
     game::{GameTickType, NonZeroGameTickType},
     id_types::CharacterId,
-    render::{character::CharacterInfo, game::GameRenderInfo},
+    render::{
+        character::{CharacterInfo, LocalCharacterRaceSplit},
+        game::GameRenderInfo,
+    },
 };
 use graphics::handles::{
     canvas::canvas::GraphicsCanvasHandle, stream::stream::GraphicsStreamHandle,
@@ -14,6 +17,8 @@ pub struct UserData<'a> {
     pub canvas_handle: &'a GraphicsCanvasHandle,
     pub stream_handle: &'a GraphicsStreamHandle,
     pub race_round_timer_counter: &'a GameTickType,
+    /// The split time of the last time checkpoint of the local character
+    pub race_split: Option<&'a LocalCharacterRaceSplit>,
     pub ticks_per_second: &'a NonZeroGameTickType,
     pub game: Option<&'a GameRenderInfo>,
 
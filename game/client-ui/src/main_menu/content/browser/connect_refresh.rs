@@ -50,7 +50,9 @@ pub fn render(ui: &mut egui::Ui, pipe: &mut UiRenderPipe<UserData>) {
     });
     // refresh
     if ui.button("\u{f2f9}").clicked() {
-        pipe.user_data.main_menu.refresh();
+        pipe.user_data
+            .main_menu
+            .refresh(&pipe.user_data.config.game.cl.master_servers);
         let profiles = pipe.user_data.profiles.clone();
         pipe.user_data.profile_tasks.user_interactions.push(
             pipe.user_data
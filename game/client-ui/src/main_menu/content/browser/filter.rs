@@ -68,6 +68,20 @@ pub fn render(ui: &mut egui::Ui, pipe: &mut UiRenderPipe<UserData>, ui_state: &m
                         ui.checkbox(&mut filter.fav_players_only, "Favorite players only");
                         ui.checkbox(&mut filter.no_password, "No password");
                         ui.checkbox(&mut filter.unfinished_maps, "Unfinished maps only");
+                        ui.horizontal(|ui| {
+                            ui.label("Max ping:");
+                            ui.add(
+                                egui::DragValue::new(&mut filter.max_ping)
+                                    .range(0..=999)
+                                    .custom_formatter(|v, _| {
+                                        if v == 0.0 {
+                                            "off".to_string()
+                                        } else {
+                                            format!("{v} ms")
+                                        }
+                                    }),
+                            );
+                        });
                         if filter != prev_filter {
                             config.set_storage("browser_filter", &filter);
                         }
@@ -35,6 +35,7 @@ pub fn render(mut body: TableBody<'_>, pipe: &mut UiRenderPipe<UserData>, cur_pa
     struct LanServer {
         server: ServerBrowserServer,
         rcon_secret: Option<[u8; 32]>,
+        cert_hash: Option<[u8; 32]>,
     }
     let server_info = &pipe.user_data.server_info;
     let (sock_addr, rcon_secret, server_cert_hash, server_browser_info, starting) =
@@ -58,7 +59,7 @@ pub fn render(mut body: TableBody<'_>, pipe: &mut UiRenderPipe<UserData>, cur_pa
                 (None, None, None, None, true)
             }
         };
-    let lan_server = [LanServer {
+    let mut lan_server = vec![LanServer {
         server: ServerBrowserServer {
             info: {
                 let mut info = server_browser_info.unwrap_or_else(|| ServerBrowserInfo {
@@ -92,7 +93,20 @@ pub fn render(mut body: TableBody<'_>, pipe: &mut UiRenderPipe<UserData>, cur_pa
             location: "default".try_into().unwrap(),
         },
         rcon_secret,
+        cert_hash: server_cert_hash,
     }];
+    // servers found in the local network
+    lan_server.extend(
+        pipe.user_data
+            .browser_data
+            .lan_servers()
+            .iter()
+            .map(|server| LanServer {
+                server: server.clone(),
+                rcon_secret: None,
+                cert_hash: Some(server.info.cert_sha256_fingerprint),
+            }),
+    );
 
     if cur_page == MENU_LAN_NAME {
         pipe.user_data.events.push(UiEvent::CheckLocalServer);
@@ -163,8 +177,14 @@ pub fn render(mut body: TableBody<'_>, pipe: &mut UiRenderPipe<UserData>, cur_pa
             let server_addr = get_addr(&server.addresses);
             let is_selected = server_addr.to_string() == cur_addr;
             row.set_selected(is_selected);
-            let (clicked, restart_clicked) =
-                super::entry::render(row, server, cur_page == MENU_LAN_NAME);
+            let ping = pipe.user_data.browser_data.ping(server);
+            // only the internal server can be restarted
+            let (clicked, restart_clicked) = super::entry::render(
+                row,
+                server,
+                ping,
+                cur_page == MENU_LAN_NAME && row_index == 0,
+            );
             let clicked = clicked
                 || (cur_page == MENU_LAN_NAME && lan_server.len() == 1)
                 || select_index
@@ -189,7 +209,7 @@ pub fn render(mut body: TableBody<'_>, pipe: &mut UiRenderPipe<UserData>, cur_pa
                     &if cur_page != MENU_LAN_NAME {
                         Some(server.info.cert_sha256_fingerprint)
                     } else {
-                        server_cert_hash
+                        lan_server[row_index].cert_hash
                     },
                 );
                 if cur_page == MENU_LAN_NAME {
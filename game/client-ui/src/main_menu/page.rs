@@ -19,6 +19,7 @@ use demo::{
 };
 use game_base::{
     assets_url::HTTP_RESOURCE_URL,
+    lan_discovery::discover_lan_servers,
     server_browser::{ServerBrowserData, ServerBrowserInfo, ServerBrowserServer},
 };
 
@@ -65,6 +66,7 @@ use super::{
 pub struct MainMenuIo {
     pub(crate) io: Io,
     cur_servers_task: Option<IoRuntimeTask<Vec<ServerBrowserServer>>>,
+    cur_lan_servers_task: Option<IoRuntimeTask<Vec<ServerBrowserServer>>>,
     cur_ddnet_info_task: Option<IoRuntimeTask<String>>,
     cur_demos_task: Option<IoRuntimeTask<DemoList>>,
     cur_demo_info_task: Option<IoRuntimeTask<(DemoHeader, DemoHeaderExt)>>,
@@ -72,8 +74,9 @@ pub struct MainMenuIo {
 }
 
 impl MainMenuInterface for MainMenuIo {
-    fn refresh(&mut self) {
-        self.cur_servers_task = Some(MainMenuUi::req_server_list(&self.io));
+    fn refresh(&mut self, master_servers: &[String]) {
+        self.cur_servers_task = Some(MainMenuUi::req_server_list(&self.io, master_servers));
+        self.cur_lan_servers_task = Some(MainMenuUi::req_lan_server_list(&self.io));
     }
 
     fn refresh_demo_list(&mut self, path: &Path) {
@@ -177,24 +180,76 @@ impl MainMenuUi {
             .cancelable()
     }
 
+    /// Combines the server lists of all master servers.
+    ///
+    /// Only fails if no master server could be reached.
     pub async fn download_server_list(
         http: &Arc<dyn HttpClientInterface>,
+        master_servers: &[String],
     ) -> anyhow::Result<Vec<ServerBrowserServer>> {
-        Self::json_to_server_browser(
-            &http
-                .download_text(
-                    "https://pg.ddnet.org:4444/ddnet/15/servers.json"
-                        .try_into()
-                        .unwrap(),
+        let mut servers: Vec<ServerBrowserServer> = Default::default();
+        let mut any_success = false;
+        let mut last_err = None;
+        for master_server in master_servers {
+            let list = async {
+                Self::json_to_server_browser(
+                    &http
+                        .download_text(master_server.as_str().try_into()?)
+                        .await?,
                 )
-                .await?,
-        )
+            }
+            .await;
+            match list {
+                Ok(list) => {
+                    // the same server can be registered on multiple master servers
+                    servers.extend(list.into_iter().filter(|server| {
+                        !servers.iter().any(|known_server| {
+                            known_server
+                                .addresses
+                                .iter()
+                                .any(|addr| server.addresses.contains(addr))
+                        })
+                    }));
+                    any_success = true;
+                }
+                Err(err) => {
+                    log::info!("failed to download the server list of {master_server}: {err}");
+                    last_err = Some(err);
+                }
+            }
+        }
+        match last_err.filter(|_| !any_success) {
+            Some(err) => Err(err),
+            None => Ok(servers),
+        }
     }
 
-    pub fn req_server_list(io: &Io) -> IoRuntimeTask<Vec<ServerBrowserServer>> {
+    pub fn req_server_list(
+        io: &Io,
+        master_servers: &[String],
+    ) -> IoRuntimeTask<Vec<ServerBrowserServer>> {
         let http = io.http.clone();
+        let master_servers = master_servers.to_vec();
+        io.rt
+            .spawn(async move { Self::download_server_list(&http, &master_servers).await })
+            .cancelable()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn lan_server_list() -> anyhow::Result<Vec<ServerBrowserServer>> {
+        // waiting for the answers of the servers must not block the async workers
+        tokio::task::spawn_blocking(|| discover_lan_servers(Duration::from_millis(500))).await?
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn lan_server_list() -> anyhow::Result<Vec<ServerBrowserServer>> {
+        // there is no network, so this fails right away
+        discover_lan_servers(Duration::from_millis(500))
+    }
+
+    pub fn req_lan_server_list(io: &Io) -> IoRuntimeTask<Vec<ServerBrowserServer>> {
         io.rt
-            .spawn(async move { Self::download_server_list(&http).await })
+            .spawn(async move { Self::lan_server_list().await })
             .cancelable()
     }
 
@@ -233,7 +288,8 @@ impl MainMenuUi {
         browser_data: ServerBrowserData,
         features: EnabledFeatures,
     ) -> Self {
-        let cur_servers_task = Self::req_server_list(&io);
+        let cur_servers_task = Self::req_server_list(&io, &config_game.cl.master_servers);
+        let cur_lan_servers_task = Self::req_lan_server_list(&io);
         let cur_ddnet_info_task = Self::req_ddnet_info(
             &io,
             config_game
@@ -295,6 +351,7 @@ impl MainMenuUi {
                 io: io.clone(),
                 cur_ddnet_info_task: Some(cur_ddnet_info_task),
                 cur_servers_task: Some(cur_servers_task),
+                cur_lan_servers_task: Some(cur_lan_servers_task),
                 cur_demos_task: None,
                 cur_demo_info_task: None,
                 remove_demo_info: false,
@@ -460,6 +517,24 @@ impl MainMenuUi {
                 }
             }
         }
+        if let Some(server_task) = &self.menu_io.cur_lan_servers_task {
+            if server_task.is_finished() {
+                match self
+                    .menu_io
+                    .cur_lan_servers_task
+                    .take()
+                    .unwrap()
+                    .get_storage()
+                {
+                    Ok(servers) => {
+                        self.browser_data.set_lan_servers(servers);
+                    }
+                    Err(err) => {
+                        log::error!("failed to find lan servers: {err}");
+                    }
+                }
+            }
+        }
         if let Some(server_task) = &self.menu_io.cur_ddnet_info_task {
             if server_task.is_finished() {
                 match self
@@ -576,5 +651,6 @@ impl UiPageInterface<Config> for MainMenuUi {
         self.community_icon_container.clear_except_default();
         self.profile_tasks = Default::default();
         self.menu_io.cur_servers_task = None;
+        self.menu_io.cur_lan_servers_task = None;
     }
 }
@@ -57,7 +57,9 @@ pub struct RenderOptions {
 }
 
 pub trait MainMenuInterface {
-    fn refresh(&mut self);
+    /// Refreshes the server lists of the master servers
+    /// & searches for servers in the local network.
+    fn refresh(&mut self, master_servers: &[String]);
 
     fn refresh_demo_list(&mut self, path: &Path);
     /// A path of `None` here means that a directory is selected
@@ -0,0 +1,401 @@
+//! The huffman compression of the legacy network protocol,
+//! which is also used for the chunks of legacy demos.
+
+use anyhow::anyhow;
+
+const MAX_SYMBOLS: usize = 256 + 1;
+const EOF_SYMBOL: usize = 256;
+const MAX_NODES: usize = MAX_SYMBOLS * 2 - 1;
+
+const NO_LEAF: u16 = u16::MAX;
+
+/// The symbol frequencies the legacy protocol was trained on.
+/// The frequency of the eof symbol is always forced to 1.
+const FREQUENCY_TABLE: [u32; MAX_SYMBOLS] = [
+    1 << 30,
+    4545,
+    2657,
+    431,
+    1950,
+    919,
+    444,
+    482,
+    2244,
+    617,
+    838,
+    542,
+    715,
+    1814,
+    304,
+    240,
+    754,
+    212,
+    647,
+    186,
+    283,
+    131,
+    146,
+    166,
+    543,
+    164,
+    167,
+    136,
+    179,
+    859,
+    363,
+    113,
+    157,
+    154,
+    204,
+    108,
+    137,
+    180,
+    202,
+    176,
+    872,
+    404,
+    168,
+    134,
+    151,
+    111,
+    113,
+    109,
+    120,
+    126,
+    129,
+    100,
+    41,
+    20,
+    16,
+    22,
+    18,
+    18,
+    17,
+    19,
+    16,
+    37,
+    13,
+    21,
+    362,
+    166,
+    99,
+    78,
+    95,
+    88,
+    81,
+    70,
+    83,
+    284,
+    91,
+    187,
+    77,
+    68,
+    52,
+    68,
+    59,
+    66,
+    61,
+    638,
+    71,
+    157,
+    50,
+    46,
+    69,
+    43,
+    11,
+    24,
+    13,
+    19,
+    10,
+    12,
+    12,
+    20,
+    14,
+    9,
+    20,
+    20,
+    10,
+    10,
+    15,
+    15,
+    12,
+    12,
+    7,
+    19,
+    15,
+    14,
+    13,
+    18,
+    35,
+    19,
+    17,
+    14,
+    8,
+    5,
+    15,
+    17,
+    9,
+    15,
+    14,
+    18,
+    8,
+    10,
+    2173,
+    134,
+    157,
+    68,
+    188,
+    60,
+    170,
+    60,
+    194,
+    62,
+    175,
+    71,
+    148,
+    67,
+    167,
+    78,
+    211,
+    67,
+    156,
+    69,
+    1674,
+    90,
+    174,
+    53,
+    147,
+    89,
+    181,
+    51,
+    174,
+    63,
+    163,
+    80,
+    167,
+    94,
+    128,
+    122,
+    223,
+    153,
+    218,
+    77,
+    200,
+    110,
+    190,
+    73,
+    174,
+    69,
+    145,
+    66,
+    277,
+    143,
+    141,
+    60,
+    136,
+    53,
+    180,
+    57,
+    142,
+    57,
+    158,
+    61,
+    166,
+    112,
+    152,
+    92,
+    26,
+    22,
+    21,
+    28,
+    20,
+    26,
+    30,
+    21,
+    32,
+    27,
+    20,
+    17,
+    23,
+    21,
+    30,
+    22,
+    22,
+    21,
+    27,
+    25,
+    17,
+    27,
+    23,
+    18,
+    39,
+    26,
+    15,
+    21,
+    12,
+    18,
+    18,
+    27,
+    20,
+    18,
+    15,
+    19,
+    11,
+    17,
+    33,
+    12,
+    18,
+    15,
+    19,
+    18,
+    16,
+    26,
+    17,
+    18,
+    9,
+    10,
+    25,
+    22,
+    22,
+    17,
+    20,
+    16,
+    6,
+    16,
+    15,
+    20,
+    14,
+    18,
+    24,
+    335,
+    1517,
+];
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    /// The code of a symbol, the first bit is the least significant bit.
+    bits: u32,
+    /// Zero for nodes that are not a leaf.
+    num_bits: u32,
+    leafs: [u16; 2],
+    symbol: u16,
+}
+
+/// A huffman tree, built exactly like the legacy implementation,
+/// since different trees produce different codes.
+#[derive(Debug, Clone)]
+pub struct Huffman {
+    nodes: Vec<Node>,
+    start_node: usize,
+}
+
+impl Default for Huffman {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Huffman {
+    pub fn new() -> Self {
+        let mut nodes: Vec<Node> = (0..MAX_SYMBOLS)
+            .map(|symbol| Node {
+                bits: 0,
+                num_bits: u32::MAX,
+                leafs: [NO_LEAF; 2],
+                symbol: symbol as u16,
+            })
+            .collect();
+        nodes.reserve(MAX_NODES - MAX_SYMBOLS);
+
+        // (frequency, node index)
+        let mut nodes_left: Vec<(u32, u16)> = FREQUENCY_TABLE
+            .iter()
+            .enumerate()
+            .map(|(symbol, &frequency)| {
+                let frequency = if symbol == EOF_SYMBOL { 1 } else { frequency };
+                (frequency, symbol as u16)
+            })
+            .collect();
+
+        while nodes_left.len() > 1 {
+            // the legacy implementation uses a bubble sort, which is stable.
+            // any other stable sort results in the same tree.
+            nodes_left.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+            let (last_frequency, last_id) = nodes_left.pop().unwrap();
+            let (second_last_frequency, second_last_id) = nodes_left.last_mut().unwrap();
+            nodes.push(Node {
+                bits: 0,
+                num_bits: 0,
+                leafs: [last_id, *second_last_id],
+                symbol: 0,
+            });
+            *second_last_id = (nodes.len() - 1) as u16;
+            *second_last_frequency += last_frequency;
+        }
+
+        let start_node = nodes.len() - 1;
+        Self::set_bits(&mut nodes, start_node, 0, 0);
+
+        Self { nodes, start_node }
+    }
+
+    fn set_bits(nodes: &mut [Node], node: usize, bits: u32, depth: u32) {
+        let [leaf_0, leaf_1] = nodes[node].leafs;
+        if leaf_1 != NO_LEAF {
+            Self::set_bits(nodes, leaf_1 as usize, bits | (1 << depth), depth + 1);
+        }
+        if leaf_0 != NO_LEAF {
+            Self::set_bits(nodes, leaf_0 as usize, bits, depth + 1);
+        }
+
+        let node = &mut nodes[node];
+        if node.num_bits != 0 {
+            node.bits = bits;
+            node.num_bits = depth;
+        }
+    }
+
+    pub fn compress(&self, data: &[u8]) -> Vec<u8> {
+        let mut res = Vec::with_capacity(data.len());
+        let mut bits: u64 = 0;
+        let mut bit_count = 0;
+        for node in data
+            .iter()
+            .map(|&symbol| &self.nodes[symbol as usize])
+            .chain(std::iter::once(&self.nodes[EOF_SYMBOL]))
+        {
+            bits |= (node.bits as u64) << bit_count;
+            bit_count += node.num_bits;
+            while bit_count >= 8 {
+                res.push(bits as u8);
+                bits >>= 8;
+                bit_count -= 8;
+            }
+        }
+        if bit_count > 0 {
+            res.push(bits as u8);
+        }
+        res
+    }
+
+    pub fn decompress(&self, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let mut res = Vec::with_capacity(data.len() * 2);
+        let mut bits = data
+            .iter()
+            .flat_map(|byte| (0..8).map(move |bit| (byte >> bit) & 1));
+        loop {
+            let mut node = &self.nodes[self.start_node];
+            while node.num_bits == 0 {
+                let bit = bits
+                    .next()
+                    .ok_or_else(|| anyhow!("huffman data ended before the eof symbol"))?;
+                node = &self.nodes[node.leafs[bit as usize] as usize];
+            }
+            if node.symbol as usize == EOF_SYMBOL {
+                return Ok(res);
+            }
+            res.push(node.symbol as u8);
+        }
+    }
+}
@@ -0,0 +1,327 @@
+//! Reads demos of the legacy (0.6/DDNet) client.
+
+use std::ffi::CStr;
+
+use anyhow::anyhow;
+
+use crate::huffman::Huffman;
+
+const HEADER_MARKER: &[u8; 7] = b"TWDEMO\0";
+const HEADER_SIZE: usize = 176;
+const MAX_TIMELINE_MARKERS: usize = 64;
+
+/// The first version with timeline markers.
+const VERSION_TIMELINE_MARKERS: u8 = 4;
+/// The first version with a smaller tick delta mask.
+const VERSION_TICK_COMPRESSION: u8 = 5;
+/// The first version that might contain the sha256 of the map.
+const VERSION_SHA256: u8 = 6;
+const MAX_SUPPORTED_VERSION: u8 = 6;
+
+const SHA256_EXTENSION: [u8; 16] = [
+    0x6b, 0xe6, 0xda, 0x4a, 0xce, 0xbd, 0x38, 0x0c, 0x9b, 0x5b, 0x12, 0x89, 0xc8, 0x42, 0xd7, 0x80,
+];
+
+const CHUNK_TYPE_FLAG_TICK_MARKER: u8 = 0x80;
+const CHUNK_TICK_FLAG_TICK_COMPRESSED: u8 = 0x20;
+const CHUNK_MASK_TICK: u8 = 0x1f;
+const CHUNK_MASK_TICK_LEGACY: u8 = 0x3f;
+const CHUNK_MASK_TYPE: u8 = 0x60;
+const CHUNK_MASK_SIZE: u8 = 0x1f;
+
+/// Reads a single variable length integer and returns
+/// the remaining data.
+pub fn unpack_int(data: &[u8]) -> anyhow::Result<(i32, &[u8])> {
+    let (&first, mut rest) = data
+        .split_first()
+        .ok_or_else(|| anyhow!("variable int is missing"))?;
+    let sign = ((first >> 6) & 1) as i32;
+    let mut res = (first & 0x3f) as i32;
+    let mut cur = first;
+    for (shift, mask) in [
+        (6, 0x7f),
+        (6 + 7, 0x7f),
+        (6 + 7 + 7, 0x7f),
+        (6 + 7 + 7 + 7, 0x0f),
+    ] {
+        if cur & 0x80 == 0 {
+            break;
+        }
+        let (&next, next_rest) = rest
+            .split_first()
+            .ok_or_else(|| anyhow!("variable int ended unexpectedly"))?;
+        res |= ((next & mask) as i32) << shift;
+        cur = next;
+        rest = next_rest;
+    }
+    Ok((res ^ -sign, rest))
+}
+
+pub fn pack_int(mut val: i32, res: &mut Vec<u8>) {
+    let mut byte = ((val >> 25) & 0x40) as u8;
+    val ^= val >> 31;
+    byte |= (val & 0x3f) as u8;
+    val >>= 6;
+    while val != 0 {
+        res.push(byte | 0x80);
+        byte = (val & 0x7f) as u8;
+        val >>= 7;
+    }
+    res.push(byte);
+}
+
+/// Unpacks a chunk into the native (little endian) integers
+/// it was created from.
+pub fn decompress_ints(mut data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut res = Vec::with_capacity(data.len() * 4);
+    while !data.is_empty() {
+        let (val, rest) = unpack_int(data)?;
+        res.extend(val.to_le_bytes());
+        data = rest;
+    }
+    Ok(res)
+}
+
+/// The opposite of [`decompress_ints`], the data is padded to
+/// a multiple of 4 bytes.
+pub fn compress_ints(data: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(data.len());
+    for int in data.chunks(4) {
+        let mut bytes = [0; 4];
+        bytes[..int.len()].copy_from_slice(int);
+        pack_int(i32::from_le_bytes(bytes), &mut res);
+    }
+    res
+}
+
+pub fn bytes_to_ints(data: &[u8]) -> Vec<i32> {
+    data.chunks_exact(4)
+        .map(|int| i32::from_le_bytes([int[0], int[1], int[2], int[3]]))
+        .collect()
+}
+
+fn read_be_i32(data: &[u8]) -> i32 {
+    i32::from_be_bytes([data[0], data[1], data[2], data[3]])
+}
+
+fn read_str(data: &[u8]) -> String {
+    CStr::from_bytes_until_nul(data)
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|_| String::from_utf8_lossy(data).to_string())
+}
+
+#[derive(Debug, Clone)]
+pub struct LegacyDemoHeader {
+    pub version: u8,
+    pub net_version: String,
+    pub map_name: String,
+    pub map_size: usize,
+    pub map_crc: u32,
+    pub ty: String,
+    /// Length of the demo in seconds.
+    pub length: i32,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegacyDemoChunkType {
+    /// A full snapshot.
+    Snapshot,
+    /// A network message, as it was sent by the server.
+    Message,
+    /// A snapshot delta to the last snapshot.
+    Delta,
+}
+
+/// A decompressed chunk of a legacy demo.
+#[derive(Debug, Clone)]
+pub struct LegacyDemoChunk {
+    pub tick: i32,
+    pub ty: LegacyDemoChunkType,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LegacyDemo<'a> {
+    pub header: LegacyDemoHeader,
+    /// Server ticks of the markers set while recording.
+    pub timeline_markers: Vec<i32>,
+    pub map_sha256: Option<[u8; 32]>,
+    pub map: &'a [u8],
+
+    chunks: &'a [u8],
+}
+
+impl<'a> LegacyDemo<'a> {
+    pub fn read(data: &'a [u8]) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            data.len() >= HEADER_SIZE && data.starts_with(HEADER_MARKER),
+            "not a legacy demo file"
+        );
+        let (header, rest) = data.split_at(HEADER_SIZE);
+        let version = header[7];
+        anyhow::ensure!(
+            version <= MAX_SUPPORTED_VERSION,
+            "demo version {version} is not supported"
+        );
+        let header = LegacyDemoHeader {
+            version,
+            net_version: read_str(&header[8..72]),
+            map_name: read_str(&header[72..136]),
+            map_size: read_be_i32(&header[136..140]).max(0) as usize,
+            map_crc: read_be_i32(&header[140..144]) as u32,
+            ty: read_str(&header[144..152]),
+            length: read_be_i32(&header[152..156]),
+            timestamp: read_str(&header[156..176]),
+        };
+
+        let mut rest = rest;
+        let mut timeline_markers = Vec::new();
+        if header.version >= VERSION_TIMELINE_MARKERS {
+            let markers_size = 4 + MAX_TIMELINE_MARKERS * 4;
+            anyhow::ensure!(rest.len() >= markers_size, "timeline markers are missing");
+            let (markers, markers_rest) = rest.split_at(markers_size);
+            let num_markers = (read_be_i32(markers).max(0) as usize).min(MAX_TIMELINE_MARKERS);
+            timeline_markers.extend(
+                markers[4..]
+                    .chunks_exact(4)
+                    .take(num_markers)
+                    .map(read_be_i32),
+            );
+            rest = markers_rest;
+        }
+
+        let mut map_sha256 = None;
+        if header.version >= VERSION_SHA256 && rest.starts_with(&SHA256_EXTENSION) {
+            let sha256 = rest
+                .get(SHA256_EXTENSION.len()..SHA256_EXTENSION.len() + 32)
+                .ok_or_else(|| anyhow!("map sha256 is missing"))?;
+            map_sha256 = Some(sha256.try_into()?);
+            rest = &rest[SHA256_EXTENSION.len() + 32..];
+        }
+
+        anyhow::ensure!(rest.len() >= header.map_size, "embedded map is missing");
+        let (map, chunks) = rest.split_at(header.map_size);
+
+        Ok(Self {
+            header,
+            timeline_markers,
+            map_sha256,
+            map,
+            chunks,
+        })
+    }
+
+    /// All chunks in the order they were recorded.
+    pub fn chunks(&self) -> LegacyDemoChunks<'a> {
+        LegacyDemoChunks {
+            huffman: Huffman::new(),
+            version: self.header.version,
+            data: self.chunks,
+            tick: 0,
+        }
+    }
+}
+
+pub struct LegacyDemoChunks<'a> {
+    huffman: Huffman,
+    version: u8,
+    data: &'a [u8],
+    tick: i32,
+}
+
+impl<'a> LegacyDemoChunks<'a> {
+    fn take(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        anyhow::ensure!(self.data.len() >= len, "demo chunk is truncated");
+        let (res, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(res)
+    }
+
+    fn next_chunk(&mut self) -> anyhow::Result<Option<LegacyDemoChunk>> {
+        loop {
+            let Some(&chunk) = self.data.first() else {
+                return Ok(None);
+            };
+            self.data = &self.data[1..];
+
+            if chunk & CHUNK_TYPE_FLAG_TICK_MARKER != 0 {
+                let tick_delta_legacy = chunk & CHUNK_MASK_TICK_LEGACY;
+                if self.version < VERSION_TICK_COMPRESSION && tick_delta_legacy != 0 {
+                    self.tick += tick_delta_legacy as i32;
+                } else if chunk & CHUNK_TICK_FLAG_TICK_COMPRESSED != 0 {
+                    self.tick += (chunk & CHUNK_MASK_TICK) as i32;
+                } else {
+                    self.tick = read_be_i32(self.take(4)?);
+                }
+                // the keyframe flag is only interesting for seeking
+                continue;
+            }
+
+            let ty = match (chunk & CHUNK_MASK_TYPE) >> 5 {
+                1 => LegacyDemoChunkType::Snapshot,
+                2 => LegacyDemoChunkType::Message,
+                3 => LegacyDemoChunkType::Delta,
+                ty => return Err(anyhow!("unknown demo chunk type {ty}")),
+            };
+            let size = match chunk & CHUNK_MASK_SIZE {
+                30 => self.take(1)?[0] as usize,
+                31 => {
+                    let size = self.take(2)?;
+                    u16::from_le_bytes([size[0], size[1]]) as usize
+                }
+                size => size as usize,
+            };
+            let data = self.take(size)?;
+            let data = self.huffman.decompress(data)?;
+            let data = decompress_ints(&data)?;
+
+            return Ok(Some(LegacyDemoChunk {
+                tick: self.tick,
+                ty,
+                data,
+            }));
+        }
+    }
+}
+
+impl Iterator for LegacyDemoChunks<'_> {
+    type Item = anyhow::Result<LegacyDemoChunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = self.next_chunk();
+        if res.is_err() {
+            // a broken chunk makes all following chunks unreadable
+            self.data = &[];
+        }
+        res.transpose()
+    }
+}
+
+/// Reads the fields of a legacy network message.
+pub struct LegacyUnpacker<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> LegacyUnpacker<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub fn get_int(&mut self) -> anyhow::Result<i32> {
+        let (val, rest) = unpack_int(self.data)?;
+        self.data = rest;
+        Ok(val)
+    }
+
+    pub fn get_string(&mut self) -> anyhow::Result<String> {
+        let len = self
+            .data
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| anyhow!("string is not null terminated"))?;
+        let res = String::from_utf8_lossy(&self.data[..len]).to_string();
+        self.data = &self.data[len + 1..];
+        Ok(res)
+    }
+}
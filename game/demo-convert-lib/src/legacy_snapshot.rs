@@ -0,0 +1,178 @@
+//! Snapshots of the legacy network protocol.
+
+use anyhow::anyhow;
+
+/// Types with a higher id always send their size in deltas.
+const MAX_NETOBJ_SIZES: usize = 64;
+
+/// The sizes (in integers) of the 0.6 network objects,
+/// these sizes are not part of snapshot deltas.
+const STATIC_ITEM_SIZES: [usize; 21] = [
+    0, 10, 6, 5, 4, 3, 8, 4, 15, 22, 5, 17, 3, 2, 2, 2, 2, 3, 3, 3, 3,
+];
+
+fn static_item_size(ty: i32) -> Option<usize> {
+    (ty >= 0 && (ty as usize) < MAX_NETOBJ_SIZES)
+        .then(|| STATIC_ITEM_SIZES.get(ty as usize).copied())
+        .flatten()
+        .filter(|&size| size > 0)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacySnapshotItem {
+    pub ty: i32,
+    pub id: i32,
+    pub data: Vec<i32>,
+}
+
+impl LegacySnapshotItem {
+    pub fn key(&self) -> i32 {
+        (self.ty << 16) | self.id
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LegacySnapshot {
+    pub items: Vec<LegacySnapshotItem>,
+}
+
+impl LegacySnapshot {
+    pub fn from_ints(data: &[i32]) -> anyhow::Result<Self> {
+        let [data_size, num_items, rest @ ..] = data else {
+            return Err(anyhow!("snapshot header is missing"));
+        };
+        anyhow::ensure!(
+            *data_size >= 0 && *num_items >= 0,
+            "snapshot header is invalid"
+        );
+        let num_items = *num_items as usize;
+        anyhow::ensure!(rest.len() >= num_items, "snapshot offsets are missing");
+        let (offsets, items_data) = rest.split_at(num_items);
+        let data_size = (*data_size as usize / 4).min(items_data.len());
+
+        let items = offsets
+            .iter()
+            .enumerate()
+            .map(|(index, &offset)| {
+                let start = offset.max(0) as usize / 4;
+                let end = offsets
+                    .get(index + 1)
+                    .map(|&offset| offset.max(0) as usize / 4)
+                    .unwrap_or(data_size);
+                let item = items_data
+                    .get(start..end)
+                    .filter(|item| !item.is_empty())
+                    .ok_or_else(|| anyhow!("snapshot item {index} is out of bounds"))?;
+                Ok(LegacySnapshotItem {
+                    ty: (item[0] >> 16) & 0xffff,
+                    id: item[0] & 0xffff,
+                    data: item[1..].to_vec(),
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self { items })
+    }
+
+    pub fn to_ints(&self) -> Vec<i32> {
+        let items_size: usize = self.items.iter().map(|item| item.data.len() + 1).sum();
+        let mut res = Vec::with_capacity(2 + self.items.len() + items_size);
+        res.push((items_size * 4) as i32);
+        res.push(self.items.len() as i32);
+        let mut offset = 0;
+        for item in &self.items {
+            res.push((offset * 4) as i32);
+            offset += item.data.len() + 1;
+        }
+        for item in &self.items {
+            res.push(item.key());
+            res.extend(item.data.iter().copied());
+        }
+        res
+    }
+
+    pub fn item(&self, ty: i32, id: i32) -> Option<&[i32]> {
+        self.items
+            .iter()
+            .find(|item| item.ty == ty && item.id == id)
+            .map(|item| item.data.as_slice())
+    }
+
+    pub fn items_of_type(&self, ty: i32) -> impl Iterator<Item = &LegacySnapshotItem> {
+        self.items.iter().filter(move |item| item.ty == ty)
+    }
+
+    /// Creates the snapshot that follows this snapshot
+    /// by applying the delta to it.
+    pub fn apply_delta(&self, delta: &[i32]) -> anyhow::Result<Self> {
+        let [num_deleted, num_updated, _num_temp, rest @ ..] = delta else {
+            return Err(anyhow!("snapshot delta header is missing"));
+        };
+        anyhow::ensure!(
+            *num_deleted >= 0 && *num_updated >= 0,
+            "snapshot delta header is invalid"
+        );
+        let num_deleted = *num_deleted as usize;
+        anyhow::ensure!(rest.len() >= num_deleted, "deleted keys are missing");
+        let (deleted, mut data) = rest.split_at(num_deleted);
+
+        let mut items: Vec<LegacySnapshotItem> = self
+            .items
+            .iter()
+            .filter(|item| !deleted.contains(&item.key()))
+            .cloned()
+            .collect();
+
+        for _ in 0..*num_updated {
+            let [ty, id, rest @ ..] = data else {
+                return Err(anyhow!("updated item is missing"));
+            };
+            let (size, rest) = match static_item_size(*ty) {
+                Some(size) => (size, rest),
+                None => {
+                    let [size, rest @ ..] = rest else {
+                        return Err(anyhow!("size of updated item is missing"));
+                    };
+                    anyhow::ensure!(*size >= 0, "size of updated item is negative");
+                    (*size as usize, rest)
+                }
+            };
+            anyhow::ensure!(rest.len() >= size, "updated item is out of bounds");
+            let (item_data, rest) = rest.split_at(size);
+            data = rest;
+
+            let new_data: Vec<i32> = match self
+                .items
+                .iter()
+                .find(|item| item.ty == *ty && item.id == *id)
+            {
+                Some(from) => item_data
+                    .iter()
+                    .enumerate()
+                    .map(|(index, diff)| {
+                        from.data
+                            .get(index)
+                            .copied()
+                            .unwrap_or_default()
+                            .wrapping_add(*diff)
+                    })
+                    .collect(),
+                None => item_data.to_vec(),
+            };
+
+            match items
+                .iter_mut()
+                .find(|item| item.ty == *ty && item.id == *id)
+            {
+                Some(item) => item.data = new_data,
+                None => items.push(LegacySnapshotItem {
+                    ty: *ty,
+                    id: *id,
+                    data: new_data,
+                }),
+            }
+        }
+
+        Ok(Self { items })
+    }
+}
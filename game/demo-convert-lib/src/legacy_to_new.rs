@@ -0,0 +1,1022 @@
+use std::{
+    collections::HashMap,
+    num::NonZeroU64,
+    sync::{mpsc::sync_channel, Arc},
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use base::{
+    hash::{generate_hash_for, Hash},
+    network_string::{NetworkReducedAsciiString, NetworkString},
+};
+use base_io::io::{Io, IoFileSys};
+use demo::{
+    recorder::{DemoRecorder, DemoRecorderCreateProps, DemoRecorderCreatePropsBase},
+    DemoEvent,
+};
+use game_base::network::{
+    messages::{GameModification, RenderModification},
+    types::chat::{ChatPlayerInfo, NetChatMsg, NetChatMsgPlayerChannel},
+};
+use game_interface::{
+    events::{
+        EventId, GameEvents, GameWorldAction, GameWorldActionKillWeapon, GameWorldEvent,
+        GameWorldEvents, GameWorldNotificationEvent, GameWorldSystemMessage, KillFlags,
+    },
+    interface::MAX_MAP_NAME_LEN,
+    types::{
+        character_info::{NetworkCharacterInfo, NetworkSkinInfo},
+        emoticons::EmoticonType,
+        flag::FlagType,
+        game::{GameTickCooldown, GameTickCooldownAndLastActionCounter, GameTickType},
+        id_gen::{IdGenerator, IdGeneratorIdType},
+        id_types::{CharacterId, StageId},
+        input::{cursor::CharacterInputCursor, CharacterInput},
+        laser::LaserType,
+        network_stats::PlayerNetworkStats,
+        pickup::PickupType,
+        player_info::PlayerUniqueId,
+        render::{
+            character::{CharacterBuff, PlayerCameraMode, TeeEye},
+            game::game_match::MatchSide,
+        },
+        resource_key::NetworkResourceKey,
+        snapshot::SnapshotLocalPlayer,
+        weapons::WeaponType,
+    },
+};
+use map_convert_lib::legacy_to_new::{legacy_to_new_from_buf, LegacyMapToNewOutput};
+use math::{
+    colors::legacy_color_to_rgba,
+    math::{
+        distance, normalize,
+        vector::{dvec2, ubvec4, vec2},
+    },
+};
+use num::FromPrimitive;
+use pool::{
+    datatypes::{PoolFxLinkedHashMap, PoolVec},
+    mt_recycle::Recycle as MtRecycle,
+    rc::PoolRc,
+    recycle::Recycle,
+};
+use vanilla::{
+    collision::collision::{Collision, Tunings},
+    entities::{
+        character::{
+            character::{BuffProps, CharacterCore},
+            hook::character_hook::{Hook, HookState},
+            player::player::PlayerInfo,
+        },
+        entity::entity::calc_pos_and_vel,
+        flag::flag::FlagCore,
+        laser::laser::LaserCore,
+        pickup::pickup::PickupCore,
+        projectile::projectile::ProjectileCore,
+    },
+    match_state::match_state::{Match, MatchState, MatchType, MatchWinner},
+    snapshot::snapshot::{
+        Snapshot, SnapshotCharacter, SnapshotCharacterPhasedState, SnapshotCharacterPlayerTy,
+        SnapshotFlag, SnapshotLaser, SnapshotMatchManager, SnapshotPickup, SnapshotPool,
+        SnapshotProjectile, SnapshotStage, SnapshotWorld, SnapshotWorldPool,
+    },
+    state::state::TICKS_PER_SECOND,
+};
+
+use crate::{
+    legacy_demo::{
+        bytes_to_ints, LegacyDemo, LegacyDemoChunk, LegacyDemoChunkType, LegacyUnpacker,
+    },
+    legacy_snapshot::LegacySnapshot,
+    netobj::{
+        LegacyCharacter, LegacyClientInfo, LegacyFlag, LegacyGameData, LegacyGameInfo, LegacyLaser,
+        LegacyPickup, LegacyPlayerInfo, LegacyProjectile, FLAG_TAKEN, GAMEFLAG_TEAMS,
+        GAMESTATEFLAG_GAMEOVER, GAMESTATEFLAG_PAUSED, GAMESTATEFLAG_SUDDENDEATH, HOOK_FLYING,
+        HOOK_GRABBED, HOOK_RETRACT_END, HOOK_RETRACT_START, NETOBJTYPE_CHARACTER,
+        NETOBJTYPE_CLIENTINFO, NETOBJTYPE_FLAG, NETOBJTYPE_GAMEDATA, NETOBJTYPE_GAMEINFO,
+        NETOBJTYPE_LASER, NETOBJTYPE_PICKUP, NETOBJTYPE_PLAYERINFO, NETOBJTYPE_PROJECTILE,
+        POWERUP_ARMOR, POWERUP_HEALTH, POWERUP_NINJA, POWERUP_WEAPON, TEAM_SPECTATORS,
+        WEAPON_NINJA,
+    },
+    reckoning::evolve,
+};
+
+const NETMSGTYPE_SV_MOTD: i32 = 1;
+const NETMSGTYPE_SV_CHAT: i32 = 3;
+const NETMSGTYPE_SV_KILLMSG: i32 = 4;
+const NETMSGTYPE_SV_EMOTICON: i32 = 10;
+
+/// Legacy weapon ids of kill messages that are no real weapons.
+const WEAPON_GAME: i32 = -3;
+const WEAPON_SELF: i32 = -2;
+const WEAPON_WORLD: i32 = -1;
+
+/// How long an emoticon is displayed.
+const EMOTICON_TICKS: GameTickType = 2 * TICKS_PER_SECOND;
+
+#[derive(Debug)]
+pub struct LegacyDemoToNewOutput {
+    /// The converted demo file.
+    pub demo: Vec<u8>,
+    /// The map the demo was recorded on, converted to the new format.
+    pub map: LegacyMapToNewOutput,
+    /// The serialized [`LegacyDemoToNewOutput::map`].
+    pub map_file: Vec<u8>,
+    pub map_name: NetworkReducedAsciiString<MAX_MAP_NAME_LEN>,
+    pub map_hash: Hash,
+}
+
+/// Converts a legacy (0.6/DDNet) demo, including the map
+/// that is embedded inside the demo.
+///
+/// The snapshots are converted to snapshots of the vanilla mod.
+pub fn legacy_demo_to_new(
+    demo_file: &[u8],
+    io: &Io,
+    thread_pool: &Arc<rayon::ThreadPool>,
+    optimize: bool,
+) -> anyhow::Result<LegacyDemoToNewOutput> {
+    let demo = LegacyDemo::read(demo_file)?;
+    anyhow::ensure!(
+        !demo.map.is_empty(),
+        "the demo does not contain the map, which is required for the conversion"
+    );
+
+    let map_name = NetworkReducedAsciiString::from_str_lossy(&demo.header.map_name);
+    let map = legacy_to_new_from_buf(
+        demo.map.to_vec(),
+        map_name.as_str(),
+        &IoFileSys::from(io),
+        thread_pool,
+        optimize,
+    )?;
+    let mut map_file: Vec<u8> = Default::default();
+    map.map.write(&mut map_file, thread_pool)?;
+    let map_hash = generate_hash_for(&map_file);
+
+    let collision = Collision::new(&map.map.groups.physics, true)?;
+
+    let (sender, receiver) = sync_channel(1);
+    let mut recorder = DemoRecorder::new(
+        DemoRecorderCreateProps {
+            base: DemoRecorderCreatePropsBase {
+                map: map_name.clone(),
+                map_hash,
+                game_options: Default::default(),
+                required_resources: Default::default(),
+                physics_module: GameModification::Native,
+                render_module: RenderModification::Native,
+                physics_group_name: "vanilla".try_into().unwrap(),
+            },
+            io: io.clone(),
+            in_memory: Some(sender),
+        },
+        NonZeroU64::new(TICKS_PER_SECOND).unwrap(),
+        None,
+        Some("legacy".to_string()),
+    );
+
+    let mut converter = LegacyDemoConverter::new(collision);
+    let mut markers = demo.timeline_markers.iter().copied().peekable();
+    for chunk in demo.chunks() {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                // legacy demos of crashed clients are often truncated,
+                // everything until the broken chunk is still valid
+                log::warn!("stopped reading the demo at a broken chunk: {err}");
+                break;
+            }
+        };
+        converter.first_tick.get_or_insert(chunk.tick);
+        while let Some(marker) = markers.next_if(|&marker| marker <= chunk.tick) {
+            recorder.add_event(converter.monotonic_tick(marker), DemoEvent::Marker);
+        }
+        converter.convert_chunk(chunk, &mut recorder)?;
+    }
+    let last_tick = converter.last_tick.unwrap_or_default();
+    for marker in markers {
+        recorder.add_event(
+            converter.monotonic_tick(marker).min(last_tick),
+            DemoEvent::Marker,
+        );
+    }
+
+    drop(recorder);
+    let demo = receiver.recv().map_err(|err| anyhow!(err))??;
+
+    Ok(LegacyDemoToNewOutput {
+        demo,
+        map,
+        map_file,
+        map_name,
+        map_hash,
+    })
+}
+
+/// Legacy tee colors are in HSL with a limited lightness.
+fn tee_color(legacy_color: i32) -> ubvec4 {
+    let lightness = legacy_color & 0xff;
+    let lightness = 0x80 + lightness / 2;
+    legacy_color_to_rgba((legacy_color & !0xff) | lightness, true)
+}
+
+/// Creates a counter that says that an action happened `ticks_passed` ticks ago
+/// and that the action takes `len` ticks.
+fn action_counter(
+    ticks_passed: GameTickType,
+    len: GameTickType,
+) -> GameTickCooldownAndLastActionCounter {
+    let len = NonZeroU64::new(len.max(1)).unwrap();
+    match NonZeroU64::new(len.get().saturating_sub(ticks_passed)) {
+        Some(ticks_left) => GameTickCooldownAndLastActionCounter::Cooldown {
+            ticks_left,
+            ticks_passed,
+            initial_cooldown_len: len,
+        },
+        None => GameTickCooldownAndLastActionCounter::LastActionCounter {
+            ticks_passed,
+            last_cooldown_len: len,
+        },
+    }
+}
+
+fn ms_to_ticks(ms: f32) -> GameTickType {
+    (TICKS_PER_SECOND as f32 * ms / 1000.0).ceil() as GameTickType
+}
+
+struct LegacyDemoConverter {
+    collision: Collision,
+    tunings: Tunings,
+
+    id_generator: IdGenerator,
+    event_id_generator: IdGenerator,
+    stage_id: StageId,
+    /// Owner of projectiles & lasers that have no character nearby.
+    unknown_owner: CharacterId,
+
+    snapshot_pool: SnapshotPool,
+    world_pool: SnapshotWorldPool,
+
+    /// The last snapshot, which snapshot deltas apply to.
+    last_snapshot: LegacySnapshot,
+    first_tick: Option<i32>,
+    last_tick: Option<u64>,
+
+    /// Legacy client id to character id.
+    characters: HashMap<i32, CharacterId>,
+    client_infos: HashMap<i32, (LegacyClientInfo, NetworkCharacterInfo)>,
+    /// Last known position of characters, which is used for dead characters.
+    character_positions: HashMap<i32, vec2>,
+    /// Emoticon and the legacy tick it was sent in.
+    emoticons: HashMap<i32, (EmoticonType, i32)>,
+
+    /// Legacy item key to entity id.
+    items: HashMap<(i32, i32), IdGeneratorIdType>,
+    /// Legacy item key to the owner that was assumed when
+    /// the item was seen first.
+    item_owners: HashMap<(i32, i32), CharacterId>,
+}
+
+impl LegacyDemoConverter {
+    fn new(collision: Collision) -> Self {
+        let id_generator = IdGenerator::new();
+        // same ids as the game's id generator, which creates
+        // the game state first and then the first stage.
+        let _ = id_generator.next_id::<IdGeneratorIdType>();
+        let stage_id = id_generator.next_id();
+        let unknown_owner = id_generator.next_id();
+
+        Self {
+            collision,
+            tunings: Tunings::default(),
+
+            id_generator,
+            event_id_generator: IdGenerator::new(),
+            stage_id,
+            unknown_owner,
+
+            snapshot_pool: SnapshotPool::new(64, 1),
+            world_pool: SnapshotWorldPool::new(64),
+
+            last_snapshot: Default::default(),
+            first_tick: None,
+            last_tick: None,
+
+            characters: Default::default(),
+            client_infos: Default::default(),
+            character_positions: Default::default(),
+            emoticons: Default::default(),
+
+            items: Default::default(),
+            item_owners: Default::default(),
+        }
+    }
+
+    fn monotonic_tick(&self, tick: i32) -> u64 {
+        tick.saturating_sub(self.first_tick.unwrap_or(tick)).max(0) as u64
+    }
+
+    fn character_id(&mut self, client_id: i32) -> CharacterId {
+        *self
+            .characters
+            .entry(client_id)
+            .or_insert_with(|| self.id_generator.next_id())
+    }
+
+    fn item_id<T: From<IdGeneratorIdType>>(&mut self, ty: i32, id: i32) -> T {
+        (*self
+            .items
+            .entry((ty, id))
+            .or_insert_with(|| self.id_generator.next_id()))
+        .into()
+    }
+
+    fn convert_chunk(
+        &mut self,
+        chunk: LegacyDemoChunk,
+        recorder: &mut DemoRecorder,
+    ) -> anyhow::Result<()> {
+        let monotonic_tick = self.monotonic_tick(chunk.tick);
+        self.last_tick = Some(monotonic_tick);
+        match chunk.ty {
+            LegacyDemoChunkType::Snapshot | LegacyDemoChunkType::Delta => {
+                let ints = bytes_to_ints(&chunk.data);
+                let snapshot = if chunk.ty == LegacyDemoChunkType::Snapshot {
+                    LegacySnapshot::from_ints(&ints)
+                } else {
+                    self.last_snapshot.apply_delta(&ints)
+                };
+                // a single broken snapshot is skipped, the next
+                // full snapshot or delta usually fixes it.
+                match snapshot {
+                    Ok(snapshot) => {
+                        let new_snapshot = self.convert_snapshot(&snapshot, chunk.tick);
+                        self.last_snapshot = snapshot;
+                        let mut snapshot_data: Vec<u8> = Default::default();
+                        bincode::serde::encode_into_std_write(
+                            &new_snapshot,
+                            &mut snapshot_data,
+                            bincode::config::standard(),
+                        )?;
+                        recorder.add_snapshot(monotonic_tick, snapshot_data);
+                    }
+                    Err(err) => {
+                        log::warn!("skipped a broken snapshot at tick {}: {err}", chunk.tick);
+                    }
+                }
+            }
+            LegacyDemoChunkType::Message => match self.convert_message(&chunk.data, chunk.tick) {
+                Ok(Some(event)) => recorder.add_event(monotonic_tick, event),
+                Ok(None) => {}
+                Err(err) => {
+                    log::warn!("skipped a broken message at tick {}: {err}", chunk.tick);
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn game_events(&mut self, event: GameWorldEvent) -> DemoEvent {
+        let mut events = PoolFxLinkedHashMap::new_without_pool();
+        events.insert(self.event_id_generator.next_id::<EventId>(), event);
+        let mut worlds = PoolFxLinkedHashMap::new_without_pool();
+        worlds.insert(self.stage_id, GameWorldEvents { events });
+        DemoEvent::Game(GameEvents {
+            worlds,
+            event_id: self.event_id_generator.peek_next_id(),
+        })
+    }
+
+    fn convert_message(&mut self, data: &[u8], tick: i32) -> anyhow::Result<Option<DemoEvent>> {
+        let mut unpacker = LegacyUnpacker::new(data);
+        let msg_id = unpacker.get_int()?;
+        // system messages are never recorded
+        if msg_id & 1 != 0 {
+            return Ok(None);
+        }
+        match msg_id >> 1 {
+            NETMSGTYPE_SV_MOTD => {
+                let msg = unpacker.get_string()?;
+                Ok((!msg.is_empty()).then(|| {
+                    self.game_events(GameWorldEvent::Notification(
+                        GameWorldNotificationEvent::Motd {
+                            msg: MtRecycle::from_without_pool(NetworkString::new_lossy(msg)),
+                        },
+                    ))
+                }))
+            }
+            NETMSGTYPE_SV_CHAT => {
+                let team = unpacker.get_int()?;
+                let client_id = unpacker.get_int()?;
+                let msg = unpacker.get_string()?;
+                if client_id < 0 {
+                    return Ok(Some(self.game_events(GameWorldEvent::Notification(
+                        GameWorldNotificationEvent::System(GameWorldSystemMessage::Custom(
+                            MtRecycle::from_without_pool(NetworkString::new_lossy(msg)),
+                        )),
+                    ))));
+                }
+                let id = self.character_id(client_id);
+                let (name, skin, skin_info) = self
+                    .client_infos
+                    .get(&client_id)
+                    .map(|(_, info)| (info.name.clone(), info.skin.clone(), info.skin_info))
+                    .unwrap_or_else(|| {
+                        let info = NetworkCharacterInfo::explicit_default();
+                        (info.name, info.skin, info.skin_info)
+                    });
+                Ok(Some(DemoEvent::Chat(Box::new(NetChatMsg {
+                    sender: ChatPlayerInfo {
+                        id,
+                        name,
+                        skin,
+                        skin_info,
+                    },
+                    msg,
+                    channel: if team != 0 {
+                        NetChatMsgPlayerChannel::GameTeam
+                    } else {
+                        NetChatMsgPlayerChannel::Global
+                    },
+                }))))
+            }
+            NETMSGTYPE_SV_KILLMSG => {
+                let killer = unpacker.get_int()?;
+                let victim = unpacker.get_int()?;
+                let weapon = unpacker.get_int()?;
+                let weapon = match weapon {
+                    WEAPON_GAME => return Ok(None),
+                    WEAPON_SELF | WEAPON_WORLD => GameWorldActionKillWeapon::World,
+                    WEAPON_NINJA => GameWorldActionKillWeapon::Ninja,
+                    weapon => GameWorldActionKillWeapon::Weapon {
+                        weapon: WeaponType::from_i32(weapon).unwrap_or_default(),
+                    },
+                };
+                let killer = (killer >= 0 && killer != victim).then(|| self.character_id(killer));
+                let mut victims = PoolVec::new_without_pool();
+                victims.push(self.character_id(victim));
+                Ok(Some(self.game_events(GameWorldEvent::Notification(
+                    GameWorldNotificationEvent::Action(GameWorldAction::Kill {
+                        killer,
+                        assists: PoolVec::new_without_pool(),
+                        victims,
+                        weapon,
+                        flags: KillFlags::empty(),
+                    }),
+                ))))
+            }
+            NETMSGTYPE_SV_EMOTICON => {
+                let client_id = unpacker.get_int()?;
+                let emoticon = unpacker.get_int()?;
+                if let Some(emoticon) = EmoticonType::from_i32(emoticon) {
+                    self.emoticons.insert(client_id, (emoticon, tick));
+                }
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn convert_match(&self, snapshot: &LegacySnapshot, tick: i32) -> Match {
+        let game_info = snapshot
+            .item(NETOBJTYPE_GAMEINFO, 0)
+            .and_then(LegacyGameInfo::from_ints)
+            .unwrap_or_default();
+        let game_data = snapshot
+            .item(NETOBJTYPE_GAMEDATA, 0)
+            .and_then(LegacyGameData::from_ints)
+            .unwrap_or_default();
+
+        let is_sided = game_info.game_flags & GAMEFLAG_TEAMS != 0;
+        let round_ticks_passed = tick.saturating_sub(game_info.round_start_tick).max(0) as u64;
+        let round_ticks_left: GameTickCooldown = if game_info.time_limit > 0 {
+            (game_info.time_limit as u64 * 60 * TICKS_PER_SECOND)
+                .saturating_sub(round_ticks_passed)
+                .into()
+        } else {
+            Default::default()
+        };
+        let state_flags = game_info.game_state_flags;
+        let state = if state_flags & GAMESTATEFLAG_GAMEOVER != 0 {
+            let winner = if is_sided {
+                MatchWinner::Side(if game_data.teamscore_blue > game_data.teamscore_red {
+                    MatchSide::Blue
+                } else {
+                    MatchSide::Red
+                })
+            } else {
+                let best = snapshot
+                    .items_of_type(NETOBJTYPE_PLAYERINFO)
+                    .filter_map(|item| LegacyPlayerInfo::from_ints(&item.data))
+                    .filter(|info| info.team != TEAM_SPECTATORS)
+                    .max_by_key(|info| info.score);
+                MatchWinner::Character(
+                    best.and_then(|info| self.characters.get(&info.client_id).copied())
+                        .unwrap_or(self.unknown_owner),
+                )
+            };
+            MatchState::GameOver {
+                winner,
+                new_game_in: Default::default(),
+                round_ticks_passed,
+                by_cooldown: false,
+            }
+        } else if state_flags & GAMESTATEFLAG_SUDDENDEATH != 0 {
+            if state_flags & GAMESTATEFLAG_PAUSED != 0 {
+                MatchState::PausedSuddenDeath {
+                    round_ticks_passed,
+                    by_cooldown: false,
+                }
+            } else {
+                MatchState::SuddenDeath {
+                    round_ticks_passed,
+                    by_cooldown: false,
+                }
+            }
+        } else if state_flags & GAMESTATEFLAG_PAUSED != 0 {
+            MatchState::Paused {
+                round_ticks_passed,
+                round_ticks_left,
+            }
+        } else {
+            MatchState::Running {
+                round_ticks_passed,
+                round_ticks_left,
+            }
+        };
+
+        Match {
+            ty: if is_sided {
+                MatchType::Sided {
+                    scores: [
+                        game_data.teamscore_red as i64,
+                        game_data.teamscore_blue as i64,
+                    ],
+                }
+            } else {
+                MatchType::Solo
+            },
+            state,
+            balance_tick: Default::default(),
+        }
+    }
+
+    fn update_client_infos(&mut self, snapshot: &LegacySnapshot) {
+        for item in snapshot.items_of_type(NETOBJTYPE_CLIENTINFO) {
+            let Some(client_info) = LegacyClientInfo::from_ints(&item.data) else {
+                continue;
+            };
+            if self
+                .client_infos
+                .get(&item.id)
+                .is_some_and(|(info, _)| *info == client_info)
+            {
+                continue;
+            }
+            let mut info = NetworkCharacterInfo::explicit_default();
+            info.name = NetworkString::new_lossy(client_info.name.clone());
+            info.clan = NetworkString::new_lossy(client_info.clan.clone());
+            info.skin = NetworkResourceKey::from_str_lossy(&client_info.skin);
+            info.skin_info = if client_info.use_custom_color {
+                NetworkSkinInfo::Custom {
+                    body_color: tee_color(client_info.color_body),
+                    feet_color: tee_color(client_info.color_feet),
+                }
+            } else {
+                NetworkSkinInfo::Original
+            };
+            self.client_infos.insert(item.id, (client_info, info));
+        }
+    }
+
+    fn convert_character(
+        &self,
+        id: CharacterId,
+        player: &LegacyPlayerInfo,
+        character: Option<LegacyCharacter>,
+        is_sided: bool,
+        tick: i32,
+    ) -> SnapshotCharacter {
+        let mut core = CharacterCore::default();
+        let mut reusable_core = self.world_pool.character_reusable_cores_pool.new();
+
+        core.side = is_sided.then(|| {
+            if player.team == 0 {
+                MatchSide::Red
+            } else {
+                MatchSide::Blue
+            }
+        });
+        if let Some((emoticon, emoticon_tick)) = self
+            .emoticons
+            .get(&player.client_id)
+            .filter(|(_, emoticon_tick)| *emoticon_tick <= tick)
+        {
+            let ticks_passed = (tick - *emoticon_tick) as u64;
+            if ticks_passed < EMOTICON_TICKS {
+                core.cur_emoticon = Some(*emoticon);
+                core.emoticon_tick = action_counter(ticks_passed, EMOTICON_TICKS);
+            }
+        }
+
+        let pos;
+        let phased;
+        match character {
+            Some(character) => {
+                let legacy_core = character.core;
+                pos = vec2::new(legacy_core.x as f32, legacy_core.y as f32);
+                core.core.vel = vec2::new(
+                    legacy_core.vel_x as f32 / 256.0,
+                    legacy_core.vel_y as f32 / 256.0,
+                );
+                core.health = character.health.max(0) as u32;
+                core.armor = character.armor.max(0) as u32;
+                core.active_weapon = WeaponType::from_i32(character.weapon).unwrap_or_default();
+                if character.weapon == WEAPON_NINJA {
+                    let angle = legacy_core.angle as f32 / 256.0;
+                    reusable_core.buffs.insert(
+                        CharacterBuff::Ninja,
+                        BuffProps {
+                            remaining_tick: Default::default(),
+                            interact_tick: Default::default(),
+                            interact_cursor_dir: vec2::new(angle.cos(), angle.sin()),
+                            interact_val: 0.0,
+                        },
+                    );
+                }
+                if character.attack_tick > 0 && character.attack_tick <= tick {
+                    let fire_delay = if character.weapon == WEAPON_NINJA {
+                        self.tunings.ninja_fire_delay
+                    } else {
+                        match core.active_weapon {
+                            WeaponType::Hammer => self.tunings.hammer_fire_delay,
+                            WeaponType::Gun => self.tunings.gun_fire_delay,
+                            WeaponType::Shotgun => self.tunings.shotgun_fire_delay,
+                            WeaponType::Grenade => self.tunings.grenade_fire_delay,
+                            WeaponType::Laser => self.tunings.laser_fire_delay,
+                        }
+                    };
+                    core.attack_recoil = action_counter(
+                        (tick - character.attack_tick) as u64,
+                        ms_to_ticks(fire_delay),
+                    );
+                }
+                core.eye = match character.emote {
+                    1 => TeeEye::Pain,
+                    2 => TeeEye::Happy,
+                    3 => TeeEye::Surprised,
+                    4 => TeeEye::Angry,
+                    5 => TeeEye::Blink,
+                    _ => TeeEye::Normal,
+                };
+
+                let mut input = CharacterInput::default();
+                let angle = legacy_core.angle as f64 / 256.0;
+                input
+                    .cursor
+                    .set(CharacterInputCursor::from_vec2(&dvec2::new(
+                        angle.cos() * 256.0,
+                        angle.sin() * 256.0,
+                    )));
+                input.state.dir.set(legacy_core.direction.clamp(-1, 1));
+                core.set_input(input);
+
+                let hook_state = match legacy_core.hook_state {
+                    state if (HOOK_RETRACT_START..HOOK_RETRACT_END).contains(&state) => {
+                        Some(HookState::RetractStart)
+                    }
+                    HOOK_RETRACT_END => Some(HookState::RetractEnd),
+                    HOOK_FLYING => Some(HookState::HookFlying),
+                    HOOK_GRABBED => Some(HookState::HookGrabbed),
+                    _ => None,
+                };
+                let hook = match hook_state {
+                    Some(hook_state) => {
+                        let hook_pos =
+                            vec2::new(legacy_core.hook_x as f32, legacy_core.hook_y as f32);
+                        Hook::Active {
+                            hook_pos,
+                            hook_dir: vec2::new(
+                                legacy_core.hook_dx as f32 / 256.0,
+                                legacy_core.hook_dy as f32 / 256.0,
+                            ),
+                            hook_tele_base: hook_pos,
+                            hook_tick: legacy_core.hook_tick,
+                            hook_state,
+                        }
+                    }
+                    None => Hook::None,
+                };
+                let hooked_char = (legacy_core.hooked_player >= 0)
+                    .then(|| self.characters.get(&legacy_core.hooked_player).copied())
+                    .flatten();
+                phased = SnapshotCharacterPhasedState::Normal {
+                    hook: (hook, hooked_char),
+                };
+            }
+            None => {
+                pos = self
+                    .character_positions
+                    .get(&player.client_id)
+                    .copied()
+                    .unwrap_or_default();
+                phased = SnapshotCharacterPhasedState::Dead {
+                    respawn_in_ticks: Default::default(),
+                };
+            }
+        }
+
+        let info = self
+            .client_infos
+            .get(&player.client_id)
+            .map(|(_, info)| info.clone())
+            .unwrap_or_else(NetworkCharacterInfo::explicit_default);
+
+        SnapshotCharacter {
+            core,
+            reusable_core,
+            player_info: PlayerInfo {
+                player_info: PoolRc::from_item_without_pool(info),
+                version: 1,
+                unique_identifier: PlayerUniqueId::CertFingerprint(generate_hash_for(
+                    &player.client_id.to_le_bytes(),
+                )),
+                account_name: None,
+                id: player.client_id as u64,
+            },
+            ty: SnapshotCharacterPlayerTy::Player(PlayerNetworkStats {
+                ping: Duration::from_millis(player.latency.max(0) as u64),
+                packet_loss: 0.0,
+            }),
+            pos,
+            phased,
+            score: player.score as i64,
+            game_el_id: id,
+        }
+    }
+
+    /// The character that is closest to `pos`.
+    fn closest_character(&self, characters: &[(CharacterId, vec2)], pos: &vec2) -> CharacterId {
+        characters
+            .iter()
+            .min_by(|(_, a), (_, b)| distance(a, pos).total_cmp(&distance(b, pos)))
+            .map(|(id, _)| *id)
+            .unwrap_or(self.unknown_owner)
+    }
+
+    fn convert_snapshot(&mut self, snapshot: &LegacySnapshot, tick: i32) -> Snapshot {
+        self.update_client_infos(snapshot);
+        let game_match = self.convert_match(snapshot, tick);
+        let is_sided = matches!(game_match.ty, MatchType::Sided { .. });
+
+        let mut world = SnapshotWorld::new(&self.world_pool);
+
+        // characters
+        let players: Vec<LegacyPlayerInfo> = snapshot
+            .items_of_type(NETOBJTYPE_PLAYERINFO)
+            .filter_map(|item| LegacyPlayerInfo::from_ints(&item.data))
+            .filter(|player| player.team != TEAM_SPECTATORS)
+            .collect();
+        self.characters
+            .retain(|client_id, _| players.iter().any(|p| p.client_id == *client_id));
+        self.client_infos
+            .retain(|client_id, _| snapshot.item(NETOBJTYPE_CLIENTINFO, *client_id).is_some());
+        for player in &players {
+            self.character_id(player.client_id);
+        }
+
+        let mut local_player = None;
+        let mut character_positions = Vec::new();
+        for player in &players {
+            let id = self.characters[&player.client_id];
+            let character = snapshot
+                .item(NETOBJTYPE_CHARACTER, player.client_id)
+                .and_then(LegacyCharacter::from_ints)
+                .map(|mut character| {
+                    evolve(&mut character.core, tick, &self.collision, &self.tunings);
+                    character
+                });
+            if let Some(character) = &character {
+                let pos = vec2::new(character.core.x as f32, character.core.y as f32);
+                self.character_positions.insert(player.client_id, pos);
+                character_positions.push((id, pos));
+            }
+            if player.local != 0 {
+                local_player = Some((id, player.client_id));
+            }
+            let character = self.convert_character(id, player, character, is_sided, tick);
+            world.characters.insert(id, character);
+        }
+        self.character_positions
+            .retain(|client_id, _| self.characters.contains_key(client_id));
+
+        // make sure that items that were removed get a new id
+        self.items
+            .retain(|(ty, id), _| snapshot.item(*ty, *id).is_some());
+        self.item_owners
+            .retain(|(ty, id), _| snapshot.item(*ty, *id).is_some());
+
+        for item in snapshot.items_of_type(NETOBJTYPE_PROJECTILE) {
+            let Some(projectile) = LegacyProjectile::from_ints(&item.data) else {
+                continue;
+            };
+            let ty = WeaponType::from_i32(projectile.ty).unwrap_or(WeaponType::Gun);
+            let (curvature, speed, life_time) = match ty {
+                WeaponType::Grenade => (
+                    self.tunings.grenade_curvature,
+                    self.tunings.grenade_speed,
+                    self.tunings.grenade_lifetime,
+                ),
+                WeaponType::Shotgun => (
+                    self.tunings.shotgun_curvature,
+                    self.tunings.shotgun_speed,
+                    self.tunings.shotgun_lifetime,
+                ),
+                _ => (
+                    self.tunings.gun_curvature,
+                    self.tunings.gun_speed,
+                    self.tunings.gun_lifetime,
+                ),
+            };
+            let start_pos = vec2::new(projectile.x as f32, projectile.y as f32);
+            let direction = vec2::new(
+                projectile.vel_x as f32 / 100.0,
+                projectile.vel_y as f32 / 100.0,
+            );
+            let ticks_passed = tick.saturating_sub(projectile.start_tick).max(0);
+            let mut pos = start_pos;
+            let mut vel = direction;
+            calc_pos_and_vel(
+                &mut pos,
+                &mut vel,
+                curvature,
+                speed,
+                ticks_passed as f32 / TICKS_PER_SECOND as f32,
+            );
+
+            let owner = match self.item_owners.get(&(item.ty, item.id)) {
+                Some(owner) => *owner,
+                None => {
+                    let owner = self.closest_character(&character_positions, &start_pos);
+                    self.item_owners.insert((item.ty, item.id), owner);
+                    owner
+                }
+            };
+            let id = self.item_id(item.ty, item.id);
+            world.projectiles.insert(
+                id,
+                SnapshotProjectile {
+                    core: ProjectileCore {
+                        pos,
+                        vel,
+                        direction: normalize(&direction),
+                        life_span: (life_time * TICKS_PER_SECOND as f32) as i32 - ticks_passed,
+                        damage: 1,
+                        force: 0.0,
+                        is_explosive: ty == WeaponType::Grenade,
+                        ty,
+                        side: None,
+                    },
+                    reusable_core: self.world_pool.projectile_reusable_cores_pool.new(),
+                    game_el_id: id,
+                    owner_game_el_id: owner,
+                },
+            );
+        }
+
+        let laser_len = ms_to_ticks(self.tunings.laser_bounce_delay);
+        for item in snapshot.items_of_type(NETOBJTYPE_LASER) {
+            let Some(laser) = LegacyLaser::from_ints(&item.data) else {
+                continue;
+            };
+            let pos = vec2::new(laser.x as f32, laser.y as f32);
+            let from = vec2::new(laser.from_x as f32, laser.from_y as f32);
+            let owner = match self.item_owners.get(&(item.ty, item.id)) {
+                Some(owner) => *owner,
+                None => {
+                    let owner = self.closest_character(&character_positions, &from);
+                    self.item_owners.insert((item.ty, item.id), owner);
+                    owner
+                }
+            };
+            let id = self.item_id(item.ty, item.id);
+            world.lasers.insert(
+                id,
+                SnapshotLaser {
+                    core: LaserCore {
+                        pos,
+                        from,
+                        dir: normalize(&(pos - from)),
+                        ty: LaserType::Rifle,
+                        energy: 0.0,
+                        bounces: 0,
+                        next_eval_in: GameTickCooldownAndLastActionCounter::LastActionCounter {
+                            ticks_passed: tick.saturating_sub(laser.start_tick).max(0) as u64,
+                            last_cooldown_len: NonZeroU64::new(laser_len.max(1)).unwrap(),
+                        },
+                        can_hit_others: false,
+                        can_hit_own: false,
+                        side: None,
+                        tele_pos: None,
+                    },
+                    reusable_core: self.world_pool.laser_reusable_cores_pool.new(),
+                    game_el_id: id,
+                    owner_game_el_id: owner,
+                },
+            );
+        }
+
+        for item in snapshot.items_of_type(NETOBJTYPE_PICKUP) {
+            let Some(pickup) = LegacyPickup::from_ints(&item.data) else {
+                continue;
+            };
+            let ty = match pickup.ty {
+                POWERUP_HEALTH => PickupType::PowerupHealth,
+                POWERUP_ARMOR => PickupType::PowerupArmor,
+                POWERUP_NINJA => PickupType::PowerupNinja,
+                POWERUP_WEAPON => match WeaponType::from_i32(pickup.subtype) {
+                    Some(weapon) => PickupType::PowerupWeapon(weapon),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            let id = self.item_id(item.ty, item.id);
+            world.pickups.insert(
+                id,
+                SnapshotPickup {
+                    core: PickupCore {
+                        pos: vec2::new(pickup.x as f32, pickup.y as f32),
+                        ty,
+                    },
+                    reusable_core: self.world_pool.pickup_reusable_cores_pool.new(),
+                    game_el_id: id,
+                },
+            );
+        }
+
+        let game_data = snapshot
+            .item(NETOBJTYPE_GAMEDATA, 0)
+            .and_then(LegacyGameData::from_ints);
+        for item in snapshot.items_of_type(NETOBJTYPE_FLAG) {
+            let Some(flag) = LegacyFlag::from_ints(&item.data) else {
+                continue;
+            };
+            let (ty, carrier) = if flag.team == 0 {
+                (FlagType::Red, game_data.map(|data| data.flag_carrier_red))
+            } else {
+                (FlagType::Blue, game_data.map(|data| data.flag_carrier_blue))
+            };
+            let carrier = carrier
+                .filter(|&carrier| carrier > FLAG_TAKEN)
+                .and_then(|carrier| self.characters.get(&carrier).copied());
+            let pos = vec2::new(flag.x as f32, flag.y as f32);
+            let id = self.item_id(item.ty, item.id);
+            let flag = SnapshotFlag {
+                core: FlagCore {
+                    pos,
+                    spawn_pos: pos,
+                    vel: vec2::default(),
+                    ty,
+                    carrier,
+                    drop_ticks: None,
+                    non_linear_event: 0,
+                },
+                reusable_core: self.world_pool.flag_reusable_cores_pool.new(),
+                game_el_id: id,
+            };
+            match ty {
+                FlagType::Red => world.red_flags.insert(id, flag),
+                FlagType::Blue => world.blue_flags.insert(id, flag),
+            };
+        }
+
+        let mut res = Snapshot::new(
+            &self.snapshot_pool,
+            self.id_generator.peek_next_id(),
+            None,
+            self.tunings,
+        );
+        if let Some((id, client_id)) = local_player {
+            res.local_players.insert(
+                id,
+                SnapshotLocalPlayer {
+                    id: client_id as u64,
+                    input_cam_mode: PlayerCameraMode::Default,
+                },
+            );
+        }
+        res.stages.insert(
+            self.stage_id,
+            SnapshotStage {
+                world,
+                match_manager: SnapshotMatchManager::new(game_match),
+                game_el_id: self.stage_id,
+                stage_name: Recycle::from_without_pool(NetworkString::new_lossy("")),
+                stage_color: ubvec4::new(0, 0, 0, 0),
+            },
+        );
+        res
+    }
+}
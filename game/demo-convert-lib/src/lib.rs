@@ -0,0 +1,313 @@
+pub mod huffman;
+pub mod legacy_demo;
+pub mod legacy_snapshot;
+pub mod legacy_to_new;
+pub mod netobj;
+mod reckoning;
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use base::hash::generate_hash_for;
+    use base_fs::filesys::FileSystem;
+    use base_http::http::HttpClient;
+    use base_io::io::Io;
+    use demo::{editor::DemoEditor, DemoEvent};
+
+    use crate::{
+        huffman::Huffman,
+        legacy_demo::{
+            bytes_to_ints, compress_ints, decompress_ints, pack_int, unpack_int, LegacyDemo,
+            LegacyDemoChunkType,
+        },
+        legacy_snapshot::{LegacySnapshot, LegacySnapshotItem},
+        legacy_to_new::legacy_demo_to_new,
+        netobj::{
+            ints_to_str, str_to_ints, NETOBJTYPE_CHARACTER, NETOBJTYPE_CLIENTINFO,
+            NETOBJTYPE_PLAYERINFO,
+        },
+    };
+
+    const CHUNK_SNAPSHOT: u8 = 1;
+    const CHUNK_MESSAGE: u8 = 2;
+    const CHUNK_DELTA: u8 = 3;
+
+    /// A version 6 demo header, followed by the timeline markers & the map.
+    fn legacy_demo_header(map: &[u8], markers: &[i32]) -> Vec<u8> {
+        let mut demo = vec![0; 176];
+        demo[..8].copy_from_slice(b"TWDEMO\0\x06");
+        let mut write_str = |offset: usize, s: &str| {
+            demo[offset..offset + s.len()].copy_from_slice(s.as_bytes());
+        };
+        write_str(8, "0.6 626fce9a778ab4ec");
+        write_str(72, "ctf1");
+        write_str(144, "client");
+        write_str(156, "2024-01-01_12-00-00");
+        demo[136..140].copy_from_slice(&(map.len() as i32).to_be_bytes());
+        demo[152..156].copy_from_slice(&1i32.to_be_bytes());
+
+        demo.extend((markers.len() as i32).to_be_bytes());
+        for index in 0..64 {
+            demo.extend(markers.get(index).copied().unwrap_or(0).to_be_bytes());
+        }
+        demo.extend(map);
+        demo
+    }
+
+    fn write_tick(demo: &mut Vec<u8>, tick: i32) {
+        demo.push(0x80);
+        demo.extend(tick.to_be_bytes());
+    }
+
+    fn write_tick_delta(demo: &mut Vec<u8>, tick_delta: u8) {
+        demo.push(0x80 | 0x20 | tick_delta);
+    }
+
+    fn write_chunk(demo: &mut Vec<u8>, ty: u8, data: &[u8]) {
+        let data = Huffman::new().compress(&compress_ints(data));
+        match data.len() {
+            len @ 0..=29 => demo.push((ty << 5) | len as u8),
+            len @ 30..=255 => demo.extend([(ty << 5) | 30, len as u8]),
+            len => {
+                demo.push((ty << 5) | 31);
+                demo.extend((len as u16).to_le_bytes());
+            }
+        }
+        demo.extend(data);
+    }
+
+    fn ints_to_bytes(ints: &[i32]) -> Vec<u8> {
+        ints.iter().flat_map(|int| int.to_le_bytes()).collect()
+    }
+
+    fn character(x: i32, y: i32) -> Vec<i32> {
+        let mut data = vec![0; 22];
+        data[1] = x;
+        data[2] = y;
+        // health & armor
+        data[16] = 10;
+        data[17] = 5;
+        data
+    }
+
+    fn snapshot() -> LegacySnapshot {
+        let mut client_info = str_to_ints("nameless tee", 4);
+        client_info.extend(str_to_ints("", 3));
+        client_info.push(-1);
+        client_info.extend(str_to_ints("default", 6));
+        client_info.extend([0, 0, 0]);
+        LegacySnapshot {
+            items: vec![
+                LegacySnapshotItem {
+                    ty: NETOBJTYPE_PLAYERINFO,
+                    id: 0,
+                    data: vec![1, 0, 0, 3, 20],
+                },
+                LegacySnapshotItem {
+                    ty: NETOBJTYPE_CLIENTINFO,
+                    id: 0,
+                    data: client_info,
+                },
+                LegacySnapshotItem {
+                    ty: NETOBJTYPE_CHARACTER,
+                    id: 0,
+                    data: character(1000, 500),
+                },
+            ],
+        }
+    }
+
+    /// A chat message of client 0.
+    fn chat_msg(msg: &str) -> Vec<u8> {
+        let mut data = Vec::new();
+        pack_int(3 << 1, &mut data);
+        pack_int(0, &mut data);
+        pack_int(0, &mut data);
+        data.extend(msg.as_bytes());
+        data.push(0);
+        data
+    }
+
+    /// A snapshot & a chat message at tick 100,
+    /// followed by a delta two ticks later that moves the character.
+    fn legacy_demo(map: &[u8]) -> Vec<u8> {
+        let mut demo = legacy_demo_header(map, &[101]);
+        write_tick(&mut demo, 100);
+        write_chunk(
+            &mut demo,
+            CHUNK_SNAPSHOT,
+            &ints_to_bytes(&snapshot().to_ints()),
+        );
+        write_chunk(&mut demo, CHUNK_MESSAGE, &chat_msg("gl hf"));
+        write_tick_delta(&mut demo, 2);
+        let mut delta = vec![0, 1, 0, NETOBJTYPE_CHARACTER, 0];
+        delta.extend(character(32, 0));
+        write_chunk(&mut demo, CHUNK_DELTA, &ints_to_bytes(&delta));
+        demo
+    }
+
+    #[test]
+    fn huffman_roundtrip() {
+        let huffman = Huffman::new();
+        let data: Vec<u8> = (0..=255).chain([0, 0, 0, 1, 2, 3, 255]).collect();
+        let compressed = huffman.compress(&data);
+        assert_eq!(huffman.decompress(&compressed).unwrap(), data);
+        assert!(huffman.decompress(&[]).is_err());
+    }
+
+    #[test]
+    fn var_int_roundtrip() {
+        for val in [0, 1, -1, 63, 64, -64, -65, 1234567, i32::MAX, i32::MIN] {
+            let mut packed = Vec::new();
+            pack_int(val, &mut packed);
+            let (unpacked, rest) = unpack_int(&packed).unwrap();
+            assert_eq!(unpacked, val);
+            assert!(rest.is_empty());
+        }
+
+        let data: Vec<u8> = [5i32, -7, 100000]
+            .iter()
+            .flat_map(|val| val.to_le_bytes())
+            .collect();
+        assert_eq!(decompress_ints(&compress_ints(&data)).unwrap(), data);
+    }
+
+    #[test]
+    fn snapshot_delta() {
+        let snapshot = LegacySnapshot {
+            items: vec![
+                LegacySnapshotItem {
+                    ty: 4,
+                    id: 1,
+                    data: vec![10, 20, 0, 0],
+                },
+                LegacySnapshotItem {
+                    ty: 5,
+                    id: 0,
+                    data: vec![1, 2, 0],
+                },
+            ],
+        };
+        assert_eq!(
+            LegacySnapshot::from_ints(&snapshot.to_ints()).unwrap(),
+            snapshot
+        );
+
+        // delete the flag, move the pickup & add an item of unknown size
+        let delta = [1, 2, 0, (5 << 16), 4, 1, 5, -5, 0, 0, 100, 3, 2, 7, 8];
+        let next = snapshot.apply_delta(&delta).unwrap();
+        assert_eq!(next.item(4, 1), Some([15, 15, 0, 0].as_slice()));
+        assert_eq!(next.item(5, 0), None);
+        assert_eq!(next.item(100, 3), Some([7, 8].as_slice()));
+        assert!(snapshot.apply_delta(&[0, 1, 0, 4]).is_err());
+    }
+
+    #[test]
+    fn int_strings() {
+        let ints = str_to_ints("nameless tee", 4);
+        assert_eq!(ints.len(), 4);
+        assert_eq!(ints_to_str(&ints), "nameless tee");
+        assert_eq!(
+            ints_to_str(&str_to_ints("a too long name!", 4)),
+            "a too long name"
+        );
+    }
+
+    #[test]
+    fn legacy_demo_chunks() {
+        let file = legacy_demo(&[]);
+        let demo = LegacyDemo::read(&file).unwrap();
+        assert_eq!(demo.header.version, 6);
+        assert_eq!(demo.header.net_version, "0.6 626fce9a778ab4ec");
+        assert_eq!(demo.header.map_name, "ctf1");
+        assert_eq!(demo.header.ty, "client");
+        assert_eq!(demo.header.length, 1);
+        assert_eq!(demo.timeline_markers, [101]);
+        assert_eq!(demo.map_sha256, None);
+        assert!(demo.map.is_empty());
+
+        let chunks = demo.chunks().collect::<anyhow::Result<Vec<_>>>().unwrap();
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| (chunk.tick, chunk.ty))
+                .collect::<Vec<_>>(),
+            [
+                (100, LegacyDemoChunkType::Snapshot),
+                (100, LegacyDemoChunkType::Message),
+                (102, LegacyDemoChunkType::Delta),
+            ]
+        );
+
+        let first = LegacySnapshot::from_ints(&bytes_to_ints(&chunks[0].data)).unwrap();
+        assert_eq!(first, snapshot());
+        assert!(chunks[1].data.starts_with(&chat_msg("gl hf")));
+        let next = first.apply_delta(&bytes_to_ints(&chunks[2].data)).unwrap();
+        assert_eq!(
+            next.item(NETOBJTYPE_CHARACTER, 0)
+                .map(|data| (data[1], data[2])),
+            Some((1032, 500))
+        );
+    }
+
+    #[test]
+    fn legacy_demo_truncated() {
+        let file = legacy_demo(&[]);
+        assert!(LegacyDemo::read(&file[..100]).is_err());
+        // the header says that there is a map, but the file ends before it
+        let map_missing = legacy_demo_header(&[0; 8], &[]);
+        assert!(LegacyDemo::read(&map_missing[..map_missing.len() - 1]).is_err());
+
+        // the delta is cut off, everything before it is still readable
+        let file = &file[..file.len() - 3];
+        let demo = LegacyDemo::read(file).unwrap();
+        let chunks: Vec<_> = demo.chunks().collect();
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks[0].is_ok());
+        assert!(chunks[1].is_ok());
+        assert!(chunks[2].is_err());
+    }
+
+    #[test]
+    fn legacy_demo_to_new_with_map() {
+        let workspace_root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../");
+        std::env::set_current_dir(workspace_root).unwrap();
+        let io = Io::new(
+            |rt| {
+                Arc::new(
+                    FileSystem::new(rt, "ddnet-test", "ddnet-test", "ddnet-test", "ddnet-test")
+                        .unwrap(),
+                )
+            },
+            Arc::new(HttpClient::new()),
+        );
+        let thread_pool = Arc::new(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(1)
+                .build()
+                .unwrap(),
+        );
+
+        let fs = io.fs.clone();
+        let map = io
+            .rt
+            .spawn(async move { Ok(fs.read_file("legacy/maps/ctf1.map".as_ref()).await?) })
+            .get_storage()
+            .unwrap();
+
+        let output = legacy_demo_to_new(&legacy_demo(&map), &io, &thread_pool, false).unwrap();
+        assert_eq!(output.map_name.as_str(), "ctf1");
+        assert_eq!(output.map_hash, generate_hash_for(&output.map_file));
+
+        let demo = DemoEditor::from_file(&output.demo).unwrap();
+        assert_eq!(demo.header_ext.map.as_str(), "ctf1");
+        assert_eq!(demo.header_ext.map_hash, output.map_hash);
+        // ticks are relative to the first chunk
+        assert_eq!(demo.snapshots.keys().copied().collect::<Vec<_>>(), [0, 2]);
+        assert_eq!(demo.markers().collect::<Vec<_>>(), [1]);
+        assert!(demo.events.get(&0).is_some_and(|events| events
+            .iter()
+            .any(|event| matches!(event, DemoEvent::Chat(msg) if msg.msg == "gl hf"))));
+    }
+}
@@ -0,0 +1,329 @@
+//! The snapshot items of the 0.6 network protocol.
+
+pub const NETOBJTYPE_PROJECTILE: i32 = 2;
+pub const NETOBJTYPE_LASER: i32 = 3;
+pub const NETOBJTYPE_PICKUP: i32 = 4;
+pub const NETOBJTYPE_FLAG: i32 = 5;
+pub const NETOBJTYPE_GAMEINFO: i32 = 6;
+pub const NETOBJTYPE_GAMEDATA: i32 = 7;
+pub const NETOBJTYPE_CHARACTER: i32 = 9;
+pub const NETOBJTYPE_PLAYERINFO: i32 = 10;
+pub const NETOBJTYPE_CLIENTINFO: i32 = 11;
+
+pub const GAMEFLAG_TEAMS: i32 = 1;
+
+pub const GAMESTATEFLAG_GAMEOVER: i32 = 1;
+pub const GAMESTATEFLAG_SUDDENDEATH: i32 = 2;
+pub const GAMESTATEFLAG_PAUSED: i32 = 4;
+
+pub const TEAM_SPECTATORS: i32 = -1;
+
+pub const WEAPON_NINJA: i32 = 5;
+
+pub const POWERUP_HEALTH: i32 = 0;
+pub const POWERUP_ARMOR: i32 = 1;
+pub const POWERUP_WEAPON: i32 = 2;
+pub const POWERUP_NINJA: i32 = 3;
+
+pub const HOOK_RETRACTED: i32 = -1;
+pub const HOOK_IDLE: i32 = 0;
+pub const HOOK_RETRACT_START: i32 = 1;
+pub const HOOK_RETRACT_END: i32 = 3;
+pub const HOOK_FLYING: i32 = 4;
+pub const HOOK_GRABBED: i32 = 5;
+
+/// Flag carriers that are no client id.
+pub const FLAG_TAKEN: i32 = -1;
+
+/// Reads a fixed number of integers into the given fields,
+/// returns `None` if the item is too small.
+macro_rules! read_fields {
+    ($data:expr, $ty:ident { $($field:ident),* $(,)? }) => {{
+        let mut data = $data.iter().copied();
+        Some($ty {
+            $($field: data.next()?,)*
+        })
+    }};
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LegacyProjectile {
+    pub x: i32,
+    pub y: i32,
+    pub vel_x: i32,
+    pub vel_y: i32,
+    pub ty: i32,
+    pub start_tick: i32,
+}
+
+impl LegacyProjectile {
+    pub fn from_ints(data: &[i32]) -> Option<Self> {
+        read_fields!(
+            data,
+            Self {
+                x,
+                y,
+                vel_x,
+                vel_y,
+                ty,
+                start_tick
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LegacyLaser {
+    pub x: i32,
+    pub y: i32,
+    pub from_x: i32,
+    pub from_y: i32,
+    pub start_tick: i32,
+}
+
+impl LegacyLaser {
+    pub fn from_ints(data: &[i32]) -> Option<Self> {
+        read_fields!(
+            data,
+            Self {
+                x,
+                y,
+                from_x,
+                from_y,
+                start_tick
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LegacyPickup {
+    pub x: i32,
+    pub y: i32,
+    pub ty: i32,
+    pub subtype: i32,
+}
+
+impl LegacyPickup {
+    pub fn from_ints(data: &[i32]) -> Option<Self> {
+        read_fields!(data, Self { x, y, ty, subtype })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LegacyFlag {
+    pub x: i32,
+    pub y: i32,
+    pub team: i32,
+}
+
+impl LegacyFlag {
+    pub fn from_ints(data: &[i32]) -> Option<Self> {
+        read_fields!(data, Self { x, y, team })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LegacyGameInfo {
+    pub game_flags: i32,
+    pub game_state_flags: i32,
+    pub round_start_tick: i32,
+    pub warmup_timer: i32,
+    pub score_limit: i32,
+    /// In minutes.
+    pub time_limit: i32,
+    pub round_num: i32,
+    pub round_current: i32,
+}
+
+impl LegacyGameInfo {
+    pub fn from_ints(data: &[i32]) -> Option<Self> {
+        read_fields!(
+            data,
+            Self {
+                game_flags,
+                game_state_flags,
+                round_start_tick,
+                warmup_timer,
+                score_limit,
+                time_limit,
+                round_num,
+                round_current,
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LegacyGameData {
+    pub teamscore_red: i32,
+    pub teamscore_blue: i32,
+    pub flag_carrier_red: i32,
+    pub flag_carrier_blue: i32,
+}
+
+impl LegacyGameData {
+    pub fn from_ints(data: &[i32]) -> Option<Self> {
+        read_fields!(
+            data,
+            Self {
+                teamscore_red,
+                teamscore_blue,
+                flag_carrier_red,
+                flag_carrier_blue,
+            }
+        )
+    }
+}
+
+/// The part of a character that is predicted by the client.
+///
+/// Velocities and directions are multiplied by 256.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LegacyCharacterCore {
+    /// The tick the core was calculated for, if not zero
+    /// the client must simulate the core to the current tick.
+    pub tick: i32,
+    pub x: i32,
+    pub y: i32,
+    pub vel_x: i32,
+    pub vel_y: i32,
+    pub angle: i32,
+    pub direction: i32,
+    pub jumped: i32,
+    pub hooked_player: i32,
+    pub hook_state: i32,
+    pub hook_tick: i32,
+    pub hook_x: i32,
+    pub hook_y: i32,
+    pub hook_dx: i32,
+    pub hook_dy: i32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LegacyCharacter {
+    pub core: LegacyCharacterCore,
+    pub player_flags: i32,
+    pub health: i32,
+    pub armor: i32,
+    pub ammo_count: i32,
+    pub weapon: i32,
+    pub emote: i32,
+    pub attack_tick: i32,
+}
+
+impl LegacyCharacter {
+    pub fn from_ints(data: &[i32]) -> Option<Self> {
+        let core = read_fields!(
+            data,
+            LegacyCharacterCore {
+                tick,
+                x,
+                y,
+                vel_x,
+                vel_y,
+                angle,
+                direction,
+                jumped,
+                hooked_player,
+                hook_state,
+                hook_tick,
+                hook_x,
+                hook_y,
+                hook_dx,
+                hook_dy,
+            }
+        )?;
+        let mut data = data.iter().skip(15).copied();
+        Some(Self {
+            core,
+            player_flags: data.next()?,
+            health: data.next()?,
+            armor: data.next()?,
+            ammo_count: data.next()?,
+            weapon: data.next()?,
+            emote: data.next()?,
+            attack_tick: data.next()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LegacyPlayerInfo {
+    pub local: i32,
+    pub client_id: i32,
+    pub team: i32,
+    pub score: i32,
+    pub latency: i32,
+}
+
+impl LegacyPlayerInfo {
+    pub fn from_ints(data: &[i32]) -> Option<Self> {
+        read_fields!(
+            data,
+            Self {
+                local,
+                client_id,
+                team,
+                score,
+                latency
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LegacyClientInfo {
+    pub name: String,
+    pub clan: String,
+    pub country: i32,
+    pub skin: String,
+    pub use_custom_color: bool,
+    pub color_body: i32,
+    pub color_feet: i32,
+}
+
+impl LegacyClientInfo {
+    pub fn from_ints(data: &[i32]) -> Option<Self> {
+        let data = data.get(..17)?;
+        Some(Self {
+            name: ints_to_str(&data[0..4]),
+            clan: ints_to_str(&data[4..7]),
+            country: data[7],
+            skin: ints_to_str(&data[8..14]),
+            use_custom_color: data[14] != 0,
+            color_body: data[15],
+            color_feet: data[16],
+        })
+    }
+}
+
+/// Strings are packed into integers, four bytes per integer,
+/// each byte is offset by 128.
+pub fn ints_to_str(ints: &[i32]) -> String {
+    let bytes: Vec<u8> = ints
+        .iter()
+        .flat_map(|int| int.to_be_bytes())
+        .map(|byte| byte.wrapping_sub(128))
+        // the last byte is always treated as null terminator
+        .take((ints.len() * 4).saturating_sub(1))
+        .take_while(|&byte| byte != 0)
+        .collect();
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+pub fn str_to_ints(s: &str, num_ints: usize) -> Vec<i32> {
+    let mut bytes: Vec<u8> = s.bytes().take(num_ints * 4 - 1).collect();
+    bytes.resize(num_ints * 4, 0);
+    bytes
+        .chunks_exact(4)
+        .map(|int| {
+            i32::from_be_bytes([
+                int[0].wrapping_add(128),
+                int[1].wrapping_add(128),
+                int[2].wrapping_add(128),
+                int[3].wrapping_add(128),
+            ])
+        })
+        .collect()
+}
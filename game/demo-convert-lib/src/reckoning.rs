@@ -0,0 +1,237 @@
+//! The dead reckoning of the legacy client.
+//!
+//! Legacy servers only resend a character core if the client's prediction
+//! of it would be wrong, so the core inside a snapshot is usually older than
+//! the snapshot itself and has to be simulated to the snapshot's tick.
+//! This is a port of `CCharacterCore::Tick(false)` & `Move` of the 0.6 client
+//! running in an otherwise empty world.
+
+use game_base::mapdef_06::DdraceTileNum;
+use math::math::{
+    distance, length, normalize, round_to_int,
+    vector::{ivec2, vec2},
+};
+use vanilla::collision::collision::{Collision, CollisionTile, CollisionTypes, Tunings};
+
+use crate::netobj::{
+    LegacyCharacterCore, HOOK_FLYING, HOOK_GRABBED, HOOK_IDLE, HOOK_RETRACTED, HOOK_RETRACT_END,
+    HOOK_RETRACT_START,
+};
+
+const PHYS_SIZE: f32 = 28.0;
+
+/// Cores that are older than this are not simulated anymore,
+/// the server would never send such a core.
+const MAX_EVOLVE_TICKS: i32 = 50 * 10;
+
+struct ReckoningCore {
+    pos: vec2,
+    vel: vec2,
+    hook_pos: vec2,
+    hook_dir: vec2,
+    hook_tick: i32,
+    hook_state: i32,
+    hooked_player: i32,
+    direction: i32,
+}
+
+impl ReckoningCore {
+    fn read(core: &LegacyCharacterCore) -> Self {
+        Self {
+            pos: vec2::new(core.x as f32, core.y as f32),
+            vel: vec2::new(core.vel_x as f32 / 256.0, core.vel_y as f32 / 256.0),
+            hook_pos: vec2::new(core.hook_x as f32, core.hook_y as f32),
+            hook_dir: vec2::new(core.hook_dx as f32 / 256.0, core.hook_dy as f32 / 256.0),
+            hook_tick: core.hook_tick,
+            hook_state: core.hook_state,
+            hooked_player: core.hooked_player,
+            direction: core.direction,
+        }
+    }
+
+    fn write(&self, core: &mut LegacyCharacterCore) {
+        core.x = round_to_int(self.pos.x);
+        core.y = round_to_int(self.pos.y);
+        core.vel_x = round_to_int(self.vel.x * 256.0);
+        core.vel_y = round_to_int(self.vel.y * 256.0);
+        core.hook_x = round_to_int(self.hook_pos.x);
+        core.hook_y = round_to_int(self.hook_pos.y);
+        core.hook_dx = round_to_int(self.hook_dir.x * 256.0);
+        core.hook_dy = round_to_int(self.hook_dir.y * 256.0);
+        core.hook_tick = self.hook_tick;
+        core.hook_state = self.hook_state;
+        core.hooked_player = self.hooked_player;
+    }
+
+    fn saturated_add(min: f32, max: f32, current: f32, modifier: f32) -> f32 {
+        if modifier < 0.0 {
+            if current < min {
+                return current;
+            }
+            (current + modifier).max(min)
+        } else {
+            if current > max {
+                return current;
+            }
+            (current + modifier).min(max)
+        }
+    }
+
+    fn velocity_ramp(value: f32, start: f32, range: f32, curvature: f32) -> f32 {
+        if value < start {
+            return 1.0;
+        }
+        1.0 / curvature.powf((value - start) / range)
+    }
+
+    fn tick(&mut self, collision: &Collision, tunings: &Tunings) {
+        let grounded = collision.check_pointf(
+            self.pos.x + PHYS_SIZE / 2.0,
+            self.pos.y + PHYS_SIZE / 2.0 + 5.0,
+        ) || collision.check_pointf(
+            self.pos.x - PHYS_SIZE / 2.0,
+            self.pos.y + PHYS_SIZE / 2.0 + 5.0,
+        );
+
+        self.vel.y += tunings.gravity;
+
+        let (max_speed, accel, friction) = if grounded {
+            (
+                tunings.ground_control_speed,
+                tunings.ground_control_accel,
+                tunings.ground_friction,
+            )
+        } else {
+            (
+                tunings.air_control_speed,
+                tunings.air_control_accel,
+                tunings.air_friction,
+            )
+        };
+        if self.direction < 0 {
+            self.vel.x = Self::saturated_add(-max_speed, max_speed, self.vel.x, -accel);
+        } else if self.direction > 0 {
+            self.vel.x = Self::saturated_add(-max_speed, max_speed, self.vel.x, accel);
+        } else {
+            self.vel.x *= friction;
+        }
+
+        if self.hook_state == HOOK_IDLE {
+            self.hooked_player = -1;
+            self.hook_pos = self.pos;
+        } else if (HOOK_RETRACT_START..HOOK_RETRACT_END).contains(&self.hook_state) {
+            self.hook_state += 1;
+        } else if self.hook_state == HOOK_RETRACT_END {
+            self.hook_state = HOOK_RETRACTED;
+        } else if self.hook_state == HOOK_FLYING {
+            let mut new_pos = self.hook_pos + self.hook_dir * tunings.hook_fire_speed;
+            if distance(&self.pos, &new_pos) > tunings.hook_length {
+                self.hook_state = HOOK_RETRACT_START;
+                new_pos = self.pos + normalize(&(new_pos - self.pos)) * tunings.hook_length;
+            }
+
+            let mut before_col = vec2::default();
+            let target_pos = new_pos;
+            let hit = collision.intersect_line(
+                &self.hook_pos,
+                &target_pos,
+                &mut new_pos,
+                &mut before_col,
+                CollisionTypes::SOLID,
+            );
+
+            if self.hook_state == HOOK_FLYING {
+                match hit {
+                    CollisionTile::Solid(DdraceTileNum::NoHook) => {
+                        self.hook_state = HOOK_RETRACT_START;
+                    }
+                    CollisionTile::Solid(_) => {
+                        self.hook_state = HOOK_GRABBED;
+                    }
+                    _ => {}
+                }
+                self.hook_pos = new_pos;
+            }
+        }
+
+        if self.hook_state == HOOK_GRABBED {
+            // there are no other characters in the world of the dead reckoning
+            if self.hooked_player != -1 {
+                self.hooked_player = -1;
+                self.hook_state = HOOK_RETRACTED;
+                self.hook_pos = self.pos;
+            }
+
+            if self.hooked_player == -1 && distance(&self.hook_pos, &self.pos) > 46.0 {
+                let mut hook_vel = normalize(&(self.hook_pos - self.pos)) * tunings.hook_drag_accel;
+                if hook_vel.y > 0.0 {
+                    hook_vel.y *= 0.3;
+                }
+                if (hook_vel.x < 0.0 && self.direction < 0)
+                    || (hook_vel.x > 0.0 && self.direction > 0)
+                {
+                    hook_vel.x *= 0.95;
+                } else {
+                    hook_vel.x *= 0.75;
+                }
+
+                let new_vel = self.vel + hook_vel;
+                if length(&new_vel) < tunings.hook_drag_speed
+                    || length(&new_vel) < length(&self.vel)
+                {
+                    self.vel = new_vel;
+                }
+            }
+
+            self.hook_tick += 1;
+        }
+
+        if length(&self.vel) > 6000.0 {
+            self.vel = normalize(&self.vel) * 6000.0;
+        }
+    }
+
+    fn move_core(&mut self, collision: &Collision, tunings: &Tunings) {
+        let ramp = Self::velocity_ramp(
+            length(&self.vel) * 50.0,
+            tunings.velramp_start,
+            tunings.velramp_range,
+            tunings.velramp_curvature,
+        );
+
+        self.vel.x *= ramp;
+        collision.move_box(
+            &mut self.pos,
+            &mut self.vel,
+            &ivec2::new(PHYS_SIZE as i32, PHYS_SIZE as i32),
+            0.0,
+        );
+        self.vel.x /= ramp;
+    }
+
+    fn quantize(&mut self) {
+        let mut core = LegacyCharacterCore {
+            direction: self.direction,
+            ..Default::default()
+        };
+        self.write(&mut core);
+        *self = Self::read(&core);
+    }
+}
+
+/// Simulates the core to the given tick, like the legacy client does
+/// for every character of a snapshot.
+pub fn evolve(core: &mut LegacyCharacterCore, tick: i32, collision: &Collision, tunings: &Tunings) {
+    if core.tick >= tick || tick - core.tick > MAX_EVOLVE_TICKS {
+        return;
+    }
+
+    let mut reckoning = ReckoningCore::read(core);
+    while core.tick < tick {
+        core.tick += 1;
+        reckoning.tick(collision, tunings);
+        reckoning.move_core(collision, tunings);
+        reckoning.quantize();
+    }
+    reckoning.write(core);
+}
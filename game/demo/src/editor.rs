@@ -0,0 +1,429 @@
+use std::{collections::BTreeMap, ops::RangeBounds};
+
+use anyhow::anyhow;
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    utils::{
+        comp, decomp, demo_len, deser, deser_ex, read_chunks, ser, ser_ex, write_chunk,
+        DATA_PER_CHUNK_TO_WRITE,
+    },
+    DemoEvent, DemoEvents, DemoHeader, DemoHeaderExt, DemoSnapshot, DemoTail,
+};
+
+/// A fully loaded demo that can be cut, merged and
+/// whose markers can be edited.
+///
+/// Snapshots are complete states of the game, so none of the edits
+/// have to simulate the game. The header & tail are rebuilt
+/// from scratch when the demo is written.
+#[derive(Debug, Clone)]
+pub struct DemoEditor {
+    pub header_ext: DemoHeaderExt,
+    /// the key is the monotonic tick
+    pub snapshots: BTreeMap<u64, DemoSnapshot>,
+    /// the key is the monotonic tick
+    pub events: BTreeMap<u64, DemoEvents>,
+}
+
+impl DemoEditor {
+    pub fn new(header_ext: DemoHeaderExt) -> Self {
+        Self {
+            header_ext,
+            snapshots: Default::default(),
+            events: Default::default(),
+        }
+    }
+
+    /// Reads all snapshots & events of a demo file.
+    pub fn from_file(file: &[u8]) -> anyhow::Result<Self> {
+        let mut writer: Vec<u8> = Default::default();
+
+        let (header, file_off): (DemoHeader, usize) = deser_ex(file, true)?;
+        anyhow::ensure!(
+            header.size_chunks > 0,
+            "the demo was never finished and cannot be edited."
+        );
+        let truncated = || anyhow!("the demo file is truncated.");
+        let file = file.get(file_off..).ok_or_else(truncated)?;
+
+        let size_ext: usize = header.size_ext.try_into()?;
+        let size_chunks: usize = header.size_chunks.try_into()?;
+        let (header_ext, _): (DemoHeaderExt, usize) = deser(decomp(
+            file.get(..size_ext).ok_or_else(truncated)?,
+            &mut writer,
+        )?)?;
+
+        let file = &file[size_ext..];
+        let chunks = file.get(..size_chunks).ok_or_else(truncated)?;
+        let tail = &file[size_chunks..];
+
+        let (tail, _): (DemoTail, usize) = deser(decomp(tail, &mut writer)?)?;
+
+        let mut snapshots: BTreeMap<u64, DemoSnapshot> = Default::default();
+        for offset in tail.snapshots_index.values() {
+            snapshots.append(&mut read_chunks(chunks, *offset as usize)?);
+        }
+        let mut events: BTreeMap<u64, DemoEvents> = Default::default();
+        for offset in tail.events_index.values() {
+            let chunk: BTreeMap<u64, DemoEvents> = read_chunks(chunks, *offset as usize)?;
+            for (monotonic_tick, mut chunk_events) in chunk {
+                events
+                    .entry(monotonic_tick)
+                    .or_default()
+                    .append(&mut chunk_events);
+            }
+        }
+
+        Ok(Self {
+            header_ext,
+            snapshots,
+            events,
+        })
+    }
+
+    /// Writes the demo in the same format as the demo recorder.
+    pub fn to_file(&self) -> anyhow::Result<Vec<u8>> {
+        let (first_monotonic, last_monotonic) = self
+            .first_tick()
+            .zip(self.last_tick())
+            .ok_or_else(|| anyhow!("a demo without snapshots cannot be written."))?;
+
+        let mut write_ser = Vec::new();
+        let mut write_comp = Vec::new();
+        let mut write_dst = Vec::new();
+        let mut write_data = Vec::new();
+
+        let header_ext_file = comp(
+            ser(&self.header_ext, &mut write_ser)?,
+            &mut write_comp,
+            true,
+        )?;
+        let header_ext_len = header_ext_file.len() as u64;
+        let mut header_ext = Vec::new();
+        header_ext.extend_from_slice(header_ext_file);
+
+        fn write_chunks<A: Serialize + Clone>(
+            data: &BTreeMap<u64, A>,
+            chunks: &mut Vec<u8>,
+            index: &mut BTreeMap<u64, u64>,
+
+            write_ser: &mut Vec<u8>,
+            write_comp: &mut Vec<u8>,
+            write_dst: &mut Vec<u8>,
+            write_data: &mut Vec<u8>,
+        ) -> anyhow::Result<()> {
+            for chunk in data
+                .iter()
+                .chunks(DATA_PER_CHUNK_TO_WRITE as usize)
+                .into_iter()
+                .map(|chunk| {
+                    chunk
+                        .map(|(&tick, data)| (tick, data.clone()))
+                        .collect::<BTreeMap<_, _>>()
+                })
+            {
+                if let Some((&first_tick, _)) = chunk.first_key_value() {
+                    index.insert(first_tick, chunks.len() as u64);
+                    chunks.extend_from_slice(write_chunk(
+                        chunk, write_ser, write_comp, write_dst, write_data,
+                    )?);
+                }
+            }
+            Ok(())
+        }
+
+        let mut chunks = Vec::new();
+        let mut tail = DemoTail::default();
+        write_chunks(
+            &self.snapshots,
+            &mut chunks,
+            &mut tail.snapshots_index,
+            &mut write_ser,
+            &mut write_comp,
+            &mut write_dst,
+            &mut write_data,
+        )?;
+        write_chunks(
+            &self.events,
+            &mut chunks,
+            &mut tail.events_index,
+            &mut write_ser,
+            &mut write_comp,
+            &mut write_dst,
+            &mut write_data,
+        )?;
+
+        let mut file = Vec::new();
+        file.extend_from_slice(ser_ex(
+            &DemoHeader {
+                len: demo_len(
+                    first_monotonic,
+                    last_monotonic,
+                    self.header_ext.ticks_per_second.get(),
+                ),
+                size_ext: header_ext_len,
+                size_chunks: chunks.len() as u64,
+            },
+            &mut write_ser,
+            true,
+            true,
+        )?);
+        file.append(&mut header_ext);
+        file.append(&mut chunks);
+        file.extend_from_slice(comp(ser(&tail, &mut write_ser)?, &mut write_comp, true)?);
+
+        Ok(file)
+    }
+
+    /// The first monotonic tick of the demo.
+    pub fn first_tick(&self) -> Option<u64> {
+        let first_snapshot = self.snapshots.first_key_value().map(|(&tick, _)| tick)?;
+        Some(
+            self.events
+                .first_key_value()
+                .map(|(&tick, _)| tick.min(first_snapshot))
+                .unwrap_or(first_snapshot),
+        )
+    }
+
+    /// The last monotonic tick of the demo.
+    pub fn last_tick(&self) -> Option<u64> {
+        let last_snapshot = self.snapshots.last_key_value().map(|(&tick, _)| tick)?;
+        Some(
+            self.events
+                .last_key_value()
+                .map(|(&tick, _)| tick.max(last_snapshot))
+                .unwrap_or(last_snapshot),
+        )
+    }
+
+    /// Only keeps the snapshots & events inside the given monotonic tick range.
+    ///
+    /// Fails if no snapshot would be left, a demo without snapshots cannot be played.
+    pub fn cut(&mut self, range: impl RangeBounds<u64> + Clone) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.snapshots.range(range.clone()).next().is_some(),
+            "the tick range does not contain any snapshot."
+        );
+        self.snapshots.retain(|tick, _| range.contains(tick));
+        self.events.retain(|tick, _| range.contains(tick));
+        Ok(())
+    }
+
+    /// Whether the demo was recorded with the same map, modification &
+    /// tick rate as the other demo.
+    pub fn is_compatible(&self, other: &Self) -> bool {
+        fn same<T: Serialize>(a: &T, b: &T) -> bool {
+            let mut a_ser = Vec::new();
+            let mut b_ser = Vec::new();
+            ser(a, &mut a_ser)
+                .ok()
+                .zip(ser(b, &mut b_ser).ok())
+                .is_some_and(|(a, b)| a == b)
+        }
+        let (a, b) = (&self.header_ext, &other.header_ext);
+        a.map == b.map
+            && a.map_hash == b.map_hash
+            && a.ticks_per_second == b.ticks_per_second
+            && a.physics_group_name == b.physics_group_name
+            && same(&a.physics_mod, &b.physics_mod)
+            && same(&a.render_mod, &b.render_mod)
+            && same(&a.game_options, &b.game_options)
+    }
+
+    /// Appends the other demo directly after the last tick of this demo.
+    ///
+    /// The ticks of the other demo are moved, so the
+    /// result stays monotonic.
+    pub fn append(&mut self, other: Self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.is_compatible(&other),
+            "the demos were recorded with different maps, modifications or tick rates."
+        );
+        let Some(other_first_tick) = other.first_tick() else {
+            return Ok(());
+        };
+        let Some(last_tick) = self.last_tick() else {
+            self.snapshots = other.snapshots;
+            self.events = other.events;
+            return Ok(());
+        };
+
+        let offset_tick = |tick: u64| tick - other_first_tick + last_tick + 1;
+        self.snapshots.extend(
+            other
+                .snapshots
+                .into_iter()
+                .map(|(tick, snapshot)| (offset_tick(tick), snapshot)),
+        );
+        self.events.extend(
+            other
+                .events
+                .into_iter()
+                .map(|(tick, events)| (offset_tick(tick), events)),
+        );
+        Ok(())
+    }
+
+    /// The monotonic ticks of all markers.
+    pub fn markers(&self) -> impl Iterator<Item = u64> + '_ {
+        self.events.iter().filter_map(|(&tick, events)| {
+            events
+                .iter()
+                .any(|event| matches!(event, DemoEvent::Marker))
+                .then_some(tick)
+        })
+    }
+
+    /// Adds a marker at the given monotonic tick.
+    ///
+    /// Returns `false` if there already was a marker.
+    pub fn add_marker(&mut self, monotonic_tick: u64) -> bool {
+        let events = self.events.entry(monotonic_tick).or_default();
+        if events
+            .iter()
+            .any(|event| matches!(event, DemoEvent::Marker))
+        {
+            false
+        } else {
+            events.push(DemoEvent::Marker);
+            true
+        }
+    }
+
+    /// Removes the marker at the given monotonic tick.
+    ///
+    /// Returns `false` if there was no marker.
+    pub fn remove_marker(&mut self, monotonic_tick: u64) -> bool {
+        let Some(events) = self.events.get_mut(&monotonic_tick) else {
+            return false;
+        };
+        let len = events.len();
+        events.retain(|event| !matches!(event, DemoEvent::Marker));
+        let removed = events.len() != len;
+        if events.is_empty() {
+            self.events.remove(&monotonic_tick);
+        }
+        removed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU64;
+
+    use base::hash::generate_hash_for;
+    use game_base::network::messages::{GameModification, RenderModification};
+    use game_interface::interface::GameStateCreateOptions;
+
+    use crate::{DemoEvent, DemoHeaderExt};
+
+    use super::DemoEditor;
+
+    fn synthetic_demo(map: &str, first_tick: u64, ticks: u64) -> DemoEditor {
+        let mut demo = DemoEditor::new(DemoHeaderExt {
+            server: Default::default(),
+            physics_mod: GameModification::Native,
+            render_mod: RenderModification::Native,
+            required_resources: Default::default(),
+            map: map.try_into().unwrap(),
+            map_hash: generate_hash_for(map.as_bytes()),
+            ticks_per_second: NonZeroU64::new(50).unwrap(),
+            game_options: GameStateCreateOptions::default(),
+            physics_group_name: "vanilla".try_into().unwrap(),
+        });
+        for tick in first_tick..first_tick + ticks {
+            // slightly changing snapshots, like a real game
+            let mut snapshot = vec![0; 64];
+            snapshot[(tick % 64) as usize] = tick as u8;
+            demo.snapshots.insert(tick, snapshot);
+            if tick % 25 == 0 {
+                demo.add_marker(tick);
+            }
+        }
+        demo
+    }
+
+    fn assert_same(a: &DemoEditor, b: &DemoEditor) {
+        assert_eq!(a.snapshots, b.snapshots);
+        assert_eq!(
+            a.markers().collect::<Vec<_>>(),
+            b.markers().collect::<Vec<_>>()
+        );
+        assert_eq!(a.events.len(), b.events.len());
+        assert_eq!(a.header_ext.map, b.header_ext.map);
+    }
+
+    #[test]
+    fn round_trip() {
+        // more ticks than fit into a single chunk
+        let demo = synthetic_demo("ctf1", 100, 4000);
+        let file = demo.to_file().unwrap();
+        let read_demo = DemoEditor::from_file(&file).unwrap();
+        assert_same(&demo, &read_demo);
+
+        // writing again gives the exact same file
+        assert_eq!(file, read_demo.to_file().unwrap());
+    }
+
+    #[test]
+    fn truncated() {
+        let file = synthetic_demo("ctf1", 100, 500).to_file().unwrap();
+        for len in [0, 1, 16, file.len() / 2, file.len() - 1] {
+            assert!(DemoEditor::from_file(&file[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn cut() {
+        let mut demo = synthetic_demo("ctf1", 100, 500);
+        demo.cut(200..=299).unwrap();
+        assert_eq!(demo.first_tick(), Some(200));
+        assert_eq!(demo.last_tick(), Some(299));
+        assert_eq!(demo.snapshots.len(), 100);
+        assert_eq!(demo.markers().collect::<Vec<_>>(), vec![200, 225, 250, 275]);
+
+        let read_demo = DemoEditor::from_file(&demo.to_file().unwrap()).unwrap();
+        assert_same(&demo, &read_demo);
+
+        assert!(demo.cut(1000..).is_err());
+    }
+
+    #[test]
+    fn merge() {
+        let mut demo = synthetic_demo("ctf1", 100, 100);
+        let other = synthetic_demo("ctf1", 5000, 50);
+        demo.append(other).unwrap();
+        assert_eq!(demo.first_tick(), Some(100));
+        assert_eq!(demo.last_tick(), Some(249));
+        assert_eq!(demo.snapshots.len(), 150);
+        // marker at 5000 & 5025 moved right after the first demo
+        assert_eq!(
+            demo.markers().collect::<Vec<_>>(),
+            vec![100, 125, 150, 175, 200, 225]
+        );
+
+        let read_demo = DemoEditor::from_file(&demo.to_file().unwrap()).unwrap();
+        assert_same(&demo, &read_demo);
+
+        assert!(demo.append(synthetic_demo("dm1", 0, 10)).is_err());
+    }
+
+    #[test]
+    fn markers() {
+        let mut demo = synthetic_demo("ctf1", 1, 10);
+        assert!(demo.markers().next().is_none());
+        assert!(demo.add_marker(5));
+        assert!(!demo.add_marker(5));
+        demo.events.get_mut(&5).unwrap().push(DemoEvent::Marker);
+        assert!(demo.remove_marker(5));
+        assert!(!demo.remove_marker(5));
+        assert!(demo.events.is_empty());
+
+        demo.add_marker(3);
+        let read_demo = DemoEditor::from_file(&demo.to_file().unwrap()).unwrap();
+        assert_eq!(read_demo.markers().collect::<Vec<_>>(), vec![3]);
+    }
+}
@@ -3,6 +3,7 @@
 #[cfg(feature = "recorder")]
 pub mod recorder;
 
+pub mod editor;
 pub mod utils;
 
 use std::{collections::BTreeMap, time::Duration};
@@ -20,14 +20,11 @@ use serde::Serialize;
 use game_base::network::messages::RequiredResources;
 
 use crate::{
-    ChunkHeader, DemoEvent, DemoEvents, DemoGameModification, DemoHeader, DemoHeaderExt,
-    DemoRenderModification, DemoSnapshot, DemoTail,
+    utils::{comp, demo_len, ser, ser_ex, write_chunk, DATA_PER_CHUNK_TO_WRITE},
+    DemoEvent, DemoEvents, DemoGameModification, DemoHeader, DemoHeaderExt, DemoRenderModification,
+    DemoSnapshot, DemoTail,
 };
 
-// 50 here is the assumed snap send rate
-// so it writes up to 30 seconds full of chunks
-/// number of chunks to write at once
-const DATA_PER_CHUNK_TO_WRITE: u64 = 30 * 50;
 /// time offset so that even late packets have a chance
 /// to be considered in the demo.
 const SECONDS_UNTIL_WRITE: u64 = 3;
@@ -71,7 +68,7 @@ pub struct DemoRecorder {
     /// Must stay to not be dropped
     thread_sender: Sender<DemoRecorderEvent>,
     /// the thread that writes all demo changes to disk
-    _writer_thread: JoinHandle<()>,
+    writer_thread: Option<JoinHandle<()>>,
 }
 
 impl DemoRecorder {
@@ -148,7 +145,7 @@ impl DemoRecorder {
             events: Default::default(),
 
             thread_sender,
-            _writer_thread: writer_thread,
+            writer_thread: Some(writer_thread),
         }
     }
 
@@ -178,41 +175,6 @@ impl DemoRecorder {
         };
         let size = Cell::new(0);
 
-        fn ser_ex<'a, T: Serialize>(
-            v: &T,
-            writer: &'a mut Vec<u8>,
-            clear: bool,
-            fixed_size: bool,
-        ) -> anyhow::Result<&'a mut [u8]> {
-            if clear {
-                writer.clear();
-            }
-            let config = bincode::config::standard();
-            if fixed_size {
-                bincode::serde::encode_into_std_write(v, writer, config.with_fixed_int_encoding())?;
-            } else {
-                bincode::serde::encode_into_std_write(v, writer, config)?;
-            }
-            Ok(writer.as_mut_slice())
-        }
-        fn ser<'a, T: Serialize>(v: &T, writer: &'a mut Vec<u8>) -> anyhow::Result<&'a mut [u8]> {
-            ser_ex(v, writer, true, false)
-        }
-
-        fn comp<'a>(
-            v: &[u8],
-            writer: &'a mut Vec<u8>,
-            clear_writer: bool,
-        ) -> anyhow::Result<&'a mut [u8]> {
-            if clear_writer {
-                writer.clear();
-            }
-            let mut encoder = zstd::Encoder::new(&mut *writer, 0)?;
-            encoder.write_all(v)?;
-            encoder.finish()?;
-            Ok(writer.as_mut_slice())
-        }
-
         fn write(size: &Cell<usize>, file: &mut dyn Write, v: &[u8]) -> anyhow::Result<()> {
             size.set(size.get() + v.len());
             Ok(file.write_all(v)?)
@@ -255,61 +217,6 @@ impl DemoRecorder {
 
         let size_before_chunks = size.get();
 
-        fn write_chunk<'a, A: Serialize>(
-            chunk: BTreeMap<u64, A>,
-            writer: &'a mut Vec<u8>,
-            tmp: &mut Vec<u8>,
-            tmp_dst: &mut Vec<u8>,
-            tmp_patch_data: &mut Vec<u8>,
-        ) -> anyhow::Result<&'a [u8]> {
-            writer.clear();
-
-            let mut last_data: Option<Vec<u8>> = None;
-
-            // first write chunk count
-            let len_ser = ser(&(chunk.len() as u64), &mut *tmp)?;
-            writer.write_all(len_ser)?;
-
-            for (monotonic_tick, data) in chunk {
-                tmp_patch_data.clear();
-
-                // prepare optimized data
-                let data = {
-                    let data_serialized = ser(&data, tmp_dst)?;
-                    let data = if let Some(last_data) = &last_data {
-                        bin_patch::diff(last_data, data_serialized, &mut *tmp_patch_data)?;
-                        Some(tmp_patch_data.as_mut_slice())
-                    } else {
-                        Some(comp(data_serialized, tmp_patch_data, true)?)
-                    };
-                    last_data = Some(data_serialized.to_vec());
-                    data
-                };
-
-                let mono_ser = ser(
-                    &ChunkHeader {
-                        monotonic_tick,
-                        size: data.as_ref().map(|s| s.len() as u64).unwrap_or_default(),
-                    },
-                    &mut *tmp,
-                )?;
-                writer.write_all(mono_ser)?;
-                // now write the data
-                if let Some(data) = data {
-                    writer.write_all(data)?;
-                }
-            }
-
-            tmp_dst.clear();
-            tmp_dst.extend(0_u64.to_le_bytes());
-            comp(writer, tmp_dst, false)?;
-            // write size
-            let size = (tmp_dst.len() - std::mem::size_of::<u64>()) as u64;
-            tmp_dst[0..std::mem::size_of::<u64>()].copy_from_slice(&size.to_le_bytes());
-            std::mem::swap(writer, tmp_dst);
-            Ok(writer.as_mut_slice())
-        }
-
         #[allow(clippy::too_many_arguments)]
         fn serialize_and_write_chunk<A: Serialize>(
             file: &mut dyn Write,
@@ -432,14 +339,11 @@ impl DemoRecorder {
             file.seek(std::io::SeekFrom::Start(0))?;
             file.write_all(ser_ex(
                 &DemoHeader {
-                    len: {
-                        let secs = (last_monotonic - first_monotonic) / header_ext.ticks_per_second;
-                        let nanos = ((last_monotonic - first_monotonic)
-                            % header_ext.ticks_per_second)
-                            * (Duration::from_secs(1).as_nanos() as u64
-                                / header_ext.ticks_per_second);
-                        Duration::new(secs, nanos as u32)
-                    },
+                    len: demo_len(
+                        first_monotonic,
+                        last_monotonic,
+                        header_ext.ticks_per_second.get(),
+                    ),
                     size_ext: header_ext_len as u64,
                     size_chunks: chunks_size as u64,
                 },
@@ -551,6 +455,14 @@ impl DemoRecorder {
     pub fn cancel(self) {
         self.thread_sender.send(DemoRecorderEvent::Cancel).unwrap();
     }
+
+    /// Finishes the demo, like dropping the recorder does.
+    ///
+    /// Returns the thread that writes the demo,
+    /// it ends once the demo file was written.
+    pub fn finish(mut self) -> Option<JoinHandle<()>> {
+        self.writer_thread.take()
+    }
 }
 
 impl Drop for DemoRecorder {
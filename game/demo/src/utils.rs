@@ -1,6 +1,18 @@
-use std::io::Read;
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+    time::Duration,
+};
 
-use serde::de::DeserializeOwned;
+use anyhow::anyhow;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::ChunkHeader;
+
+// 50 here is the assumed snap send rate
+// so it writes up to 30 seconds full of chunks
+/// number of chunks to write at once
+pub const DATA_PER_CHUNK_TO_WRITE: u64 = 30 * 50;
 
 pub fn decomp<'a>(v: &[u8], writer: &'a mut Vec<u8>) -> anyhow::Result<&'a [u8]> {
     writer.clear();
@@ -26,3 +38,175 @@ pub fn deser_ex<T: DeserializeOwned>(v: &[u8], fixed_size: bool) -> anyhow::Resu
 pub fn deser<T: DeserializeOwned>(v: &[u8]) -> anyhow::Result<(T, usize)> {
     deser_ex(v, false)
 }
+
+pub fn comp<'a>(
+    v: &[u8],
+    writer: &'a mut Vec<u8>,
+    clear_writer: bool,
+) -> anyhow::Result<&'a mut [u8]> {
+    if clear_writer {
+        writer.clear();
+    }
+    let mut encoder = zstd::Encoder::new(&mut *writer, 0)?;
+    encoder.write_all(v)?;
+    encoder.finish()?;
+    Ok(writer.as_mut_slice())
+}
+pub fn ser_ex<'a, T: Serialize>(
+    v: &T,
+    writer: &'a mut Vec<u8>,
+    clear: bool,
+    fixed_size: bool,
+) -> anyhow::Result<&'a mut [u8]> {
+    if clear {
+        writer.clear();
+    }
+    let config = bincode::config::standard();
+    if fixed_size {
+        bincode::serde::encode_into_std_write(v, writer, config.with_fixed_int_encoding())?;
+    } else {
+        bincode::serde::encode_into_std_write(v, writer, config)?;
+    }
+    Ok(writer.as_mut_slice())
+}
+pub fn ser<'a, T: Serialize>(v: &T, writer: &'a mut Vec<u8>) -> anyhow::Result<&'a mut [u8]> {
+    ser_ex(v, writer, true, false)
+}
+
+/// The length of a demo that starts at `first_monotonic` and
+/// ends at `last_monotonic`.
+pub fn demo_len(first_monotonic: u64, last_monotonic: u64, ticks_per_second: u64) -> Duration {
+    let secs = (last_monotonic - first_monotonic) / ticks_per_second;
+    let nanos = ((last_monotonic - first_monotonic) % ticks_per_second)
+        * (Duration::from_secs(1).as_nanos() as u64 / ticks_per_second);
+    Duration::new(secs, nanos as u32)
+}
+
+/// Serializes a chunk of snapshots or events.
+/// The result is prefixed by its size as little endian `u64`.
+pub fn write_chunk<'a, A: Serialize>(
+    chunk: BTreeMap<u64, A>,
+    writer: &'a mut Vec<u8>,
+    tmp: &mut Vec<u8>,
+    tmp_dst: &mut Vec<u8>,
+    tmp_patch_data: &mut Vec<u8>,
+) -> anyhow::Result<&'a [u8]> {
+    writer.clear();
+
+    let mut last_data: Option<Vec<u8>> = None;
+
+    // first write chunk count
+    let len_ser = ser(&(chunk.len() as u64), &mut *tmp)?;
+    writer.write_all(len_ser)?;
+
+    for (monotonic_tick, data) in chunk {
+        tmp_patch_data.clear();
+
+        // prepare optimized data
+        let data = {
+            let data_serialized = ser(&data, tmp_dst)?;
+            let data = if let Some(last_data) = &last_data {
+                bin_patch::diff(last_data, data_serialized, &mut *tmp_patch_data)?;
+                Some(tmp_patch_data.as_mut_slice())
+            } else {
+                Some(comp(data_serialized, tmp_patch_data, true)?)
+            };
+            last_data = Some(data_serialized.to_vec());
+            data
+        };
+
+        let mono_ser = ser(
+            &ChunkHeader {
+                monotonic_tick,
+                size: data.as_ref().map(|s| s.len() as u64).unwrap_or_default(),
+            },
+            &mut *tmp,
+        )?;
+        writer.write_all(mono_ser)?;
+        // now write the data
+        if let Some(data) = data {
+            writer.write_all(data)?;
+        }
+    }
+
+    tmp_dst.clear();
+    tmp_dst.extend(0_u64.to_le_bytes());
+    comp(writer, tmp_dst, false)?;
+    // write size
+    let size = (tmp_dst.len() - std::mem::size_of::<u64>()) as u64;
+    tmp_dst[0..std::mem::size_of::<u64>()].copy_from_slice(&size.to_le_bytes());
+    std::mem::swap(writer, tmp_dst);
+    Ok(writer.as_mut_slice())
+}
+
+/// Reads the chunk, that was written by [`write_chunk`], at the given offset
+/// of the demo's chunks.
+pub fn read_chunks<A: DeserializeOwned>(
+    demo_chunks: &[u8],
+    offset: usize,
+) -> anyhow::Result<BTreeMap<u64, A>> {
+    let file = demo_chunks
+        .get(offset..)
+        .ok_or_else(|| anyhow!("chunk offset outside of the demo chunks"))?;
+
+    // unpack all chunks
+    let mut data: Vec<u8> = Default::default();
+
+    anyhow::ensure!(
+        file.len() >= std::mem::size_of::<u64>(),
+        "file not huge enough to read u64 for compressed size"
+    );
+    let chunks_size = u64::from_le_bytes(file[0..std::mem::size_of::<u64>()].try_into()?);
+    let file = &file[std::mem::size_of::<u64>()..];
+
+    let chunks_size: usize = chunks_size.try_into()?;
+    decomp(
+        file.get(0..chunks_size)
+            .ok_or_else(|| anyhow!("file not huge enough to read the compressed chunks"))?,
+        &mut data,
+    )?;
+    let file = data.as_slice();
+
+    // read item count in this chunk
+    let (len, read_size) = deser::<u64>(file)?;
+
+    let mut file = &file[read_size..];
+
+    let mut res: BTreeMap<u64, A> = Default::default();
+
+    let mut last_data: Option<Vec<u8>> = None;
+
+    let mut writer: Vec<u8> = Default::default();
+
+    for _ in 0..len {
+        let (header, read_size) = deser::<ChunkHeader>(file)?;
+        file = &file[read_size..];
+
+        let data = if header.size > 0 {
+            let size: usize = header.size.try_into()?;
+            let data_slice = file
+                .get(..size)
+                .ok_or_else(|| anyhow!("file not huge enough to read the chunk data"))?;
+            let res = if let Some(last_data) = &last_data {
+                writer.clear();
+                bin_patch::patch(last_data, data_slice, &mut writer)?;
+                writer.as_slice()
+            } else {
+                decomp(data_slice, &mut writer)?;
+                writer.as_slice()
+            };
+            last_data = Some(res.to_vec());
+            let (data, _) = deser(res)?;
+            file = &file[size..];
+            Some(data)
+        } else {
+            None
+        };
+
+        if let Some(data) = data {
+            res.insert(header.monotonic_tick, data);
+        }
+    }
+
+    Ok(res)
+}
@@ -17,7 +17,7 @@ pub mod editor_wasm {
     use wasm_logic_graphics::WasmGraphicsLogic;
     use wasm_logic_http::http::WasmHttpLogic;
     use wasm_logic_sound::sound::WasmSoundLogic;
-    use wasm_runtime::{MemoryLimit, WasmManager, WasmManagerModuleType};
+    use wasm_runtime::{MemoryLimit, WasmLimits, WasmManager, WasmManagerModuleType, WasmManifest};
     use wasmer::Module;
 
     pub struct EditorWasm {
@@ -36,8 +36,8 @@ pub mod editor_wasm {
         ) -> Self {
             let sound_logic = WasmSoundLogic::new(u128::MAX / 2, sound);
             let graphics_logic = WasmGraphicsLogic::new(graphics, backend.clone(), u128::MAX / 2);
-            let fs_logic = WasmFileSystemLogic::new(io.clone());
-            let http_logic = WasmHttpLogic::new(io.clone());
+            let fs_logic = WasmFileSystemLogic::new(io.clone(), WasmManifest::unrestricted());
+            let http_logic = WasmHttpLogic::new(io.clone(), WasmManifest::unrestricted());
             let wasm_manager: WasmManager = WasmManager::new(
                 WasmManagerModuleType::FromClosure(|store| {
                     match unsafe { Module::deserialize(store, wasm_module) } {
@@ -53,6 +53,7 @@ pub mod editor_wasm {
                     Some(imports)
                 },
                 MemoryLimit::OneGibiByte,
+                WasmLimits::client(),
             )
             .unwrap();
             wasm_manager.add_param(0, &graphics.canvas_handle.window_props());
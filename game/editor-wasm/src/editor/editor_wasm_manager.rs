@@ -10,7 +10,7 @@ use graphics::graphics::graphics::Graphics;
 use graphics_backend::backend::GraphicsBackend;
 use rayon::ThreadPool;
 use sound::sound::SoundManager;
-use wasm_runtime::WasmManager;
+use wasm_runtime::{WasmLimits, WasmManager};
 
 use super::{editor_lib::editor_lib::EditorLib, editor_wasm::editor_wasm::EditorWasm};
 
@@ -57,7 +57,7 @@ impl EditorWasmManager {
         thread_pool: &Arc<ThreadPool>,
         font_data: &FontDefinitions,
     ) -> Self {
-        let cache = Arc::new(Cache::<0>::new(MODS_PATH, io));
+        let cache = Arc::new(Cache::<2>::new(MODS_PATH, io));
         // check if loading was finished
         let path_str = MODS_PATH.to_string() + "/editor.wasm";
         let fs_change_watcher = io
@@ -72,9 +72,11 @@ impl EditorWasmManager {
             cache_task
                 .load(&path_str, |wasm_bytes| {
                     Box::pin(async move {
-                        Ok(WasmManager::compile_module(&wasm_bytes)?
-                            .serialize()?
-                            .to_vec())
+                        Ok(
+                            WasmManager::compile_module(&wasm_bytes, &WasmLimits::client())?
+                                .serialize()?
+                                .to_vec(),
+                        )
                     })
                 })
                 .await
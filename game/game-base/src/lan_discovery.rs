@@ -0,0 +1,302 @@
+//! Finds game servers in the local network without any master server.
+//!
+//! The client sends a probe as UDP broadcast (and to the loopback address,
+//! which is not part of a broadcast) to all [`LAN_DISCOVERY_PORTS`].
+//! Every server listens on the first free port of this range and
+//! answers with a [`LanServerInfo`] serialized as json.
+//!
+//! To not be usable for amplification attacks, servers only answer
+//! addresses of a local network and the answer is never bigger than the probe.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket},
+    ops::RangeInclusive,
+    sync::{atomic::AtomicBool, Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use base::join_thread::JoinThread;
+use serde::{Deserialize, Serialize};
+
+use crate::server_browser::{ServerBrowserInfo, ServerBrowserServer};
+
+/// The ports servers listen on for probes.
+/// These are dedicated ports outside of the usual game ports (8303 and following),
+/// so the lan discovery never blocks the port of another server.
+pub const LAN_DISCOVERY_PORTS: RangeInclusive<u16> = 18303..=18310;
+
+/// The start of the packet a client sends to find servers.
+const LAN_PROBE: &[u8] = b"ddnet-rs-lan-probe";
+
+/// The size a probe is padded to.
+const LAN_PROBE_SIZE: usize = 4 * 1024;
+
+/// The largest response a server sends, which is the size of the probe.
+pub const MAX_LAN_RESPONSE_SIZE: usize = LAN_PROBE_SIZE;
+
+/// How long the server thread blocks at most,
+/// before it checks if it should stop.
+const SERVER_READ_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// The response of a server to a probe.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanServerInfo {
+    /// The game port of the server for ipv4.
+    pub port_v4: u16,
+    /// The game port of the server for ipv6.
+    pub port_v6: u16,
+    pub info: ServerBrowserInfo,
+}
+
+/// Whether the address is part of a local network
+/// (private, link-local or loopback).
+fn is_local_addr(ip: IpAddr) -> bool {
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => ip.is_private() || ip.is_link_local() || ip.is_loopback(),
+        IpAddr::V6(ip) => {
+            ip.is_loopback()
+                // unique local addresses (fc00::/7)
+                || (ip.segments()[0] & 0xfe00) == 0xfc00
+                // link-local addresses (fe80::/10)
+                || (ip.segments()[0] & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+fn is_probe(packet: &[u8]) -> bool {
+    packet.len() == LAN_PROBE_SIZE && packet.starts_with(LAN_PROBE)
+}
+
+fn probe() -> Vec<u8> {
+    let mut probe = LAN_PROBE.to_vec();
+    probe.resize(LAN_PROBE_SIZE, 0);
+    probe
+}
+
+/// Answers probes of clients in the local network.
+#[derive(Debug)]
+pub struct LanDiscoveryServer {
+    response: Arc<Mutex<Option<Vec<u8>>>>,
+    port: u16,
+    is_open: Arc<AtomicBool>,
+    // must be the last entry
+    _thread: JoinThread<()>,
+}
+
+impl LanDiscoveryServer {
+    /// Listens on the first free port of [`LAN_DISCOVERY_PORTS`].
+    pub fn new() -> anyhow::Result<Self> {
+        Self::with_ports(LAN_DISCOVERY_PORTS)
+    }
+
+    /// Listens on the first free port of the given ports.
+    pub fn with_ports(mut ports: RangeInclusive<u16>) -> anyhow::Result<Self> {
+        let socket = ports
+            .find_map(|port| UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port)).ok())
+            .ok_or_else(|| anyhow!("all ports for the lan discovery are in use"))?;
+        socket.set_read_timeout(Some(SERVER_READ_TIMEOUT))?;
+        let port = socket.local_addr()?.port();
+
+        let response: Arc<Mutex<Option<Vec<u8>>>> = Default::default();
+        let is_open = Arc::new(AtomicBool::new(true));
+
+        let thread_response = response.clone();
+        let thread_is_open = is_open.clone();
+        let thread = std::thread::Builder::new()
+            .name("lan-discovery".to_string())
+            .spawn(move || {
+                let mut buf = vec![0; LAN_PROBE_SIZE];
+                while thread_is_open.load(std::sync::atomic::Ordering::Relaxed) {
+                    // errors are timeouts or broken packets, both are ignored
+                    let Ok((size, addr)) = socket.recv_from(&mut buf) else {
+                        continue;
+                    };
+                    if !is_probe(&buf[..size]) || !is_local_addr(addr.ip()) {
+                        continue;
+                    }
+                    if let Some(response) = &*thread_response.lock().unwrap() {
+                        if let Err(err) = socket.send_to(response, addr) {
+                            log::debug!(target: "lan-discovery", "failed to answer {addr}: {err}");
+                        }
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            response,
+            port,
+            is_open,
+            _thread: JoinThread::new(thread),
+        })
+    }
+
+    /// The port the server listens on for probes.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Sets the information clients receive.
+    /// Players are removed if the response would be too big.
+    pub fn set_info(&self, mut info: LanServerInfo) {
+        let response = loop {
+            let response = serde_json::to_vec(&info).unwrap();
+            if response.len() <= MAX_LAN_RESPONSE_SIZE {
+                break Some(response);
+            } else if info.info.players.is_empty() {
+                break None;
+            }
+            info.info.players.truncate(info.info.players.len() / 2);
+        };
+        *self.response.lock().unwrap() = response;
+    }
+}
+
+impl Drop for LanDiscoveryServer {
+    fn drop(&mut self) {
+        self.is_open
+            .store(false, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+/// Sends a probe to all servers in the local network
+/// and collects their responses until the timeout is reached.
+///
+/// This call blocks for the whole duration of `timeout`.
+pub fn discover_lan_servers(timeout: Duration) -> anyhow::Result<Vec<ServerBrowserServer>> {
+    discover_lan_servers_on_ports(LAN_DISCOVERY_PORTS, timeout)
+}
+
+/// Like [`discover_lan_servers`], but probes the given ports.
+pub fn discover_lan_servers_on_ports(
+    ports: RangeInclusive<u16>,
+    timeout: Duration,
+) -> anyhow::Result<Vec<ServerBrowserServer>> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+    socket.set_broadcast(true)?;
+    let probe = probe();
+    for port in ports {
+        for ip in [Ipv4Addr::BROADCAST, Ipv4Addr::LOCALHOST] {
+            // the broadcast fails without any network, which is fine
+            if let Err(err) = socket.send_to(&probe, (ip, port)) {
+                log::debug!(target: "lan-discovery", "failed to probe {ip}:{port}: {err}");
+            }
+        }
+    }
+
+    let mut servers: Vec<ServerBrowserServer> = Default::default();
+    let mut buf = vec![0; MAX_LAN_RESPONSE_SIZE];
+    let end = Instant::now() + timeout;
+    loop {
+        let remaining = end.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        socket.set_read_timeout(Some(remaining))?;
+        let (size, addr) = match socket.recv_from(&mut buf) {
+            Ok(res) => res,
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                break;
+            }
+            // e.g. a port unreachable message on some systems
+            Err(_) => continue,
+        };
+        let Ok(server) = serde_json::from_slice::<LanServerInfo>(&buf[..size]) else {
+            continue;
+        };
+
+        let addr = SocketAddr::new(addr.ip(), server.port_v4);
+        // the same server might answer multiple probes, e.g. the broadcast & the loopback one
+        if let Some(known_server) = servers.iter_mut().find(|known_server| {
+            known_server.info.cert_sha256_fingerprint == server.info.cert_sha256_fingerprint
+        }) {
+            if !known_server.addresses.contains(&addr) {
+                known_server.addresses.push(addr);
+            }
+        } else {
+            servers.push(ServerBrowserServer {
+                info: server.info,
+                addresses: vec![addr],
+                location: "default".try_into().unwrap(),
+            });
+        }
+    }
+
+    Ok(servers)
+}
+
+#[cfg(test)]
+mod test {
+    use std::{net::IpAddr, time::Duration};
+
+    use crate::server_browser::ServerBrowserInfo;
+
+    use super::{
+        discover_lan_servers_on_ports, is_local_addr, is_probe, probe, LanDiscoveryServer,
+        LanServerInfo, LAN_PROBE,
+    };
+
+    #[test]
+    fn only_local_probes() {
+        let local = |ip: &str| is_local_addr(ip.parse::<IpAddr>().unwrap());
+        assert!(local("127.0.0.1"));
+        assert!(local("192.168.0.10"));
+        assert!(local("10.1.2.3"));
+        assert!(local("169.254.1.1"));
+        assert!(local("::1"));
+        assert!(local("fe80::1"));
+        assert!(local("fd00::1"));
+        assert!(local("::ffff:192.168.0.10"));
+        assert!(!local("8.8.8.8"));
+        assert!(!local("2001:db8::1"));
+        assert!(!local("::ffff:8.8.8.8"));
+
+        assert!(is_probe(&probe()));
+        // a probe that is smaller than the response is ignored
+        assert!(!is_probe(LAN_PROBE));
+    }
+
+    #[test]
+    fn discover_over_loopback() {
+        // any free port, so the test does not depend on the lan discovery ports
+        let server = LanDiscoveryServer::with_ports(0..=0).unwrap();
+        server.set_info(LanServerInfo {
+            port_v4: 8310,
+            port_v6: 8311,
+            info: ServerBrowserInfo {
+                name: "lan test server".try_into().unwrap(),
+                game_type: Default::default(),
+                version: Default::default(),
+                map: Default::default(),
+                players: Default::default(),
+                max_ingame_players: 64,
+                max_players: 64,
+                max_players_per_client: 2,
+                passworded: true,
+                tournament_mode: false,
+                cert_sha256_fingerprint: [1; 32],
+                requires_account: false,
+            },
+        });
+
+        let servers = discover_lan_servers_on_ports(
+            server.port()..=server.port(),
+            Duration::from_millis(500),
+        )
+        .unwrap();
+        let server = servers
+            .iter()
+            .find(|server| server.info.cert_sha256_fingerprint == [1; 32])
+            .unwrap();
+        assert_eq!(server.info.name.as_str(), "lan test server");
+        assert!(server.info.passworded);
+        assert!(server
+            .addresses
+            .contains(&"127.0.0.1:8310".parse().unwrap()));
+    }
+}
@@ -3,6 +3,7 @@ pub mod browser_favorite_player;
 pub mod datafile;
 pub mod game_types;
 pub mod indexmap_tests;
+pub mod lan_discovery;
 pub mod local_server_info;
 pub mod mapdef_06;
 pub mod network;
@@ -69,6 +69,12 @@ pub enum EEntityTiles {
     WeaponGrenade,
     PowerupNinja,
     WeaponLaser,
+    // DDRace laser modifiers (also used for the door length)
+    LaserShort = 210,
+    LaserMedium,
+    LaserLong,
+    // DDRace doors
+    Door = 240,
 }
 /*
 //DDRace - Main Lasers
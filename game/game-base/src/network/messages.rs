@@ -4,6 +4,7 @@ use base::{
     hash::Hash,
     network_string::{NetworkReducedAsciiString, NetworkString},
 };
+use game_config::config::MAX_SERVER_PASSWORD_LEN;
 use game_interface::{
     interface::{GameStateServerOptions, MAX_MAP_NAME_LEN},
     types::{
@@ -160,6 +161,16 @@ pub struct MsgClReady {
     pub rcon_secret: Option<[u8; 32]>,
 }
 
+/// Sent directly after the connection was established.
+///
+/// Password protected servers wait for this message
+/// before sending any information about the game.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MsgClPassword {
+    /// The password of the server, if the server requires one.
+    pub password: Option<NetworkString<MAX_SERVER_PASSWORD_LEN>>,
+}
+
 #[derive(Debug, Error, Clone, Serialize, Deserialize)]
 pub enum MsgClReadyResponseError {
     #[error("No players were added in the ready request")]
@@ -167,6 +167,11 @@ pub struct ServerFilter {
     #[serde(default)]
     #[serde_as(deserialize_as = "DefaultOnError")]
     pub unfinished_maps: bool,
+    /// Servers with a higher ping (in milliseconds) are hidden,
+    /// `0` disables this filter.
+    #[serde(default)]
+    #[serde_as(deserialize_as = "DefaultOnError")]
+    pub max_ping: u32,
 }
 
 #[derive(Debug, Hiarc, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -182,6 +187,15 @@ pub struct TableSort {
     pub sort_dir: SortDir,
 }
 
+/// The last measured round trip time of an address.
+#[derive(Debug, Hiarc, Clone, Copy)]
+pub struct ServerBrowserPing {
+    /// `None` if the server did not answer.
+    pub rtt: Option<Duration>,
+    /// The time the last probe was sent.
+    pub time: Duration,
+}
+
 #[derive(Debug, Hiarc, Default)]
 pub struct FilterCache {
     filter: ServerFilter,
@@ -223,6 +237,12 @@ pub struct ServerBrowserData {
 
     cache: FilterCache,
     filtered_sorted: Option<Arc<Vec<ServerBrowserServer>>>,
+
+    /// Servers found in the local network.
+    lan_servers: Arc<Vec<ServerBrowserServer>>,
+
+    /// Measured pings per address, kept across server list refreshes.
+    pings: HashMap<SocketAddr, ServerBrowserPing>,
 }
 
 #[hiarc_safer_rc_refcell]
@@ -260,6 +280,10 @@ impl ServerBrowserData {
 
             cache: Default::default(),
             filtered_sorted: Default::default(),
+
+            lan_servers: Default::default(),
+
+            pings: Default::default(),
         }
     }
 
@@ -269,8 +293,78 @@ impl ServerBrowserData {
 
     pub fn set_servers(&mut self, servers: Vec<ServerBrowserServer>, time: Duration) {
         if self.list.time.is_none_or(|list_time| list_time < time) {
+            let lan_servers = self.lan_servers.clone();
+            let pings = std::mem::take(&mut self.pings);
             *self = Self::from_servers(servers, time);
+            self.lan_servers = lan_servers;
+            self.pings = pings;
+        }
+    }
+
+    pub fn set_lan_servers(&mut self, servers: Vec<ServerBrowserServer>) {
+        self.lan_servers = Arc::new(servers);
+    }
+
+    pub fn lan_servers(&self) -> Arc<Vec<ServerBrowserServer>> {
+        self.lan_servers.clone()
+    }
+
+    /// Returns the addresses that should be pinged at `time` & marks them as pinged.
+    ///
+    /// An address is only pinged again after `min_interval` passed,
+    /// so refreshing the list does not flood the servers with probes.
+    /// Only one address per server is pinged, ipv4 is preferred.
+    pub fn start_pings(&mut self, time: Duration, min_interval: Duration) -> Vec<SocketAddr> {
+        let addresses: Vec<SocketAddr> = self
+            .list
+            .servers
+            .iter()
+            .chain(self.lan_servers.iter())
+            .filter_map(|server| {
+                server
+                    .addresses
+                    .iter()
+                    .find(|addr| addr.is_ipv4())
+                    .or(server.addresses.first())
+                    .copied()
+            })
+            .filter(|addr| {
+                self.pings
+                    .get(addr)
+                    .is_none_or(|ping| time.saturating_sub(ping.time) >= min_interval)
+            })
+            .collect();
+        for addr in addresses.iter() {
+            let rtt = self.pings.get(addr).and_then(|ping| ping.rtt);
+            self.pings.insert(*addr, ServerBrowserPing { rtt, time });
         }
+        addresses
+    }
+
+    /// Sets the results of pings started by [`Self::start_pings`].
+    /// Addresses without a round trip time did not answer.
+    pub fn set_pings(&mut self, pings: Vec<(SocketAddr, Option<Duration>)>, time: Duration) {
+        for (addr, rtt) in pings {
+            self.pings.insert(addr, ServerBrowserPing { rtt, time });
+        }
+        // sorting & filtering depend on the pings
+        self.filtered_sorted = None;
+    }
+
+    /// The lowest ping of all addresses of the server.
+    pub fn ping(&self, server: &ServerBrowserServer) -> Option<Duration> {
+        Self::server_ping(&self.pings, server)
+    }
+
+    fn server_ping(
+        pings: &HashMap<SocketAddr, ServerBrowserPing>,
+        server: &ServerBrowserServer,
+    ) -> Option<Duration> {
+        server
+            .addresses
+            .iter()
+            .filter_map(|addr| pings.get(addr).and_then(|ping| ping.rtt))
+            .min()
     }
 
     pub fn find(&self, addr: SocketAddr) -> Option<ServerBrowserServer> {
@@ -313,6 +407,7 @@ impl ServerBrowserData {
         filter: &'a ServerFilter,
         favorites: &'a FavoritePlayers,
         finished_maps: &'a HashSet<NetworkReducedAsciiString<MAX_MAP_NAME_LEN>>,
+        pings: &'a HashMap<SocketAddr, ServerBrowserPing>,
     ) -> impl Iterator<Item = &'a ServerBrowserServer> {
         servers.iter().filter(move |server| {
             (server
@@ -338,10 +433,17 @@ impl ServerBrowserData {
                         .iter()
                         .any(|p| favorites.iter().any(|f| f.name == p.name)))
                 && (!filter.unfinished_maps || finished_maps.contains(&server.info.map.name))
+                && (filter.max_ping == 0
+                    || Self::server_ping(pings, server)
+                        .is_some_and(|ping| ping <= Duration::from_millis(filter.max_ping as u64)))
         })
     }
 
-    fn servers_sorted(servers: &mut [ServerBrowserServer], sort: &TableSort) {
+    fn servers_sorted(
+        servers: &mut [ServerBrowserServer],
+        sort: &TableSort,
+        pings: &HashMap<SocketAddr, ServerBrowserPing>,
+    ) {
         servers.sort_by(|d1, d2| {
             let order = match sort.name.as_str() {
                 "Name" => d1
@@ -362,7 +464,12 @@ impl ServerBrowserData {
                     .to_lowercase()
                     .cmp(&d2.info.map.name.as_str().to_lowercase()),
                 "Players" => d1.info.players.len().cmp(&d2.info.players.len()),
-                // TODO: "Ping"
+                "Ping" => {
+                    // servers without a ping count as the highest ping
+                    let ping1 = Self::server_ping(pings, d1).unwrap_or(Duration::MAX);
+                    let ping2 = Self::server_ping(pings, d2).unwrap_or(Duration::MAX);
+                    ping1.cmp(&ping2)
+                }
                 _ => d1
                     .info
                     .name
@@ -393,14 +500,180 @@ impl ServerBrowserData {
         {
             filtered_sorted.clone()
         } else {
-            let mut servers_filtered: Vec<_> =
-                Self::servers_filtered(&self.list.servers, filter, favorites, finished_maps)
-                    .cloned()
-                    .collect();
-            Self::servers_sorted(&mut servers_filtered, sort);
+            let mut servers_filtered: Vec<_> = Self::servers_filtered(
+                &self.list.servers,
+                filter,
+                favorites,
+                finished_maps,
+                &self.pings,
+            )
+            .cloned()
+            .collect();
+            Self::servers_sorted(&mut servers_filtered, sort, &self.pings);
             let servers = Arc::new(servers_filtered);
             self.filtered_sorted = Some(servers.clone());
             servers
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashSet, net::SocketAddr, time::Duration};
+
+    use super::{
+        ServerBrowserData, ServerBrowserInfo, ServerBrowserServer, ServerFilter, SortDir, TableSort,
+    };
+
+    fn server(name: &str, addresses: &[&str]) -> ServerBrowserServer {
+        ServerBrowserServer {
+            info: ServerBrowserInfo {
+                name: name.try_into().unwrap(),
+                game_type: Default::default(),
+                version: Default::default(),
+                map: Default::default(),
+                players: Default::default(),
+                max_ingame_players: 64,
+                max_players: 64,
+                max_players_per_client: 2,
+                passworded: false,
+                tournament_mode: false,
+                cert_sha256_fingerprint: Default::default(),
+                requires_account: false,
+            },
+            addresses: addresses.iter().map(|addr| addr.parse().unwrap()).collect(),
+            location: "default".try_into().unwrap(),
+        }
+    }
+
+    fn addr(addr: &str) -> SocketAddr {
+        addr.parse().unwrap()
+    }
+
+    fn browser() -> ServerBrowserData {
+        ServerBrowserData::new(
+            vec![
+                server("fast", &["[::1]:8303", "127.0.0.1:8303"]),
+                server("slow", &["127.0.0.2:8303"]),
+                server("offline", &["127.0.0.3:8303"]),
+            ],
+            Duration::from_secs(1),
+        )
+    }
+
+    fn names(browser: &ServerBrowserData, filter: &ServerFilter, sort: &TableSort) -> Vec<String> {
+        browser
+            .filtered_and_sorted(filter, &Default::default(), sort, &HashSet::default())
+            .iter()
+            .map(|server| server.info.name.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn pings_are_rate_limited() {
+        let browser = browser();
+        let interval = Duration::from_secs(5);
+
+        // ipv4 is preferred over the other addresses of a server
+        let pinged = browser.start_pings(Duration::from_secs(10), interval);
+        assert_eq!(
+            pinged,
+            [
+                addr("127.0.0.1:8303"),
+                addr("127.0.0.2:8303"),
+                addr("127.0.0.3:8303")
+            ]
+        );
+        assert!(browser
+            .start_pings(Duration::from_secs(12), interval)
+            .is_empty());
+
+        browser.set_pings(
+            vec![(addr("127.0.0.1:8303"), Some(Duration::from_millis(20)))],
+            Duration::from_secs(12),
+        );
+        // only the answered address was set again
+        assert_eq!(
+            browser.start_pings(Duration::from_secs(15), interval),
+            [addr("127.0.0.2:8303"), addr("127.0.0.3:8303")]
+        );
+        assert_eq!(
+            browser.start_pings(Duration::from_secs(17), interval),
+            [addr("127.0.0.1:8303")]
+        );
+        // a new probe keeps the last measured ping
+        let fast = browser.find(addr("127.0.0.1:8303")).unwrap();
+        assert_eq!(browser.ping(&fast), Some(Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn set_pings_of_all_addresses() {
+        let browser = browser();
+        browser.set_pings(
+            vec![
+                (addr("127.0.0.1:8303"), Some(Duration::from_millis(20))),
+                (addr("[::1]:8303"), Some(Duration::from_millis(10))),
+                (addr("127.0.0.2:8303"), Some(Duration::from_millis(80))),
+                (addr("127.0.0.3:8303"), None),
+            ],
+            Duration::from_secs(1),
+        );
+
+        // the lowest ping of all addresses of the server
+        let fast = browser.find(addr("127.0.0.1:8303")).unwrap();
+        assert_eq!(browser.ping(&fast), Some(Duration::from_millis(10)));
+        let slow = browser.find(addr("127.0.0.2:8303")).unwrap();
+        assert_eq!(browser.ping(&slow), Some(Duration::from_millis(80)));
+        let offline = browser.find(addr("127.0.0.3:8303")).unwrap();
+        assert_eq!(browser.ping(&offline), None);
+
+        // pings are kept when the list is refreshed
+        browser.set_servers(
+            vec![server("fast", &["127.0.0.1:8303"])],
+            Duration::from_secs(2),
+        );
+        let fast = browser.find(addr("127.0.0.1:8303")).unwrap();
+        assert_eq!(browser.ping(&fast), Some(Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn sort_and_filter_by_ping() {
+        let browser = browser();
+        let filter = ServerFilter::default();
+        let mut sort = TableSort {
+            name: "Ping".to_string(),
+            sort_dir: SortDir::Asc,
+        };
+        assert_eq!(names(&browser, &filter, &sort), ["fast", "slow", "offline"]);
+
+        // servers without a ping are sorted last
+        browser.set_pings(
+            vec![(addr("127.0.0.2:8303"), Some(Duration::from_millis(80)))],
+            Duration::from_secs(1),
+        );
+        assert_eq!(names(&browser, &filter, &sort), ["slow", "fast", "offline"]);
+
+        // setting pings invalidates the cached list
+        browser.set_pings(
+            vec![
+                (addr("127.0.0.1:8303"), Some(Duration::from_millis(20))),
+                (addr("127.0.0.3:8303"), None),
+            ],
+            Duration::from_secs(2),
+        );
+        assert_eq!(names(&browser, &filter, &sort), ["fast", "slow", "offline"]);
+        sort.sort_dir = SortDir::Desc;
+        assert_eq!(names(&browser, &filter, &sort), ["offline", "slow", "fast"]);
+
+        let filter = ServerFilter {
+            max_ping: 50,
+            ..Default::default()
+        };
+        assert_eq!(names(&browser, &filter, &sort), ["fast"]);
+        let filter = ServerFilter {
+            max_ping: 80,
+            ..Default::default()
+        };
+        assert_eq!(names(&browser, &filter, &sort), ["slow", "fast"]);
+    }
+}
@@ -261,9 +261,19 @@ pub struct ConfigClient {
     /// are reserved names and won't cause any mod to load.
     #[default = ""]
     pub render_mod: String,
+    /// The capabilities render mods, e.g. the ones the server requires,
+    /// are allowed to use.
+    pub render_mod_grants: ConfigWasmModGrants,
     #[conf_valid(length(max = MAX_LANG_NAME_LEN))]
     #[default = "en"]
     pub language: String,
+    /// The urls of the server lists of the master servers.
+    /// The lists of all master servers are combined.
+    #[default = vec!["https://pg.ddnet.org:4444/ddnet/15/servers.json".to_string()]]
+    pub master_servers: Vec<String>,
+    /// Passwords of password protected servers that were
+    /// entered before. The key is the address of the server.
+    pub server_passwords: HashMap<String, String>,
 }
 
 #[config_default]
@@ -557,13 +567,18 @@ pub struct ConfigServerDatabaseConnection {
 #[derive(Debug, Clone, Serialize, Deserialize, ConfigInterface)]
 pub struct ConfigServerDatabase {
     /// Connections to a database.
-    /// The key value here is the type of databse (mysql, sqlite).
+    /// The key value here is the type of databse (mysql, sqlite, postgres).
     /// Additionally the key allows `_backup` as suffix to connect to a backup database.
     pub connections: HashMap<String, ConfigServerDatabaseConnection>,
     /// Specify the database type where accounts will be enabled.
     /// Only one database type is allowed and must be enabled in the connections.
     #[default = ""]
     pub enable_accounts: String,
+    /// Whether pending schema migrations of the game mod are applied
+    /// automatically. If disabled, they must be applied with the
+    /// `db_migrate up` rcon command.
+    #[default = true]
+    pub auto_migrate: bool,
 }
 
 #[config_default]
@@ -577,7 +592,53 @@ pub struct ConfigServerRenderMod {
     pub required: bool,
 }
 
+/// The capabilities of the host a WASM mod is allowed to use.
+/// A mod only gets the capabilities that it requests
+/// in its manifest and that are granted here.
+#[config_default]
+#[derive(Debug, Clone, Serialize, Deserialize, ConfigInterface)]
+pub struct ConfigWasmModGrants {
+    /// Hosts the mod may send http requests to, e.g. `ddnet.org`.
+    /// A leading `*.` allows all subdomains, `*` allows all hosts.
+    #[default = Vec::new()]
+    pub http_hosts: Vec<String>,
+    /// Path prefixes, relative to the data directory,
+    /// the mod may read and write. `*` allows all paths.
+    #[default = Vec::new()]
+    pub fs_paths: Vec<String>,
+    /// Database kinds the mod may use, e.g. `sqlite`. `*` allows all kinds.
+    /// Only game mods on a server have access to a database.
+    #[default = vec!["*".to_string()]]
+    pub db_kinds: Vec<String>,
+}
+
+/// The default of [`ConfigServerGameModLimits::call_time_budget_ms`].
+pub const DEFAULT_GAME_MOD_CALL_TIME_BUDGET_MS: u64 = 5000;
+/// Roughly the number of wasm instructions a mod executes per millisecond,
+/// used to derive the default fuel from the default time budget.
+pub const GAME_MOD_FUEL_PER_MS: u64 = 1_000_000;
+
+/// Resource limits for the game mod module.
+/// A mod that exceeds them is replaced by vanilla.
+#[config_default]
+#[derive(Debug, Clone, Serialize, Deserialize, ConfigInterface)]
+pub struct ConfigServerGameModLimits {
+    /// The maximum number of wasm instructions a single call
+    /// into the mod (e.g. a tick) may execute. 0 means unlimited.
+    /// The default is about as much as fits into the default time budget.
+    #[default = DEFAULT_GAME_MOD_CALL_TIME_BUDGET_MS * GAME_MOD_FUEL_PER_MS]
+    pub fuel_per_call: u64,
+    /// The maximum memory in MiB the mod may allocate. 0 means unlimited.
+    #[default = 1024]
+    pub max_memory_mib: u64,
+    /// The maximum time in milliseconds a single call into the mod
+    /// may take. 0 means unlimited.
+    #[default = DEFAULT_GAME_MOD_CALL_TIME_BUDGET_MS]
+    pub call_time_budget_ms: u64,
+}
+
 pub const MAX_SERVER_NAME_LEN: usize = 64;
+pub const MAX_SERVER_PASSWORD_LEN: usize = 64;
 #[config_default]
 #[derive(Debug, Clone, Serialize, Deserialize, ConfigInterface)]
 pub struct ConfigServer {
@@ -587,6 +648,23 @@ pub struct ConfigServer {
     #[conf_valid(length(max = MAX_MAP_NAME_LEN))]
     #[default = "ctf1"]
     pub map: String,
+    /// The maps the server rotates through.
+    /// Empty disables the map rotation.
+    #[default = Vec::new()]
+    pub maprotation: Vec<String>,
+    /// Play the maps of the rotation in a random order.
+    #[default = false]
+    pub maprotation_shuffle: bool,
+    /// After how many ended rounds the next map
+    /// of the rotation is loaded.
+    /// 0 never changes the map automatically.
+    #[default = 1]
+    pub maprotation_rounds: u32,
+    /// Map changes at fixed times of the day
+    /// in the format `HH:MM map`, e.g. `20:00 ctf5`.
+    /// The time is the local time of the server.
+    #[default = Vec::new()]
+    pub map_schedule: Vec<String>,
     #[default = "0.0.0.0"]
     pub bind_addr_v4: String,
     #[default = "::0"]
@@ -634,14 +712,32 @@ pub struct ConfigServer {
     /// in the database configuration.
     #[default = false]
     pub account_only: bool,
+    /// The password clients have to send to join the server.
+    /// Empty disables the password.
+    #[conf_valid(length(max = MAX_SERVER_PASSWORD_LEN))]
+    #[default = ""]
+    pub password: String,
     #[default = false]
     pub register: bool,
+    /// The register urls of the master servers
+    /// the server registers itself to.
+    #[default = vec!["https://pg.ddnet.org:4444/ddnet/15/register".to_string()]]
+    pub master_servers: Vec<String>,
+    /// Answer probes of clients in the local network,
+    /// so the server is listed in their LAN tab.
+    /// Only clients of a local network get an answer.
+    #[default = false]
+    pub lan_discovery: bool,
     /// The game mod module to load
     /// empty string, "default", "native", "vanilla" & "ddnet"
     /// are reserved names and will not cause
     /// loading a game mod module
     #[default = ""]
     pub game_mod: String,
+    /// Resource limits for the game mod module.
+    pub game_mod_limits: ConfigServerGameModLimits,
+    /// The capabilities the game mod module is allowed to use.
+    pub game_mod_grants: ConfigWasmModGrants,
     /// The render mod module, that the client should load.
     /// Empty string, "default", "native", "vanilla" & "ddnet"
     /// are reserved names and will not cause
@@ -673,6 +769,27 @@ pub struct ConfigServer {
     /// traffic.
     #[default = false]
     pub spatial_chat: bool,
+    /// Record a server side demo of every round automatically.
+    /// The demos are saved in `demos/server_demos/auto`.
+    #[default = false]
+    pub auto_record: bool,
+    /// The maximum number of automatically recorded demos,
+    /// the oldest demos are removed first.
+    /// Demos that were marked to be kept are not counted.
+    /// 0 means unlimited.
+    #[default = 100]
+    pub auto_record_max_count: u32,
+    /// The maximum size of all automatically recorded demos in MiB,
+    /// the oldest demos are removed first.
+    /// Demos that were marked to be kept are not counted.
+    /// 0 means unlimited.
+    #[default = 0]
+    pub auto_record_max_size: u64,
+    /// Write a log of all joins, inputs & commands that change the game,
+    /// which can be replayed to reproduce a game (similar to a teehistorian).
+    /// The logs are saved in `input_history`.
+    #[default = false]
+    pub input_history: bool,
 }
 
 /// Sound configs used during rendering sound & graphics.
@@ -432,7 +432,7 @@ impl GameEvents {
 ///
 /// Generally the implementation is free to ignore any of these. This might
 /// lead to inconsitencies in the user experience tho (see also [`crate::types::snapshot::SnapshotClientInfo`])
-#[derive(Debug, Hiarc, Serialize, Deserialize)]
+#[derive(Debug, Hiarc, Clone, Serialize, Deserialize)]
 pub struct EventClientInfo {
     /// A list of players the client requests the snapshot for.
     /// Usually these are the local players (including the dummy).
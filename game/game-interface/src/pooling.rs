@@ -26,6 +26,7 @@ use crate::types::render::scoreboard::{
     ScoreboardCharacterInfo, ScoreboardPlayerSpectatorInfo, ScoreboardStageInfo,
 };
 use crate::types::render::stage::StageRenderInfo;
+use crate::types::render::switch::SwitchRenderInfo;
 use crate::types::resource_key::{MtNetworkResourceKeyPool, NetworkResourceKeyPool};
 
 /// Make your life easier by simply using all required pools for the interface
@@ -56,6 +57,8 @@ pub struct GamePooling {
         Pool<LinkedHashMap<LaserId, LaserRenderInfo, rustc_hash::FxBuildHasher>>,
     pub pickup_render_info_pool:
         Pool<LinkedHashMap<PickupId, PickupRenderInfo, rustc_hash::FxBuildHasher>>,
+    pub switch_render_info_pool:
+        Pool<LinkedHashMap<u8, SwitchRenderInfo, rustc_hash::FxBuildHasher>>,
     pub stage_scoreboard_pool:
         Pool<LinkedHashMap<StageId, ScoreboardStageInfo, rustc_hash::FxBuildHasher>>,
     pub character_scoreboard_pool: Pool<Vec<ScoreboardCharacterInfo>>,
@@ -96,6 +99,7 @@ impl GamePooling {
             flag_render_info_pool: Pool::with_capacity(hint_max_characters_client),
             laser_render_info_pool: Pool::with_capacity(hint_max_characters_client),
             pickup_render_info_pool: Pool::with_capacity(hint_max_characters_client),
+            switch_render_info_pool: Pool::with_capacity(2),
             stage_scoreboard_pool: Pool::with_capacity(2),
             character_scoreboard_pool: Pool::with_capacity(hint_max_characters_client),
             player_spectator_scoreboard_pool: Pool::with_capacity(hint_max_characters_client),
@@ -11,6 +11,11 @@ pub struct RconCommand {
     pub args: Vec<CommandArg>,
     pub usage: NetworkString<65536>,
     pub description: NetworkString<65536>,
+    /// The minimum auth level required to execute this command.
+    pub auth_level: AuthLevel,
+    /// Named permission groups that allow to execute this command
+    /// independent of the auth level (e.g. `bans`).
+    pub groups: Vec<NetworkString<1024>>,
 }
 
 /// Commands supported by the server.
@@ -20,7 +25,11 @@ pub struct RconCommands {
     pub cmds: HashMap<NetworkString<65536>, RconCommand>,
 }
 
-#[derive(Debug, Hiarc, Default, Clone, Copy, Serialize, Deserialize)]
+/// The auth levels are ordered, a higher level
+/// includes the rights of all lower levels.
+#[derive(
+    Debug, Hiarc, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
 pub enum AuthLevel {
     #[default]
     None,
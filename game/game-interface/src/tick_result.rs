@@ -17,6 +17,9 @@ pub enum TickEvent {
         until: Option<chrono::DateTime<chrono::Utc>>,
         reason: PlayerBanReason,
     },
+    /// The round of the main stage ended and a new one started,
+    /// the server might change the map now.
+    RoundEnded,
 }
 
 /// The tick result contains per tick data
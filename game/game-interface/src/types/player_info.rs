@@ -23,7 +23,7 @@ impl PlayerUniqueId {
 }
 
 /// a player from a client
-#[derive(Debug, Hiarc, Serialize, Deserialize)]
+#[derive(Debug, Hiarc, Clone, Serialize, Deserialize)]
 pub struct PlayerClientInfo {
     pub info: NetworkCharacterInfo,
     /// An _unique_ id given by the client to this player, so the client
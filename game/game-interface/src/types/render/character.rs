@@ -143,6 +143,9 @@ pub struct CharacterRenderInfo {
     /// How many game ticks have passed for this character.
     /// This is the race time, or ticks in an active round.
     pub game_ticks_passed: GameTickType,
+    /// The race time of this character in ticks,
+    /// `None` if the character is not in a race.
+    pub race_ticks_passed: Option<GameTickType>,
 
     /// emoticon ticks passed & emoticon type
     pub emoticon: Option<(GameTickType, EmoticonType)>,
@@ -253,6 +256,20 @@ pub struct CharacterInfo {
     pub browser_eye: TeeEye,
 }
 
+/// The split time of the last time checkpoint the local character
+/// passed during its current race.
+#[derive(Debug, Hiarc, Clone, Copy, Serialize, Deserialize)]
+pub struct LocalCharacterRaceSplit {
+    /// The index of the time checkpoint
+    pub checkpoint: u8,
+    /// The race time at which the checkpoint was passed
+    pub time: Duration,
+    /// The difference in milliseconds to the best run at this checkpoint.
+    /// Negative values mean that the current run is faster.
+    /// `None` if there is no best run that passed this checkpoint.
+    pub diff_to_best_millis: Option<i64>,
+}
+
 /// The local character info for vanilla based mods
 #[derive(Debug, Hiarc, Clone, Copy, Serialize, Deserialize)]
 pub struct LocalCharacterVanilla {
@@ -261,6 +278,9 @@ pub struct LocalCharacterVanilla {
 
     /// A value of `None` means unlimited
     pub ammo_of_weapon: Option<u32>,
+
+    /// The split time of the last passed time checkpoint in a race, if any
+    pub race_split: Option<LocalCharacterRaceSplit>,
 }
 
 /// The local character info for ddrace based mods
@@ -292,6 +312,8 @@ pub struct LocalCharacterDdrace {
     pub can_collide: bool,
     /// The current checkpoint of the user
     pub checkpoint: Option<u8>,
+    /// The split time of the last passed time checkpoint in a race, if any
+    pub race_split: Option<LocalCharacterRaceSplit>,
 }
 
 /// Information about the local character
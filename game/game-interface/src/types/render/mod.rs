@@ -6,4 +6,5 @@ pub mod pickup;
 pub mod projectiles;
 pub mod scoreboard;
 pub mod stage;
+pub mod switch;
 pub mod world;
@@ -0,0 +1,16 @@
+use hiarc::Hiarc;
+use serde::{Deserialize, Serialize};
+
+use crate::types::game::NonZeroGameTickType;
+
+/// The state of a single switch number inside a stage,
+/// which opens & closes doors and other switch-gated tiles.
+#[derive(Debug, Hiarc, Copy, Clone, Serialize, Deserialize)]
+pub struct SwitchRenderInfo {
+    /// Whether doors & other tiles using this switch number
+    /// currently take effect.
+    pub active: bool,
+    /// For timed switches, the ticks until the switch
+    /// reverts its state.
+    pub revert_in: Option<NonZeroGameTickType>,
+}
@@ -6,7 +6,7 @@ use crate::types::id_types::{CharacterId, CtfFlagId, LaserId, PickupId, Projecti
 
 use super::{
     character::CharacterRenderInfo, flag::FlagRenderInfo, laser::LaserRenderInfo,
-    pickup::PickupRenderInfo, projectiles::ProjectileRenderInfo,
+    pickup::PickupRenderInfo, projectiles::ProjectileRenderInfo, switch::SwitchRenderInfo,
 };
 
 /// This represents a single world in the game.
@@ -23,4 +23,7 @@ pub struct WorldRenderInfo {
     pub pickups: PoolFxLinkedHashMap<PickupId, PickupRenderInfo>,
     /// Contains all information about characters that should be rendered
     pub characters: PoolFxLinkedHashMap<CharacterId, CharacterRenderInfo>,
+    /// The state of the switches, the key is the switch number.
+    /// Switch numbers that are not part of this map are active.
+    pub switches: PoolFxLinkedHashMap<u8, SwitchRenderInfo>,
 }
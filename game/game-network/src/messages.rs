@@ -26,8 +26,9 @@ use game_interface::{
 use pool::mt_datatypes::PoolCow;
 use serde::{Deserialize, Serialize};
 use game_base::network::messages::{
-    MsgClAddLocalPlayer, MsgClChatMsg, MsgClInputs, MsgClLoadVotes, MsgClReady, MsgClReadyResponse,
-    MsgClSnapshotAck, MsgSvAddLocalPlayerResponse, MsgSvChatMsg, MsgSvServerInfo,
+    MsgClAddLocalPlayer, MsgClChatMsg, MsgClInputs, MsgClLoadVotes, MsgClPassword, MsgClReady,
+    MsgClReadyResponse, MsgClSnapshotAck, MsgSvAddLocalPlayerResponse, MsgSvChatMsg,
+    MsgSvServerInfo,
 };
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -141,6 +142,9 @@ pub enum ServerToClientMessage<'a> {
         entities: HashMap<PlayerId, MsgSvSpatialChatOfEntitity>,
     },
     AddLocalPlayerResponse(MsgSvAddLocalPlayerResponse),
+    /// A notice for the user from the server,
+    /// e.g. why the game is about to be reloaded.
+    Notice(NetworkString<1024>),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -196,4 +200,5 @@ pub enum ClientToServerMessage<'a> {
     /// Notify the server that the clients wants no
     /// more spatial chat packets.
     SpatialChatDeactivated,
+    Password(MsgClPassword),
 }
@@ -38,7 +38,9 @@ pub mod state_wasm {
     use pool::datatypes::{PoolFxLinkedHashMap, PoolVec};
     use pool::mt_datatypes::PoolCow as MtPoolCow;
     use wasm_logic_db::db::WasmDatabaseLogic;
-    use wasm_runtime::{MemoryLimit, WasmManager, WasmManagerModuleType};
+    use wasm_runtime::{
+        MemoryLimit, WasmCallError, WasmLimits, WasmManager, WasmManagerModuleType, WasmManifest,
+    };
     use wasmer::Module;
 
     use game_interface::{
@@ -63,8 +65,10 @@ pub mod state_wasm {
             info: &mut GameStateStaticInfo,
             io_rt: IoRuntime,
             db: Arc<dyn DbInterface>,
+            manifest: WasmManifest,
+            limits: WasmLimits,
         ) -> anyhow::Result<Self> {
-            let db_logic = WasmDatabaseLogic::new(io_rt, db);
+            let db_logic = WasmDatabaseLogic::new(io_rt, db, manifest);
 
             let wasm_manager = WasmManager::new(
                 WasmManagerModuleType::FromClosure(|store| {
@@ -78,17 +82,25 @@ pub mod state_wasm {
                     Some(imports)
                 },
                 MemoryLimit::TenMebiBytes,
+                limits,
             )?;
             wasm_manager.add_param(0, &map);
             wasm_manager.add_param(1, &map_name);
             wasm_manager.add_param(2, &options);
-            wasm_manager.run_by_name::<()>("game_state_new").unwrap();
+            wasm_manager.run_by_name::<()>("game_state_new")?;
             *info = wasm_manager
                 .get_result_as::<Result<GameStateStaticInfo, String>>()
                 .map_err(|err| anyhow::anyhow!(err))?;
 
             Ok(Self { wasm_manager })
         }
+
+        /// The error of the last call into the mod that failed.
+        ///
+        /// Calls that fail panic, the mod must not be used afterwards.
+        pub fn last_error(&self) -> Option<WasmCallError> {
+            self.wasm_manager.last_error()
+        }
     }
 
     impl GameStateCreate for StateWasm {
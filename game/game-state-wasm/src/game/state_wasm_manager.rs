@@ -1,4 +1,6 @@
+use std::cell::RefCell;
 use std::num::{NonZero, NonZeroU64};
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -39,7 +41,7 @@ use math::math::vector::vec2;
 use pool::datatypes::{PoolFxLinkedHashMap, PoolVec};
 use pool::mt_datatypes::PoolCow as MtPoolCow;
 use vanilla::state::state::GameState;
-use wasm_runtime::WasmManager;
+use wasm_runtime::{WasmCallError, WasmLimits, WasmManager, WasmManifest};
 
 use game_interface::{
     interface::GameStateInterface,
@@ -55,7 +57,11 @@ use super::state_wasm::state_wasm::StateWasm;
 pub enum GameStateMod {
     Native,
     Ddnet,
-    Wasm { file: Vec<u8> },
+    Wasm {
+        file: Vec<u8>,
+        /// The capabilities the module is allowed to use.
+        manifest: WasmManifest,
+    },
 }
 
 enum GameStateWrapper {
@@ -82,8 +88,21 @@ impl GameStateWrapper {
     }
 }
 
+/// Everything required to replace a failing wasm mod
+/// by the native game state.
+struct NativeFallback {
+    map: Vec<u8>,
+    map_name: NetworkReducedAsciiString<MAX_MAP_NAME_LEN>,
+    options: GameStateCreateOptions,
+    io_rt: IoRuntime,
+    db: Arc<dyn DbInterface>,
+}
+
 pub struct GameStateWasmManager {
-    state: GameStateWrapper,
+    state: RefCell<GameStateWrapper>,
+    fallback: Option<NativeFallback>,
+    /// The error that made the wasm mod fail.
+    wasm_error: RefCell<Option<WasmCallError>>,
 
     pub info: GameStateStaticInfo,
 
@@ -91,17 +110,26 @@ pub struct GameStateWasmManager {
 }
 
 pub const STATE_MODS_PATH: &str = "mods/state";
+/// Cache of the modules compiled with metering, see [`WasmManager::compile_module`].
+const METERED_STATE_MODS_CACHE: &str = "mods/state-metered";
 
 impl GameStateWasmManager {
+    /// Compiles the module for the limits it will be used with.
     pub async fn load_module(
         fs: &Arc<dyn FileSystemInterface>,
         file: Vec<u8>,
+        limits: WasmLimits,
     ) -> anyhow::Result<Vec<u8>> {
-        let cache = Arc::new(Cache::<0>::new_async(STATE_MODS_PATH, fs).await);
+        let cache_name = if limits.effective_fuel_per_call().is_some() {
+            METERED_STATE_MODS_CACHE
+        } else {
+            STATE_MODS_PATH
+        };
+        let cache = Arc::new(Cache::<1>::new_async(cache_name, fs).await);
         cache
             .load_from_binary(file, |wasm_bytes| {
                 Box::pin(async move {
-                    Ok(WasmManager::compile_module(&wasm_bytes)?
+                    Ok(WasmManager::compile_module(&wasm_bytes, &limits)?
                         .serialize()?
                         .to_vec())
                 })
@@ -116,7 +144,9 @@ impl GameStateWasmManager {
         options: GameStateCreateOptions,
         io: &Io,
         db: Arc<dyn DbInterface>,
+        limits: WasmLimits,
     ) -> anyhow::Result<Self> {
+        let mut fallback = None;
         let (state, info) = match game_mod {
             GameStateMod::Native => {
                 let (state, info) = GameState::new(map, map_name, options, io.rt.clone(), db)
@@ -130,7 +160,10 @@ impl GameStateWasmManager {
                     .map_err(|err| anyhow!(err))?;
                 (GameStateWrapper::Native(state), info)
             }
-            GameStateMod::Wasm { file: wasm_module } => {
+            GameStateMod::Wasm {
+                file: wasm_module,
+                manifest,
+            } => {
                 let mut info = GameStateStaticInfo {
                     ticks_in_a_second: NonZero::new(50).unwrap(),
                     chat_commands: Default::default(),
@@ -141,6 +174,13 @@ impl GameStateWasmManager {
                     version: "".try_into().unwrap(),
                     options: GameStateServerOptions::default(),
                 };
+                fallback = Some(NativeFallback {
+                    map: map.clone(),
+                    map_name: map_name.clone(),
+                    options: options.clone(),
+                    io_rt: io.rt.clone(),
+                    db: db.clone(),
+                });
                 let state = StateWasm::new(
                     map,
                     map_name,
@@ -149,12 +189,16 @@ impl GameStateWasmManager {
                     &mut info,
                     io.rt.clone(),
                     db,
+                    manifest,
+                    limits,
                 )?;
                 (GameStateWrapper::Wasm(state), info)
             }
         };
         Ok(Self {
-            state,
+            state: RefCell::new(state),
+            fallback,
+            wasm_error: Default::default(),
 
             info,
 
@@ -166,6 +210,76 @@ impl GameStateWasmManager {
     pub fn game_tick_speed(&self) -> NonZeroGameTickType {
         self.info.ticks_in_a_second
     }
+
+    /// The error that made the wasm mod fail, e.g. because it
+    /// exceeded its [`WasmLimits`].
+    ///
+    /// After such an error the mod was replaced by the native game state,
+    /// which does not know anything about the previous game, so the
+    /// game should be reloaded without the mod.
+    pub fn wasm_error(&self) -> Option<WasmCallError> {
+        self.wasm_error.borrow().clone()
+    }
+
+    fn with_state<A: Clone, R>(
+        &self,
+        args: A,
+        f: impl Fn(&mut dyn GameStateInterface, A) -> R,
+    ) -> R {
+        let mut state = self.state.borrow_mut();
+        if !matches!(*state, GameStateWrapper::Wasm(_)) {
+            return f(state.as_mut(), args);
+        }
+        let (res, err) = call_with_fallback(
+            &mut *state,
+            |state| f(state.as_mut(), args.clone()),
+            |state| match state {
+                GameStateWrapper::Wasm(wasm_state) => wasm_state.last_error(),
+                GameStateWrapper::Native(_) => None,
+            },
+            || {
+                let fallback = self
+                    .fallback
+                    .as_ref()
+                    .expect("wasm mods always have a fallback");
+                let (native_state, _) = GameState::new(
+                    fallback.map.clone(),
+                    fallback.map_name.clone(),
+                    fallback.options.clone(),
+                    fallback.io_rt.clone(),
+                    fallback.db.clone(),
+                )
+                .unwrap_or_else(|err| panic!("failed to create the native game state: {err}"));
+                GameStateWrapper::Native(native_state)
+            },
+        );
+        if let Some(err) = err {
+            *self.wasm_error.borrow_mut() = Some(err);
+        }
+        res
+    }
+}
+
+/// Calls `f` on the state. If a call into the wasm module failed,
+/// the state is replaced by the `fallback` and `f` is called on it instead.
+///
+/// Returns the error of the failed call.
+fn call_with_fallback<S, R>(
+    state: &mut S,
+    f: impl Fn(&mut S) -> R,
+    last_error: impl FnOnce(&S) -> Option<WasmCallError>,
+    fallback: impl FnOnce() -> S,
+) -> (R, Option<WasmCallError>) {
+    let panic = match catch_unwind(AssertUnwindSafe(|| f(&mut *state))) {
+        Ok(res) => return (res, None),
+        Err(panic) => panic,
+    };
+    // only failed calls into the module are handled
+    let Some(err) = last_error(state) else {
+        resume_unwind(panic);
+    };
+    *state = fallback();
+    (f(state), Some(err))
 }
 
 impl GameStateCreate for GameStateWasmManager {
@@ -185,40 +299,44 @@ impl GameStateCreate for GameStateWasmManager {
 
 impl GameStateInterface for GameStateWasmManager {
     fn collect_characters_info(&self) -> PoolFxLinkedHashMap<CharacterId, CharacterInfo> {
-        self.state.as_ref().collect_characters_info()
+        self.with_state((), |state, ()| state.collect_characters_info())
     }
 
     fn collect_render_ext(&self) -> PoolVec<u8> {
-        self.state.as_ref().collect_render_ext()
+        self.with_state((), |state, ()| state.collect_render_ext())
     }
 
     fn collect_scoreboard_info(&self) -> Scoreboard {
-        self.state.as_ref().collect_scoreboard_info()
+        self.with_state((), |state, ()| state.collect_scoreboard_info())
     }
 
     fn all_stages(&self, ratio: f64) -> PoolFxLinkedHashMap<StageId, StageRenderInfo> {
-        self.state.as_ref().all_stages(ratio)
+        self.with_state(ratio, |state, ratio| state.all_stages(ratio))
     }
 
     fn collect_character_local_render_info(
         &self,
         player_id: &PlayerId,
     ) -> LocalCharacterRenderInfo {
-        self.state
-            .as_ref()
-            .collect_character_local_render_info(player_id)
+        self.with_state(player_id, |state, player_id| {
+            state.collect_character_local_render_info(player_id)
+        })
     }
 
     fn get_client_camera_join_pos(&self) -> vec2 {
-        self.state.as_ref().get_client_camera_join_pos()
+        self.with_state((), |state, ()| state.get_client_camera_join_pos())
     }
 
     fn player_join(&mut self, player_info: &PlayerClientInfo) -> PlayerId {
-        self.state.as_mut().player_join(player_info)
+        self.with_state(player_info, |state, player_info| {
+            state.player_join(player_info)
+        })
     }
 
     fn player_drop(&mut self, player_id: &PlayerId, reason: PlayerDropReason) {
-        self.state.as_mut().player_drop(player_id, reason)
+        self.with_state((player_id, reason), |state, (player_id, reason)| {
+            state.player_drop(player_id, reason)
+        })
     }
 
     fn try_overwrite_player_character_info(
@@ -227,15 +345,18 @@ impl GameStateInterface for GameStateWasmManager {
         info: &NetworkCharacterInfo,
         version: NonZeroU64,
     ) {
-        self.state
-            .as_mut()
-            .try_overwrite_player_character_info(id, info, version)
+        self.with_state((id, info, version), |state, (id, info, version)| {
+            state.try_overwrite_player_character_info(id, info, version)
+        })
     }
 
     fn account_created(&mut self, account_id: AccountId, cert_fingerprint: Hash) {
-        self.state
-            .as_mut()
-            .account_created(account_id, cert_fingerprint)
+        self.with_state(
+            (account_id, cert_fingerprint),
+            |state, (account_id, cert_fingerprint)| {
+                state.account_created(account_id, cert_fingerprint)
+            },
+        )
     }
 
     fn account_renamed(
@@ -243,19 +364,23 @@ impl GameStateInterface for GameStateWasmManager {
         account_id: AccountId,
         new_name: &NetworkReducedAsciiString<MAX_ACCOUNT_NAME_LEN>,
     ) {
-        self.state.as_mut().account_renamed(account_id, new_name)
+        self.with_state((account_id, new_name), |state, (account_id, new_name)| {
+            state.account_renamed(account_id, new_name)
+        })
     }
 
     fn network_stats(&mut self, stats: PoolFxLinkedHashMap<PlayerId, PlayerNetworkStats>) {
-        self.state.as_mut().network_stats(stats)
+        self.with_state(stats, |state, stats| state.network_stats(stats))
     }
 
     fn settings(&self) -> GameStateSettings {
-        self.state.as_ref().settings()
+        self.with_state((), |state, ()| state.settings())
     }
 
     fn client_command(&mut self, player_id: &PlayerId, cmd: ClientCommand) {
-        self.state.as_mut().client_command(player_id, cmd)
+        self.with_state((player_id, cmd), |state, (player_id, cmd)| {
+            state.client_command(player_id, cmd)
+        })
     }
 
     fn rcon_command(
@@ -263,68 +388,151 @@ impl GameStateInterface for GameStateWasmManager {
         player_id: Option<PlayerId>,
         cmd: ExecRconCommand,
     ) -> Vec<NetworkString<65536>> {
-        self.state.as_mut().rcon_command(player_id, cmd)
+        self.with_state((player_id, cmd), |state, (player_id, cmd)| {
+            state.rcon_command(player_id, cmd)
+        })
     }
 
     fn vote_command(&mut self, cmd: VoteCommand) -> VoteCommandResult {
-        self.state.as_mut().vote_command(cmd)
+        self.with_state(cmd, |state, cmd| state.vote_command(cmd))
     }
 
     fn voted_player(&mut self, player_id: Option<PlayerId>) {
-        self.state.as_mut().voted_player(player_id)
+        self.with_state(player_id, |state, player_id| state.voted_player(player_id))
     }
 
     fn set_player_inputs(&mut self, inps: PoolFxLinkedHashMap<PlayerId, CharacterInputInfo>) {
-        self.state.as_mut().set_player_inputs(inps)
+        self.with_state(inps, |state, inps| state.set_player_inputs(inps))
     }
 
     fn set_player_emoticon(&mut self, player_id: &PlayerId, emoticon: EmoticonType) {
-        self.state.as_mut().set_player_emoticon(player_id, emoticon)
+        self.with_state((player_id, emoticon), |state, (player_id, emoticon)| {
+            state.set_player_emoticon(player_id, emoticon)
+        })
     }
 
     fn set_player_eye(&mut self, player_id: &PlayerId, eye: TeeEye, duration: Duration) {
-        self.state.as_mut().set_player_eye(player_id, eye, duration)
+        self.with_state(
+            (player_id, eye, duration),
+            |state, (player_id, eye, duration)| state.set_player_eye(player_id, eye, duration),
+        )
     }
 
     fn tick(&mut self, options: TickOptions) -> TickResult {
-        self.state.as_mut().tick(options)
+        self.with_state(options, |state, options| state.tick(options))
     }
 
     fn snapshot_for(&self, client: SnapshotClientInfo) -> MtPoolCow<'static, [u8]> {
-        self.state.as_ref().snapshot_for(client)
+        self.with_state(client, |state, client| state.snapshot_for(client))
     }
 
     fn build_from_snapshot(&mut self, snapshot: &MtPoolCow<'static, [u8]>) -> SnapshotLocalPlayers {
-        self.state.as_mut().build_from_snapshot(snapshot)
+        self.with_state(snapshot, |state, snapshot| {
+            state.build_from_snapshot(snapshot)
+        })
     }
 
     fn snapshot_for_hotreload(&self) -> Option<MtPoolCow<'static, [u8]>> {
-        self.state.as_ref().snapshot_for_hotreload()
+        self.with_state((), |state, ()| state.snapshot_for_hotreload())
     }
 
     fn build_from_snapshot_by_hotreload(&mut self, snapshot: &MtPoolCow<'static, [u8]>) {
-        self.state
-            .as_mut()
-            .build_from_snapshot_by_hotreload(snapshot)
+        self.with_state(snapshot, |state, snapshot| {
+            state.build_from_snapshot_by_hotreload(snapshot)
+        })
     }
 
     fn build_from_snapshot_for_prev(&mut self, snapshot: &MtPoolCow<'static, [u8]>) {
-        self.state.as_mut().build_from_snapshot_for_prev(snapshot)
+        self.with_state(snapshot, |state, snapshot| {
+            state.build_from_snapshot_for_prev(snapshot)
+        })
     }
 
     fn build_ghosts_from_snapshot(&self, snapshot: &MtPoolCow<'static, [u8]>) -> GhostResult {
-        self.state.as_ref().build_ghosts_from_snapshot(snapshot)
+        self.with_state(snapshot, |state, snapshot| {
+            state.build_ghosts_from_snapshot(snapshot)
+        })
     }
 
     fn events_for(&self, client: EventClientInfo) -> GameEvents {
-        self.state.as_ref().events_for(client)
+        self.with_state(client, |state, client| state.events_for(client))
     }
 
     fn clear_events(&mut self) {
-        self.state.as_mut().clear_events()
+        self.with_state((), |state, ()| state.clear_events())
     }
 
     fn sync_event_id(&self, event_id: IdGeneratorIdType) {
-        self.state.as_ref().sync_event_id(event_id)
+        self.with_state(event_id, |state, event_id| state.sync_event_id(event_id))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use wasm_runtime::WasmCallError;
+
+    use super::call_with_fallback;
+
+    #[derive(Debug)]
+    enum TestState {
+        Wasm { error: Option<WasmCallError> },
+        Native,
+    }
+
+    fn last_error(state: &TestState) -> Option<WasmCallError> {
+        match state {
+            TestState::Wasm { error } => error.clone(),
+            TestState::Native => None,
+        }
+    }
+
+    #[test]
+    fn no_fallback_if_the_call_succeeds() {
+        let mut state = TestState::Wasm { error: None };
+        let (res, err) = call_with_fallback(&mut state, |_| 1, last_error, || TestState::Native);
+        assert_eq!(res, 1);
+        assert!(err.is_none());
+        assert!(matches!(state, TestState::Wasm { error: None }));
+    }
+
+    #[test]
+    fn fallback_after_a_failed_call() {
+        let mut state = TestState::Wasm { error: None };
+        let (res, err) = call_with_fallback(
+            &mut state,
+            |state| match state {
+                TestState::Wasm { error } => {
+                    // like a call that exceeds the limits
+                    *error = Some(WasmCallError::FuelExhausted { limit: 10 });
+                    panic!("the call into the module failed");
+                }
+                TestState::Native => 2,
+            },
+            last_error,
+            || TestState::Native,
+        );
+        assert_eq!(res, 2);
+        assert!(matches!(
+            err,
+            Some(WasmCallError::FuelExhausted { limit: 10 })
+        ));
+        assert!(matches!(state, TestState::Native));
+    }
+
+    #[test]
+    fn other_panics_are_not_handled() {
+        let mut state = TestState::Wasm { error: None };
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            call_with_fallback(
+                &mut state,
+                |_| -> i32 { panic!("not a failed call into the module") },
+                last_error,
+                || TestState::Native,
+            )
+        }));
+        assert!(res.is_err());
+        assert!(matches!(state, TestState::Wasm { error: None }));
     }
 }
@@ -0,0 +1,248 @@
+//! A compact, append-only log of everything that changes the game state
+//! on the server (joins, drops, inputs, chat & rcon commands, notifications
+//! like account renames and the ticks),
+//! similar to the teehistorian of DDNet.
+//!
+//! Since the game is deterministic, replaying the log through a fresh
+//! [`game_interface::interface::GameStateInterface`] ([`replay::InputHistoryReplay`])
+//! reproduces the game state of the server tick by tick, e.g. to verify records
+//! or to investigate cheating reports after the fact.
+//!
+//! # File format
+//! The file starts with [`INPUT_HISTORY_MAGIC`] & [`INPUT_HISTORY_VERSION`],
+//! followed by blocks. Every block is prefixed by its size as little endian `u32`
+//! and contains zstd compressed bincode.
+//! The first block is the [`InputHistoryHeader`], all other blocks
+//! are a list of [`InputHistoryEntry`].
+//! Since blocks are only appended, a log of a crashed server is still readable
+//! up to the last complete block.
+
+pub mod replay;
+pub mod writer;
+
+use std::{num::NonZeroU64, time::Duration};
+
+use base::{hash::Hash, network_string::NetworkReducedAsciiString};
+use demo::utils::{decomp, deser};
+use game_base::network::messages::GameModification;
+use game_interface::{
+    account_info::MAX_ACCOUNT_NAME_LEN,
+    client_commands::ClientCommand,
+    interface::{GameStateCreateOptions, MAX_MAP_NAME_LEN},
+    rcon_commands::ExecRconCommand,
+    types::{
+        character_info::NetworkCharacterInfo,
+        emoticons::EmoticonType,
+        game::{GameTickType, NonZeroGameTickType},
+        id_types::PlayerId,
+        input::CharacterInputInfo,
+        network_stats::PlayerNetworkStats,
+        player_info::{AccountId, PlayerClientInfo, PlayerDropReason},
+        render::character::TeeEye,
+    },
+    vote_commands::VoteCommand,
+};
+use serde::{Deserialize, Serialize};
+
+pub const INPUT_HISTORY_MAGIC: [u8; 8] = *b"twinphst";
+pub const INPUT_HISTORY_VERSION: u32 = 1;
+
+/// Everything that is required to create the game state
+/// the log was recorded with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputHistoryHeader {
+    pub map: NetworkReducedAsciiString<MAX_MAP_NAME_LEN>,
+    pub map_hash: Hash,
+    pub game_mod: GameModification,
+    pub game_options: GameStateCreateOptions,
+    pub ticks_per_second: NonZeroGameTickType,
+    /// The monotonic tick of the server when the log was started.
+    pub start_monotonic_tick: GameTickType,
+    pub start_time: chrono::DateTime<chrono::Utc>,
+    /// The game state when the log was started,
+    /// see [`game_interface::interface::GameStateInterface::snapshot_for_hotreload`].
+    pub snapshot: Option<Vec<u8>>,
+}
+
+/// A single call into the game state, in the order the server made them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum InputHistoryEntry {
+    Join {
+        /// The id the game returned for this player.
+        player_id: PlayerId,
+        info: PlayerClientInfo,
+    },
+    Drop {
+        player_id: PlayerId,
+        reason: PlayerDropReason,
+    },
+    Inputs(Vec<(PlayerId, CharacterInputInfo)>),
+    Emoticon {
+        player_id: PlayerId,
+        emoticon: EmoticonType,
+    },
+    Eye {
+        player_id: PlayerId,
+        eye: TeeEye,
+        duration: Duration,
+    },
+    CharacterInfo {
+        player_id: PlayerId,
+        info: NetworkCharacterInfo,
+        version: NonZeroU64,
+    },
+    AccountCreated {
+        account_id: AccountId,
+        cert_fingerprint: Hash,
+    },
+    AccountRenamed {
+        account_id: AccountId,
+        new_name: NetworkReducedAsciiString<MAX_ACCOUNT_NAME_LEN>,
+    },
+    NetworkStats(Vec<(PlayerId, PlayerNetworkStats)>),
+    ClientCommand {
+        player_id: PlayerId,
+        cmd: ClientCommand,
+    },
+    RconCommand {
+        player_id: Option<PlayerId>,
+        cmd: ExecRconCommand,
+    },
+    VoteCommand(VoteCommand),
+    VotedPlayer(Option<PlayerId>),
+    /// The game ticked once.
+    Tick,
+}
+
+/// A fully read input history.
+#[derive(Debug)]
+pub struct InputHistory {
+    pub header: InputHistoryHeader,
+    pub entries: Vec<InputHistoryEntry>,
+    /// Whether the end of the log was broken,
+    /// e.g. because the server crashed while writing.
+    pub truncated: bool,
+}
+
+fn read_block(file: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (size, file) = file.split_at_checked(std::mem::size_of::<u32>())?;
+    let size = u32::from_le_bytes(size.try_into().unwrap()) as usize;
+    file.split_at_checked(size)
+}
+
+impl InputHistory {
+    pub fn read(file: &[u8]) -> anyhow::Result<Self> {
+        let (magic, file) = file
+            .split_at_checked(INPUT_HISTORY_MAGIC.len())
+            .ok_or_else(|| anyhow::anyhow!("file is too small for an input history"))?;
+        anyhow::ensure!(magic == INPUT_HISTORY_MAGIC, "not an input history file");
+        let (version, file) = file
+            .split_at_checked(std::mem::size_of::<u32>())
+            .ok_or_else(|| anyhow::anyhow!("file is too small for an input history"))?;
+        let version = u32::from_le_bytes(version.try_into().unwrap());
+        anyhow::ensure!(
+            version == INPUT_HISTORY_VERSION,
+            "input history version {version} is not supported"
+        );
+
+        let mut tmp = Vec::new();
+        let (block, mut file) =
+            read_block(file).ok_or_else(|| anyhow::anyhow!("the header is incomplete"))?;
+        let (header, _) = deser::<InputHistoryHeader>(decomp(block, &mut tmp)?)?;
+
+        let mut entries = Vec::new();
+        let mut truncated = false;
+        while !file.is_empty() {
+            let Some((block, rest)) = read_block(file) else {
+                truncated = true;
+                break;
+            };
+            let Ok((block_entries, _)) =
+                decomp(block, &mut tmp).and_then(deser::<Vec<InputHistoryEntry>>)
+            else {
+                truncated = true;
+                break;
+            };
+            entries.extend(block_entries);
+            file = rest;
+        }
+
+        Ok(Self {
+            header,
+            entries,
+            truncated,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::NonZeroU64;
+
+    use game_base::network::messages::GameModification;
+    use game_interface::{
+        rcon_commands::{AuthLevel, ExecRconCommand},
+        types::{id_gen::IdGenerator, id_types::PlayerId, player_info::PlayerDropReason},
+    };
+
+    use crate::{writer::InputHistoryWriter, InputHistory, InputHistoryEntry, InputHistoryHeader};
+
+    #[test]
+    fn write_and_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.twinputs");
+        let player_id: PlayerId = IdGenerator::new().next_id();
+        let mut writer = InputHistoryWriter::new(
+            &path,
+            &InputHistoryHeader {
+                map: "ctf1".try_into().unwrap(),
+                map_hash: Default::default(),
+                game_mod: GameModification::Native,
+                game_options: Default::default(),
+                ticks_per_second: NonZeroU64::new(2).unwrap(),
+                start_monotonic_tick: 10,
+                start_time: chrono::Utc::now(),
+                snapshot: None,
+            },
+        )
+        .unwrap();
+        writer.add(InputHistoryEntry::RconCommand {
+            player_id: None,
+            cmd: ExecRconCommand {
+                raw: "sv_test 1".try_into().unwrap(),
+                auth_level: AuthLevel::Admin,
+            },
+        });
+        // the first block is written after 2 ticks
+        writer.add(InputHistoryEntry::Tick);
+        writer.add(InputHistoryEntry::Tick);
+        writer.add(InputHistoryEntry::Tick);
+        writer.add(InputHistoryEntry::Drop {
+            player_id,
+            reason: PlayerDropReason::Disconnect,
+        });
+        drop(writer);
+
+        let mut file = std::fs::read(&path).unwrap();
+
+        let history = InputHistory::read(&file).unwrap();
+        assert!(!history.truncated);
+        assert_eq!(history.header.map.as_str(), "ctf1");
+        assert_eq!(history.header.start_monotonic_tick, 10);
+        assert_eq!(history.entries.len(), 5);
+        assert!(matches!(
+            &history.entries[0],
+            InputHistoryEntry::RconCommand { player_id: None, cmd } if cmd.raw.as_str() == "sv_test 1"
+        ));
+        assert!(matches!(
+            history.entries[4],
+            InputHistoryEntry::Drop { player_id: id, .. } if id == player_id
+        ));
+
+        // a crash while writing only loses the last block
+        file.truncate(file.len() - 1);
+        let history = InputHistory::read(&file).unwrap();
+        assert!(history.truncated);
+        assert_eq!(history.entries.len(), 3);
+    }
+}
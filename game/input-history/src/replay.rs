@@ -0,0 +1,336 @@
+use std::borrow::Cow;
+
+use game_interface::{
+    interface::GameStateInterface,
+    types::{game::GameTickType, id_types::PlayerId},
+};
+use pool::{datatypes::PoolFxLinkedHashMap, mt_datatypes::PoolCow as MtPoolCow};
+
+use crate::{InputHistory, InputHistoryEntry};
+
+/// Replays an [`InputHistory`] through a game state.
+///
+/// The game state must be freshly created from the information of the
+/// [`crate::InputHistoryHeader`] (same map, game mod & options),
+/// otherwise the replay will not match the game on the server.
+///
+/// Note that anything that does not go through the game state interface,
+/// e.g. answers of database queries, is not part of the log.
+pub struct InputHistoryReplay {
+    entries: std::vec::IntoIter<InputHistoryEntry>,
+    monotonic_tick: GameTickType,
+}
+
+impl InputHistoryReplay {
+    /// Restores the game state at the start of the log.
+    pub fn new<G: GameStateInterface>(history: InputHistory, game: &mut G) -> Self {
+        if let Some(snapshot) = history.header.snapshot {
+            game.build_from_snapshot_by_hotreload(&MtPoolCow::from_without_pool(Cow::Owned(
+                snapshot,
+            )));
+        }
+        Self {
+            entries: history.entries.into_iter(),
+            monotonic_tick: history.header.start_monotonic_tick,
+        }
+    }
+
+    /// The monotonic tick of the server the game is currently at.
+    pub fn monotonic_tick(&self) -> GameTickType {
+        self.monotonic_tick
+    }
+
+    fn apply<G: GameStateInterface>(game: &mut G, entry: InputHistoryEntry) -> anyhow::Result<()> {
+        match entry {
+            InputHistoryEntry::Join { player_id, info } => {
+                let replay_player_id = game.player_join(&info);
+                anyhow::ensure!(
+                    replay_player_id == player_id,
+                    "the replay diverged: player {player_id:?} joined as {replay_player_id:?}"
+                );
+            }
+            InputHistoryEntry::Drop { player_id, reason } => {
+                game.player_drop(&player_id, reason);
+            }
+            InputHistoryEntry::Inputs(inputs) => {
+                let mut inps: PoolFxLinkedHashMap<PlayerId, _> =
+                    PoolFxLinkedHashMap::new_without_pool();
+                inps.extend(inputs);
+                game.set_player_inputs(inps);
+            }
+            InputHistoryEntry::Emoticon {
+                player_id,
+                emoticon,
+            } => {
+                game.set_player_emoticon(&player_id, emoticon);
+            }
+            InputHistoryEntry::Eye {
+                player_id,
+                eye,
+                duration,
+            } => {
+                game.set_player_eye(&player_id, eye, duration);
+            }
+            InputHistoryEntry::CharacterInfo {
+                player_id,
+                info,
+                version,
+            } => {
+                game.try_overwrite_player_character_info(&player_id, &info, version);
+            }
+            InputHistoryEntry::AccountCreated {
+                account_id,
+                cert_fingerprint,
+            } => {
+                game.account_created(account_id, cert_fingerprint);
+            }
+            InputHistoryEntry::AccountRenamed {
+                account_id,
+                new_name,
+            } => {
+                game.account_renamed(account_id, &new_name);
+            }
+            InputHistoryEntry::NetworkStats(stats) => {
+                let mut player_stats: PoolFxLinkedHashMap<PlayerId, _> =
+                    PoolFxLinkedHashMap::new_without_pool();
+                player_stats.extend(stats);
+                game.network_stats(player_stats);
+            }
+            InputHistoryEntry::ClientCommand { player_id, cmd } => {
+                game.client_command(&player_id, cmd);
+            }
+            InputHistoryEntry::RconCommand { player_id, cmd } => {
+                game.rcon_command(player_id, cmd);
+            }
+            InputHistoryEntry::VoteCommand(cmd) => {
+                game.vote_command(cmd);
+            }
+            InputHistoryEntry::VotedPlayer(player_id) => {
+                game.voted_player(player_id);
+            }
+            InputHistoryEntry::Tick => {
+                game.tick(Default::default());
+                // the server sends the events to the clients, which are not needed here
+                game.clear_events();
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies all entries up to the next tick of the game, including the tick.
+    ///
+    /// Returns `false` if the log has no more ticks,
+    /// in which case the remaining entries (e.g. drops) were applied.
+    pub fn next_tick<G: GameStateInterface>(&mut self, game: &mut G) -> anyhow::Result<bool> {
+        for entry in self.entries.by_ref() {
+            let is_tick = matches!(entry, InputHistoryEntry::Tick);
+            Self::apply(game, entry)?;
+            if is_tick {
+                self.monotonic_tick += 1;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Replays the whole log, `on_tick` is called after every tick
+    /// with the monotonic tick of the server.
+    pub fn run<G: GameStateInterface>(
+        mut self,
+        game: &mut G,
+        mut on_tick: impl FnMut(GameTickType, &mut G),
+    ) -> anyhow::Result<()> {
+        while self.next_tick(game)? {
+            on_tick(self.monotonic_tick, game);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{num::NonZeroU64, sync::Arc, time::Duration};
+
+    use base_io::{io::create_runtime, runtime::IoRuntime};
+    use game_base::network::messages::GameModification;
+    use game_database::dummy::DummyDb;
+    use game_interface::{
+        client_commands::ClientCommand,
+        interface::{GameStateCreate, GameStateInterface},
+        types::{
+            character_info::NetworkCharacterInfo,
+            emoticons::EmoticonType,
+            id_types::PlayerId,
+            input::{CharacterInput, CharacterInputInfo},
+            network_stats::PlayerNetworkStats,
+            player_info::{PlayerClientInfo, PlayerDropReason, PlayerUniqueId},
+            render::character::TeeEye,
+        },
+    };
+    use pool::datatypes::PoolFxLinkedHashMap;
+    use vanilla::state::state::GameState;
+
+    use crate::{
+        replay::InputHistoryReplay, writer::InputHistoryWriter, InputHistory, InputHistoryEntry,
+        InputHistoryHeader,
+    };
+
+    fn new_game() -> GameState {
+        let file = include_bytes!("../../../data/map/maps/ctf1.twmap");
+        GameState::new(
+            file.to_vec(),
+            "ctf1".try_into().unwrap(),
+            Default::default(),
+            IoRuntime::new(create_runtime()),
+            Arc::new(DummyDb),
+        )
+        .unwrap()
+        .0
+    }
+
+    /// Calls into the game and logs the call, like the server does.
+    struct LoggedGame {
+        game: GameState,
+        writer: InputHistoryWriter,
+    }
+
+    impl LoggedGame {
+        fn join(&mut self, id: u64) -> PlayerId {
+            let info = PlayerClientInfo {
+                info: NetworkCharacterInfo::explicit_default(),
+                id,
+                unique_identifier: PlayerUniqueId::Account(0),
+                initial_network_stats: PlayerNetworkStats::default(),
+            };
+            let player_id = self.game.player_join(&info);
+            self.writer.add(InputHistoryEntry::Join { player_id, info });
+            player_id
+        }
+
+        fn tick(&mut self) {
+            self.writer.add(InputHistoryEntry::Tick);
+            self.game.tick(Default::default());
+            self.game.clear_events();
+        }
+    }
+
+    #[test]
+    fn replay_matches_game() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.twinputs");
+        let mut logged = LoggedGame {
+            game: new_game(),
+            writer: InputHistoryWriter::new(
+                &path,
+                &InputHistoryHeader {
+                    map: "ctf1".try_into().unwrap(),
+                    map_hash: Default::default(),
+                    game_mod: GameModification::Native,
+                    game_options: Default::default(),
+                    ticks_per_second: NonZeroU64::new(50).unwrap(),
+                    start_monotonic_tick: 0,
+                    start_time: chrono::Utc::now(),
+                    snapshot: None,
+                },
+            )
+            .unwrap(),
+        };
+
+        let player1 = logged.join(0);
+        let player2 = logged.join(1);
+        for tick in 0..200 {
+            let mut inp = CharacterInput::default();
+            inp.state.dir.set(if (tick / 20) % 2 == 0 { 1 } else { -1 });
+            inp.state.jump.set(tick % 30 < 5);
+            inp.state.fire.set(tick % 10 == 0);
+            let inputs = vec![
+                (
+                    player1,
+                    CharacterInputInfo {
+                        inp,
+                        diff: Default::default(),
+                    },
+                ),
+                (
+                    player2,
+                    CharacterInputInfo {
+                        inp: CharacterInput::default(),
+                        diff: Default::default(),
+                    },
+                ),
+            ];
+            let mut inps = PoolFxLinkedHashMap::new_without_pool();
+            inps.extend(inputs.iter().cloned());
+            logged.writer.add(InputHistoryEntry::Inputs(inputs));
+            logged.game.set_player_inputs(inps);
+
+            match tick {
+                50 => {
+                    logged.writer.add(InputHistoryEntry::Emoticon {
+                        player_id: player1,
+                        emoticon: EmoticonType::HEARTS,
+                    });
+                    logged
+                        .game
+                        .set_player_emoticon(&player1, EmoticonType::HEARTS);
+                    logged.writer.add(InputHistoryEntry::Eye {
+                        player_id: player2,
+                        eye: TeeEye::Happy,
+                        duration: Duration::from_secs(1),
+                    });
+                    logged
+                        .game
+                        .set_player_eye(&player2, TeeEye::Happy, Duration::from_secs(1));
+                }
+                80 => {
+                    let mut info = NetworkCharacterInfo::explicit_default();
+                    info.name = "renamed".try_into().unwrap();
+                    let version = NonZeroU64::new(2).unwrap();
+                    logged.writer.add(InputHistoryEntry::CharacterInfo {
+                        player_id: player2,
+                        info: info.clone(),
+                        version,
+                    });
+                    logged
+                        .game
+                        .try_overwrite_player_character_info(&player2, &info, version);
+                }
+                100 => {
+                    logged.writer.add(InputHistoryEntry::ClientCommand {
+                        player_id: player2,
+                        cmd: ClientCommand::Kill,
+                    });
+                    logged.game.client_command(&player2, ClientCommand::Kill);
+                }
+                150 => {
+                    logged.writer.add(InputHistoryEntry::Drop {
+                        player_id: player2,
+                        reason: PlayerDropReason::Disconnect,
+                    });
+                    logged
+                        .game
+                        .player_drop(&player2, PlayerDropReason::Disconnect);
+                }
+                _ => {}
+            }
+            logged.tick();
+        }
+        let LoggedGame { game, writer } = logged;
+        drop(writer);
+
+        let file = std::fs::read(&path).unwrap();
+        let history = InputHistory::read(&file).unwrap();
+        assert!(!history.truncated);
+
+        let mut replay_game = new_game();
+        let mut ticks = 0;
+        InputHistoryReplay::new(history, &mut replay_game)
+            .run(&mut replay_game, |_, _| ticks += 1)
+            .unwrap();
+        assert_eq!(ticks, 200);
+
+        let snapshot = game.snapshot_for_hotreload().unwrap();
+        let replay_snapshot = replay_game.snapshot_for_hotreload().unwrap();
+        assert!(*snapshot == *replay_snapshot);
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    fs::File,
+    io::Write,
+    path::Path,
+    sync::mpsc::{channel, Sender},
+};
+
+use base::join_thread::JoinThread;
+use demo::utils::{comp, ser};
+use game_interface::types::game::GameTickType;
+use serde::Serialize;
+
+use crate::{InputHistoryEntry, InputHistoryHeader, INPUT_HISTORY_MAGIC, INPUT_HISTORY_VERSION};
+
+/// Serializes & compresses a value into a block of the file,
+/// which is prefixed by its size.
+fn encode_block<T: Serialize>(v: &T, tmp: &mut Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    let mut block = 0_u32.to_le_bytes().to_vec();
+    comp(ser(v, tmp)?, &mut block, false)?;
+    let size = (block.len() - std::mem::size_of::<u32>()) as u32;
+    block[0..std::mem::size_of::<u32>()].copy_from_slice(&size.to_le_bytes());
+    Ok(block)
+}
+
+/// Writes an input history, see the crate documentation.
+///
+/// Entries are collected and written as a single block once per second
+/// (in game ticks) by a separate thread, the remaining entries
+/// are written when the writer is dropped.
+#[derive(Debug)]
+pub struct InputHistoryWriter {
+    entries: Vec<InputHistoryEntry>,
+    ticks_in_block: GameTickType,
+    ticks_per_block: GameTickType,
+    tmp: Vec<u8>,
+
+    sender: Option<Sender<Vec<u8>>>,
+    // must be the last entry
+    _thread: JoinThread<()>,
+}
+
+impl InputHistoryWriter {
+    pub fn new(path: &Path, header: &InputHistoryHeader) -> anyhow::Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = File::create(path)?;
+        let mut tmp = Vec::new();
+        file.write_all(&INPUT_HISTORY_MAGIC)?;
+        file.write_all(&INPUT_HISTORY_VERSION.to_le_bytes())?;
+        file.write_all(&encode_block(header, &mut tmp)?)?;
+
+        let (sender, recv) = channel::<Vec<u8>>();
+        let log_path = path.to_path_buf();
+        let thread = std::thread::Builder::new()
+            .name("input-history".to_string())
+            .spawn(move || {
+                while let Ok(block) = recv.recv() {
+                    if let Err(err) = file.write_all(&block).and_then(|_| file.flush()) {
+                        log::error!(
+                            "failed to write the input history {}: {err}",
+                            log_path.display()
+                        );
+                        break;
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            entries: Default::default(),
+            ticks_in_block: 0,
+            ticks_per_block: header.ticks_per_second.get(),
+            tmp,
+
+            sender: Some(sender),
+            _thread: JoinThread::new(thread),
+        })
+    }
+
+    pub fn add(&mut self, entry: InputHistoryEntry) {
+        let is_tick = matches!(entry, InputHistoryEntry::Tick);
+        self.entries.push(entry);
+        if is_tick {
+            self.ticks_in_block += 1;
+            if self.ticks_in_block >= self.ticks_per_block {
+                self.flush();
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        self.ticks_in_block = 0;
+        if self.entries.is_empty() {
+            return;
+        }
+        match encode_block(&self.entries, &mut self.tmp) {
+            Ok(block) => {
+                if let Some(sender) = &self.sender {
+                    // if the writer thread died, the error was already logged
+                    let _ = sender.send(block);
+                }
+            }
+            Err(err) => log::error!("failed to encode the input history: {err}"),
+        }
+        self.entries.clear();
+    }
+}
+
+impl Drop for InputHistoryWriter {
+    fn drop(&mut self) {
+        self.flush();
+        // ends the writer thread after all blocks are written
+        self.sender.take();
+    }
+}
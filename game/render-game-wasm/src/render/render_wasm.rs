@@ -21,7 +21,7 @@ pub mod render_wasm {
     use wasm_logic_graphics::WasmGraphicsLogic;
     use wasm_logic_http::http::WasmHttpLogic;
     use wasm_logic_sound::sound::WasmSoundLogic;
-    use wasm_runtime::{MemoryLimit, WasmManager, WasmManagerModuleType};
+    use wasm_runtime::{MemoryLimit, WasmLimits, WasmManager, WasmManagerModuleType, WasmManifest};
     use wasmer::Module;
 
     pub struct RenderWasm {
@@ -38,14 +38,15 @@ pub mod render_wasm {
             backend: &Rc<GraphicsBackend>,
             io: &Io,
             wasm_module: &[u8],
+            manifest: WasmManifest,
             map_file: Vec<u8>,
             config: &ConfigDebug,
             props: RenderGameCreateOptions,
         ) -> anyhow::Result<Self> {
             let sound_logic = WasmSoundLogic::new(u128::MAX / 2, sound);
             let graphics_logic = WasmGraphicsLogic::new(graphics, backend.clone(), u128::MAX / 2);
-            let fs_logic = WasmFileSystemLogic::new(io.clone());
-            let http_logic = WasmHttpLogic::new(io.clone());
+            let fs_logic = WasmFileSystemLogic::new(io.clone(), manifest.clone());
+            let http_logic = WasmHttpLogic::new(io.clone(), manifest);
             let wasm_manager: WasmManager = WasmManager::new(
                 WasmManagerModuleType::FromClosure(|store| {
                     match unsafe { Module::deserialize(store, wasm_module) } {
@@ -61,6 +62,7 @@ pub mod render_wasm {
                     Some(imports)
                 },
                 MemoryLimit::OneGibiByte,
+                WasmLimits::client(),
             )
             .unwrap();
             wasm_manager.add_param(0, &map_file);
@@ -16,14 +16,18 @@ use graphics_backend::backend::GraphicsBackend;
 use graphics_types::types::WindowProps;
 use rayon::ThreadPool;
 use sound::sound::SoundManager;
-use wasm_runtime::WasmManager;
+use wasm_runtime::{WasmLimits, WasmManager, WasmManifest};
 
 use super::render_wasm::render_wasm::RenderWasm;
 
 #[derive(Debug, Clone)]
 pub enum RenderGameMod {
     Native,
-    Wasm { file: Vec<u8> },
+    Wasm {
+        file: Vec<u8>,
+        /// The capabilities the module is allowed to use.
+        manifest: WasmManifest,
+    },
 }
 
 pub enum RenderGameWrapper {
@@ -63,14 +67,16 @@ impl RenderGameWasmManager {
         fs: &Arc<dyn FileSystemInterface>,
         file: Vec<u8>,
     ) -> anyhow::Result<Vec<u8>> {
-        let cache = Arc::new(Cache::<0>::new_async(RENDER_MODS_PATH, fs).await);
+        let cache = Arc::new(Cache::<2>::new_async(RENDER_MODS_PATH, fs).await);
 
         cache
             .load_from_binary(file, |wasm_bytes| {
                 Box::pin(async move {
-                    Ok(WasmManager::compile_module(&wasm_bytes)?
-                        .serialize()?
-                        .to_vec())
+                    Ok(
+                        WasmManager::compile_module(&wasm_bytes, &WasmLimits::client())?
+                            .serialize()?
+                            .to_vec(),
+                    )
                 })
             })
             .await
@@ -107,9 +113,10 @@ impl RenderGameWasmManager {
                 .map_err(|err| anyhow!(err))?;
                 RenderGameWrapper::Native(state)
             }
-            RenderGameMod::Wasm { file } => {
-                let state =
-                    RenderWasm::new(sound, graphics, backend, io, &file, map_file, config, props)?;
+            RenderGameMod::Wasm { file, manifest } => {
+                let state = RenderWasm::new(
+                    sound, graphics, backend, io, &file, manifest, map_file, config, props,
+                )?;
                 RenderGameWrapper::Wasm(state)
             }
         };
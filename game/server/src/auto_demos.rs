@@ -0,0 +1,237 @@
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::anyhow;
+
+/// The sub directory (of the demo directory) of automatically recorded demos.
+pub const AUTO_DEMOS_DIR: &str = "server_demos/auto";
+/// The sub directory (of the demo directory) of automatically recorded demos
+/// that were marked to be kept. These are never cleaned up.
+pub const KEPT_DEMOS_DIR: &str = "server_demos/kept";
+
+const DEMO_EXTENSION: &str = "twdemo";
+
+#[derive(Debug)]
+pub struct AutoDemo {
+    /// The file name without extension.
+    pub name: String,
+    /// The size of the file in bytes.
+    pub size: u64,
+    pub kept: bool,
+    pub modified: SystemTime,
+}
+
+/// Manages the demos that are recorded for every round,
+/// if `sv.auto_record` is enabled.
+///
+/// Old demos are removed by [`AutoDemos::cleanup`], unless they
+/// were marked to be kept, which moves them into their own directory.
+#[derive(Debug, Clone)]
+pub struct AutoDemos {
+    auto_path: PathBuf,
+    kept_path: PathBuf,
+}
+
+impl AutoDemos {
+    pub fn new(demos_path: &Path) -> Self {
+        Self {
+            auto_path: demos_path.join(AUTO_DEMOS_DIR),
+            kept_path: demos_path.join(KEPT_DEMOS_DIR),
+        }
+    }
+
+    /// The name of a new demo of the given map,
+    /// which includes the current time, so it is unique per round.
+    pub fn demo_name(map: &str) -> String {
+        format!("{map}_{}", chrono::Local::now().format("%Y_%m_%d_%H_%M_%S"))
+    }
+
+    fn read_dir(path: &Path, kept: bool) -> anyhow::Result<Vec<AutoDemo>> {
+        let dir = match std::fs::read_dir(path) {
+            Ok(dir) => dir,
+            // nothing was recorded yet
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        let mut demos = Vec::new();
+        for entry in dir {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != DEMO_EXTENSION) {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let meta = entry.metadata()?;
+            if !meta.is_file() {
+                continue;
+            }
+            demos.push(AutoDemo {
+                name: name.to_string(),
+                size: meta.len(),
+                kept,
+                modified: meta.modified()?,
+            });
+        }
+        Ok(demos)
+    }
+
+    /// All automatically recorded demos, kept ones included,
+    /// sorted from oldest to newest.
+    pub fn list(&self) -> anyhow::Result<Vec<AutoDemo>> {
+        let mut demos = Self::read_dir(&self.auto_path, false)?;
+        demos.extend(Self::read_dir(&self.kept_path, true)?);
+        demos.sort_by(|d1, d2| d1.modified.cmp(&d2.modified).then(d1.name.cmp(&d2.name)));
+        Ok(demos)
+    }
+
+    /// Marks a demo to be kept, so it is never removed by the cleanup.
+    pub fn keep(&self, name: &str) -> anyhow::Result<()> {
+        // the name must not leave the directory
+        anyhow::ensure!(
+            !name.is_empty() && !name.contains(['/', '\\']) && name != "..",
+            "{name} is not a valid demo name"
+        );
+        let file_name = format!("{name}.{DEMO_EXTENSION}");
+        let path = self.auto_path.join(&file_name);
+        if !path.is_file() {
+            return Err(if self.kept_path.join(&file_name).is_file() {
+                anyhow!("The demo {name} is already kept")
+            } else {
+                anyhow!("The demo {name} was not found")
+            });
+        }
+        std::fs::create_dir_all(&self.kept_path)?;
+        std::fs::rename(path, self.kept_path.join(file_name))?;
+        Ok(())
+    }
+
+    /// Removes the oldest demos that are not kept, until at most `max_count`
+    /// demos with a total size of at most `max_size` bytes are left.
+    /// A limit of `0` disables it.
+    ///
+    /// Returns the names of the removed demos.
+    pub fn cleanup(&self, max_count: usize, max_size: u64) -> anyhow::Result<Vec<String>> {
+        let mut demos = Self::read_dir(&self.auto_path, false)?;
+        demos.sort_by(|d1, d2| d1.modified.cmp(&d2.modified).then(d1.name.cmp(&d2.name)));
+
+        let mut total_size: u64 = demos.iter().map(|demo| demo.size).sum();
+        let mut count = demos.len();
+        let mut removed = Vec::new();
+        for demo in demos {
+            if (max_count == 0 || count <= max_count) && (max_size == 0 || total_size <= max_size) {
+                break;
+            }
+            std::fs::remove_file(
+                self.auto_path
+                    .join(format!("{}.{DEMO_EXTENSION}", demo.name)),
+            )?;
+            count -= 1;
+            total_size -= demo.size;
+            removed.push(demo.name);
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        fs::File,
+        path::Path,
+        time::{Duration, SystemTime},
+    };
+
+    use super::{AutoDemos, AUTO_DEMOS_DIR, KEPT_DEMOS_DIR};
+
+    fn write_demo(dir: &Path, name: &str, size: u64, age: u64) {
+        std::fs::create_dir_all(dir).unwrap();
+        let file = File::create(dir.join(format!("{name}.twdemo"))).unwrap();
+        file.set_len(size).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(age))
+            .unwrap();
+    }
+
+    /// Five demos of 10 bytes each, `demo_0` is the oldest.
+    fn demos(path: &Path) -> AutoDemos {
+        for i in 0..5 {
+            write_demo(
+                &path.join(AUTO_DEMOS_DIR),
+                &format!("demo_{i}"),
+                10,
+                100 - i,
+            );
+        }
+        // not a demo
+        std::fs::write(path.join(AUTO_DEMOS_DIR).join("notes.txt"), "").unwrap();
+        AutoDemos::new(path)
+    }
+
+    fn names(demos: &AutoDemos) -> Vec<String> {
+        demos
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|demo| demo.name)
+            .collect()
+    }
+
+    #[test]
+    fn cleanup_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let demos = demos(dir.path());
+        assert_eq!(demos.cleanup(0, 0).unwrap(), Vec::<String>::new());
+        assert_eq!(demos.cleanup(3, 0).unwrap(), vec!["demo_0", "demo_1"]);
+        assert_eq!(names(&demos), vec!["demo_2", "demo_3", "demo_4"]);
+        assert!(dir.path().join(AUTO_DEMOS_DIR).join("notes.txt").is_file());
+    }
+
+    #[test]
+    fn cleanup_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let demos = demos(dir.path());
+        assert_eq!(
+            demos.cleanup(0, 25).unwrap(),
+            vec!["demo_0", "demo_1", "demo_2"]
+        );
+        assert_eq!(names(&demos), vec!["demo_3", "demo_4"]);
+
+        // both limits apply
+        assert_eq!(demos.cleanup(5, 10).unwrap(), vec!["demo_3"]);
+    }
+
+    #[test]
+    fn kept_demos() {
+        let dir = tempfile::tempdir().unwrap();
+        let demos = demos(dir.path());
+        demos.keep("demo_0").unwrap();
+        assert!(dir
+            .path()
+            .join(KEPT_DEMOS_DIR)
+            .join("demo_0.twdemo")
+            .is_file());
+        assert!(demos.keep("demo_0").is_err());
+
+        // kept demos neither count to the limits nor are removed
+        assert_eq!(demos.cleanup(2, 0).unwrap(), vec!["demo_1", "demo_2"]);
+        let list = demos.list().unwrap();
+        assert_eq!(list.len(), 3);
+        assert!(list[0].kept && list[0].name == "demo_0");
+        assert!(list[1..].iter().all(|demo| !demo.kept));
+    }
+
+    #[test]
+    fn keep_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let demos = demos(dir.path());
+        write_demo(&dir.path().join("server_demos"), "outside", 10, 0);
+        for name in ["", "..", "../outside", "../auto/demo_0", "a\\b", "missing"] {
+            assert!(demos.keep(name).is_err(), "{name}");
+        }
+        assert!(dir.path().join("server_demos/outside.twdemo").is_file());
+        assert_eq!(names(&demos).len(), 5);
+    }
+}
@@ -0,0 +1,214 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use anyhow::anyhow;
+use base_io::io::Io;
+use network::network::connection_ban::{ConnectionBans, IpRangeBan};
+use serde::{Deserialize, Serialize};
+
+use crate::network_plugins::cert_ban::{CertBans, IdBan};
+
+const BAN_LIST_PATH: &str = "bans.json";
+/// A ban list that could not be parsed is moved here,
+/// so the bans are not lost when the ban list is saved again.
+const BAN_LIST_BACKUP_PATH: &str = "bans.json.bak";
+
+/// All bans of the server that survive a restart.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BanList {
+    pub ip_bans: Vec<IpRangeBan>,
+    /// Account & cert fingerprint bans.
+    pub id_bans: Vec<IdBan>,
+}
+
+impl BanList {
+    /// Loads the saved ban list, an empty one if none was saved yet.
+    ///
+    /// A ban list that can not be parsed is backed up to [`BAN_LIST_BACKUP_PATH`].
+    pub fn load(io: &Io) -> anyhow::Result<Self> {
+        let fs = io.fs.clone();
+
+        io.rt
+            .spawn(async move {
+                let file = match fs.read_file(BAN_LIST_PATH.as_ref()).await {
+                    Ok(file) => file,
+                    // nobody was banned yet
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                        return Ok(Self::default());
+                    }
+                    Err(err) => return Err(anyhow!("failed to read {BAN_LIST_PATH}: {err}")),
+                };
+                match Self::from_json(&file) {
+                    Ok(ban_list) => Ok(ban_list),
+                    Err(err) => {
+                        fs.write_file(BAN_LIST_BACKUP_PATH.as_ref(), file)
+                            .await
+                            .map_err(|backup_err| {
+                                anyhow!(
+                                    "failed to parse {BAN_LIST_PATH}: {err}, \
+                                    backing it up failed: {backup_err}"
+                                )
+                            })?;
+                        log::error!(
+                            "failed to parse {BAN_LIST_PATH}, \
+                            it was backed up to {BAN_LIST_BACKUP_PATH}: {err}"
+                        );
+                        Ok(Self::default())
+                    }
+                }
+            })
+            .get_storage()
+    }
+
+    fn from_json(file: &[u8]) -> anyhow::Result<Self> {
+        Ok(serde_json::from_slice(file)?)
+    }
+
+    fn to_json(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(serde_json::to_vec_pretty(self)?)
+    }
+
+    pub fn from_bans(connection_bans: &ConnectionBans, cert_bans: Option<&CertBans>) -> Self {
+        Self {
+            ip_bans: connection_bans.bans(),
+            id_bans: cert_bans.map(|bans| bans.bans()).unwrap_or_default(),
+        }
+    }
+
+    /// Adds all bans of this list to the ban plugins.
+    ///
+    /// Returns the account & cert fingerprint bans that could not be
+    /// applied, because accounts are disabled on this server.
+    pub fn apply(
+        self,
+        connection_bans: &ConnectionBans,
+        cert_bans: Option<&CertBans>,
+    ) -> Vec<IdBan> {
+        for IpRangeBan { net, ban } in self.ip_bans {
+            // nobody is connected yet
+            let _ = connection_bans.ban_range(net, ban.ty, ban.until);
+        }
+        match cert_bans {
+            Some(cert_bans) => {
+                for IdBan { id, ban } in self.id_bans {
+                    let _ = cert_bans.ban_id(id, ban.ty, ban.until);
+                }
+                Vec::new()
+            }
+            None => self.id_bans,
+        }
+    }
+}
+
+/// Writes the ban list in the background, one write at a time.
+///
+/// A write is skipped if a newer ban list was saved in the meantime,
+/// so the file always ends up with the newest bans.
+#[derive(Debug, Default)]
+pub struct BanListWriter {
+    generation: Arc<AtomicU64>,
+    write_lock: Arc<futures::lock::Mutex<()>>,
+}
+
+impl BanListWriter {
+    pub fn save(&self, ban_list: &BanList, io: &Io) {
+        let file = match ban_list.to_json() {
+            Ok(file) => file,
+            Err(err) => {
+                log::error!("failed to serialize the ban list: {err}");
+                return;
+            }
+        };
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let newest_generation = self.generation.clone();
+        let write_lock = self.write_lock.clone();
+        let fs = io.fs.clone();
+        io.rt.spawn_without_lifetime(async move {
+            let _g = write_lock.lock().await;
+            if newest_generation.load(Ordering::SeqCst) == generation {
+                fs.write_file(BAN_LIST_PATH.as_ref(), file).await?;
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use game_interface::types::player_info::PlayerUniqueId;
+    use network::network::{
+        connection_ban::{Ban, ConnectionBans},
+        errors::BanType,
+    };
+
+    use crate::network_plugins::cert_ban::IdBan;
+
+    use super::BanList;
+
+    #[test]
+    fn round_trip() {
+        let until = chrono::Utc::now() + chrono::Duration::hours(1);
+        let connection_bans = ConnectionBans::default();
+        let _ = connection_bans.ban_ip("127.0.0.1".parse().unwrap(), BanType::Admin, None);
+        let _ = connection_bans.ban_range(
+            "10.0.0.0/8".parse().unwrap(),
+            BanType::Custom("reason".to_string()),
+            Some(until),
+        );
+        let _ = connection_bans.ban_ip("::1".parse().unwrap(), BanType::Vpn, None);
+
+        let mut ban_list = BanList::from_bans(&connection_bans, None);
+        ban_list.id_bans.push(IdBan {
+            id: PlayerUniqueId::Account(5),
+            ban: Ban {
+                until: Some(until),
+                ty: BanType::Admin,
+            },
+        });
+
+        let loaded = BanList::from_json(&ban_list.to_json().unwrap()).unwrap();
+        let loaded_bans = ConnectionBans::default();
+        // without cert bans, the id bans are returned
+        let id_bans = loaded.apply(&loaded_bans, None);
+        assert_eq!(id_bans.len(), 1);
+        assert_eq!(id_bans[0].id, PlayerUniqueId::Account(5));
+        assert_eq!(id_bans[0].ban.until, Some(until));
+
+        let mut bans: Vec<_> = loaded_bans
+            .bans()
+            .into_iter()
+            .map(|ban| {
+                (
+                    ban.net.to_string(),
+                    ban.ban.until,
+                    format!("{:?}", ban.ban.ty),
+                )
+            })
+            .collect();
+        bans.sort();
+        assert_eq!(
+            bans,
+            vec![
+                (
+                    "10.0.0.0/8".to_string(),
+                    Some(until),
+                    "Custom(\"reason\")".to_string()
+                ),
+                ("127.0.0.1/32".to_string(), None, "Admin".to_string()),
+                ("::1/128".to_string(), None, "Vpn".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn corrupt() {
+        assert!(BanList::from_json(b"{\"ip_bans\": [").is_err());
+        assert!(BanList::from_json(b"").is_err());
+
+        let empty = BanList::from_json(&BanList::default().to_json().unwrap()).unwrap();
+        assert!(empty.ip_bans.is_empty());
+        assert!(empty.id_bans.is_empty());
+    }
+}
@@ -13,9 +13,22 @@ use game_network::messages::MsgSvInputAck;
 use network::network::connection::NetworkConnectionId;
 use pool::{datatypes::PoolFxLinkedHashMap, pool::Pool};
 use game_base::network::messages::PlayerInputChainable;
+use subtle::ConstantTimeEq;
 
 use crate::server_game::ClientAuth;
 
+/// How long clients of password protected servers have to send their password.
+pub const PASSWORD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Whether the password a client sent allows it to join the server.
+///
+/// An empty server password allows every client.
+pub fn password_matches(server_password: &str, password: Option<&str>) -> bool {
+    server_password.is_empty()
+        || password
+            .is_some_and(|password| password.as_bytes().ct_eq(server_password.as_bytes()).into())
+}
+
 /// A network queued client is a client that isn't actually part of the game,
 /// but e.g. waiting for a slot.
 #[derive(Debug)]
@@ -65,6 +78,7 @@ impl ServerNetworkClient {
             auth: ClientAuth {
                 cert,
                 level: Default::default(),
+                permissions: Default::default(),
             },
             network_stats,
         }
@@ -159,6 +173,8 @@ impl ServerClient {
 
 #[derive(Debug)]
 pub struct Clients {
+    /// Clients of a password protected server that did not send their password yet.
+    pub network_password_clients: HashMap<NetworkConnectionId, ServerNetworkQueuedClient>,
     pub network_queued_clients: FxLinkedHashMap<NetworkConnectionId, ServerNetworkQueuedClient>,
     pub network_clients: HashMap<NetworkConnectionId, ServerNetworkClient>,
     pub clients: HashMap<NetworkConnectionId, ServerClient>,
@@ -176,12 +192,29 @@ impl Clients {
                     rustc_hash::FxBuildHasher,
                 )
             }),
+            network_password_clients: Default::default(),
             network_queued_clients: Default::default(),
             network_clients: Default::default(),
             clients: Default::default(),
         }
     }
 
+    /// Removes the clients that did not send their password within [`PASSWORD_TIMEOUT`].
+    pub fn take_expired_password_clients(
+        &mut self,
+        cur_time: &Duration,
+    ) -> Vec<NetworkConnectionId> {
+        let mut expired = Vec::new();
+        self.network_password_clients.retain(|con_id, client| {
+            let is_expired = cur_time.saturating_sub(client.connect_timestamp) > PASSWORD_TIMEOUT;
+            if is_expired {
+                expired.push(*con_id);
+            }
+            !is_expired
+        });
+        expired
+    }
+
     pub fn try_client_ready(&mut self, con_id: &NetworkConnectionId) -> Option<&mut ServerClient> {
         // check if the client can be part of the game
         self.network_clients.remove(con_id).and_then(|net_client| {
@@ -210,3 +243,22 @@ impl Clients {
         ips.len()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::password_matches;
+
+    #[test]
+    fn password() {
+        // no password required
+        assert!(password_matches("", None));
+        assert!(password_matches("", Some("secret")));
+
+        // wrong, missing & correct password
+        assert!(!password_matches("secret", Some("Secret")));
+        assert!(!password_matches("secret", Some("secret2")));
+        assert!(!password_matches("secret", Some("")));
+        assert!(!password_matches("secret", None));
+        assert!(password_matches("secret", Some("secret")));
+    }
+}
@@ -1,10 +1,14 @@
 #![allow(clippy::too_many_arguments)]
 
+pub mod auto_demos;
 pub mod auto_map_votes;
+pub mod ban_list;
 pub mod client;
 pub mod local_server;
+pub mod map_rotation;
 pub mod map_votes;
 pub mod network_plugins;
+pub mod postgres_accounts;
 pub mod rcon;
 pub mod server;
 pub mod server_game;
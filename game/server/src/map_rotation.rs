@@ -0,0 +1,267 @@
+use std::collections::VecDeque;
+
+use chrono::{Duration, NaiveDateTime, NaiveTime, Timelike};
+use game_config::config::ConfigServer;
+use rand::seq::SliceRandom;
+
+/// Changes the map after a number of rounds, on request
+/// or at scheduled times of the day.
+///
+/// The maps are read from the config every time, so changes
+/// to the rotation (e.g. by rcon) apply immediately.
+#[derive(Debug, Default)]
+pub struct MapRotation {
+    /// The maps that are left in the current shuffled rotation.
+    shuffled: VecDeque<String>,
+    /// Ended rounds since the last map change.
+    rounds: u32,
+    /// The minute the schedule was checked the last time.
+    last_schedule_check: Option<NaiveDateTime>,
+    /// A map change that should happen as soon as possible.
+    pending: Option<String>,
+}
+
+impl MapRotation {
+    pub fn new(config: &ConfigServer) -> Self {
+        for entry in config
+            .map_schedule
+            .iter()
+            .filter(|entry| Self::parse_schedule_entry(entry).is_none())
+        {
+            log::warn!("Ignoring invalid map schedule entry \"{entry}\", expected \"HH:MM map\"");
+        }
+        Self::default()
+    }
+
+    fn parse_schedule_entry(entry: &str) -> Option<(NaiveTime, &str)> {
+        let (time, map) = entry.trim().split_once(char::is_whitespace)?;
+        let time = NaiveTime::parse_from_str(time, "%H:%M").ok()?;
+        let map = map.trim();
+        (!map.is_empty()).then_some((time, map))
+    }
+
+    /// The map that follows the current map in the rotation.
+    pub fn next_map(&mut self, config: &ConfigServer, cur_map: &str) -> Option<String> {
+        let maps = &config.maprotation;
+        if maps.is_empty() {
+            return None;
+        }
+
+        if !config.maprotation_shuffle {
+            let next_index = maps
+                .iter()
+                .position(|map| map == cur_map)
+                .map(|index| index + 1)
+                .unwrap_or_default();
+            return maps.get(next_index % maps.len()).cloned();
+        }
+
+        // maps might have been removed from the rotation in the meantime
+        self.shuffled.retain(|map| maps.contains(map));
+        if self.shuffled.is_empty() {
+            self.shuffled.extend(maps.iter().cloned());
+            self.shuffled
+                .make_contiguous()
+                .shuffle(&mut rand::rngs::OsRng);
+        }
+        // don't play the same map twice in a row
+        if self.shuffled.len() > 1 && self.shuffled.front().is_some_and(|map| map == cur_map) {
+            self.shuffled.rotate_left(1);
+        }
+        self.shuffled.pop_front()
+    }
+
+    /// Counts the ended round, returns `true` if the map
+    /// should be changed to the next map of the rotation.
+    pub fn round_ended(&mut self, config: &ConfigServer) -> bool {
+        if config.maprotation.is_empty() || config.maprotation_rounds == 0 {
+            return false;
+        }
+        self.rounds += 1;
+        self.rounds >= config.maprotation_rounds
+    }
+
+    /// The map of the latest schedule entry whose time was reached
+    /// since the last check.
+    ///
+    /// The first check only remembers the time, so restarting the
+    /// server does not immediately load a scheduled map.
+    pub fn scheduled_map(&mut self, config: &ConfigServer, now: NaiveDateTime) -> Option<String> {
+        let now = now.with_second(0)?.with_nanosecond(0)?;
+        let last_check = self.last_schedule_check.replace(now)?;
+        if last_check == now {
+            return None;
+        }
+
+        config
+            .map_schedule
+            .iter()
+            .filter_map(|entry| Self::parse_schedule_entry(entry))
+            .filter_map(|(time, map)| {
+                // the last time the entry was due, which might be yesterday
+                let mut due = now.date().and_time(time);
+                if due > now {
+                    due -= Duration::days(1);
+                }
+                (due > last_check).then_some((due, map))
+            })
+            .max_by_key(|(due, _)| *due)
+            .map(|(_, map)| map.to_string())
+    }
+
+    /// Requests to change the map as soon as possible,
+    /// overwriting earlier requests.
+    pub fn request_map(&mut self, map: String) {
+        self.pending = Some(map);
+    }
+
+    pub fn take_pending_map(&mut self) -> Option<String> {
+        self.pending.take()
+    }
+
+    /// Must be called whenever a new map was loaded.
+    pub fn map_changed(&mut self) {
+        self.rounds = 0;
+        self.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use chrono::{NaiveDate, NaiveDateTime};
+    use game_config::config::ConfigServer;
+
+    use super::MapRotation;
+
+    fn config(maps: &[&str], shuffle: bool) -> ConfigServer {
+        ConfigServer {
+            maprotation: maps.iter().map(|map| map.to_string()).collect(),
+            maprotation_shuffle: shuffle,
+            ..Default::default()
+        }
+    }
+
+    fn time(day: u32, hour: u32, min: u32, sec: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_hms_opt(hour, min, sec)
+            .unwrap()
+    }
+
+    #[test]
+    fn next_map() {
+        let mut rotation = MapRotation::default();
+        assert_eq!(rotation.next_map(&config(&[], false), "ctf1"), None);
+
+        let config = config(&["a", "b", "c"], false);
+        assert_eq!(rotation.next_map(&config, "a").as_deref(), Some("b"));
+        assert_eq!(rotation.next_map(&config, "c").as_deref(), Some("a"));
+        // a map that is not part of the rotation starts it
+        assert_eq!(rotation.next_map(&config, "ctf1").as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn shuffle() {
+        let mut rotation = MapRotation::default();
+        let mut config = config(&["a", "b", "c"], true);
+
+        let mut cur_map = "a".to_string();
+        for _ in 0..100 {
+            // every map is played once per shuffled rotation
+            let mut played = HashSet::new();
+            for _ in 0..3 {
+                let next_map = rotation.next_map(&config, &cur_map).unwrap();
+                assert_ne!(next_map, cur_map);
+                played.insert(next_map.clone());
+                cur_map = next_map;
+            }
+            assert_eq!(played.len(), 3);
+        }
+
+        // removed maps are not played anymore
+        config.maprotation.retain(|map| map != "b");
+        for _ in 0..10 {
+            let next_map = rotation.next_map(&config, &cur_map).unwrap();
+            assert!(next_map == "a" || next_map == "c");
+            cur_map = next_map;
+        }
+
+        // a single map is played again
+        config.maprotation = vec!["a".to_string()];
+        assert_eq!(rotation.next_map(&config, "a").as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn round_ended() {
+        let mut rotation = MapRotation::default();
+        let mut config = config(&["a", "b"], false);
+        config.maprotation_rounds = 2;
+        assert!(!rotation.round_ended(&config));
+        assert!(rotation.round_ended(&config));
+        rotation.map_changed();
+        assert!(!rotation.round_ended(&config));
+
+        config.maprotation_rounds = 0;
+        assert!(!rotation.round_ended(&config));
+        config.maprotation_rounds = 1;
+        config.maprotation.clear();
+        assert!(!rotation.round_ended(&config));
+    }
+
+    #[test]
+    fn scheduled_map() {
+        let mut rotation = MapRotation::default();
+        let config = ConfigServer {
+            map_schedule: vec![
+                "12:00 a".to_string(),
+                "18:30  b ".to_string(),
+                "invalid".to_string(),
+                "25:00 c".to_string(),
+                "13:00".to_string(),
+            ],
+            ..Default::default()
+        };
+
+        // the first check only remembers the time
+        assert_eq!(rotation.scheduled_map(&config, time(1, 12, 30, 0)), None);
+        assert_eq!(rotation.scheduled_map(&config, time(1, 11, 59, 0)), None);
+        assert_eq!(
+            rotation
+                .scheduled_map(&config, time(1, 12, 0, 10))
+                .as_deref(),
+            Some("a")
+        );
+        // the same minute is only checked once
+        assert_eq!(rotation.scheduled_map(&config, time(1, 12, 0, 30)), None);
+        assert_eq!(rotation.scheduled_map(&config, time(1, 12, 1, 0)), None);
+
+        // only the latest of the entries that were due since the last check
+        assert_eq!(
+            rotation
+                .scheduled_map(&config, time(1, 19, 0, 0))
+                .as_deref(),
+            Some("b")
+        );
+        assert_eq!(
+            rotation
+                .scheduled_map(&config, time(2, 19, 0, 0))
+                .as_deref(),
+            Some("b")
+        );
+        // nothing was due since the check of the previous day
+        assert_eq!(
+            rotation
+                .scheduled_map(&config, time(3, 11, 0, 0))
+                .as_deref(),
+            None
+        );
+        assert_eq!(
+            rotation
+                .scheduled_map(&config, time(3, 12, 0, 0))
+                .as_deref(),
+            Some("a")
+        );
+    }
+}
@@ -7,26 +7,36 @@ use std::{
 use async_trait::async_trait;
 use ddnet_account_client_http_fs::cert_downloader::CertsDownloader;
 use ddnet_accounts_shared::game_server::user_id::{UserId, VerifyingKey};
-use game_interface::types::player_info::AccountId;
+use game_interface::types::player_info::{AccountId, PlayerUniqueId};
 use network::network::{
     connection::NetworkConnectionId,
     errors::{BanType, Banned},
     plugins::{ConnectionEvent, NetworkPluginConnection},
 };
+use serde::{Deserialize, Serialize};
 use x509_cert::der::Encode;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Ban {
     pub until: Option<chrono::DateTime<chrono::Utc>>,
     pub ty: BanType,
 }
 
+/// A ban of an account or, if the user has no account,
+/// of the fingerprint of the user's certificate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdBan {
+    pub id: PlayerUniqueId,
+    pub ban: Ban,
+}
+
 #[derive(Debug, Default)]
 pub struct BanState {
     account_bans: HashMap<AccountId, Ban>,
     cert_bans: HashMap<[u8; 32], Ban>,
 
     active_connections: HashMap<[u8; 32], HashSet<NetworkConnectionId>>,
+    active_accounts: HashMap<AccountId, HashSet<NetworkConnectionId>>,
 }
 
 impl BanState {
@@ -85,7 +95,7 @@ impl CertBans {
         )
     }
 
-    /// Returns all network ids for that ip.
+    /// Returns all network ids for that cert.
     #[must_use]
     pub fn ban(
         &self,
@@ -94,27 +104,80 @@ impl CertBans {
         until: Option<chrono::DateTime<chrono::Utc>>,
     ) -> HashSet<NetworkConnectionId> {
         let user_id = Self::user_id(&self.account_server_certs_downloader.public_keys(), cert);
+        self.ban_id(
+            user_id
+                .account_id
+                .map(PlayerUniqueId::Account)
+                .unwrap_or(PlayerUniqueId::CertFingerprint(user_id.public_key)),
+            reason,
+            until,
+        )
+    }
+
+    /// Returns all network ids for that account or cert fingerprint.
+    #[must_use]
+    pub fn ban_id(
+        &self,
+        id: PlayerUniqueId,
+        reason: BanType,
+        until: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> HashSet<NetworkConnectionId> {
         let mut state = self.state.lock().unwrap();
-        let ids = state
-            .active_connections
-            .get(&user_id.public_key)
-            .cloned()
-            .unwrap_or_default();
-
-        match user_id.account_id {
-            Some(account_id) => {
+        match id {
+            PlayerUniqueId::Account(account_id) => {
                 state
                     .account_bans
                     .insert(account_id, Ban { until, ty: reason });
+                state
+                    .active_accounts
+                    .get(&account_id)
+                    .cloned()
+                    .unwrap_or_default()
             }
-            None => {
+            PlayerUniqueId::CertFingerprint(public_key) => {
                 state
                     .cert_bans
-                    .insert(user_id.public_key, Ban { until, ty: reason });
+                    .insert(public_key, Ban { until, ty: reason });
+                state
+                    .active_connections
+                    .get(&public_key)
+                    .cloned()
+                    .unwrap_or_default()
             }
         }
+    }
 
-        ids
+    /// Returns `true` if a ban for that account or cert fingerprint existed.
+    pub fn unban_id(&self, id: &PlayerUniqueId) -> bool {
+        let mut state = self.state.lock().unwrap();
+        match id {
+            PlayerUniqueId::Account(account_id) => state.account_bans.remove(account_id).is_some(),
+            PlayerUniqueId::CertFingerprint(public_key) => {
+                state.cert_bans.remove(public_key).is_some()
+            }
+        }
+    }
+
+    /// All bans that are not expired yet.
+    pub fn bans(&self) -> Vec<IdBan> {
+        let now = chrono::Utc::now();
+        let state = self.state.lock().unwrap();
+        state
+            .account_bans
+            .iter()
+            .map(|(&account_id, ban)| (PlayerUniqueId::Account(account_id), ban))
+            .chain(
+                state
+                    .cert_bans
+                    .iter()
+                    .map(|(&public_key, ban)| (PlayerUniqueId::CertFingerprint(public_key), ban)),
+            )
+            .filter(|(_, ban)| ban.until.is_none_or(|until| now < until))
+            .map(|(id, ban)| IdBan {
+                id,
+                ban: ban.clone(),
+            })
+            .collect()
     }
 }
 
@@ -148,6 +211,13 @@ impl NetworkPluginConnection for CertBans {
                 .entry(user_id.public_key)
                 .or_default()
                 .insert(*id);
+            if let Some(account_id) = user_id.account_id {
+                state
+                    .active_accounts
+                    .entry(account_id)
+                    .or_default()
+                    .insert(*id);
+            }
 
             ConnectionEvent::Allow
         }
@@ -166,5 +236,13 @@ impl NetworkPluginConnection for CertBans {
                 state.active_connections.remove(&user_id.public_key);
             }
         }
+        if let Some(account_id) = user_id.account_id {
+            if let Some(connections) = state.active_accounts.get_mut(&account_id) {
+                connections.remove(id);
+                if connections.is_empty() {
+                    state.active_accounts.remove(&account_id);
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,48 @@
+//! The account users of the game server on postgres databases,
+//! which are not supported by the account game server crate.
+
+use anyhow::anyhow;
+use ddnet_accounts_shared::game_server::user_id::UserId;
+use sqlx::PgPool;
+
+/// Creates the user table if it does not exist yet.
+pub async fn setup(pool: &PgPool) -> anyhow::Result<()> {
+    sqlx::query(include_str!("postgres_accounts/setup.sql"))
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Creates a user for the account, if the user has none yet.
+///
+/// Returns `true` if a new user was created.
+pub async fn auto_login(pool: &PgPool, user_id: &UserId) -> anyhow::Result<bool> {
+    let Some(account_id) = user_id.account_id else {
+        return Ok(false);
+    };
+    let res = sqlx::query(include_str!("postgres_accounts/auto_login.sql"))
+        .bind(format!("autouser{account_id}"))
+        .bind(account_id)
+        .execute(pool)
+        .await?;
+    Ok(res.rows_affected() > 0)
+}
+
+pub async fn rename(pool: &PgPool, user_id: &UserId, name: &str) -> anyhow::Result<()> {
+    let account_id = user_id
+        .account_id
+        .ok_or_else(|| anyhow!("user had no account"))?;
+    let res = sqlx::query(include_str!("postgres_accounts/rename.sql"))
+        .bind(name)
+        .bind(account_id)
+        .execute(pool)
+        .await
+        .map_err(|err| match err {
+            sqlx::Error::Database(err) if err.is_unique_violation() => {
+                anyhow!("the name {name} is already in use")
+            }
+            err => err.into(),
+        })?;
+    anyhow::ensure!(res.rows_affected() > 0, "user had no account");
+    Ok(())
+}
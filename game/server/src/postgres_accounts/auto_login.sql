@@ -0,0 +1,5 @@
+INSERT INTO
+    "user" (name, account_id)
+VALUES
+    ($1, $2)
+ON CONFLICT (account_id) DO NOTHING;
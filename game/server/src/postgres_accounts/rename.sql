@@ -0,0 +1,6 @@
+UPDATE
+    "user"
+SET
+    name = $1
+WHERE
+    "user".account_id = $2;
@@ -1,18 +1,152 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use anyhow::anyhow;
+use base::network_string::NetworkString;
 use base_io::io::Io;
+use command_parser::parser::{Command, CommandType, Syn};
 use game_interface::{
-    rcon_commands::AuthLevel,
+    rcon_commands::{AuthLevel, ExecRconCommand, RconCommand},
     types::player_info::{AccountId, PlayerUniqueId},
 };
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::client::ServerClient;
 
+/// Per-account overrides of the rights the auth level gives.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct AuthPermissions {
+    /// Permission groups of the account, they allow all
+    /// commands that are part of one of these groups.
+    #[serde(default)]
+    pub groups: HashSet<String>,
+    /// Commands the account is always allowed to execute.
+    #[serde(default)]
+    pub allow: HashSet<String>,
+    /// Commands the account is never allowed to execute.
+    #[serde(default)]
+    pub deny: HashSet<String>,
+}
+
+impl AuthPermissions {
+    /// Whether any rcon command could be executed at all.
+    pub fn has_access(&self, level: AuthLevel) -> bool {
+        level > AuthLevel::None || !self.groups.is_empty() || !self.allow.is_empty()
+    }
+
+    pub fn can_exec(&self, level: AuthLevel, name: &str, cmd: &RconCommand) -> bool {
+        if self.deny.contains(name) {
+            false
+        } else if self.allow.contains(name) {
+            true
+        } else {
+            (level > AuthLevel::None && level >= cmd.auth_level)
+                || cmd
+                    .groups
+                    .iter()
+                    .any(|group| self.groups.contains(group.as_str()))
+        }
+    }
+
+    fn check_mod_cmd(
+        &self,
+        level: AuthLevel,
+        cmd: &Command,
+        mod_cmds: &HashMap<NetworkString<65536>, RconCommand>,
+    ) -> anyhow::Result<AuthLevel> {
+        let Some(mod_cmd) = mod_cmds.get(cmd.ident.as_str()) else {
+            return Err(anyhow!("Command {} not found", cmd.ident));
+        };
+        if !self.can_exec(level, &cmd.ident, mod_cmd) {
+            return Err(anyhow!("You are not allowed to execute {}", cmd.ident));
+        }
+        // commands as arguments might be executed by the mod, too
+        for (arg, _) in &cmd.args {
+            match arg {
+                Syn::Command(cmd) => {
+                    self.check_mod_cmd(level, cmd, mod_cmds)?;
+                }
+                Syn::Commands(cmds) => {
+                    for cmd in cmds {
+                        self.check_mod_cmd(level, cmd, mod_cmds)?;
+                    }
+                }
+                Syn::Text(_)
+                | Syn::Number(_)
+                | Syn::Float(_)
+                | Syn::JsonObjectLike(_)
+                | Syn::JsonArrayLike(_) => {}
+            }
+        }
+        // only a command that was allowed by an override
+        // needs a higher level than the caller has.
+        Ok(if level >= mod_cmd.auth_level {
+            level
+        } else {
+            mod_cmd.auth_level
+        })
+    }
+
+    /// Checks every command of a parsed rcon command line of a mod,
+    /// including chained (`cmd1 x; cmd2`) and nested commands,
+    /// so an allowed command can't be used to execute one that is not allowed.
+    ///
+    /// Returns the commands to execute one by one, each with the auth level
+    /// the caller has for it.
+    pub fn check_mod_cmds(
+        &self,
+        level: AuthLevel,
+        cmds: &[CommandType],
+        mod_cmds: &HashMap<NetworkString<65536>, RconCommand>,
+    ) -> anyhow::Result<Vec<ExecRconCommand>> {
+        cmds.iter()
+            .map(|cmd| match cmd {
+                CommandType::Full(cmd) => {
+                    let auth_level = self.check_mod_cmd(level, cmd, mod_cmds)?;
+                    Ok(ExecRconCommand {
+                        raw: cmd.to_string().as_str().try_into()?,
+                        auth_level,
+                    })
+                }
+                CommandType::Partial(err) => Err(anyhow!("{err}")),
+            })
+            .collect()
+    }
+}
+
+/// The rights of a single account.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct AccountAuth {
+    pub level: AuthLevel,
+    #[serde(flatten)]
+    pub permissions: AuthPermissions,
+}
+
+/// An entry in `auth.json`, either just the auth level
+/// or the auth level together with per-account overrides.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum AuthEntry {
+    Level(AuthLevel),
+    Account(AccountAuth),
+}
+
+impl From<AuthEntry> for AccountAuth {
+    fn from(value: AuthEntry) -> Self {
+        match value {
+            AuthEntry::Level(level) => Self {
+                level,
+                permissions: Default::default(),
+            },
+            AuthEntry::Account(auth) => auth,
+        }
+    }
+}
+
 /// Everything the server needs for rcon
 #[derive(Debug)]
 pub struct Rcon {
-    pub auths: HashMap<AccountId, AuthLevel>,
+    pub auths: HashMap<AccountId, AccountAuth>,
     /// gives full access, mostly interesting for internal servers
     pub rcon_secret: [u8; 32],
 }
@@ -25,9 +159,12 @@ impl Rcon {
             .rt
             .spawn(async move {
                 let file = fs.read_file("auth.json".as_ref()).await?;
-                Ok(serde_json::from_slice::<HashMap<AccountId, AuthLevel>>(
-                    &file,
-                )?)
+                Ok(
+                    serde_json::from_slice::<HashMap<AccountId, AuthEntry>>(&file)?
+                        .into_iter()
+                        .map(|(account_id, auth)| (account_id, auth.into()))
+                        .collect(),
+                )
             })
             .get_storage()
             .unwrap_or_default();
@@ -46,10 +183,12 @@ impl Rcon {
         if let Some(auth) =
             unique_identifier.is_account_then(|account_id| self.auths.get(&account_id))
         {
-            client.auth.level = *auth;
+            client.auth.level = auth.level;
+            client.auth.permissions = auth.permissions.clone();
             true
         } else if rcon_secret.is_some_and(|rcon_secret| self.rcon_secret.eq(rcon_secret)) {
             client.auth.level = AuthLevel::Admin;
+            client.auth.permissions = Default::default();
             true
         } else {
             false
@@ -60,8 +199,128 @@ impl Rcon {
 #[derive(Debug)]
 pub enum ServerRconCommand {
     BanId,
+    BanRange,
+    BanAccount,
+    Unban,
+    Bans,
     KickId,
     Status,
     ConfVariable,
     RecordDemo,
+    AutoDemos,
+    AutoDemoKeep,
+    DbJournal,
+    DbMigrate,
+    DbMigrateDown,
+    NextMap,
+    RotationAdd,
+    RotationRemove,
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{HashMap, HashSet};
+
+    use base::network_string::NetworkString;
+    use command_parser::parser::{parse, CommandArg, CommandArgType, ParserCache};
+    use game_interface::rcon_commands::{AuthLevel, RconCommand};
+
+    use super::AuthPermissions;
+
+    fn mod_cmds() -> HashMap<NetworkString<65536>, RconCommand> {
+        [
+            (
+                "allowed_cmd",
+                RconCommand {
+                    args: vec![CommandArg {
+                        ty: CommandArgType::Text,
+                        user_ty: None,
+                    }],
+                    auth_level: AuthLevel::Moderator,
+                    ..Default::default()
+                },
+            ),
+            (
+                "denied_cmd",
+                RconCommand {
+                    auth_level: AuthLevel::Admin,
+                    groups: vec!["bans".try_into().unwrap()],
+                    ..Default::default()
+                },
+            ),
+        ]
+        .into_iter()
+        .map(|(name, cmd)| (name.try_into().unwrap(), cmd))
+        .collect()
+    }
+
+    fn check(
+        permissions: &AuthPermissions,
+        level: AuthLevel,
+        raw: &str,
+    ) -> anyhow::Result<Vec<(String, AuthLevel)>> {
+        let mod_cmds = mod_cmds();
+        let parser_cmds: HashMap<_, _> = mod_cmds
+            .iter()
+            .map(|(name, cmd)| (name.clone(), cmd.args.clone()))
+            .collect();
+        let cmds = parse(raw, &parser_cmds, &mut ParserCache::default());
+        Ok(permissions
+            .check_mod_cmds(level, &cmds, &mod_cmds)?
+            .into_iter()
+            .map(|cmd| (cmd.raw.as_str().trim().to_string(), cmd.auth_level))
+            .collect())
+    }
+
+    #[test]
+    fn chained_commands() {
+        let permissions = AuthPermissions::default();
+        assert_eq!(
+            check(&permissions, AuthLevel::Moderator, "allowed_cmd x").unwrap(),
+            vec![("allowed_cmd x".to_string(), AuthLevel::Moderator)]
+        );
+        assert!(check(
+            &permissions,
+            AuthLevel::Moderator,
+            "allowed_cmd x; denied_cmd"
+        )
+        .is_err());
+        assert!(check(
+            &permissions,
+            AuthLevel::Moderator,
+            "allowed_cmd x; unknown_cmd"
+        )
+        .is_err());
+        assert_eq!(
+            check(&permissions, AuthLevel::Admin, "allowed_cmd x; denied_cmd")
+                .unwrap()
+                .len(),
+            2
+        );
+
+        // a deny override also applies to chained commands
+        let permissions = AuthPermissions {
+            deny: HashSet::from(["denied_cmd".to_string()]),
+            ..Default::default()
+        };
+        assert!(check(&permissions, AuthLevel::Admin, "allowed_cmd x; denied_cmd").is_err());
+
+        // a group only raises the level for the command it allows
+        let permissions = AuthPermissions {
+            groups: HashSet::from(["bans".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(
+            check(
+                &permissions,
+                AuthLevel::Moderator,
+                "allowed_cmd x; denied_cmd"
+            )
+            .unwrap(),
+            vec![
+                ("allowed_cmd x".to_string(), AuthLevel::Moderator),
+                ("denied_cmd".to_string(), AuthLevel::Admin)
+            ]
+        );
+    }
 }
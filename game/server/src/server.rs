@@ -1,5 +1,5 @@
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::Debug,
     net::IpAddr,
     num::NonZeroUsize,
@@ -11,7 +11,7 @@ use std::{
 
 use anyhow::anyhow;
 use base::{
-    hash::{fmt_hash, generate_hash_for, Hash},
+    hash::{decode_hash, fmt_hash, generate_hash_for, Hash},
     linked_hash_map_view::{FxLinkedHashMap, FxLinkedHashSet},
     network_string::{NetworkReducedAsciiString, NetworkString},
     system::{System, SystemTimeInterface},
@@ -28,6 +28,7 @@ use config::{config::ConfigEngine, traits::ConfigInterface};
 use ddnet_account_client_http_fs::{
     cert_downloader::CertsDownloader, client::ClientHttpTokioFs, fs::Fs,
 };
+use ddnet_account_sql::any::AnyPool;
 use ddnet_accounts_shared::game_server::user_id::{UserId, VerifyingKey};
 use demo::recorder::{DemoRecorder, DemoRecorderCreateProps, DemoRecorderCreatePropsBase};
 use ed25519_dalek::SigningKey;
@@ -35,10 +36,12 @@ use either::Either;
 use game_config::config::{ConfigDebug, ConfigGame, ConfigServer, ConfigServerDatabase};
 use game_database::{
     dummy::DummyDb,
+    migration::{MigrationState, MigrationStatus},
     traits::{DbInterface, DbKind, DbKindExtra},
 };
 use game_database_backend::GameDbBackend;
 use http_accounts::http::AccountHttp;
+use input_history::{writer::InputHistoryWriter, InputHistoryHeader};
 use master_server_types::response::RegisterResponse;
 use network::network::{
     connection::NetworkConnectionId,
@@ -61,21 +64,30 @@ use pool::{datatypes::PoolFxLinkedHashMap, mt_datatypes::PoolCow, pool::Pool};
 use rand::RngCore;
 use vanilla::{
     command_chain::{Command, CommandChain},
-    sql::account_info::AccountInfo,
+    sql::{account_info::AccountInfo, migrations},
 };
 use game_state_wasm::game::state_wasm_manager::GameStateWasmManager;
 use sql::database::{Database, DatabaseDetails};
+use sqlx::PgPool;
+use wasm_runtime::{WasmLimits, WasmManifest};
 use x509_cert::der::Encode;
 
 use crate::{
+    auto_demos::{AutoDemos, AUTO_DEMOS_DIR},
     auto_map_votes::AutoMapVotes,
+    ban_list::{BanList, BanListWriter},
     client::{
-        ClientSnapshotForDiff, ClientSnapshotStorage, Clients, ServerClient, ServerClientPlayer,
-        ServerNetworkClient, ServerNetworkQueuedClient,
+        password_matches, ClientSnapshotForDiff, ClientSnapshotStorage, Clients, ServerClient,
+        ServerClientPlayer, ServerNetworkClient, ServerNetworkQueuedClient,
     },
+    map_rotation::MapRotation,
     map_votes::{MapVotes, ServerMapVotes},
-    network_plugins::{accounts_only::AccountsOnly, cert_ban::CertBans},
-    rcon::{Rcon, ServerRconCommand},
+    network_plugins::{
+        accounts_only::AccountsOnly,
+        cert_ban::{CertBans, IdBan},
+    },
+    postgres_accounts,
+    rcon::{AuthPermissions, Rcon, ServerRconCommand},
     server_game::{
         ClientAuth, ServerExtraVoteInfo, ServerGame, ServerVote, RESERVED_DDNET_NAMES,
         RESERVED_VANILLA_NAMES,
@@ -84,6 +96,7 @@ use crate::{
 
 use game_base::{
     game_types::{is_next_tick, time_until_tick},
+    lan_discovery::{LanDiscoveryServer, LanServerInfo},
     local_server_info::{LocalServerConnectInfo, LocalServerInfo, LocalServerState, ServerDbgGame},
     network::{
         messages::{
@@ -104,7 +117,7 @@ use game_interface::{
     client_commands::ClientCommand,
     events::EventClientInfo,
     interface::{GameStateCreateOptions, GameStateInterface, MAX_MAP_NAME_LEN},
-    rcon_commands::{AuthLevel, ExecRconCommand, RconCommand, RconCommands},
+    rcon_commands::{AuthLevel, RconCommand, RconCommands},
     tick_result::TickEvent,
     types::{
         game::{GameEntityId, GameTickType},
@@ -132,16 +145,52 @@ use game_network::{
     },
 };
 
+#[derive(Clone)]
+enum AccountDbPool {
+    /// MySQL & SQLite are handled by the account game server crate.
+    Any {
+        pool: AnyPool,
+        shared: Arc<ddnet_account_game_server::shared::Shared>,
+    },
+    Postgres(PgPool),
+}
+
 #[derive(Clone)]
 pub struct AccountDb {
     kind: DbKind,
-    shared: Arc<ddnet_account_game_server::shared::Shared>,
+    pool: AccountDbPool,
     info: AccountInfo,
 }
 
+impl AccountDb {
+    /// Returns `true` if a new account was created.
+    async fn auto_login(&self, user_id: &UserId) -> anyhow::Result<bool> {
+        match &self.pool {
+            AccountDbPool::Any { pool, shared } => Ok(
+                ddnet_account_game_server::auto_login::auto_login(shared.clone(), pool, user_id)
+                    .await?,
+            ),
+            AccountDbPool::Postgres(pool) => postgres_accounts::auto_login(pool, user_id).await,
+        }
+    }
+
+    async fn rename(&self, user_id: &UserId, name: &str) -> anyhow::Result<()> {
+        match &self.pool {
+            AccountDbPool::Any { pool, shared } => {
+                ddnet_account_game_server::rename::rename(shared.clone(), pool, user_id, name)
+                    .await
+                    .map(|_| ())
+                    .map_err(|err| anyhow!("{err}"))
+            }
+            AccountDbPool::Postgres(pool) => postgres_accounts::rename(pool, user_id, name).await,
+        }
+    }
+}
+
 type DbSetup = (
     Option<Arc<Database>>,
     Arc<dyn DbInterface>,
+    Option<Arc<GameDbBackend>>,
     Option<AccountDb>,
 );
 
@@ -161,8 +210,44 @@ enum GameServerDbAccount {
     },
 }
 
+/// An action of `db_migrate`.
+#[derive(Debug, Clone)]
+pub enum DbMigrateAction {
+    Status,
+    Up,
+    /// Reverts the newest applied migration of the migrations
+    /// with the given name on the database of the given type.
+    ///
+    /// Down scripts usually drop data, so without `confirmed`
+    /// it is only shown what would be reverted.
+    Down {
+        migrations: String,
+        database: String,
+        confirmed: bool,
+    },
+}
+
+impl std::fmt::Display for DbMigrateAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Status => write!(f, "status"),
+            Self::Up => write!(f, "up"),
+            Self::Down {
+                migrations,
+                database,
+                ..
+            } => write!(f, "down {migrations} {database}"),
+        }
+    }
+}
+
 enum GameServerDb {
     Account(GameServerDbAccount),
+    /// The result of a rcon command that ran on the database.
+    Rcon {
+        con_id: Option<NetworkConnectionId>,
+        result: String,
+    },
 }
 
 pub struct Server {
@@ -178,6 +263,11 @@ pub struct Server {
     // network
     network: QuinnNetworks,
     connection_bans: Arc<ConnectionBans>,
+    cert_bans: Option<Arc<CertBans>>,
+    /// Account & cert fingerprint bans that are only persisted,
+    /// because accounts are disabled.
+    inactive_id_bans: Vec<IdBan>,
+    ban_list_writer: BanListWriter,
 
     is_open: Arc<AtomicBool>,
 
@@ -200,6 +290,7 @@ pub struct Server {
     last_register_time: Option<Duration>,
     register_task: Option<IoRuntimeTask<()>>,
     last_register_serial: u32,
+    lan_discovery: Option<LanDiscoveryServer>,
 
     last_network_stats_time: Duration,
 
@@ -213,6 +304,9 @@ pub struct Server {
 
     // server side demos
     demo_recorder: Option<DemoRecorder>,
+    /// The demo of the current round, if `sv.auto_record` is enabled.
+    auto_demo_recorder: Option<DemoRecorder>,
+    auto_demos: AutoDemos,
 
     // votes
     map_votes: ServerMapVotes,
@@ -220,9 +314,14 @@ pub struct Server {
     misc_votes: BTreeMap<NetworkString<MAX_CATEGORY_NAME_LEN>, BTreeMap<MiscVoteKey, MiscVote>>,
     misc_votes_hash: Hash,
 
+    // map rotation
+    map_rotation: MapRotation,
+
     // database
     db: Option<Arc<Database>>,
     game_db: Arc<dyn DbInterface>,
+    /// `None` if no database is configured.
+    game_db_backend: Option<Arc<GameDbBackend>>,
     db_requests: Vec<IoRuntimeTask<GameServerDb>>,
     db_requests_helper: Vec<IoRuntimeTask<GameServerDb>>,
 
@@ -247,6 +346,8 @@ impl Server {
             "sqlite" => DbKind::Sqlite(DbKindExtra::Main),
             "mysql_backup" => DbKind::MySql(DbKindExtra::Backup),
             "sqlite_backup" => DbKind::Sqlite(DbKindExtra::Backup),
+            "postgres" => DbKind::Postgres(DbKindExtra::Main),
+            "postgres_backup" => DbKind::Postgres(DbKindExtra::Backup),
             _ => {
                 return Err(anyhow!("Database of type: {ty} is not allowed/supported"));
             }
@@ -287,31 +388,42 @@ impl Server {
             if !config_db.connections.is_empty() {
                 let db = Self::db_setup(&config_db).await?;
 
-                let game_db: Arc<dyn DbInterface> = Arc::new(GameDbBackend::new(db.clone())?);
+                let game_db_backend = Arc::new(
+                    GameDbBackend::new(db.clone())?.with_auto_migrate(config_db.auto_migrate),
+                );
+                let game_db: Arc<dyn DbInterface> = game_db_backend.clone();
 
                 let accounts = if !config_db.enable_accounts.is_empty() {
                     let kind = Self::config_ty_to_db_kind(&config_db.enable_accounts)?;
-                    let pool = db.pools.get(&kind).ok_or_else(|| {
-                        anyhow!(
+                    let pool = if let Some(pool) = db.pools.get(&kind) {
+                        ddnet_account_game_server::setup::setup(pool).await?;
+                        AccountDbPool::Any {
+                            pool: pool.clone(),
+                            shared: ddnet_account_game_server::prepare::prepare(pool).await?,
+                        }
+                    } else if let Some(pool) = db.postgres_pools.get(&kind) {
+                        postgres_accounts::setup(pool).await?;
+                        AccountDbPool::Postgres(pool.clone())
+                    } else {
+                        return Err(anyhow!(
                             "database connection was not intiailized for {:?}.",
                             config_db.enable_accounts
-                        )
-                    })?;
-                    ddnet_account_game_server::setup::setup(pool).await?;
+                        ));
+                    };
 
                     Some(AccountDb {
                         kind,
-                        shared: ddnet_account_game_server::prepare::prepare(pool).await?,
+                        pool,
                         info: AccountInfo::new(game_db.clone(), Some(kind)).await?,
                     })
                 } else {
                     None
                 };
 
-                Ok((Some(db), game_db, accounts))
+                Ok((Some(db), game_db, Some(game_db_backend), accounts))
             } else {
                 let game_db: Arc<dyn DbInterface> = Arc::new(DummyDb);
-                Ok((None, game_db, Default::default()))
+                Ok((None, game_db, None, Default::default()))
             }
         })
     }
@@ -352,6 +464,26 @@ impl Server {
         )
     }
 
+    fn config_game_mod_limits(config_game: &ConfigGame) -> WasmLimits {
+        let limits = &config_game.sv.game_mod_limits;
+        WasmLimits {
+            fuel_per_call: (limits.fuel_per_call > 0).then_some(limits.fuel_per_call),
+            max_memory_bytes: (limits.max_memory_mib > 0)
+                .then(|| limits.max_memory_mib.saturating_mul(1024 * 1024)),
+            call_time_budget: (limits.call_time_budget_ms > 0)
+                .then(|| Duration::from_millis(limits.call_time_budget_ms)),
+        }
+    }
+
+    fn config_game_mod_grants(config_game: &ConfigGame) -> WasmManifest {
+        let grants = &config_game.sv.game_mod_grants;
+        WasmManifest {
+            http_hosts: grants.http_hosts.clone(),
+            fs_paths: grants.fs_paths.iter().map(Into::into).collect(),
+            db_kinds: grants.db_kinds.clone(),
+        }
+    }
+
     pub fn new(
         sys: System,
         is_open: Arc<AtomicBool>,
@@ -437,6 +569,7 @@ impl Server {
                 .unwrap(),
         )));
 
+        let mut cert_bans = None;
         if let Some(account_certs_downloader) = account_certs_downloader.as_ref() {
             if accounts_only {
                 connection_plugins.push(Arc::new(AccountsOnly::new(
@@ -444,9 +577,13 @@ impl Server {
                 )));
             }
 
-            connection_plugins.push(Arc::new(CertBans::new(account_certs_downloader.clone())));
+            let bans = Arc::new(CertBans::new(account_certs_downloader.clone()));
+            connection_plugins.push(bans.clone());
+            cert_bans = Some(bans);
         }
 
+        let inactive_id_bans = BanList::load(&io)?.apply(&connection_bans, cert_bans.as_deref());
+
         let mut packet_plugins: Vec<Arc<dyn NetworkPluginPacket>> = vec![];
 
         if config_game.sv.train_packet_dictionary {
@@ -504,7 +641,7 @@ impl Server {
             },
         )?;
 
-        let (db, game_db, accounts) = task.get_storage()?;
+        let (db, game_db, game_db_backend, accounts) = task.get_storage()?;
 
         let account_server_cert_downloader_task = if let Some(account_certs_downloader) =
             account_certs_downloader.clone()
@@ -604,10 +741,106 @@ impl Server {
                         }],
                         description: "Ban a user with the given player id".try_into().unwrap(),
                         usage: "ban_id <player_id>".try_into().unwrap(),
+                        auth_level: AuthLevel::Moderator,
+                        groups: vec!["bans".try_into().unwrap()],
                     },
                     cmd: ServerRconCommand::BanId,
                 },
             ),
+            (
+                "ban_range".try_into().unwrap(),
+                Command {
+                    rcon: RconCommand {
+                        args: vec![
+                            CommandArg {
+                                ty: CommandArgType::Text,
+                                user_ty: Some("CIDR".try_into().unwrap()),
+                            },
+                            CommandArg {
+                                ty: CommandArgType::Number,
+                                user_ty: Some("MINUTES".try_into().unwrap()),
+                            },
+                            CommandArg {
+                                ty: CommandArgType::Text,
+                                user_ty: Some("REASON".try_into().unwrap()),
+                            },
+                        ],
+                        description: "Ban an ip range for the given amount of minutes \
+                            (0 bans permanently)"
+                            .try_into()
+                            .unwrap(),
+                        usage: "ban_range <cidr> <minutes> <reason>".try_into().unwrap(),
+                        auth_level: AuthLevel::Admin,
+                        groups: vec!["bans".try_into().unwrap()],
+                    },
+                    cmd: ServerRconCommand::BanRange,
+                },
+            ),
+            (
+                "ban_account".try_into().unwrap(),
+                Command {
+                    rcon: RconCommand {
+                        args: vec![
+                            CommandArg {
+                                ty: CommandArgType::Text,
+                                user_ty: Some("ACCOUNT_OR_CERT".try_into().unwrap()),
+                            },
+                            CommandArg {
+                                ty: CommandArgType::Number,
+                                user_ty: Some("MINUTES".try_into().unwrap()),
+                            },
+                            CommandArg {
+                                ty: CommandArgType::Text,
+                                user_ty: Some("REASON".try_into().unwrap()),
+                            },
+                        ],
+                        description: "Ban an account or, for users without account, \
+                            a cert fingerprint for the given amount of minutes \
+                            (0 bans permanently)"
+                            .try_into()
+                            .unwrap(),
+                        usage: "ban_account <account_id|cert_fingerprint> <minutes> <reason>"
+                            .try_into()
+                            .unwrap(),
+                        auth_level: AuthLevel::Admin,
+                        groups: vec!["bans".try_into().unwrap()],
+                    },
+                    cmd: ServerRconCommand::BanAccount,
+                },
+            ),
+            (
+                "unban".try_into().unwrap(),
+                Command {
+                    rcon: RconCommand {
+                        args: vec![CommandArg {
+                            ty: CommandArgType::Text,
+                            user_ty: Some("BAN".try_into().unwrap()),
+                        }],
+                        description: "Remove the ban of an ip range, account or cert fingerprint"
+                            .try_into()
+                            .unwrap(),
+                        usage: "unban <cidr|account_id|cert_fingerprint>"
+                            .try_into()
+                            .unwrap(),
+                        auth_level: AuthLevel::Admin,
+                        groups: vec!["bans".try_into().unwrap()],
+                    },
+                    cmd: ServerRconCommand::Unban,
+                },
+            ),
+            (
+                "bans".try_into().unwrap(),
+                Command {
+                    rcon: RconCommand {
+                        args: Default::default(),
+                        description: "List all active bans".try_into().unwrap(),
+                        usage: "bans".try_into().unwrap(),
+                        auth_level: AuthLevel::Moderator,
+                        groups: vec!["bans".try_into().unwrap()],
+                    },
+                    cmd: ServerRconCommand::Bans,
+                },
+            ),
             (
                 "kick_id".try_into().unwrap(),
                 Command {
@@ -618,6 +851,8 @@ impl Server {
                         }],
                         description: "Kick a user with the given player id".try_into().unwrap(),
                         usage: "kick_id <player_id>".try_into().unwrap(),
+                        auth_level: AuthLevel::Moderator,
+                        groups: Default::default(),
                     },
                     cmd: ServerRconCommand::KickId,
                 },
@@ -632,6 +867,8 @@ impl Server {
                                 .try_into()
                                 .unwrap(),
                         usage: "status".try_into().unwrap(),
+                        auth_level: AuthLevel::Moderator,
+                        groups: Default::default(),
                     },
                     cmd: ServerRconCommand::Status,
                 },
@@ -643,10 +880,164 @@ impl Server {
                         args: Default::default(),
                         description: "Start to record a server side demo.".try_into().unwrap(),
                         usage: "record_demo".try_into().unwrap(),
+                        auth_level: AuthLevel::Admin,
+                        groups: vec!["demos".try_into().unwrap()],
                     },
                     cmd: ServerRconCommand::RecordDemo,
                 },
             ),
+            (
+                "auto_demos".try_into().unwrap(),
+                Command {
+                    rcon: RconCommand {
+                        args: Default::default(),
+                        description: "List the automatically recorded demos".try_into().unwrap(),
+                        usage: "auto_demos".try_into().unwrap(),
+                        auth_level: AuthLevel::Admin,
+                        groups: vec!["demos".try_into().unwrap()],
+                    },
+                    cmd: ServerRconCommand::AutoDemos,
+                },
+            ),
+            (
+                "auto_demo_keep".try_into().unwrap(),
+                Command {
+                    rcon: RconCommand {
+                        args: vec![CommandArg {
+                            ty: CommandArgType::Text,
+                            user_ty: Some("DEMO".try_into().unwrap()),
+                        }],
+                        description: "Keep an automatically recorded demo, \
+                            so it is never removed by the cleanup"
+                            .try_into()
+                            .unwrap(),
+                        usage: "auto_demo_keep <demo>".try_into().unwrap(),
+                        auth_level: AuthLevel::Admin,
+                        groups: vec!["demos".try_into().unwrap()],
+                    },
+                    cmd: ServerRconCommand::AutoDemoKeep,
+                },
+            ),
+            (
+                "db_journal".try_into().unwrap(),
+                Command {
+                    rcon: RconCommand {
+                        args: Default::default(),
+                        description:
+                            "Show the writes that wait to be replayed into the main database"
+                                .try_into()
+                                .unwrap(),
+                        usage: "db_journal".try_into().unwrap(),
+                        auth_level: AuthLevel::Admin,
+                        groups: vec!["database".try_into().unwrap()],
+                    },
+                    cmd: ServerRconCommand::DbJournal,
+                },
+            ),
+            (
+                "db_migrate".try_into().unwrap(),
+                Command {
+                    rcon: RconCommand {
+                        args: vec![CommandArg {
+                            ty: CommandArgType::TextFrom(vec![
+                                "status".try_into().unwrap(),
+                                "up".try_into().unwrap(),
+                            ]),
+                            user_ty: Some("ACTION".try_into().unwrap()),
+                        }],
+                        description: "Show the schema migrations of the game mod \
+                            or apply the pending ones"
+                            .try_into()
+                            .unwrap(),
+                        usage: "db_migrate <status|up>".try_into().unwrap(),
+                        auth_level: AuthLevel::Admin,
+                        groups: vec!["database".try_into().unwrap()],
+                    },
+                    cmd: ServerRconCommand::DbMigrate,
+                },
+            ),
+            (
+                "db_migrate_down".try_into().unwrap(),
+                Command {
+                    rcon: RconCommand {
+                        args: vec![
+                            CommandArg {
+                                ty: CommandArgType::Text,
+                                user_ty: Some("MIGRATIONS".try_into().unwrap()),
+                            },
+                            CommandArg {
+                                ty: CommandArgType::Text,
+                                user_ty: Some("DATABASE".try_into().unwrap()),
+                            },
+                            CommandArg {
+                                ty: CommandArgType::TextFrom(vec![
+                                    "preview".try_into().unwrap(),
+                                    "confirm".try_into().unwrap(),
+                                ]),
+                                user_ty: Some("MODE".try_into().unwrap()),
+                            },
+                        ],
+                        description: "Revert the newest applied schema migration of the \
+                            migrations with the given name on the database of the given type. \
+                            Only reverts it with confirm, since this might delete data"
+                            .try_into()
+                            .unwrap(),
+                        usage: "db_migrate_down <migrations> <database> <preview|confirm>"
+                            .try_into()
+                            .unwrap(),
+                        auth_level: AuthLevel::Admin,
+                        groups: vec!["database".try_into().unwrap()],
+                    },
+                    cmd: ServerRconCommand::DbMigrateDown,
+                },
+            ),
+            (
+                "next_map".try_into().unwrap(),
+                Command {
+                    rcon: RconCommand {
+                        args: Default::default(),
+                        description: "Load the next map of the map rotation".try_into().unwrap(),
+                        usage: "next_map".try_into().unwrap(),
+                        auth_level: AuthLevel::Moderator,
+                        groups: vec!["maps".try_into().unwrap()],
+                    },
+                    cmd: ServerRconCommand::NextMap,
+                },
+            ),
+            (
+                "rotation_add".try_into().unwrap(),
+                Command {
+                    rcon: RconCommand {
+                        args: vec![CommandArg {
+                            ty: CommandArgType::Text,
+                            user_ty: Some("MAP".try_into().unwrap()),
+                        }],
+                        description: "Add a map to the end of the map rotation"
+                            .try_into()
+                            .unwrap(),
+                        usage: "rotation_add <map>".try_into().unwrap(),
+                        auth_level: AuthLevel::Admin,
+                        groups: vec!["maps".try_into().unwrap()],
+                    },
+                    cmd: ServerRconCommand::RotationAdd,
+                },
+            ),
+            (
+                "rotation_remove".try_into().unwrap(),
+                Command {
+                    rcon: RconCommand {
+                        args: vec![CommandArg {
+                            ty: CommandArgType::Text,
+                            user_ty: Some("MAP".try_into().unwrap()),
+                        }],
+                        description: "Remove a map from the map rotation".try_into().unwrap(),
+                        usage: "rotation_remove <map>".try_into().unwrap(),
+                        auth_level: AuthLevel::Admin,
+                        groups: vec!["maps".try_into().unwrap()],
+                    },
+                    cmd: ServerRconCommand::RotationRemove,
+                },
+            ),
         ];
 
         config::parsing::parse_conf_values_as_str_list(
@@ -659,6 +1050,8 @@ impl Server {
                             args: add.args,
                             usage: add.usage.as_str().try_into().unwrap(),
                             description: add.description.as_str().try_into().unwrap(),
+                            auth_level: AuthLevel::Admin,
+                            groups: Default::default(),
                         },
                         cmd: ServerRconCommand::ConfVariable,
                     },
@@ -686,6 +1079,9 @@ impl Server {
 
             network: network_server,
             connection_bans,
+            cert_bans,
+            inactive_id_bans,
+            ban_list_writer: Default::default(),
 
             is_open,
 
@@ -699,6 +1095,8 @@ impl Server {
                 &render_mod_hash.try_into().unwrap_or_default(),
                 render_mod_required,
                 config_mod,
+                Self::config_game_mod_limits(&config_game),
+                Self::config_game_mod_grants(&config_game),
                 &thread_pool,
                 &io,
                 &game_db,
@@ -712,6 +1110,13 @@ impl Server {
             last_register_time: None,
             register_task: None,
             last_register_serial: 0,
+            lan_discovery: if config_game.sv.lan_discovery && !shared_info.is_internal_server {
+                LanDiscoveryServer::new()
+                    .inspect_err(|err| log::warn!("lan discovery is not available: {err}"))
+                    .ok()
+            } else {
+                None
+            },
 
             last_network_stats_time: sys.time_get(),
 
@@ -727,6 +1132,8 @@ impl Server {
 
             // server side demo recorder
             demo_recorder: None,
+            auto_demo_recorder: None,
+            auto_demos: AutoDemos::new(&io.fs.get_save_path().join("demos")),
 
             // votes
             map_votes,
@@ -734,9 +1141,13 @@ impl Server {
             misc_votes: Default::default(),
             misc_votes_hash: generate_hash_for(&[]),
 
+            // map rotation
+            map_rotation: MapRotation::new(&config_game.sv),
+
             // database
             db,
             game_db,
+            game_db_backend,
             db_requests: Default::default(),
             db_requests_helper: Default::default(),
 
@@ -791,6 +1202,24 @@ impl Server {
         ip: IpAddr,
         cert: Arc<x509_cert::Certificate>,
         network_stats: PlayerNetworkStats,
+        password: Option<&str>,
+    ) {
+        // password protected servers only accept clients that know the password,
+        // before they get any information about the game
+        if !password_matches(&self.config_game.sv.password, password) {
+            self.network.kick(con_id, KickType::WrongPassword);
+            return;
+        }
+        self.client_connect(con_id, timestamp, ip, cert, network_stats);
+    }
+
+    fn client_connect(
+        &mut self,
+        con_id: &NetworkConnectionId,
+        timestamp: &Duration,
+        ip: IpAddr,
+        cert: Arc<x509_cert::Certificate>,
+        network_stats: PlayerNetworkStats,
     ) {
         // check if the client can be part of the game
         if self.can_another_player_connect() {
@@ -836,6 +1265,7 @@ impl Server {
                     ClientAuth {
                         cert,
                         level: Default::default(),
+                        permissions: Default::default(),
                     },
                     network_stats,
                 ),
@@ -889,6 +1319,16 @@ impl Server {
         con_id: &NetworkConnectionId,
         _reason: &str,
     ) -> Option<PoolFxLinkedHashMap<PlayerId, ServerClientPlayer>> {
+        // find client in clients that did not send their password yet
+        if self
+            .clients
+            .network_password_clients
+            .remove(con_id)
+            .is_some()
+        {
+            return None;
+        }
+
         // find client in queued clients
         if self.clients.network_queued_clients.contains_key(con_id) {
             self.drop_client_from_queue(con_id);
@@ -909,7 +1349,7 @@ impl Server {
                     .1
                     .connect_timestamp;
                 let p = self.drop_client_from_queue(&con_id_queue).unwrap();
-                self.try_client_connect(
+                self.client_connect(
                     &con_id_queue,
                     &timestamp_queue,
                     p.ip,
@@ -954,7 +1394,7 @@ impl Server {
                         .1
                         .connect_timestamp;
                     let drop_player = self.drop_client_from_queue(&con_id_queue).unwrap();
-                    self.try_client_connect(
+                    self.client_connect(
                         &con_id_queue,
                         &timestamp_queue,
                         drop_player.ip,
@@ -1076,7 +1516,7 @@ impl Server {
                                     .prefixes
                                     .contains(&msg.chars().next().unwrap())
                                 {
-                                    self.game_server.game.client_command(
+                                    self.game_server.client_command(
                                         player_id,
                                         ClientCommand::Chat(ClientChatCommand {
                                             raw: msg
@@ -1102,7 +1542,11 @@ impl Server {
                                         channel: channel.clone(),
                                     };
 
-                                    if let Some(recorder) = &mut self.demo_recorder {
+                                    for recorder in self
+                                        .demo_recorder
+                                        .iter_mut()
+                                        .chain(self.auto_demo_recorder.iter_mut())
+                                    {
                                         recorder.add_event(
                                             self.game_server.cur_monotonic_tick,
                                             demo::DemoEvent::Chat(Box::new(msg.clone())),
@@ -1199,12 +1643,10 @@ impl Server {
                     }
                     ClientToServerPlayerMessage::Kill => {
                         self.game_server
-                            .game
                             .client_command(player_id, ClientCommand::Kill);
                     }
                     ClientToServerPlayerMessage::JoinSpectator => {
                         self.game_server
-                            .game
                             .client_command(player_id, ClientCommand::JoinSpectator);
                     }
                     ClientToServerPlayerMessage::StartVote(vote) => {
@@ -1309,7 +1751,6 @@ impl Server {
                                             if can_be_kicked && !is_same_client && !is_same_network
                                             {
                                                 self.game_server
-                                                    .game
                                                     .voted_player(Some(key.voted_player_id));
                                                 Either::Left((
                                                     if kicked {
@@ -1435,45 +1876,45 @@ impl Server {
                     }
                     ClientToServerPlayerMessage::JoinStage(join_stage) => {
                         self.game_server
-                            .game
                             .client_command(player_id, ClientCommand::JoinStage(join_stage));
                     }
                     ClientToServerPlayerMessage::JoinVanillaSide(side) => {
                         self.game_server
-                            .game
                             .client_command(player_id, ClientCommand::JoinSide(side));
                     }
                     ClientToServerPlayerMessage::SwitchToCamera(mode) => {
                         self.game_server
-                            .game
                             .client_command(player_id, ClientCommand::SetCameraMode(mode));
                     }
                     ClientToServerPlayerMessage::UpdateCharacterInfo { info, version } => {
                         self.game_server
-                            .game
                             .try_overwrite_player_character_info(player_id, &info, version);
                     }
                     ClientToServerPlayerMessage::RconExec { name, args } => {
                         let auth_level = player.auth.level;
-                        if matches!(auth_level, AuthLevel::Moderator | AuthLevel::Admin) {
-                            let res = if self
-                                .game_server
-                                .game
-                                .info
-                                .rcon_commands
-                                .cmds
-                                .contains_key(name.as_str())
-                            {
-                                self.game_server.game.rcon_command(
-                                    Some(*player_id),
-                                    ExecRconCommand {
-                                        raw: format!("{} {}", name.as_str(), args.as_str())
-                                            .as_str()
-                                            .try_into()
-                                            .unwrap(),
-                                        auth_level,
-                                    },
-                                )
+                        let permissions = player.auth.permissions.clone();
+                        if permissions.has_access(auth_level) {
+                            let mod_cmds = &self.game_server.game.info.rcon_commands.cmds;
+                            let res = if mod_cmds.contains_key(name.as_str()) {
+                                // commands can be chained, so every single one must be checked
+                                let parser_cmds: HashMap<_, _> = mod_cmds
+                                    .iter()
+                                    .map(|(name, cmd)| (name.clone(), cmd.args.clone()))
+                                    .collect();
+                                let cmds = command_parser::parser::parse(
+                                    &format!("{} {}", name.as_str(), args.as_str()),
+                                    &parser_cmds,
+                                    &mut self.cache,
+                                );
+                                match permissions.check_mod_cmds(auth_level, &cmds, mod_cmds) {
+                                    Ok(cmds) => cmds
+                                        .into_iter()
+                                        .flat_map(|cmd| {
+                                            self.game_server.rcon_command(Some(*player_id), cmd)
+                                        })
+                                        .collect(),
+                                    Err(err) => vec![NetworkString::new_lossy(err.to_string())],
+                                }
                             } else {
                                 // if not a mod rcon, try to execute it inside the server
                                 let cmds = command_parser::parser::parse(
@@ -1481,7 +1922,12 @@ impl Server {
                                     &self.rcon_chain.parser,
                                     &mut self.cache,
                                 );
-                                self.handle_rcon_commands(Some(player_id), auth_level, cmds)
+                                self.handle_rcon_commands(
+                                    Some(player_id),
+                                    auth_level,
+                                    &permissions,
+                                    cmds,
+                                )
                             };
                             self.network.send_in_order_to(
                                 &ServerToClientMessage::RconExecResult { results: res },
@@ -1497,6 +1943,133 @@ impl Server {
         }
     }
 
+    fn save_bans(&self) {
+        let mut ban_list = BanList::from_bans(&self.connection_bans, self.cert_bans.as_deref());
+        ban_list
+            .id_bans
+            .extend(self.inactive_id_bans.iter().cloned());
+        self.ban_list_writer.save(&ban_list, &self.io);
+    }
+
+    fn fmt_migrations(migrations: &[MigrationStatus]) -> String {
+        migrations
+            .iter()
+            .map(|migration| {
+                format!(
+                    "{} {:?}: {} {} ({:?})",
+                    migration.migrations,
+                    migration.kind,
+                    migration.version,
+                    migration.name,
+                    migration.state
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// All migrations and what `db_migrate up` and `db_migrate down`
+    /// would do, without changing the database.
+    async fn db_migration_status(db: &GameDbBackend) -> anyhow::Result<String> {
+        let status = db.migration_status().await?;
+        // the newest applied migration of every migrations & database
+        let mut newest: Vec<MigrationStatus> = Default::default();
+        for status in status
+            .iter()
+            .filter(|status| status.state == MigrationState::Applied)
+        {
+            newest.retain(|newest| {
+                newest.migrations != status.migrations || newest.kind != status.kind
+            });
+            newest.push(status.clone());
+        }
+        let mut res = format!("Migrations:\n{}", Self::fmt_migrations(&status));
+        // the status already knows what `db_migrate up` would do,
+        // without failing on modified migrations
+        let modified: Vec<_> = status
+            .iter()
+            .filter(|status| status.state == MigrationState::ChecksumMismatch)
+            .cloned()
+            .collect();
+        if modified.is_empty() {
+            res.push_str(&format!(
+                "\n\"db_migrate up\" would apply {} migration(s)",
+                status
+                    .iter()
+                    .filter(|status| status.state == MigrationState::Pending)
+                    .count()
+            ));
+        } else {
+            res.push_str(&format!(
+                "\n\"db_migrate up\" would fail, these migrations were modified \
+                after they were applied:\n{}",
+                Self::fmt_migrations(&modified)
+            ));
+        }
+        res.push_str(&format!(
+            "\n\"db_migrate down <migrations> <database>\" would revert one of:\n{}",
+            Self::fmt_migrations(&newest)
+        ));
+        Ok(res)
+    }
+
+    /// Runs `db_migrate <action>` in the background,
+    /// the result is sent to the rcon of the player later.
+    fn run_db_migrate(
+        &mut self,
+        player_id: Option<&PlayerId>,
+        action: DbMigrateAction,
+    ) -> anyhow::Result<String> {
+        let db = self
+            .game_db_backend
+            .clone()
+            .ok_or_else(|| anyhow!("No database is configured on this server"))?;
+        let con_id = player_id
+            .and_then(|player_id| self.game_server.players.get(player_id))
+            .map(|player| player.network_id);
+        let res = format!("Running \"db_migrate {action}\"...");
+        // migrations can take a while, the result is sent later
+        self.db_requests.push(self.io.rt.spawn(async move {
+            let result = Self::db_migrate(&db, &action).await;
+            Ok(GameServerDb::Rcon {
+                con_id,
+                result: result.unwrap_or_else(|err| format!("db_migrate {action} failed: {err}")),
+            })
+        }));
+        Ok(res)
+    }
+
+    /// Runs `db_migrate <action>`, see the rcon commands.
+    async fn db_migrate(db: &GameDbBackend, action: &DbMigrateAction) -> anyhow::Result<String> {
+        match action {
+            DbMigrateAction::Status => Self::db_migration_status(db).await,
+            DbMigrateAction::Up => db.migrate_up(false).await.map(|applied| {
+                format!(
+                    "Applied {} migration(s)\n{}",
+                    applied.len(),
+                    Self::fmt_migrations(&applied)
+                )
+            }),
+            DbMigrateAction::Down {
+                migrations,
+                database,
+                confirmed,
+            } => {
+                let kind = Self::config_ty_to_db_kind(database)?;
+                let reverted = db.migrate_down(migrations, kind, !confirmed).await?;
+                Ok(if *confirmed {
+                    format!("Reverted {}", Self::fmt_migrations(&[reverted]))
+                } else {
+                    format!(
+                        "Would revert {}\n\
+                        The downgrade script might delete data, confirm to revert it.",
+                        Self::fmt_migrations(&[reverted])
+                    )
+                })
+            }
+        }
+    }
+
     fn user_id(account_server_public_key: &[VerifyingKey], auth: &ClientAuth) -> UserId {
         ddnet_accounts_shared::game_server::user_id::user_id_from_cert(
             account_server_public_key,
@@ -1528,7 +2101,12 @@ impl Server {
         }
     }
 
+    /// Only sends the commands the client is allowed to execute.
     fn send_rcon_commands(&self, con_id: &NetworkConnectionId) {
+        let Some(client) = self.clients.clients.get(con_id) else {
+            return;
+        };
+        let auth = &client.auth;
         let mut rcon_commands = RconCommands {
             cmds: self
                 .rcon_chain
@@ -1541,6 +2119,9 @@ impl Server {
         rcon_commands
             .cmds
             .extend(self.game_server.game.info.rcon_commands.cmds.clone());
+        rcon_commands
+            .cmds
+            .retain(|name, cmd| auth.permissions.can_exec(auth.level, name, cmd));
         self.network.send_in_order_to(
             &ServerToClientMessage::RconCommands(rcon_commands),
             con_id,
@@ -1550,10 +2131,127 @@ impl Server {
         );
     }
 
+    /// Starts a new demo of the current game in a sub directory of the demo directory.
+    fn new_demo_recorder(&self, sub_dir: &str, name: Option<String>) -> DemoRecorder {
+        DemoRecorder::new(
+            DemoRecorderCreateProps {
+                base: DemoRecorderCreatePropsBase {
+                    map: self.game_server.map.name.as_str().try_into().unwrap(),
+                    map_hash: generate_hash_for(&self.game_server.map.map_file),
+                    game_options: GameStateCreateOptions {
+                        hint_max_characters: Some(self.config_game.sv.max_players as usize),
+                        account_db: None,
+                        config: self.game_server.game.info.config.clone(),
+                    },
+                    required_resources: Default::default(), /* TODO: */
+                    physics_module: self.game_server.game_mod.clone(),
+                    render_module: self.game_server.render_mod.clone(),
+                    physics_group_name: self
+                        .game_server
+                        .game
+                        .info
+                        .options
+                        .physics_group_name
+                        .clone(),
+                },
+                io: self.io.clone(),
+                in_memory: None,
+            },
+            self.game_server.game.info.ticks_in_a_second,
+            Some(sub_dir.as_ref()),
+            name,
+        )
+    }
+
+    /// Starts the demo of a new round.
+    fn start_auto_demo(&mut self) {
+        let name = AutoDemos::demo_name(self.game_server.map.name.as_str());
+        self.auto_demo_recorder = Some(self.new_demo_recorder(AUTO_DEMOS_DIR, Some(name)));
+    }
+
+    /// Finishes the demo of the current round and cleans up
+    /// the old demos, once the demo file was written.
+    fn finish_auto_demo(&mut self) {
+        let Some(recorder) = self.auto_demo_recorder.take() else {
+            return;
+        };
+        let writer_thread = recorder.finish();
+        let auto_demos = self.auto_demos.clone();
+        let max_count = self.config_game.sv.auto_record_max_count as usize;
+        let max_size = self
+            .config_game
+            .sv
+            .auto_record_max_size
+            .saturating_mul(1024 * 1024);
+        let res = std::thread::Builder::new()
+            .name("auto-demo-cleanup".to_string())
+            .spawn(move || {
+                if let Some(writer_thread) = writer_thread {
+                    // the writer logs its own errors
+                    let _ = writer_thread.join();
+                }
+                match auto_demos.cleanup(max_count, max_size) {
+                    Ok(removed) => {
+                        if !removed.is_empty() {
+                            log::info!("removed old auto recorded demos: {}", removed.join(", "));
+                        }
+                    }
+                    Err(err) => log::warn!("failed to clean up the auto recorded demos: {err}"),
+                }
+            });
+        if let Err(err) = res {
+            log::warn!("failed to clean up the auto recorded demos: {err}");
+        }
+    }
+
+    /// Starts the input history log of the current game.
+    fn start_input_history(&mut self) {
+        let header = InputHistoryHeader {
+            map: self.game_server.map.name.as_str().try_into().unwrap(),
+            map_hash: generate_hash_for(&self.game_server.map.map_file),
+            game_mod: self.game_server.game_mod.clone(),
+            game_options: GameStateCreateOptions {
+                hint_max_characters: Some(self.config_game.sv.max_players as usize),
+                account_db: None,
+                config: self.game_server.game.info.config.clone(),
+            },
+            ticks_per_second: self.game_server.game.info.ticks_in_a_second,
+            start_monotonic_tick: self.game_server.cur_monotonic_tick,
+            start_time: chrono::Utc::now(),
+            snapshot: self
+                .game_server
+                .game
+                .snapshot_for_hotreload()
+                .map(|snapshot| snapshot.to_vec()),
+        };
+        let path = self
+            .io
+            .fs
+            .get_save_path()
+            .join("input_history")
+            .join(format!(
+                "{}_{}.twinputs",
+                self.game_server.map.name.as_str(),
+                chrono::Local::now().format("%Y_%m_%d_%H_%M_%S")
+            ));
+        match InputHistoryWriter::new(&path, &header) {
+            Ok(writer) => self.game_server.input_history = Some(writer),
+            Err(err) => {
+                log::error!(
+                    "failed to create the input history {}: {err}",
+                    path.display()
+                );
+                // don't retry every tick
+                self.config_game.sv.input_history = false;
+            }
+        }
+    }
+
     fn handle_rcon_commands(
         &mut self,
-        _player_id: Option<&PlayerId>,
-        _auth: AuthLevel,
+        player_id: Option<&PlayerId>,
+        auth: AuthLevel,
+        permissions: &AuthPermissions,
         cmds: Vec<CommandType>,
     ) -> Vec<NetworkString<65536>> {
         fn syn_vec_to_config_val(args: &[(Syn, Range<usize>)]) -> Option<String> {
@@ -1578,6 +2276,9 @@ impl Server {
                         let Some(chain_cmd) = self.rcon_chain.cmds.get(&cmd.ident) else {
                             return Err(anyhow!("Command {} not found", cmd.ident));
                         };
+                        if !permissions.can_exec(auth, &cmd.ident, &chain_cmd.rcon) {
+                            return Err(anyhow!("You are not allowed to execute {}", cmd.ident));
+                        }
 
                         fn ban_or_kick(
                             cmd: &parser::Command,
@@ -1604,6 +2305,64 @@ impl Server {
                             Ok(())
                         }
 
+                        fn ban_args(
+                            cmd: &parser::Command,
+                        ) -> anyhow::Result<(&str, BanType, Option<chrono::DateTime<chrono::Utc>>)>
+                        {
+                            let (Syn::Text(target), Syn::Number(minutes), Syn::Text(reason)) =
+                                (&cmd.args[0].0, &cmd.args[1].0, &cmd.args[2].0)
+                            else {
+                                panic!("Command parser returned a non requested command arg");
+                            };
+                            let minutes: u64 = minutes.parse()?;
+                            let until = (minutes > 0).then(|| {
+                                chrono::Utc::now() + Duration::from_secs(minutes.saturating_mul(60))
+                            });
+                            Ok((target, BanType::Custom(reason.clone()), until))
+                        }
+
+                        fn parse_unique_id(id: &str) -> anyhow::Result<PlayerUniqueId> {
+                            if let Ok(account_id) = id.parse() {
+                                Ok(PlayerUniqueId::Account(account_id))
+                            } else {
+                                decode_hash(id)
+                                    .map(PlayerUniqueId::CertFingerprint)
+                                    .ok_or_else(|| {
+                                        anyhow!(
+                                            "{id} is neither an account id nor a cert fingerprint"
+                                        )
+                                    })
+                            }
+                        }
+
+                        fn kick_banned(
+                            network: &QuinnNetworks,
+                            clients: &mut Clients,
+                            ids: HashSet<NetworkConnectionId>,
+                            ty: &BanType,
+                            until: Option<chrono::DateTime<chrono::Utc>>,
+                        ) -> String {
+                            for id in &ids {
+                                if let Some(client) = clients.clients.get_mut(id) {
+                                    client.drop_reason = Some(PlayerDropReason::Banned {
+                                        reason: PlayerBanReason::Rcon,
+                                        until,
+                                    });
+                                }
+                                network.kick(
+                                    id,
+                                    KickType::Ban(Banned {
+                                        msg: ty.clone(),
+                                        until,
+                                    }),
+                                );
+                            }
+                            ids.into_iter()
+                                .map(|id| id.to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        }
+
                         match chain_cmd.cmd {
                             ServerRconCommand::BanId => {
                                 let mut res = String::new();
@@ -1643,8 +2402,96 @@ impl Server {
                                         res = format!("Banned the following id(s): {}", text);
                                     },
                                 )?;
+                                self.save_bans();
                                 anyhow::Ok(res)
                             }
+                            ServerRconCommand::BanRange => {
+                                let (net, ty, until) = ban_args(&cmd)?;
+                                let net = match net.parse::<IpAddr>() {
+                                    Ok(ip) => ip.into(),
+                                    Err(_) => net.parse()?,
+                                };
+                                let ids = self.connection_bans.ban_range(net, ty.clone(), until);
+                                let text =
+                                    kick_banned(&self.network, &mut self.clients, ids, &ty, until);
+                                self.save_bans();
+                                Ok(format!(
+                                    "Banned {net}, kicked the following id(s): {}",
+                                    text
+                                ))
+                            }
+                            ServerRconCommand::BanAccount => {
+                                let (id, ty, until) = ban_args(&cmd)?;
+                                let id = parse_unique_id(id)?;
+                                let Some(cert_bans) = &self.cert_bans else {
+                                    return Err(anyhow!("Accounts are disabled on this server."));
+                                };
+                                let ids = cert_bans.ban_id(id, ty.clone(), until);
+                                let text =
+                                    kick_banned(&self.network, &mut self.clients, ids, &ty, until);
+                                self.save_bans();
+                                Ok(format!("Kicked the following id(s): {}", text))
+                            }
+                            ServerRconCommand::Unban => {
+                                let Syn::Text(ban) = &cmd.args[0].0 else {
+                                    panic!("Command parser returned a non requested command arg");
+                                };
+                                let unbanned = if let Ok(ip) = ban.parse::<IpAddr>() {
+                                    self.connection_bans.unban_range(ip.into())
+                                } else if let Ok(net) = ban.parse() {
+                                    self.connection_bans.unban_range(net)
+                                } else {
+                                    let id = parse_unique_id(ban)?;
+                                    let inactive_bans = self.inactive_id_bans.len();
+                                    self.inactive_id_bans.retain(|ban| ban.id != id);
+                                    self.cert_bans
+                                        .as_ref()
+                                        .is_some_and(|cert_bans| cert_bans.unban_id(&id))
+                                        || inactive_bans != self.inactive_id_bans.len()
+                                };
+                                if unbanned {
+                                    self.save_bans();
+                                    Ok(format!("Removed the ban of {ban}"))
+                                } else {
+                                    Err(anyhow!("No ban for {ban} found"))
+                                }
+                            }
+                            ServerRconCommand::Bans => {
+                                let ban_list = BanList::from_bans(
+                                    &self.connection_bans,
+                                    self.cert_bans.as_deref(),
+                                );
+                                let res: Vec<String> = ban_list
+                                    .ip_bans
+                                    .into_iter()
+                                    .map(|ban| (ban.net.to_string(), ban.ban.ty, ban.ban.until))
+                                    .chain(
+                                        ban_list
+                                            .id_bans
+                                            .into_iter()
+                                            .chain(self.inactive_id_bans.iter().cloned())
+                                            .map(|ban| {
+                                                let id = match ban.id {
+                                                    PlayerUniqueId::Account(account_id) => {
+                                                        format!("account {account_id}")
+                                                    }
+                                                    PlayerUniqueId::CertFingerprint(hash) => {
+                                                        format!("cert {}", fmt_hash(&hash))
+                                                    }
+                                                };
+                                                (id, ban.ban.ty, ban.ban.until)
+                                            }),
+                                    )
+                                    .map(|(target, ty, until)| {
+                                        format!("{target}: {}", Banned { msg: ty, until })
+                                    })
+                                    .collect();
+                                if res.is_empty() {
+                                    Ok("There are no active bans".to_string())
+                                } else {
+                                    Ok(res.join("\n"))
+                                }
+                            }
                             ServerRconCommand::KickId => {
                                 let mut res = String::new();
                                 ban_or_kick(
@@ -1692,46 +2539,92 @@ impl Server {
                                     config::traits::ConfigFromStrOperation::Set,
                                 )?)
                             }
+                            ServerRconCommand::DbJournal => {
+                                let status = self
+                                    .game_db_backend
+                                    .as_ref()
+                                    .and_then(|db| db.journal_status())
+                                    .ok_or_else(|| {
+                                        anyhow!(
+                                            "The write journal requires a \"sqlite_backup\" database"
+                                        )
+                                    })?;
+                                let mut res = format!(
+                                    "Pending writes: {}, replayed: {}, dropped: {}",
+                                    status.pending, status.replayed, status.dropped
+                                );
+                                if let Some(err) = status.last_error {
+                                    res.push_str(&format!("\nLast error: {err}"));
+                                }
+                                Ok(res)
+                            }
+                            ServerRconCommand::DbMigrate => {
+                                let Syn::Text(action) = &cmd.args[0].0 else {
+                                    panic!("Command parser returned a non requested command arg");
+                                };
+                                let action = match action.as_str() {
+                                    "status" => DbMigrateAction::Status,
+                                    _ => DbMigrateAction::Up,
+                                };
+                                self.run_db_migrate(player_id, action)
+                            }
+                            ServerRconCommand::DbMigrateDown => {
+                                let (Syn::Text(migrations), Syn::Text(database), Syn::Text(mode)) =
+                                    (&cmd.args[0].0, &cmd.args[1].0, &cmd.args[2].0)
+                                else {
+                                    panic!("Command parser returned a non requested command arg");
+                                };
+                                self.run_db_migrate(
+                                    player_id,
+                                    DbMigrateAction::Down {
+                                        migrations: migrations.clone(),
+                                        database: database.clone(),
+                                        confirmed: mode == "confirm",
+                                    },
+                                )
+                            }
+                            ServerRconCommand::NextMap => {
+                                let map = self
+                                    .map_rotation
+                                    .next_map(&self.config_game.sv, &self.config_game.sv.map)
+                                    .ok_or_else(|| anyhow!("The map rotation is empty"))?;
+                                let res = format!("Changing the map to {map}");
+                                self.map_rotation.request_map(map);
+                                Ok(res)
+                            }
+                            ServerRconCommand::RotationAdd => {
+                                let Syn::Text(map) = &cmd.args[0].0 else {
+                                    panic!("Command parser returned a non requested command arg");
+                                };
+                                NetworkReducedAsciiString::<MAX_MAP_NAME_LEN>::try_from(
+                                    map.as_str(),
+                                )
+                                .map_err(|err| anyhow!("{map} is not a valid map name: {err}"))?;
+                                let rotation = &mut self.config_game.sv.maprotation;
+                                anyhow::ensure!(
+                                    !rotation.contains(map),
+                                    "{map} is already part of the map rotation"
+                                );
+                                rotation.push(map.clone());
+                                Ok(format!("Added {map} to the map rotation"))
+                            }
+                            ServerRconCommand::RotationRemove => {
+                                let Syn::Text(map) = &cmd.args[0].0 else {
+                                    panic!("Command parser returned a non requested command arg");
+                                };
+                                let rotation = &mut self.config_game.sv.maprotation;
+                                let len = rotation.len();
+                                rotation.retain(|m| m != map);
+                                anyhow::ensure!(
+                                    rotation.len() != len,
+                                    "{map} is not part of the map rotation"
+                                );
+                                Ok(format!("Removed {map} from the map rotation"))
+                            }
                             ServerRconCommand::RecordDemo => {
                                 let had_demo_recorder = self.demo_recorder.is_some();
-                                self.demo_recorder = Some(DemoRecorder::new(
-                                    DemoRecorderCreateProps {
-                                        base: DemoRecorderCreatePropsBase {
-                                            map: self
-                                                .game_server
-                                                .map
-                                                .name
-                                                .as_str()
-                                                .try_into()
-                                                .unwrap(),
-                                            map_hash: generate_hash_for(
-                                                &self.game_server.map.map_file,
-                                            ),
-                                            game_options: GameStateCreateOptions {
-                                                hint_max_characters: Some(
-                                                    self.config_game.sv.max_players as usize,
-                                                ),
-                                                account_db: None,
-                                                config: self.game_server.game.info.config.clone(),
-                                            },
-                                            required_resources: Default::default(), /* TODO: */
-                                            physics_module: self.game_server.game_mod.clone(),
-                                            render_module: self.game_server.render_mod.clone(),
-                                            physics_group_name: self
-                                                .game_server
-                                                .game
-                                                .info
-                                                .options
-                                                .physics_group_name
-                                                .clone(),
-                                        },
-                                        io: self.io.clone(),
-                                        in_memory: None,
-                                    },
-                                    self.game_server.game.info.ticks_in_a_second,
-                                    Some("server_demos".as_ref()),
-                                    None,
-                                ));
+                                self.demo_recorder =
+                                    Some(self.new_demo_recorder("server_demos", None));
                                 Ok(format!(
                                     "Started demo recording.{}",
                                     if had_demo_recorder {
@@ -1741,6 +2634,31 @@ impl Server {
                                     }
                                 ))
                             }
+                            ServerRconCommand::AutoDemos => {
+                                let demos = self.auto_demos.list()?;
+                                if demos.is_empty() {
+                                    return Ok("No demos were recorded automatically".to_string());
+                                }
+                                Ok(demos
+                                    .iter()
+                                    .map(|demo| {
+                                        format!(
+                                            "{} ({:.1} MiB){}",
+                                            demo.name,
+                                            demo.size as f64 / (1024.0 * 1024.0),
+                                            if demo.kept { " [kept]" } else { "" }
+                                        )
+                                    })
+                                    .collect::<Vec<_>>()
+                                    .join("\n"))
+                            }
+                            ServerRconCommand::AutoDemoKeep => {
+                                let Syn::Text(name) = &cmd.args[0].0 else {
+                                    panic!("Command parser returned a non requested command arg");
+                                };
+                                self.auto_demos.keep(name)?;
+                                Ok(format!("The demo {name} is kept"))
+                            }
                         }
                     }
                     CommandType::Partial(cmd) => {
@@ -1750,6 +2668,9 @@ impl Server {
                         let Some(chain_cmd) = self.rcon_chain.cmds.get(&cmd.ident) else {
                             return Err(anyhow!("Command {} not found", cmd.ident));
                         };
+                        if !permissions.can_exec(auth, &cmd.ident, &chain_cmd.rcon) {
+                            return Err(anyhow!("You are not allowed to execute {}", cmd.ident));
+                        }
 
                         if let ServerRconCommand::ConfVariable = chain_cmd.cmd {
                             Ok(self.config_game.try_set_from_str(
@@ -1858,22 +2779,14 @@ impl Server {
                             self.send_rcon_commands(con_id);
                         }
 
-                        if let Some((accounts, db)) = self.accounts.as_ref().zip(self.db.as_ref()) {
-                            if let Some(pool) = db.pools.get(&accounts.kind) {
-                                let shared = accounts.shared.clone();
-                                let pool = pool.clone();
-                                self.db_requests.push(self.io.rt.spawn(async move {
-                                    let new_account_was_created =
-                                        ddnet_account_game_server::auto_login::auto_login(
-                                            shared, &pool, &user_id,
-                                        )
-                                        .await?;
-                                    Ok(GameServerDb::Account(GameServerDbAccount::AutoLogin {
-                                        user_id,
-                                        new_account_was_created,
-                                    }))
-                                }));
-                            }
+                        if let Some(accounts) = self.accounts.clone() {
+                            self.db_requests.push(self.io.rt.spawn(async move {
+                                let new_account_was_created = accounts.auto_login(&user_id).await?;
+                                Ok(GameServerDb::Account(GameServerDbAccount::AutoLogin {
+                                    user_id,
+                                    new_account_was_created,
+                                }))
+                            }));
                         }
 
                         self.network.send_unordered_to(
@@ -2113,31 +3026,18 @@ impl Server {
                             &client.auth,
                         );
 
-                        if let Some((accounts, db)) = self.accounts.as_ref().zip(self.db.as_ref()) {
-                            if let Some(pool) = db.pools.get(&accounts.kind) {
-                                let shared = accounts.shared.clone();
-                                let pool = pool.clone();
-                                let con_id = *con_id;
-                                self.db_requests.push(self.io.rt.spawn(async move {
-                                    let rename_res = ddnet_account_game_server::rename::rename(
-                                        shared,
-                                        &pool,
-                                        &user_id,
-                                        new_name.as_str(),
-                                    )
-                                    .await
-                                    .map(|_| ());
-                                    Ok(GameServerDb::Account(GameServerDbAccount::Rename {
-                                        con_id,
-                                        rename_result: rename_res
-                                            .map_err(|err| {
-                                                NetworkString::new_lossy(err.to_string())
-                                            })
-                                            .map(|_| new_name),
-                                        account_id: user_id.account_id,
-                                    }))
-                                }));
-                            }
+                        if let Some(accounts) = self.accounts.clone() {
+                            let con_id = *con_id;
+                            self.db_requests.push(self.io.rt.spawn(async move {
+                                let rename_res = accounts.rename(&user_id, new_name.as_str()).await;
+                                Ok(GameServerDb::Account(GameServerDbAccount::Rename {
+                                    con_id,
+                                    rename_result: rename_res
+                                        .map_err(|err| NetworkString::new_lossy(err.to_string()))
+                                        .map(|_| new_name),
+                                    account_id: user_id.account_id,
+                                }))
+                            }));
                         } else {
                             self.network.send_unordered_to(
                                 &ServerToClientMessage::AccountRenameRes(Err(
@@ -2245,6 +3145,18 @@ impl Server {
                     }
                 }
             }
+            ClientToServerMessage::Password(msg) => {
+                if let Some(client) = self.clients.network_password_clients.remove(con_id) {
+                    self.try_client_connect(
+                        con_id,
+                        &client.connect_timestamp,
+                        client.ip,
+                        client.auth.cert,
+                        client.network_stats,
+                        msg.password.as_ref().map(|password| password.as_str()),
+                    );
+                }
+            }
             ClientToServerMessage::SpatialChatDeactivated => {
                 if let Some(spatial_chat) = &mut self.game_server.spatial_world {
                     if self.clients.clients.contains_key(con_id) {
@@ -2369,10 +3281,7 @@ impl Server {
     }
 
     pub fn register(&mut self) {
-        let master_servers = [
-            //"https://master1.ddnet.org/ddnet/15/register",
-            "https://pg.ddnet.org:4444/ddnet/15/register",
-        ];
+        let master_servers = self.config_game.sv.master_servers.clone();
 
         let http_v4 = self.io.http.clone();
         let http_v6 = self.http_v6.clone();
@@ -2411,7 +3320,7 @@ impl Server {
             max_players: self.config_game.sv.max_players,
             max_players_per_client: self.config_game.sv.max_players_per_client,
             tournament_mode: settings.tournament_mode,
-            passworded: false, // TODO:
+            passworded: !self.config_game.sv.password.is_empty(),
             cert_sha256_fingerprint: self.cert_sha256_fingerprint,
             requires_account: self.accounts_only,
         };
@@ -2426,6 +3335,14 @@ impl Server {
             *browser_info = Some(register_info.clone())
         }
 
+        if let Some(lan_discovery) = &self.lan_discovery {
+            lan_discovery.set_info(LanServerInfo {
+                port_v4,
+                port_v6,
+                info: register_info.clone(),
+            });
+        }
+
         let register_info = loop {
             let json = serde_json::to_string(&register_info).unwrap();
             if json.len() <= 16 * 1024 {
@@ -2443,7 +3360,7 @@ impl Server {
             }
         };
 
-        if !self.config_game.sv.register {
+        if !self.config_game.sv.register || master_servers.is_empty() {
             return;
         }
 
@@ -2462,11 +3379,16 @@ impl Server {
                                     http: Arc<dyn HttpClientInterface>,
                                     ipv4: bool,
                                     port: u16| {
+                        let master_servers = master_servers.clone();
                         Box::pin(async move {
                             for master_server in master_servers {
+                                let Ok(url) = master_server.as_str().try_into() else {
+                                    log::warn!("{master_server} is not a valid master server url");
+                                    continue;
+                                };
                                 match http
                                     .custom_request(
-                                        master_server.try_into().unwrap(),
+                                        url,
                                         vec![
                                             (
                                                 "Address",
@@ -2569,18 +3491,37 @@ impl Server {
                                 addr,
                             } => {
                                 log::debug!(target: "server", "connect time sv: {}", timestamp.as_nanos());
-                                self.try_client_connect(
-                                    &con_id,
-                                    &timestamp,
-                                    addr.ip(),
-                                    cert,
-                                    PlayerNetworkStats {
-                                        ping: initial_network_stats.ping,
-                                        packet_loss: initial_network_stats.packets_lost as f32
-                                            / initial_network_stats.packets_sent.clamp(1, u64::MAX)
-                                                as f32,
-                                    },
-                                );
+                                let network_stats = PlayerNetworkStats {
+                                    ping: initial_network_stats.ping,
+                                    packet_loss: initial_network_stats.packets_lost as f32
+                                        / initial_network_stats.packets_sent.clamp(1, u64::MAX)
+                                            as f32,
+                                };
+                                if self.config_game.sv.password.is_empty() {
+                                    self.try_client_connect(
+                                        &con_id,
+                                        &timestamp,
+                                        addr.ip(),
+                                        cert,
+                                        network_stats,
+                                        None,
+                                    );
+                                } else {
+                                    // wait for the password before telling the client anything
+                                    self.clients.network_password_clients.insert(
+                                        con_id,
+                                        ServerNetworkQueuedClient::new(
+                                            &timestamp,
+                                            addr.ip(),
+                                            ClientAuth {
+                                                cert,
+                                                level: Default::default(),
+                                                permissions: Default::default(),
+                                            },
+                                            network_stats,
+                                        ),
+                                    );
+                                }
                             }
                             NetworkEvent::Disconnected(reason) => {
                                 log::debug!(target: "server", "got disconnected event from network");
@@ -2640,7 +3581,7 @@ impl Server {
                                             player_stats.insert(*player_id, client.network_stats);
                                         }
                                     }
-                                    self.game_server.game.network_stats(player_stats);
+                                    self.game_server.network_stats(player_stats);
                                 }
                             }
                             NetworkEvent::ConnectingFailed(_) => {
@@ -2662,6 +3603,10 @@ impl Server {
             // get time before checking ticks
             cur_time = self.sys.time_get();
 
+            for con_id in self.clients.take_expired_password_clients(&cur_time) {
+                self.network.kick(&con_id, KickType::WrongPassword);
+            }
+
             // update vote
             if let Some(vote) = &mut self.game_server.cur_vote {
                 // check if vote is over
@@ -2674,67 +3619,66 @@ impl Server {
                     let vote = self.game_server.cur_vote.take().unwrap();
                     // fake democracy
                     if vote.state.yes_votes > vote.state.no_votes {
-                        let vote_result =
-                            match vote.state.vote {
-                                VoteType::Map { key, .. } => {
-                                    self.load_map(&key.map.name);
-                                    None
-                                }
-                                VoteType::RandomUnfinishedMap { key } => Some(
-                                    self.game_server
-                                        .game
-                                        .vote_command(VoteCommand::RandomUnfinishedMap(key)),
-                                ),
-                                VoteType::VoteKickPlayer { .. } => {
-                                    if let ServerExtraVoteInfo::Player { to_kick_player, ip } =
-                                        &vote.extra_vote_info
-                                    {
-                                        let until =
-                                            Some(chrono::Utc::now() + Duration::from_secs(60 * 15));
-
-                                        let ty = BanType::Custom("by vote".to_string());
-
-                                        // kick that player
-                                        let ids =
-                                            self.connection_bans.ban_ip(*ip, ty.clone(), until);
-                                        for id in ids {
-                                            if let Some(c) = self.clients.clients.get_mut(&id) {
-                                                c.drop_reason = Some(PlayerDropReason::Banned {
-                                                    reason: PlayerBanReason::Vote,
-                                                    until,
-                                                });
-                                            }
-
-                                            self.network.kick(
-                                                &id,
-                                                KickType::Ban(Banned {
-                                                    msg: ty.clone(),
-                                                    until,
-                                                }),
-                                            );
+                        let vote_result = match vote.state.vote {
+                            VoteType::Map { key, .. } => {
+                                self.load_map(&key.map.name);
+                                None
+                            }
+                            VoteType::RandomUnfinishedMap { key } => Some(
+                                self.game_server
+                                    .vote_command(VoteCommand::RandomUnfinishedMap(key)),
+                            ),
+                            VoteType::VoteKickPlayer { .. } => {
+                                if let ServerExtraVoteInfo::Player { to_kick_player, ip } =
+                                    &vote.extra_vote_info
+                                {
+                                    let until =
+                                        Some(chrono::Utc::now() + Duration::from_secs(60 * 15));
+
+                                    let ty = BanType::Custom("by vote".to_string());
+
+                                    // kick that player
+                                    let ids = self.connection_bans.ban_ip(*ip, ty.clone(), until);
+                                    for id in ids {
+                                        if let Some(c) = self.clients.clients.get_mut(&id) {
+                                            c.drop_reason = Some(PlayerDropReason::Banned {
+                                                reason: PlayerBanReason::Vote,
+                                                until,
+                                            });
                                         }
+
                                         self.network.kick(
-                                            to_kick_player,
-                                            KickType::Ban(Banned { msg: ty, until }),
+                                            &id,
+                                            KickType::Ban(Banned {
+                                                msg: ty.clone(),
+                                                until,
+                                            }),
                                         );
                                     }
-                                    None
-                                }
-                                VoteType::VoteSpecPlayer { key, .. } => {
-                                    // try to move player to spec
-                                    Some(self.game_server.game.vote_command(
-                                        VoteCommand::JoinSpectator(key.voted_player_id),
-                                    ))
-                                }
-                                VoteType::Misc { vote, .. } => {
-                                    // exec the vote command in the game
-                                    Some(
-                                        self.game_server
-                                            .game
-                                            .vote_command(VoteCommand::Misc(vote.command.clone())),
-                                    )
+                                    self.network.kick(
+                                        to_kick_player,
+                                        KickType::Ban(Banned { msg: ty, until }),
+                                    );
+                                    self.save_bans();
                                 }
-                            };
+                                None
+                            }
+                            VoteType::VoteSpecPlayer { key, .. } => {
+                                // try to move player to spec
+                                Some(
+                                    self.game_server.vote_command(VoteCommand::JoinSpectator(
+                                        key.voted_player_id,
+                                    )),
+                                )
+                            }
+                            VoteType::Misc { vote, .. } => {
+                                // exec the vote command in the game
+                                Some(
+                                    self.game_server
+                                        .vote_command(VoteCommand::Misc(vote.command.clone())),
+                                )
+                            }
+                        };
 
                         if let Some(vote_result) = vote_result {
                             for ev in vote_result.events {
@@ -2748,7 +3692,7 @@ impl Server {
                     }
 
                     self.send_vote(None, Duration::ZERO);
-                    self.game_server.game.voted_player(None);
+                    self.game_server.voted_player(None);
                 }
             }
 
@@ -2769,13 +3713,13 @@ impl Server {
                             }
                         }
                     }
-                    self.game_server.game.set_player_inputs(inps);
+                    self.game_server.set_player_inputs(inps);
                 }
 
                 self.game_server.cur_monotonic_tick += 1;
 
                 // game ticks
-                let mut tick_res = self.game_server.game.tick(Default::default());
+                let mut tick_res = self.game_server.tick();
 
                 for event in tick_res.events.drain(..) {
                     match event {
@@ -2822,6 +3766,20 @@ impl Server {
                                         }),
                                     );
                                 }
+                                self.save_bans();
+                            }
+                        }
+                        TickEvent::RoundEnded => {
+                            // finalize the demo of the round,
+                            // the next one is started after a possible map change
+                            self.finish_auto_demo();
+                            if self.map_rotation.round_ended(&self.config_game.sv) {
+                                if let Some(map) = self
+                                    .map_rotation
+                                    .next_map(&self.config_game.sv, &self.config_game.sv.map)
+                                {
+                                    self.map_rotation.request_map(map);
+                                }
                             }
                         }
                     }
@@ -2840,23 +3798,29 @@ impl Server {
                     );
                 }
 
-                if let Some(recorder) = &mut self.demo_recorder {
-                    recorder.add_snapshot(
-                        self.game_server.cur_monotonic_tick,
-                        self.game_server
-                            .game
-                            .snapshot_for(SnapshotClientInfo::Everything)
-                            .to_vec(),
-                    );
+                if self.demo_recorder.is_some() || self.auto_demo_recorder.is_some() {
+                    let snapshot = self
+                        .game_server
+                        .game
+                        .snapshot_for(SnapshotClientInfo::Everything)
+                        .to_vec();
                     let events = self.game_server.game.events_for(EventClientInfo {
                         client_player_ids: self.player_ids_pool.new(),
                         everything: true,
                         other_stages: true,
                     });
-                    recorder.add_event(
-                        self.game_server.cur_monotonic_tick,
-                        demo::DemoEvent::Game(events),
-                    );
+                    for recorder in self
+                        .demo_recorder
+                        .iter_mut()
+                        .chain(self.auto_demo_recorder.iter_mut())
+                    {
+                        recorder
+                            .add_snapshot(self.game_server.cur_monotonic_tick, snapshot.clone());
+                        recorder.add_event(
+                            self.game_server.cur_monotonic_tick,
+                            demo::DemoEvent::Game(events.clone()),
+                        );
+                    }
                 }
 
                 // snap shot building
@@ -2971,13 +3935,48 @@ impl Server {
                 spatial_world.update(&self.network);
             }
 
+            if let Some(map) = self
+                .map_rotation
+                .scheduled_map(&self.config_game.sv, chrono::Local::now().naive_local())
+            {
+                log::info!("loading the scheduled map {map}");
+                self.map_rotation.request_map(map);
+            }
+
             // after tick checks
             // if the game should reload, reload all game related stuff
             // send the client a load event, which is used for map reloads etc.
-            if self.game_server.should_reload() {
+            if let Some(err) = self.game_server.game.wasm_error() {
+                // the mod was already replaced by vanilla, but the clients
+                // still run the mod, so load the game again without it.
+                log::error!("the game mod failed and is replaced by vanilla: {err}");
+                let notice = ServerToClientMessage::Notice(NetworkString::new_lossy(format!(
+                    "The game mod failed and is replaced by vanilla: {err}"
+                )));
+                self.broadcast_in_order(notice, NetworkInOrderChannel::Global);
+                self.config_game.sv.game_mod = "vanilla".to_string();
+                let map = self.config_game.sv.map.as_str().try_into().unwrap();
+                self.load_impl(None, &map);
+            } else if let Some(map) = self.map_rotation.take_pending_map() {
+                match map.as_str().try_into() {
+                    Ok(map) => self.load_map(&map),
+                    Err(err) => log::warn!("{map} is not a valid map name: {err}"),
+                }
+            } else if self.game_server.should_reload() {
                 self.reload();
             }
 
+            if !self.config_game.sv.auto_record {
+                self.finish_auto_demo();
+            } else if self.auto_demo_recorder.is_none() {
+                self.start_auto_demo();
+            }
+            if !self.config_game.sv.input_history {
+                self.game_server.input_history = None;
+            } else if self.game_server.input_history.is_none() {
+                self.start_input_history();
+            }
+
             // check db requests
             self.db_requests_helper.clear();
             for db_req in self.db_requests.drain(..) {
@@ -2993,7 +3992,7 @@ impl Server {
                                     if let (Ok(name), Some(account_id)) =
                                         (&rename_result, account_id)
                                     {
-                                        self.game_server.game.account_renamed(account_id, name);
+                                        self.game_server.account_renamed(account_id, name);
                                     }
                                     if self.clients.clients.contains_key(&con_id) {
                                         self.network.send_unordered_to(
@@ -3023,11 +4022,30 @@ impl Server {
                                     {
                                         // A new account was created, tell the game mod
                                         self.game_server
-                                            .game
                                             .account_created(account_id, user_id.public_key);
                                     }
                                 }
                             },
+                            GameServerDb::Rcon { con_id, result } => {
+                                match con_id
+                                    .filter(|con_id| self.clients.clients.contains_key(con_id))
+                                {
+                                    Some(con_id) => {
+                                        self.network.send_in_order_to(
+                                            &ServerToClientMessage::RconExecResult {
+                                                results: vec![NetworkString::new_lossy(result)],
+                                            },
+                                            &con_id,
+                                            NetworkInOrderChannel::Custom(
+                                                7302, // reads as "rcon"
+                                            ),
+                                        );
+                                    }
+                                    None => {
+                                        log::info!(target: "server-db-requests", "{result}");
+                                    }
+                                }
+                            }
                         },
                         Err(err) => {
                             log::error!(target: "server-db-requests", "{err}");
@@ -3064,6 +4082,9 @@ impl Server {
         snapshot: Option<PoolCow<'static, [u8]>>,
         map: &NetworkReducedAsciiString<MAX_MAP_NAME_LEN>,
     ) {
+        // the demo of the old game is finished
+        self.finish_auto_demo();
+
         // reload the whole game server, including the map
         let mod_name = Self::config_physics_mod_name(&self.config_game);
         let (render_mod_name, render_mod_hash, render_mod_required) =
@@ -3078,6 +4099,8 @@ impl Server {
             &render_mod_hash.try_into().unwrap_or_default(),
             render_mod_required,
             config,
+            Self::config_game_mod_limits(&self.config_game),
+            Self::config_game_mod_grants(&self.config_game),
             &self.thread_pool,
             &self.io,
             &self.game_db,
@@ -3141,6 +4164,7 @@ impl Server {
 
     fn load_map(&mut self, map: &NetworkReducedAsciiString<MAX_MAP_NAME_LEN>) {
         self.config_game.sv.map = map.to_string();
+        self.map_rotation.map_changed();
         self.load_impl(None, map)
     }
 }
@@ -3166,6 +4190,39 @@ pub fn load_config(game_cfg_path: Option<&Path>) -> (Io, ConfigEngine, ConfigGam
     (io, config_engine, config_game)
 }
 
+/// Runs `db_migrate <action>` on the configured
+/// databases without starting the server.
+///
+/// Only the migrations of the built-in game mod are known without loading a game,
+/// the migrations of wasm mods are managed with the `db_migrate` rcon command.
+pub fn ddnet_server_db_migrate(
+    game_cfg_path: Option<&Path>,
+    action: DbMigrateAction,
+) -> anyhow::Result<String> {
+    let (io, _, config_game) = load_config(game_cfg_path);
+    let mod_name = Server::config_physics_mod_name(&config_game);
+    anyhow::ensure!(
+        mod_name == "vanilla" || mod_name == "ddnet",
+        "the migrations of the game mod {mod_name} are only known to a running server, \
+        use the db_migrate rcon command instead"
+    );
+    let mut config_db = config_game.sv.db;
+    anyhow::ensure!(
+        !config_db.connections.is_empty(),
+        "No database is configured on this server"
+    );
+    // only the action changes the database
+    config_db.auto_migrate = false;
+    io.rt
+        .spawn(async move {
+            let db = Arc::new(GameDbBackend::new(Server::db_setup(&config_db).await?)?);
+            let game_db: Arc<dyn DbInterface> = db.clone();
+            db.register_migrations(migrations::migrations(&game_db))?;
+            Server::db_migrate(&db, &action).await
+        })
+        .get_storage()
+}
+
 pub fn ddnet_server_main<const IS_INTERNAL_SERVER: bool>(
     sys: System,
     cert_and_private_key: (x509_cert::Certificate, SigningKey),
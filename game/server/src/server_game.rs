@@ -1,13 +1,13 @@
 use std::{
-    collections::HashMap, future::Future, net::IpAddr, path::Path, pin::Pin, sync::Arc,
-    time::Duration,
+    collections::HashMap, future::Future, net::IpAddr, num::NonZeroU64, path::Path, pin::Pin,
+    sync::Arc, time::Duration,
 };
 
 use anyhow::anyhow;
 use base::{
     hash::{fmt_hash, name_and_hash, Hash},
     linked_hash_map_view::FxLinkedHashMap,
-    network_string::NetworkReducedAsciiString,
+    network_string::{NetworkReducedAsciiString, NetworkString},
 };
 use base_http::http_server::HttpDownloadServer;
 use base_io::io::Io;
@@ -21,18 +21,25 @@ use game_state_wasm::game::state_wasm_manager::{
 use map::map::{resources::MapResourceMetaData, Map};
 use network::network::connection::NetworkConnectionId;
 use pool::{datatypes::PoolFxLinkedHashMap, pool::Pool};
+use wasm_runtime::{WasmLimits, WasmManifest};
 
 use game_interface::{
+    account_info::MAX_ACCOUNT_NAME_LEN,
+    client_commands::ClientCommand,
     interface::{GameStateCreateOptions, GameStateInterface, MAX_MAP_NAME_LEN},
-    rcon_commands::AuthLevel,
+    rcon_commands::{AuthLevel, ExecRconCommand},
+    tick_result::TickResult,
     types::{
+        character_info::NetworkCharacterInfo,
         emoticons::EmoticonType,
         game::GameTickType,
         id_types::{CharacterId, PlayerId},
         input::CharacterInputInfo,
-        player_info::{PlayerClientInfo, PlayerDropReason},
+        network_stats::PlayerNetworkStats,
+        player_info::{AccountId, PlayerClientInfo, PlayerDropReason},
         render::character::{CharacterInfo, TeeEye},
     },
+    vote_commands::{VoteCommand, VoteCommandResult},
     votes::{VoteState, Voted},
 };
 use game_base::{
@@ -40,7 +47,9 @@ use game_base::{
     player_input::PlayerInput,
 };
 
-use crate::spatial_chat::SpatialWorld;
+use input_history::{writer::InputHistoryWriter, InputHistoryEntry};
+
+use crate::{rcon::AuthPermissions, spatial_chat::SpatialWorld};
 
 #[derive(Debug)]
 pub struct ServerPlayer {
@@ -337,6 +346,7 @@ impl ServerMap {
 pub struct ClientAuth {
     pub cert: Arc<x509_cert::Certificate>,
     pub level: AuthLevel,
+    pub permissions: AuthPermissions,
 }
 
 #[derive(Debug, Default)]
@@ -385,6 +395,10 @@ pub struct ServerGame {
 
     pub cached_character_infos: PoolFxLinkedHashMap<CharacterId, CharacterInfo>,
 
+    /// Logs all calls into the game that change its state,
+    /// if `sv.input_history` is enabled.
+    pub input_history: Option<InputHistoryWriter>,
+
     // pools
     pub(crate) inps_pool: Pool<FxLinkedHashMap<PlayerId, CharacterInputInfo>>,
 }
@@ -397,6 +411,8 @@ impl ServerGame {
         render_mod_hash: &[u8; 32],
         render_mod_required: bool,
         config: Option<Vec<u8>>,
+        game_mod_limits: WasmLimits,
+        game_mod_grants: WasmManifest,
         runtime_thread_pool: &Arc<rayon::ThreadPool>,
         io: &Io,
         db: &Arc<dyn DbInterface>,
@@ -431,22 +447,52 @@ impl ServerGame {
                 game_mod => {
                     let path = format!("{}/{}.wasm", STATE_MODS_PATH, game_mod);
                     let file_path = path.clone();
-                    let (file, wasm_module) = {
+                    let manifest_path = format!("{}/{}.manifest.json", STATE_MODS_PATH, game_mod);
+                    let (file, wasm_module, manifest) = {
                         let fs = io.fs.clone();
 
                         io.rt
                             .spawn(async move {
                                 let file = fs.read_file(file_path.as_ref()).await?;
-                                let wasm_module =
-                                    GameStateWasmManager::load_module(&fs, file.clone()).await?;
+                                // a manifest next to the module wins over the embedded one
+                                let manifest = match fs.read_file(manifest_path.as_ref()).await {
+                                    Ok(manifest) => Some(WasmManifest::from_json(&manifest)?),
+                                    Err(_) => WasmManifest::from_module(&file)?,
+                                };
+                                let wasm_module = GameStateWasmManager::load_module(
+                                    &fs,
+                                    file.clone(),
+                                    game_mod_limits,
+                                )
+                                .await?;
 
-                                Ok((file, wasm_module))
+                                Ok((file, wasm_module, manifest))
                             })
                             .get_storage()?
                     };
+                    let manifest = manifest.unwrap_or_else(|| {
+                        log::warn!(
+                            "game mod {game_mod} has no manifest, \
+                            it is not allowed to use any capability"
+                        );
+                        Default::default()
+                    });
+                    // the manifest only requests capabilities,
+                    // the operator grants them in the config.
+                    let granted = manifest.granted(&game_mod_grants);
+                    if granted != manifest {
+                        log::warn!(
+                            "game mod {game_mod} requested {manifest:?}, \
+                            but only {granted:?} was granted by sv.game_mod_grants"
+                        );
+                    }
+                    log::info!("game mod {game_mod} is allowed to use: {granted:?}");
                     let (name, hash) = name_and_hash(game_mod, &file);
                     (
-                        GameStateMod::Wasm { file: wasm_module },
+                        GameStateMod::Wasm {
+                            file: wasm_module,
+                            manifest: granted,
+                        },
                         GameModification::Wasm {
                             name: name.as_str().try_into()?,
                             hash,
@@ -468,6 +514,7 @@ impl ServerGame {
             },
             io,
             db.clone(),
+            game_mod_limits,
         )?;
         let (map_name, map_hash) = name_and_hash(map.name.as_str(), &map.map_file);
 
@@ -562,6 +609,8 @@ impl ServerGame {
 
             cached_character_infos: PoolFxLinkedHashMap::new_without_pool(),
 
+            input_history: None,
+
             inps_pool: Pool::with_capacity(2),
         })
     }
@@ -581,14 +630,69 @@ impl ServerGame {
         let player_id = self.game.player_join(player_info);
         self.players
             .insert(player_id, ServerPlayer::new(network_id, &player_id));
+        self.log_input_history(|| InputHistoryEntry::Join {
+            player_id,
+            info: player_info.clone(),
+        });
         player_id
     }
 
     pub fn player_drop(&mut self, player_id: &PlayerId, reason: PlayerDropReason) {
         self.players.remove(player_id);
+        self.log_input_history(|| InputHistoryEntry::Drop {
+            player_id: *player_id,
+            reason: reason.clone(),
+        });
         self.game.player_drop(player_id, reason);
     }
 
+    fn log_input_history(&mut self, entry: impl FnOnce() -> InputHistoryEntry) {
+        if let Some(input_history) = &mut self.input_history {
+            input_history.add(entry());
+        }
+    }
+
+    pub fn set_player_inputs(&mut self, inps: PoolFxLinkedHashMap<PlayerId, CharacterInputInfo>) {
+        self.log_input_history(|| {
+            InputHistoryEntry::Inputs(
+                inps.iter()
+                    .map(|(player_id, inp)| (*player_id, inp.clone()))
+                    .collect(),
+            )
+        });
+        self.game.set_player_inputs(inps);
+    }
+
+    pub fn client_command(&mut self, player_id: &PlayerId, cmd: ClientCommand) {
+        self.log_input_history(|| InputHistoryEntry::ClientCommand {
+            player_id: *player_id,
+            cmd: cmd.clone(),
+        });
+        self.game.client_command(player_id, cmd);
+    }
+
+    pub fn rcon_command(
+        &mut self,
+        player_id: Option<PlayerId>,
+        cmd: ExecRconCommand,
+    ) -> Vec<NetworkString<65536>> {
+        self.log_input_history(|| InputHistoryEntry::RconCommand {
+            player_id,
+            cmd: cmd.clone(),
+        });
+        self.game.rcon_command(player_id, cmd)
+    }
+
+    pub fn vote_command(&mut self, cmd: VoteCommand) -> VoteCommandResult {
+        self.log_input_history(|| InputHistoryEntry::VoteCommand(cmd.clone()));
+        self.game.vote_command(cmd)
+    }
+
+    pub fn tick(&mut self) -> TickResult {
+        self.log_input_history(|| InputHistoryEntry::Tick);
+        self.game.tick(Default::default())
+    }
+
     pub fn player_inp(
         &mut self,
         player_id: &PlayerId,
@@ -614,7 +718,7 @@ impl ServerGame {
                             diff,
                         },
                     );
-                    self.game.set_player_inputs(inps);
+                    self.set_player_inputs(inps);
                 }
             } else if for_monotonic_tick > cur_monotonic_tick + 1
                 && (for_monotonic_tick - cur_monotonic_tick) < self.game.game_tick_speed().get() * 3
@@ -630,10 +734,71 @@ impl ServerGame {
     }
 
     pub fn set_player_emoticon(&mut self, player_id: &PlayerId, emoticon: EmoticonType) {
+        self.log_input_history(|| InputHistoryEntry::Emoticon {
+            player_id: *player_id,
+            emoticon,
+        });
         self.game.set_player_emoticon(player_id, emoticon);
     }
 
     pub fn set_player_eye(&mut self, player_id: &PlayerId, eye: TeeEye, duration: Duration) {
+        self.log_input_history(|| InputHistoryEntry::Eye {
+            player_id: *player_id,
+            eye,
+            duration,
+        });
         self.game.set_player_eye(player_id, eye, duration)
     }
+
+    pub fn try_overwrite_player_character_info(
+        &mut self,
+        player_id: &PlayerId,
+        info: &NetworkCharacterInfo,
+        version: NonZeroU64,
+    ) {
+        self.log_input_history(|| InputHistoryEntry::CharacterInfo {
+            player_id: *player_id,
+            info: info.clone(),
+            version,
+        });
+        self.game
+            .try_overwrite_player_character_info(player_id, info, version);
+    }
+
+    pub fn account_created(&mut self, account_id: AccountId, cert_fingerprint: Hash) {
+        self.log_input_history(|| InputHistoryEntry::AccountCreated {
+            account_id,
+            cert_fingerprint,
+        });
+        self.game.account_created(account_id, cert_fingerprint);
+    }
+
+    pub fn account_renamed(
+        &mut self,
+        account_id: AccountId,
+        new_name: &NetworkReducedAsciiString<MAX_ACCOUNT_NAME_LEN>,
+    ) {
+        self.log_input_history(|| InputHistoryEntry::AccountRenamed {
+            account_id,
+            new_name: new_name.clone(),
+        });
+        self.game.account_renamed(account_id, new_name);
+    }
+
+    pub fn network_stats(&mut self, stats: PoolFxLinkedHashMap<PlayerId, PlayerNetworkStats>) {
+        self.log_input_history(|| {
+            InputHistoryEntry::NetworkStats(
+                stats
+                    .iter()
+                    .map(|(player_id, stats)| (*player_id, *stats))
+                    .collect(),
+            )
+        });
+        self.game.network_stats(stats);
+    }
+
+    pub fn voted_player(&mut self, player_id: Option<PlayerId>) {
+        self.log_input_history(|| InputHistoryEntry::VotedPlayer(player_id));
+        self.game.voted_player(player_id);
+    }
 }
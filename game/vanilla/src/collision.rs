@@ -7,19 +7,21 @@ pub mod collision {
         layers::{
             physics::MapLayerPhysics,
             tiles::{
-                rotation_180, rotation_270, SpeedupTile, SwitchTile, TeleTile, TileBase, TuneTile,
-                ROTATION_0, ROTATION_90,
+                rotation_180, rotation_270, SpeedupTile, SwitchTile, TeleTile, TileBase, TileFlags,
+                TuneTile, ROTATION_0, ROTATION_90,
             },
         },
         MapGroupPhysics,
     };
     use num::FromPrimitive;
+    use rustc_hash::FxHashMap;
     use serde::{Deserialize, Serialize};
-    use game_base::mapdef_06::DdraceTileNum;
+    use game_base::mapdef_06::{DdraceTileNum, EEntityTiles};
 
     use math::math::{
         distance, dot, mix, round_to_int,
         vector::{ivec2, vec2},
+        Rng, PI,
     };
 
     use crate::state::state::TICKS_PER_SECOND;
@@ -144,6 +146,59 @@ pub mod collision {
         }
     }
 
+    /// Directions a character is not allowed to move to,
+    /// see [`Collision::get_move_restrictions`].
+    pub enum CannotMove {
+        Left = 1 << 0,
+        Right = 1 << 1,
+        Up = 1 << 2,
+        Down = 1 << 3,
+    }
+
+    const CANNOT_MOVE_ALL: i32 = CannotMove::Left as i32
+        | CannotMove::Right as i32
+        | CannotMove::Up as i32
+        | CannotMove::Down as i32;
+
+    /// The positions relative to a character that are checked for move restrictions,
+    /// together with the restrictions that apply when moving onto them.
+    const MOVE_RESTRICTION_DIRS: [(vec2, i32); 5] = [
+        (vec2 { x: 0.0, y: 0.0 }, 0),
+        (vec2 { x: 1.0, y: 0.0 }, CannotMove::Right as i32),
+        (vec2 { x: 0.0, y: 1.0 }, CannotMove::Down as i32),
+        (vec2 { x: -1.0, y: 0.0 }, CannotMove::Left as i32),
+        (vec2 { x: 0.0, y: -1.0 }, CannotMove::Up as i32),
+    ];
+
+    /// The move restrictions of a stopper tile in all directions.
+    fn stopper_move_restrictions(tile: &TileBase) -> i32 {
+        match DdraceTileNum::from_u8(tile.index) {
+            // one-way stoppers block moving down by default.
+            // the x-flip has no effect on them.
+            Some(DdraceTileNum::Stop) => {
+                match (
+                    tile.flags.contains(TileFlags::YFLIP),
+                    tile.flags.contains(TileFlags::ROTATE),
+                ) {
+                    (false, false) => CannotMove::Down as i32,
+                    (false, true) => CannotMove::Left as i32,
+                    (true, false) => CannotMove::Up as i32,
+                    (true, true) => CannotMove::Right as i32,
+                }
+            }
+            // two-way stoppers block moving up & down by default.
+            Some(DdraceTileNum::StopS) => {
+                if tile.flags.contains(TileFlags::ROTATE) {
+                    CannotMove::Left as i32 | CannotMove::Right as i32
+                } else {
+                    CannotMove::Up as i32 | CannotMove::Down as i32
+                }
+            }
+            Some(DdraceTileNum::StopA) => CANNOT_MOVE_ALL,
+            _ => 0,
+        }
+    }
+
     #[derive(Debug, Hiarc, PartialEq, Eq, PartialOrd, Ord)]
     pub enum CollisionTile {
         None,
@@ -163,6 +218,15 @@ pub mod collision {
         Tune(&'a TuneTile),
     }
 
+    /// The laser of a door, in world coordinates.
+    #[derive(Debug, Clone, Copy)]
+    pub struct DoorLaser {
+        pub from: vec2,
+        pub to: vec2,
+        /// The switch number that opens and closes the door.
+        pub number: u8,
+    }
+
     #[derive(Default)]
     pub struct Collision {
         tiles: Vec<TileBase>,
@@ -174,6 +238,14 @@ pub mod collision {
         width: u32,
         height: u32,
 
+        /// All tele out positions (tile centers) by tele number
+        tele_outs: FxHashMap<u8, Vec<vec2>>,
+        /// All tele checkpoint out positions (tile centers) by tele number
+        tele_check_outs: FxHashMap<u8, Vec<vec2>>,
+        /// Tiles blocked by doors, by tile index with the door's switch number
+        door_tiles: FxHashMap<usize, u8>,
+        doors: Vec<DoorLaser>,
+
         pub(crate) tune_zones: Vec<Tunings>,
     }
 
@@ -187,6 +259,8 @@ pub mod collision {
             let mut front_layer = None;
             let mut tune_layer = None;
             let mut tele_layer = None;
+            let mut speedup_layer = None;
+            let mut switch_layer = None;
             physics_group.layers.iter().for_each(|layer| match layer {
                 MapLayerPhysics::Arbitrary(_) => {}
                 MapLayerPhysics::Game(layer) => {
@@ -198,8 +272,13 @@ pub mod collision {
                 MapLayerPhysics::Tele(layer) => {
                     tele_layer = load_all_layers.then_some(layer);
                 }
-                MapLayerPhysics::Speedup(_) => {}
-                MapLayerPhysics::Switch(_) => {}
+                MapLayerPhysics::Speedup(layer) => {
+                    speedup_layer = load_all_layers.then_some(layer);
+                }
+                MapLayerPhysics::Switch(layer) => {
+                    // the doors are also needed to render them
+                    switch_layer = Some(layer);
+                }
                 MapLayerPhysics::Tune(layer) => {
                     tune_layer = load_all_layers.then_some(layer);
                 }
@@ -251,7 +330,28 @@ pub mod collision {
                     tune_tiles
                 };
 
-            Ok(Self {
+            let tele_tiles = tele_layer
+                .map(|l| l.base.tiles.to_vec())
+                .unwrap_or_else(|| vec![Default::default(); game_layer.tiles.len()]);
+
+            let mut tele_outs: FxHashMap<u8, Vec<vec2>> = Default::default();
+            let mut tele_check_outs: FxHashMap<u8, Vec<vec2>> = Default::default();
+            for (index, tile) in tele_tiles.iter().enumerate() {
+                let outs = if tile.base.index == DdraceTileNum::TeleOut as u8 {
+                    &mut tele_outs
+                } else if tile.base.index == DdraceTileNum::TeleCheckOut as u8 {
+                    &mut tele_check_outs
+                } else {
+                    continue;
+                };
+                let x = (index % width as usize) as f32;
+                let y = (index / width as usize) as f32;
+                outs.entry(tile.number)
+                    .or_default()
+                    .push(vec2::new(x * 32.0 + 16.0, y * 32.0 + 16.0));
+            }
+
+            let mut collision = Self {
                 width,
                 height,
                 tiles: {
@@ -264,12 +364,94 @@ pub mod collision {
                 front_tiles: front_layer
                     .map(|l| l.tiles.to_vec())
                     .unwrap_or_else(|| vec![Default::default(); game_layer.tiles.len()]),
-                tele_tiles: tele_layer
+                tele_tiles,
+                tele_outs,
+                tele_check_outs,
+                speedup_tiles: speedup_layer
+                    .map(|l| l.tiles.to_vec())
+                    .unwrap_or_else(|| vec![Default::default(); game_layer.tiles.len()]),
+                switch_tiles: switch_layer
+                    .filter(|_| load_all_layers)
                     .map(|l| l.base.tiles.to_vec())
                     .unwrap_or_else(|| vec![Default::default(); game_layer.tiles.len()]),
-                speedup_tiles: vec![Default::default(); game_layer.tiles.len()],
-                switch_tiles: vec![Default::default(); game_layer.tiles.len()],
-            })
+                door_tiles: Default::default(),
+                doors: Default::default(),
+            };
+            if let Some(switch_layer) = switch_layer {
+                (collision.door_tiles, collision.doors) =
+                    collision.collect_doors(&switch_layer.base.tiles);
+            }
+            Ok(collision)
+        }
+
+        /// Evaluates all door entities of the switch layer and
+        /// collects the tiles their lasers block, together with the lasers.
+        fn collect_doors(
+            &self,
+            switch_tiles: &[SwitchTile],
+        ) -> (FxHashMap<usize, u8>, Vec<DoorLaser>) {
+            let mut door_tiles: FxHashMap<usize, u8> = Default::default();
+            let mut doors: Vec<DoorLaser> = Default::default();
+            let (width, height) = (self.width as i32, self.height as i32);
+            let switch_tile_at = |x: i32, y: i32| {
+                ((0..width).contains(&x) && (0..height).contains(&y))
+                    .then(|| switch_tiles[(y * width + x) as usize].base.index)
+                    .unwrap_or_default()
+            };
+            // neighbours of a door in order of the door's rotation (clockwise from below)
+            const SIDES: [(i32, i32); 8] = [
+                (0, 1),
+                (1, 1),
+                (1, 0),
+                (1, -1),
+                (0, -1),
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+            ];
+
+            for (index, tile) in switch_tiles.iter().enumerate() {
+                if tile.base.index != EEntityTiles::Door as u8 {
+                    continue;
+                }
+                let x = (index % self.width as usize) as i32;
+                let y = (index / self.width as usize) as i32;
+                let pos = vec2::new(x as f32 * 32.0 + 16.0, y as f32 * 32.0 + 16.0);
+                for (side, (off_x, off_y)) in SIDES.iter().enumerate() {
+                    let side_index = switch_tile_at(x + off_x, y + off_y);
+                    if side_index < EEntityTiles::LaserShort as u8
+                        || side_index > EEntityTiles::LaserLong as u8
+                    {
+                        continue;
+                    }
+                    let length =
+                        32 * 3 + 32 * (side_index - EEntityTiles::LaserShort as u8) as i32 * 3;
+                    let rotation = PI / 4.0 * side as f32;
+                    let dir = vec2::new(rotation.sin(), rotation.cos());
+                    let mut to = pos;
+                    for i in 0..length - 1 {
+                        let cur_pos = pos + dir * i as f32;
+                        if self.check_pointf(cur_pos.x, cur_pos.y) {
+                            break;
+                        }
+                        door_tiles.insert(self.tile_indexf(cur_pos.x, cur_pos.y), tile.number);
+                        to = cur_pos;
+                    }
+                    doors.push(DoorLaser {
+                        from: pos,
+                        to,
+                        number: tile.number,
+                    });
+                }
+            }
+
+            (door_tiles, doors)
+        }
+
+        /// The lasers of all doors of the map.
+        /// A door blocks characters while its switch is active.
+        pub fn doors(&self) -> &[DoorLaser] {
+            &self.doors
         }
 
         pub fn get_playfield_width(&self) -> u32 {
@@ -481,7 +663,9 @@ pub mod collision {
 
         fn is_teleport(&self, index: usize) -> Option<u8> {
             let tile = &self.tele_tiles[index];
-            (tile.base.index == DdraceTileNum::TeleIn as u8).then_some(tile.number)
+            (tile.base.index == DdraceTileNum::TeleIn as u8
+                || tile.base.index == DdraceTileNum::TeleInEvil as u8)
+                .then_some(tile.number)
         }
 
         fn is_teleport_hook(&self, index: usize) -> Option<u8> {
@@ -697,9 +881,228 @@ pub mod collision {
             }
         }
 
+        /// Picks one of the outs for a teleporter.
+        ///
+        /// The selection only depends on the tele number and the position
+        /// where the teleporter was entered, so the server and the client's
+        /// prediction always select the same out.
+        fn select_tele_out(outs: Option<&Vec<vec2>>, number: u8, in_pos: &vec2) -> Option<vec2> {
+            let outs = outs.filter(|outs| !outs.is_empty())?;
+            if outs.len() == 1 {
+                return Some(outs[0]);
+            }
+            let pos_seed = ((in_pos.x.to_bits() as u64) << 32) | in_pos.y.to_bits() as u64;
+            let seed = pos_seed ^ ((number as u64) << 56);
+            Some(outs[Rng::new(seed).random_index(outs)])
+        }
+
+        /// The position of a [`DdraceTileNum::TeleOut`] for the given tele number,
+        /// if any exists.
+        pub fn tele_out(&self, number: u8, in_pos: &vec2) -> Option<vec2> {
+            Self::select_tele_out(self.tele_outs.get(&number), number, in_pos)
+        }
+
+        /// The position of a [`DdraceTileNum::TeleCheckOut`] for the given tele number,
+        /// if any exists.
+        pub fn tele_check_out(&self, number: u8, in_pos: &vec2) -> Option<vec2> {
+            Self::select_tele_out(self.tele_check_outs.get(&number), number, in_pos)
+        }
+
+        /// A collision without any tiles, that only has the given tele outs.
+        #[cfg(test)]
+        pub(crate) fn with_tele_outs(
+            tele_outs: FxHashMap<u8, Vec<vec2>>,
+            tele_check_outs: FxHashMap<u8, Vec<vec2>>,
+        ) -> Self {
+            Self {
+                tele_outs,
+                tele_check_outs,
+                ..Default::default()
+            }
+        }
+
+        /// The move restrictions (see [`CannotMove`]) for a character at the given position.
+        ///
+        /// Doors are only considered if `is_switch_active` is `Some` and
+        /// returns `true` for the door's switch number.
+        pub fn get_move_restrictions(
+            &self,
+            is_switch_active: Option<&dyn Fn(u8) -> bool>,
+            pos: &vec2,
+            distance: f32,
+        ) -> i32 {
+            debug_assert!((0.0..=32.0).contains(&distance), "invalid distance");
+            let mut restrictions = 0;
+            for (index, (dir, mask)) in MOVE_RESTRICTION_DIRS.into_iter().enumerate() {
+                let mod_pos = *pos + dir * distance;
+                let tile_index = self.tile_index(mod_pos.x as i32, mod_pos.y as i32);
+
+                for tile in [&self.tiles[tile_index], &self.front_tiles[tile_index]] {
+                    let tile_restrictions = stopper_move_restrictions(tile);
+                    // stoppers generally only block moving onto them,
+                    // one-way stoppers also block while being on top of them.
+                    restrictions |= if index == 0 && tile.index == DdraceTileNum::Stop as u8 {
+                        tile_restrictions
+                    } else {
+                        tile_restrictions & mask
+                    };
+                }
+
+                let door_active = is_switch_active.is_some_and(|is_switch_active| {
+                    self.door_tiles
+                        .get(&tile_index)
+                        .is_some_and(|&number| is_switch_active(number))
+                });
+                if door_active {
+                    // doors block all directions, but only when moving onto them
+                    restrictions |= CANNOT_MOVE_ALL & mask;
+                }
+            }
+            restrictions
+        }
+
         pub fn get_tune_at(&self, pos: &vec2) -> &Tunings {
             let tune_tile = &self.tune_tiles[self.tile_indexf(pos.x, pos.y)];
             &self.tune_zones[tune_tile.number as usize]
         }
     }
+
+    #[cfg(test)]
+    mod test {
+        use game_base::mapdef_06::DdraceTileNum;
+        use map::map::groups::layers::tiles::{
+            rotation_180, rotation_270, TileBase, TileFlags, ROTATION_0, ROTATION_90,
+        };
+        use math::math::vector::vec2;
+
+        use super::{CannotMove, Collision, CANNOT_MOVE_ALL};
+
+        const SIZE: u32 = 3;
+        /// The center of the middle tile
+        const CENTER: vec2 = vec2 { x: 48.0, y: 48.0 };
+
+        fn collision_with(tiles: &[(u32, u32, u8, TileFlags)], front: bool) -> Collision {
+            let mut layer = vec![TileBase::default(); (SIZE * SIZE) as usize];
+            for (x, y, tile, flags) in tiles {
+                layer[(y * SIZE + x) as usize] = TileBase {
+                    index: *tile,
+                    flags: *flags,
+                };
+            }
+            let empty = vec![TileBase::default(); (SIZE * SIZE) as usize];
+            let (tiles, front_tiles) = if front {
+                (empty, layer)
+            } else {
+                (layer, empty)
+            };
+            Collision {
+                tiles,
+                front_tiles,
+                width: SIZE,
+                height: SIZE,
+                ..Default::default()
+            }
+        }
+
+        fn restrictions(collision: &Collision) -> i32 {
+            collision.get_move_restrictions(None, &CENTER, 18.0)
+        }
+
+        #[test]
+        fn one_way_stopper_on_top() {
+            for (flags, restriction) in [
+                (ROTATION_0, CannotMove::Down),
+                (ROTATION_90, CannotMove::Left),
+                (rotation_180(), CannotMove::Up),
+                (rotation_270(), CannotMove::Right),
+                (TileFlags::YFLIP, CannotMove::Up),
+                (TileFlags::YFLIP | ROTATION_90, CannotMove::Right),
+                (TileFlags::XFLIP, CannotMove::Down),
+                (TileFlags::XFLIP | ROTATION_90, CannotMove::Left),
+            ] {
+                for front in [false, true] {
+                    let collision =
+                        collision_with(&[(1, 1, DdraceTileNum::Stop as u8, flags)], front);
+                    assert_eq!(restrictions(&collision), restriction as i32);
+                }
+            }
+        }
+
+        #[test]
+        fn one_way_stopper_next_to() {
+            // only blocks moving onto it from the matching side
+            let collision = collision_with(&[(1, 2, DdraceTileNum::Stop as u8, ROTATION_0)], false);
+            assert_eq!(restrictions(&collision), CannotMove::Down as i32);
+            let collision = collision_with(&[(1, 0, DdraceTileNum::Stop as u8, ROTATION_0)], false);
+            assert_eq!(restrictions(&collision), 0);
+            let collision =
+                collision_with(&[(2, 1, DdraceTileNum::Stop as u8, rotation_270())], false);
+            assert_eq!(restrictions(&collision), CannotMove::Right as i32);
+            let collision =
+                collision_with(&[(0, 1, DdraceTileNum::Stop as u8, rotation_270())], false);
+            assert_eq!(restrictions(&collision), 0);
+        }
+
+        #[test]
+        fn two_way_stopper() {
+            // no effect while on top of it
+            let collision =
+                collision_with(&[(1, 1, DdraceTileNum::StopS as u8, ROTATION_0)], false);
+            assert_eq!(restrictions(&collision), 0);
+
+            let collision = collision_with(
+                &[
+                    (1, 0, DdraceTileNum::StopS as u8, ROTATION_0),
+                    (1, 2, DdraceTileNum::StopS as u8, rotation_180()),
+                ],
+                false,
+            );
+            assert_eq!(
+                restrictions(&collision),
+                CannotMove::Up as i32 | CannotMove::Down as i32
+            );
+
+            let collision =
+                collision_with(&[(0, 1, DdraceTileNum::StopS as u8, ROTATION_90)], true);
+            assert_eq!(restrictions(&collision), CannotMove::Left as i32);
+            let collision = collision_with(&[(0, 1, DdraceTileNum::StopS as u8, ROTATION_0)], true);
+            assert_eq!(restrictions(&collision), 0);
+        }
+
+        #[test]
+        fn all_way_stopper() {
+            let collision =
+                collision_with(&[(1, 1, DdraceTileNum::StopA as u8, ROTATION_0)], false);
+            assert_eq!(restrictions(&collision), 0);
+
+            let collision =
+                collision_with(&[(2, 1, DdraceTileNum::StopA as u8, ROTATION_90)], true);
+            assert_eq!(restrictions(&collision), CannotMove::Right as i32);
+
+            let collision = collision_with(
+                &[
+                    (0, 1, DdraceTileNum::StopA as u8, ROTATION_0),
+                    (2, 1, DdraceTileNum::StopA as u8, ROTATION_0),
+                    (1, 0, DdraceTileNum::StopA as u8, ROTATION_0),
+                    (1, 2, DdraceTileNum::StopA as u8, ROTATION_0),
+                ],
+                false,
+            );
+            assert_eq!(restrictions(&collision), CANNOT_MOVE_ALL);
+        }
+
+        #[test]
+        fn game_and_front_layer_combine() {
+            let mut collision =
+                collision_with(&[(1, 2, DdraceTileNum::StopA as u8, ROTATION_0)], false);
+            collision.front_tiles[SIZE as usize + 2] = TileBase {
+                index: DdraceTileNum::StopA as u8,
+                flags: ROTATION_0,
+            };
+            assert_eq!(
+                restrictions(&collision),
+                CannotMove::Down as i32 | CannotMove::Right as i32
+            );
+        }
+    }
 }
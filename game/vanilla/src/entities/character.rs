@@ -2,6 +2,7 @@ pub mod core;
 pub mod hook;
 pub mod player;
 pub mod pos;
+pub mod race;
 pub mod score;
 
 pub mod character {
@@ -9,6 +10,7 @@ pub mod character {
         collections::VecDeque,
         marker::PhantomData,
         num::{NonZeroI64, NonZeroU64},
+        time::Duration,
     };
 
     use crate::reusable::{CloneWithCopyableElements, ReusableCore};
@@ -37,6 +39,7 @@ pub mod character {
         },
     };
     use hiarc::{hiarc_safer_rc_refcell, Hiarc};
+    use num::FromPrimitive;
     use pool::{datatypes::PoolFxLinkedHashMap, mt_pool::Pool as MtPool};
     use rustc_hash::FxHashSet;
     use game_base::mapdef_06::DdraceTileNum;
@@ -46,6 +49,7 @@ pub mod character {
         hook::character_hook::{CharacterHook, Hook, HookedCharacters},
         player::player::{PlayerInfo, Players, SpectatorPlayer, SpectatorPlayers},
         pos::character_pos::{CharacterPos, CharacterPositionPlayfield},
+        race::character_race::{race_ticks_to_duration, CharacterRace},
         score::character_score::{CharacterScore, CharacterScores},
     };
     use crate::{
@@ -56,7 +60,9 @@ pub mod character {
             GameWorldPendingEvents, SimulationEventWorldEntityType, SimulationPipeCharacter,
             SimulationWorldEvents,
         },
+        spawns::GameSpawns,
         state::state::TICKS_PER_SECOND,
+        switches::switches::{SwitchType, Switches},
         types::types::GameOptions,
         weapons::definitions::weapon_def::Weapon,
     };
@@ -72,6 +78,8 @@ pub mod character {
     use super::player::player::Player;
 
     pub const TICKS_UNTIL_RECOIL_ENDED: GameTickType = 7;
+    /// How long freeze tiles freeze a character.
+    pub const FREEZE_SECONDS: GameTickType = 3;
 
     pub enum DamageTypes<'a> {
         Character(&'a CharacterId),
@@ -125,11 +133,38 @@ pub mod character {
 
         pub(crate) input: CharacterInput,
 
+        /// The number of the last tele checkpoint the character passed.
+        pub tele_checkpoint: u8,
+
+        /// The race timer, started by start tiles.
+        pub race: CharacterRace,
+
+        /// Deep frozen characters stay frozen until
+        /// they touch a deep unfreeze tile.
+        pub deep_frozen: bool,
+        /// Live frozen characters can neither move nor jump,
+        /// but can still hook.
+        pub live_frozen: bool,
+        /// A running freeze can only be refreshed after this cooldown.
+        pub freeze_refresh_in: GameTickCooldown,
+
         /// is timeout e.g. by a network disconnect.
         /// this is a hint, not a logic variable.
         pub is_timeout: bool,
     }
 
+    impl CharacterCore {
+        pub fn input(&self) -> &CharacterInput {
+            &self.input
+        }
+
+        /// Overwrites the last known input, e.g. for snapshots
+        /// that are not created by the simulation itself.
+        pub fn set_input(&mut self, input: CharacterInput) {
+            self.input = input;
+        }
+    }
+
     #[derive(Debug, Hiarc, Serialize, Deserialize, Clone)]
     pub struct CharacterReusableCore {
         pub core: CoreReusable,
@@ -496,6 +531,7 @@ pub mod character {
                 core.default_eye_reset_in = prev_core.default_eye_reset_in;
                 core.eye = prev_core.default_eye;
                 core.normal_eye_in = prev_core.normal_eye_in;
+                core.race = prev_core.race.new_run();
             }
             (core, reusable_core, pos)
         }
@@ -611,6 +647,113 @@ pub mod character {
             }
         }
 
+        pub fn is_frozen(&self) -> bool {
+            self.reusable_core
+                .debuffs
+                .contains_key(&CharacterDebuff::Freeze)
+        }
+
+        /// The remaining freeze time, `None` if the character
+        /// is not frozen or deep frozen.
+        pub fn freeze_remaining_time(&self) -> Option<Duration> {
+            if self.core.deep_frozen {
+                return None;
+            }
+            self.reusable_core
+                .debuffs
+                .get(&CharacterDebuff::Freeze)
+                .and_then(|debuff| debuff.remaining_tick.get())
+                .map(|ticks| Duration::from_millis(ticks.get() * 1000 / TICKS_PER_SECOND))
+        }
+
+        /// Freezes the character for the given amount of seconds.
+        ///
+        /// A running freeze is only refreshed if it was started at least a second ago
+        /// and would not be shortened by the new freeze.
+        pub fn freeze(&mut self, seconds: GameTickType) -> bool {
+            if seconds == 0 || self.core.core.is_super {
+                return false;
+            }
+            let ticks = seconds * TICKS_PER_SECOND;
+            if let Some(debuff) = self.reusable_core.debuffs.get(&CharacterDebuff::Freeze) {
+                if debuff
+                    .remaining_tick
+                    .get()
+                    .is_some_and(|remaining| remaining.get() > ticks)
+                    || self.core.freeze_refresh_in.is_some()
+                {
+                    return false;
+                }
+            }
+            let debuff = self
+                .reusable_core
+                .debuffs
+                .entry(CharacterDebuff::Freeze)
+                .or_insert_with(|| BuffProps {
+                    remaining_tick: 0.into(),
+                    interact_tick: 0.into(),
+                    interact_cursor_dir: vec2::default(),
+                    interact_val: 0.0,
+                });
+            debuff.remaining_tick = ticks.into();
+            self.core.freeze_refresh_in = TICKS_PER_SECOND.into();
+            true
+        }
+
+        pub fn unfreeze(&mut self) {
+            self.reusable_core.debuffs.remove(&CharacterDebuff::Freeze);
+            self.core.freeze_refresh_in = Default::default();
+        }
+
+        fn handle_freeze_tile(&mut self, index: u8) {
+            match DdraceTileNum::from_u8(index) {
+                Some(DdraceTileNum::Freeze) => {
+                    if !self.core.deep_frozen {
+                        self.freeze(FREEZE_SECONDS);
+                    }
+                }
+                Some(DdraceTileNum::Unfreeze) => {
+                    if !self.core.deep_frozen {
+                        self.unfreeze();
+                    }
+                }
+                Some(DdraceTileNum::DFreeze) => {
+                    self.core.deep_frozen = true;
+                }
+                Some(DdraceTileNum::DUnfreeze) => {
+                    self.core.deep_frozen = false;
+                }
+                Some(DdraceTileNum::LFreeze) => {
+                    self.core.live_frozen = true;
+                }
+                Some(DdraceTileNum::LUnfreeze) => {
+                    self.core.live_frozen = false;
+                }
+                _ => {}
+            }
+        }
+
+        /// The input that is used for the physics.
+        /// Frozen characters can neither move, jump nor hook,
+        /// live frozen characters can still hook.
+        fn physics_input(&mut self) -> CharacterInput {
+            let mut input = self.core.input;
+            if self.core.core.is_super {
+                return input;
+            }
+            let frozen = self.is_frozen();
+            if frozen || self.core.live_frozen {
+                input.state.dir.set(0);
+                input.state.jump.set(false);
+                self.core.core.queued_jumps = 0;
+            }
+            if frozen {
+                input.state.hook.set(false);
+                self.core.core.queued_hooks.clicked = 0;
+            }
+            input
+        }
+
         fn push_sound(&self, pos: vec2, ev: GameWorldEntitySoundEvent) {
             self.game_pending_events
                 .push_sound(Some(self.base.game_element_id), Some(pos), ev);
@@ -621,30 +764,193 @@ pub mod character {
                 .push_effect(Some(self.base.game_element_id), pos, ev);
         }
 
+        fn handle_tele(
+            &mut self,
+            tele: DdraceTileNum,
+            number: u8,
+            collision: &Collision,
+            spawns: &GameSpawns,
+        ) {
+            if self.core.core.is_super {
+                return;
+            }
+            let is_evil = matches!(
+                tele,
+                DdraceTileNum::TeleInEvil | DdraceTileNum::TeleCheckInEvil
+            );
+            let tele_out = match tele_target(
+                tele,
+                number,
+                self.core.tele_checkpoint,
+                collision,
+                self.pos.pos(),
+            ) {
+                Some(TeleTarget::Pos(tele_out)) => tele_out,
+                Some(TeleTarget::Spawn) => {
+                    // like ddnet, the character is moved to the spawn without dying
+                    let Some(spawn) = spawns.first_spawn(self.core.side) else {
+                        return;
+                    };
+                    self.pos.move_pos(spawn);
+                    self.core.core.vel = vec2::default();
+                    let hook = self.phased.hook_mut();
+                    hook.reset();
+                    hook.release_hooked_by();
+                    return;
+                }
+                None => return,
+            };
+
+            self.pos.move_pos(tele_out);
+            let hook = self.phased.hook_mut();
+            hook.reset();
+            if is_evil {
+                self.core.core.vel = vec2::default();
+                hook.release_hooked_by();
+            }
+        }
+
+        fn handle_race_tile(&mut self, index: u8) {
+            let time_checkpoints =
+                DdraceTileNum::TimeCheckpointFirst as u8..=DdraceTileNum::TimeCheckpointLast as u8;
+            if time_checkpoints.contains(&index) {
+                self.core
+                    .race
+                    .pass_checkpoint(index - DdraceTileNum::TimeCheckpointFirst as u8);
+            } else if index == DdraceTileNum::Start as u8 {
+                self.core.race.start();
+            } else if index == DdraceTileNum::Finish as u8 {
+                if let Some(ticks) = self.core.race.finish() {
+                    self.game_pending_events.push(GameWorldEvent::Notification(
+                        GameWorldNotificationEvent::Action(GameWorldAction::RaceFinish {
+                            character: self.base.game_element_id,
+                            finish_time: race_ticks_to_duration(ticks),
+                        }),
+                    ));
+                    self.simulation_events
+                        .push_world(SimulationEventWorldEntityType::Character {
+                            ev: CharacterEvent::RaceFinish {
+                                id: self.base.game_element_id,
+                                ticks,
+                            },
+                        });
+                }
+            }
+        }
+
         #[must_use]
-        fn handle_tiles(&mut self, old_pos: vec2, collision: &Collision) -> CharacterDamageResult {
+        fn handle_tiles(
+            &mut self,
+            old_pos: vec2,
+            collision: &Collision,
+            switches: &mut Switches,
+            spawns: &GameSpawns,
+        ) -> CharacterDamageResult {
             let mut res = CharacterDamageResult::None;
+            let mut tele = None;
+            let (mut on_add_time, mut on_subtract_time) = (false, false);
             let cur_pos = *self.pos.pos();
+
+            let is_switch_active = |number: u8| switches.is_active(number);
+            let move_restrictions = collision.get_move_restrictions(
+                Some(&is_switch_active as &dyn Fn(u8) -> bool),
+                &cur_pos,
+                18.0,
+            );
+            self.core.core.apply_move_restrictions(move_restrictions);
+
             collision.intersect_line_feedback(&old_pos, &cur_pos, |tile| match tile {
                 HitTile::Game(tile) => {
                     if tile.index == DdraceTileNum::Death as u8 {
                         self.die(None, GameWorldActionKillWeapon::World, Default::default());
                         res = CharacterDamageResult::Death;
                     }
+                    self.handle_race_tile(tile.index);
+                    self.handle_freeze_tile(tile.index);
                 }
                 HitTile::Front(tile) => {
                     if tile.index == DdraceTileNum::Death as u8 {
                         self.die(None, GameWorldActionKillWeapon::World, Default::default());
                         res = CharacterDamageResult::Death;
                     }
+                    self.handle_race_tile(tile.index);
+                    self.handle_freeze_tile(tile.index);
+                }
+                HitTile::Tele(tile) => match DdraceTileNum::from_u8(tile.base.index) {
+                    Some(DdraceTileNum::TeleCheck) => {
+                        self.core.tele_checkpoint = tile.number;
+                    }
+                    Some(
+                        ty @ (DdraceTileNum::TeleIn
+                        | DdraceTileNum::TeleInEvil
+                        | DdraceTileNum::TeleCheckIn
+                        | DdraceTileNum::TeleCheckInEvil),
+                    ) => {
+                        // only the first teleporter on the way is used
+                        if tele.is_none() {
+                            tele = Some((ty, tile.number));
+                        }
+                    }
+                    _ => {}
+                },
+                HitTile::Speedup(tile) => {
+                    if tile.base.index == DdraceTileNum::Boost as u8 && tile.force > 0 {
+                        self.core
+                            .core
+                            .speedup(tile.force, tile.max_speed, tile.angle);
+                    }
+                }
+                HitTile::Switch(tile) => {
+                    if let Some(ty) = SwitchType::from_tile(tile.base.index) {
+                        if !self.core.core.is_super {
+                            switches.activate(ty, tile.number, tile.delay);
+                        }
+                    }
+                    if switches.is_active(tile.number) {
+                        match DdraceTileNum::from_u8(tile.base.index) {
+                            // the delay is the freeze time in seconds
+                            Some(DdraceTileNum::Freeze) => {
+                                self.freeze(tile.delay as GameTickType);
+                            }
+                            Some(
+                                DdraceTileNum::DFreeze
+                                | DdraceTileNum::DUnfreeze
+                                | DdraceTileNum::LFreeze
+                                | DdraceTileNum::LUnfreeze,
+                            ) => {
+                                self.handle_freeze_tile(tile.base.index);
+                            }
+                            _ => {}
+                        }
+                    }
+                    // the delay are the minutes, the number the seconds
+                    let time_ticks = (tile.delay as GameTickType * 60
+                        + tile.number as GameTickType)
+                        * TICKS_PER_SECOND;
+                    if tile.base.index == DdraceTileNum::AddTime as u8 {
+                        if !self.core.race.on_add_time && !on_add_time {
+                            self.core.race.add_time(time_ticks);
+                        }
+                        on_add_time = true;
+                    } else if tile.base.index == DdraceTileNum::SubtractTime as u8 {
+                        if !self.core.race.on_subtract_time && !on_subtract_time {
+                            self.core.race.subtract_time(time_ticks);
+                        }
+                        on_subtract_time = true;
+                    }
                 }
-                HitTile::Tele(_) => {}
-                HitTile::Speedup(_) => {}
-                HitTile::Switch(_) => {}
                 HitTile::Tune(_) => {
                     // tune tiles are handled on the fly where needed
                 }
             });
+            self.core.race.on_add_time = on_add_time;
+            self.core.race.on_subtract_time = on_subtract_time;
+            if res == CharacterDamageResult::Death {
+                return res;
+            }
+            if let Some((ty, number)) = tele {
+                self.handle_tele(ty, number, collision, spawns);
+            }
             res
         }
 
@@ -1242,6 +1548,13 @@ pub mod character {
                 }
                 buff.remaining_tick.is_some()
             });
+            self.reusable_core.debuffs.retain_with_order(|_, debuff| {
+                debuff.remaining_tick.tick();
+                debuff.remaining_tick.is_some()
+            });
+            if self.core.deep_frozen {
+                self.freeze(FREEZE_SECONDS);
+            }
 
             self.handle_ninja(pipe);
         }
@@ -1430,6 +1743,8 @@ pub mod character {
                 self.core.last_dmg_angle = 0.0;
             }
             self.core.emoticon_tick.tick();
+            self.core.race.tick();
+            self.core.freeze_refresh_in.tick();
 
             self.handle_emoticon_queue();
         }
@@ -1458,9 +1773,11 @@ pub mod character {
             self.handle_weapon_switch(None, None);
 
             let old_pos = *self.pos.pos();
+            let input = self.physics_input();
             let mut core_pipe = CorePipe {
                 characters: pipe.characters,
-                input: &self.core.input,
+                input: &input,
+                switches: pipe.switches,
             };
             self.core.core.physics_tick(
                 &mut self.pos,
@@ -1480,7 +1797,7 @@ pub mod character {
                 return EntityTickResult::RemoveEntity;
             }
 
-            let tiles_res = self.handle_tiles(old_pos, pipe.collision);
+            let tiles_res = self.handle_tiles(old_pos, pipe.collision, pipe.switches, pipe.spawns);
             if matches!(tiles_res, CharacterDamageResult::Death) {
                 return EntityTickResult::RemoveEntity;
             }
@@ -1492,9 +1809,11 @@ pub mod character {
         }
 
         fn tick_deferred(&mut self, pipe: &mut SimulationPipeCharacter) -> EntityTickResult {
+            let input = self.physics_input();
             let mut core_pipe = CorePipe {
                 characters: pipe.characters,
-                input: &self.core.input,
+                input: &input,
+                switches: pipe.switches,
             };
             self.core
                 .core
@@ -1665,6 +1984,42 @@ pub mod character {
         }
     }
 
+    /// Where a teleporter moves a character to.
+    #[derive(Debug, PartialEq)]
+    pub(crate) enum TeleTarget {
+        Pos(vec2),
+        /// A checkpoint teleporter without a reachable checkpoint out,
+        /// the character goes back to a spawn.
+        Spawn,
+    }
+
+    /// The target of the teleporter `tele` with the given number, entered at `pos`
+    /// by a character that reached `tele_checkpoint` last.
+    pub(crate) fn tele_target(
+        tele: DdraceTileNum,
+        number: u8,
+        tele_checkpoint: u8,
+        collision: &Collision,
+        pos: &vec2,
+    ) -> Option<TeleTarget> {
+        match tele {
+            DdraceTileNum::TeleIn | DdraceTileNum::TeleInEvil => {
+                collision.tele_out(number, pos).map(TeleTarget::Pos)
+            }
+            DdraceTileNum::TeleCheckIn | DdraceTileNum::TeleCheckInEvil => {
+                // use the out of the last checkpoint, if it has none, try the previous ones
+                Some(
+                    (1..=tele_checkpoint)
+                        .rev()
+                        .find_map(|checkpoint| collision.tele_check_out(checkpoint, pos))
+                        .map(TeleTarget::Pos)
+                        .unwrap_or(TeleTarget::Spawn),
+                )
+            }
+            _ => None,
+        }
+    }
+
     pub fn lerp_core_pos(char1: &Character, char2: &Character, amount: f64) -> vec2 {
         lerp(char1.pos.pos(), char2.pos.pos(), amount as f32)
     }
@@ -1682,4 +2037,68 @@ pub mod character {
             None
         }
     }
+
+    #[cfg(test)]
+    mod test {
+        use game_base::mapdef_06::DdraceTileNum;
+        use math::math::vector::vec2;
+
+        use crate::collision::collision::Collision;
+
+        use super::{tele_target, TeleTarget};
+
+        /// The position the teleporter is entered at
+        const CENTER: vec2 = vec2 { x: 48.0, y: 48.0 };
+
+        fn tele_collision() -> Collision {
+            Collision::with_tele_outs(
+                [(1, vec![vec2::new(16.0, 16.0)])].into_iter().collect(),
+                [
+                    (1, vec![vec2::new(80.0, 16.0)]),
+                    (3, vec![vec2::new(80.0, 80.0)]),
+                ]
+                .into_iter()
+                .collect(),
+            )
+        }
+
+        #[test]
+        fn tele_in() {
+            let collision = tele_collision();
+            for tele in [DdraceTileNum::TeleIn, DdraceTileNum::TeleInEvil] {
+                assert_eq!(
+                    tele_target(tele, 1, 0, &collision, &CENTER),
+                    Some(TeleTarget::Pos(vec2::new(16.0, 16.0)))
+                );
+                // no out for this number
+                assert_eq!(tele_target(tele, 2, 0, &collision, &CENTER), None);
+            }
+        }
+
+        #[test]
+        fn tele_check_in() {
+            let collision = tele_collision();
+            for tele in [DdraceTileNum::TeleCheckIn, DdraceTileNum::TeleCheckInEvil] {
+                assert_eq!(
+                    tele_target(tele, 0, 3, &collision, &CENTER),
+                    Some(TeleTarget::Pos(vec2::new(80.0, 80.0)))
+                );
+                // checkpoint 2 has no out, falls back to checkpoint 1
+                assert_eq!(
+                    tele_target(tele, 0, 2, &collision, &CENTER),
+                    Some(TeleTarget::Pos(vec2::new(80.0, 16.0)))
+                );
+                // no checkpoint reached, back to spawn
+                assert_eq!(
+                    tele_target(tele, 0, 0, &collision, &CENTER),
+                    Some(TeleTarget::Spawn)
+                );
+            }
+            let collision = Collision::default();
+            assert_eq!(
+                tele_target(DdraceTileNum::TeleCheckIn, 0, 3, &collision, &CENTER),
+                Some(TeleTarget::Spawn)
+            );
+        }
+    }
 }
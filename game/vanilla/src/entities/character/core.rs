@@ -18,7 +18,7 @@ pub mod character_core {
     use game_base::mapdef_06::DdraceTileNum;
 
     use crate::{
-        collision::collision::{Collision, CollisionTile, CollisionTypes},
+        collision::collision::{CannotMove, Collision, CollisionTile, CollisionTypes},
         entities::character::{
             character::{Characters, CharactersView},
             hook::character_hook::{CharacterHook, Hook, HookState},
@@ -28,6 +28,7 @@ pub mod character_core {
             GameWorldPendingEvents, SimulationPipeCharactersGetter,
         },
         state::state::TICKS_PER_SECOND,
+        switches::switches::Switches,
         world::world::GameWorld,
     };
 
@@ -35,6 +36,7 @@ pub mod character_core {
         closest_point_on_line, distance, distance_squared, dot, length, mix, normalize,
         round_f64_to_int, round_to_int,
         vector::{dvec2, ivec2, vec2},
+        PI,
     };
 
     use pool::traits::Recyclable;
@@ -87,7 +89,7 @@ pub mod character_core {
         solo: bool,
         collision_disabled: bool,
         hook_hit_disabled: bool,
-        is_super: bool,
+        pub(crate) is_super: bool,
 
         move_restrictions: i32,
     }
@@ -95,6 +97,7 @@ pub mod character_core {
     pub struct CorePipe<'a> {
         pub(crate) characters: &'a mut dyn SimulationPipeCharactersGetter,
         pub(crate) input: &'a CharacterInput,
+        pub(crate) switches: &'a Switches,
     }
 
     impl CorePipe<'_> {
@@ -139,13 +142,6 @@ pub mod character_core {
         }
     }
 
-    enum CannotMove {
-        Left = 1 << 0,
-        Right = 1 << 1,
-        Up = 1 << 2,
-        Down = 1 << 3,
-    }
-
     pub const PHYSICAL_SIZE: f32 = 28.0;
     const fn physical_size() -> f32 {
         PHYSICAL_SIZE
@@ -176,6 +172,17 @@ pub mod character_core {
             vel
         }
 
+        /// Applies the move restrictions of the current position.
+        /// Moving down onto a stopper restores the jumps, like standing on ground.
+        pub(crate) fn apply_move_restrictions(&mut self, move_restrictions: i32) {
+            if self.vel.y > 0.0 && (move_restrictions & CannotMove::Down as i32) != 0 {
+                self.jumped = 0;
+                self.jumped_total = 0;
+            }
+            self.move_restrictions = move_restrictions;
+            self.vel = Self::clamp_vel(move_restrictions, &self.vel);
+        }
+
         fn saturated_add<T: AddAssign + PartialOrd + num::traits::Zero>(
             min_val: T,
             max_val: T,
@@ -227,7 +234,13 @@ pub mod character_core {
                     },
                 ..
             } = &pipe.input;
-            self.move_restrictions = 0; // TODO core.m_pCollision->GetMoveRestrictions(UseInput ? IsSwitchActiveCb : 0, this, core.m_Pos);
+            let switches = pipe.switches;
+            let is_switch_active = |number: u8| switches.is_active(number);
+            self.move_restrictions = collision.get_move_restrictions(
+                use_input.then_some(&is_switch_active as &dyn Fn(u8) -> bool),
+                pos.pos(),
+                18.0,
+            );
 
             // get ground state
             let grounded: bool = collision.check_pointf(
@@ -459,20 +472,23 @@ pub mod character_core {
                                 }
                                 *hook_pos = new_pos;
                             }
-                            CollisionTile::PlayerTele(_) => todo!(),
-                            CollisionTile::HookTele(_) => {
-                                if false
-                                /* TODO: && self.m_pTeleOuts && !self.m_pTeleOuts->empty() && !(*self.m_pTeleOuts)[teleNr - 1].empty()*/
-                                {
+                            CollisionTile::HookTele(number) => {
+                                if let Some(tele_out) = collision.tele_out(number, &new_pos) {
                                     hooked_char = None;
                                     self.new_hook = true;
-                                    let _random_out = 0; // TODO: self.m_pWorld->RandomOr0((*self.m_pTeleOuts)[teleNr - 1].size());
-                                    *hook_pos = vec2::default(); // TODO: (*self.m_pTeleOuts)[teleNr - 1][RandomOut] + TargetDirection * PhysicalSize() * 1.5f;
+                                    *hook_pos = tele_out + target_direction * physical_size() * 1.5;
                                     *hook_dir = target_direction;
                                     *hook_tele_base = *hook_pos;
+                                } else {
+                                    // a tele without any outs behaves like an unhookable tile
+                                    *hook_state = HookState::RetractStart;
+                                    *hook_pos = new_pos;
                                 }
                             }
-                            CollisionTile::WeaponTele(_) => todo!(),
+                            CollisionTile::PlayerTele(_) | CollisionTile::WeaponTele(_) => {
+                                // not part of the hook's collision types
+                                *hook_pos = new_pos;
+                            }
                         }
                     }
                 }
@@ -674,6 +690,67 @@ pub mod character_core {
             }
         }
 
+        /// The angle of a direction in DDNet's speedup convention,
+        /// in the range `[0, 2 * PI)`, counter-clockwise with y pointing down.
+        fn speedup_angle(dir: &vec2) -> f32 {
+            let angle = if dir.x > 0.0000001 {
+                -(dir.y / dir.x).atan()
+            } else if dir.x < 0.0000001 {
+                (dir.y / dir.x).atan() + PI
+            } else if dir.y > 0.0000001 {
+                PI / 2.0
+            } else {
+                -PI / 2.0
+            };
+            if angle < 0.0 {
+                2.0 * PI + angle
+            } else {
+                angle
+            }
+        }
+
+        /// Applies a speedup (boost) tile to the velocity.
+        ///
+        /// `force` and `max_speed` are the raw values of the tile,
+        /// `angle` is in degrees.
+        pub(crate) fn speedup(&mut self, force: u8, max_speed: u8, angle: i16) {
+            let angle = angle as f32 * (PI / 180.0);
+            let dir = vec2::new(angle.cos(), angle.sin());
+            let force = force as i32;
+            let mut max_speed = max_speed as i32;
+
+            if force == 255 && max_speed > 0 {
+                self.vel = dir * (max_speed / 5) as f32;
+                return;
+            }
+
+            let mut vel = self.vel;
+            if max_speed > 0 {
+                max_speed = max_speed.max(5);
+
+                let speeder_angle = Self::speedup_angle(&dir);
+                let tee_speed = length(&vel);
+                // the angle of a standing character is undefined, it has no influence anyway
+                let tee_vel_in_dir = if tee_speed > 0.0 {
+                    (speeder_angle - Self::speedup_angle(&vel)).cos() * tee_speed
+                } else {
+                    0.0
+                };
+                let speed_left = max_speed as f32 / 5.0 - tee_vel_in_dir;
+                if (speed_left as i32).abs() > force && speed_left > 0.0000001 {
+                    vel += dir * force as f32;
+                } else if (speed_left as i32).abs() > force {
+                    vel += dir * -force as f32;
+                } else {
+                    vel += dir * speed_left;
+                }
+            } else {
+                vel += dir * force as f32;
+            }
+
+            self.vel = Self::clamp_vel(self.move_restrictions, &vel);
+        }
+
         fn velocity_ramp(value: f32, start: f32, range: f32, curvature: f32) -> f32 {
             if value < start {
                 return 1.0;
@@ -863,4 +940,87 @@ pub mod character_core {
             }
         }
     }
+
+    #[cfg(test)]
+    mod test {
+        use math::math::vector::vec2;
+
+        use crate::collision::collision::CannotMove;
+
+        use super::Core;
+
+        fn assert_vel(core: &Core, x: f32, y: f32) {
+            assert!(
+                (core.vel.x - x).abs() < 0.001 && (core.vel.y - y).abs() < 0.001,
+                "expected ({x}, {y}), got {:?}",
+                core.vel
+            );
+        }
+
+        fn core_with_vel(x: f32, y: f32) -> Core {
+            Core {
+                vel: vec2::new(x, y),
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn speedup_without_max_speed() {
+            let mut core = core_with_vel(1.0, 1.0);
+            core.speedup(2, 0, 0);
+            assert_vel(&core, 3.0, 1.0);
+            core.speedup(2, 0, 90);
+            assert_vel(&core, 3.0, 3.0);
+            core.speedup(2, 0, 180);
+            assert_vel(&core, 1.0, 3.0);
+
+            // a force of 255 sets the velocity to the max speed
+            let mut core = core_with_vel(-3.0, 4.0);
+            core.speedup(255, 50, 0);
+            assert_vel(&core, 10.0, 0.0);
+        }
+
+        #[test]
+        fn speedup_max_speed() {
+            // accelerates until the max speed is reached
+            let mut core = core_with_vel(0.0, 0.0);
+            core.speedup(3, 20, 0);
+            assert_vel(&core, 3.0, 0.0);
+            core.speedup(3, 20, 0);
+            assert_vel(&core, 4.0, 0.0);
+            core.speedup(3, 20, 0);
+            assert_vel(&core, 4.0, 0.0);
+
+            // faster characters are slowed down by the force
+            let mut core = core_with_vel(10.0, 0.0);
+            core.speedup(3, 20, 0);
+            assert_vel(&core, 7.0, 0.0);
+
+            // the max speed is at least 1
+            let mut core = core_with_vel(0.0, 0.0);
+            core.speedup(3, 1, 0);
+            assert_vel(&core, 1.0, 0.0);
+        }
+
+        #[test]
+        fn speedup_angles() {
+            // moving against the speedup counts as negative speed
+            let mut core = core_with_vel(2.0, 0.0);
+            core.speedup(3, 20, 180);
+            assert_vel(&core, -1.0, 0.0);
+            core.speedup(3, 20, 180);
+            assert_vel(&core, -4.0, 0.0);
+
+            // the velocity perpendicular to the speedup is kept
+            let mut core = core_with_vel(0.0, 5.0);
+            core.speedup(3, 20, 0);
+            assert_vel(&core, 3.0, 5.0);
+
+            // move restrictions still apply
+            let mut core = core_with_vel(0.0, 0.0);
+            core.apply_move_restrictions(CannotMove::Right as i32);
+            core.speedup(3, 20, 0);
+            assert_vel(&core, 0.0, 0.0);
+        }
+    }
 }
@@ -127,6 +127,21 @@ pub mod character_hook {
             }
         }
 
+        /// Releases the hooks of all characters that currently hook the given character.
+        pub(super) fn release_hooked_by(&mut self, id: &CharacterId) {
+            let Some(char) = self.characters.get_mut(id) else {
+                return;
+            };
+
+            for hooked_by in std::mem::take(&mut char.hooked_by) {
+                let hooking_char = self.characters.get_mut(&hooked_by).unwrap();
+                hooking_char.hooked_char = None;
+                if let Hook::Active { .. } = hooking_char.hook {
+                    hooking_char.hook = Hook::WaitsForRelease;
+                }
+            }
+        }
+
         pub(super) fn get_hook(&self, id: &CharacterId) -> (Hook, Option<CharacterId>) {
             let char = self.characters.get(id).unwrap();
             (char.hook, char.hooked_char)
@@ -165,6 +180,20 @@ pub mod character_hook {
             self.hooked_players.add_or_set(self.id, hook, hooked_char)
         }
 
+        /// Retracts the own hook, e.g. after a teleport.
+        pub fn reset(&mut self) {
+            let hook = match self.hook() {
+                Hook::Active { .. } => Hook::WaitsForRelease,
+                hook => hook,
+            };
+            self.set(hook, None);
+        }
+
+        /// Releases the hooks of all characters hooking this character.
+        pub fn release_hooked_by(&mut self) {
+            self.hooked_players.release_hooked_by(&self.id);
+        }
+
         pub fn quantinize(&mut self) {
             let (mut hook, hooked_char) = self.get();
             if let Hook::Active {
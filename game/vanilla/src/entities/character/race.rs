@@ -0,0 +1,124 @@
+pub mod character_race {
+    use std::time::Duration;
+
+    use game_interface::types::game::GameTickType;
+    use hiarc::Hiarc;
+    use serde::{Deserialize, Serialize};
+
+    use crate::state::state::TICKS_PER_SECOND;
+
+    /// The amount of time checkpoint tiles
+    /// (`TimeCheckpointFirst..=TimeCheckpointLast`).
+    pub const NUM_TIME_CHECKPOINTS: usize = 25;
+
+    /// The race ticks of a single run at each time checkpoint.
+    pub type RaceCheckpointTicks = [Option<GameTickType>; NUM_TIME_CHECKPOINTS];
+
+    #[derive(Debug, Hiarc, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub enum RaceState {
+        /// The character did not pass a start tile yet.
+        #[default]
+        None,
+        Started,
+        Finished,
+    }
+
+    /// The race timer of a single character.
+    #[derive(Debug, Hiarc, Default, Clone, Copy, Serialize, Deserialize)]
+    pub struct CharacterRace {
+        pub state: RaceState,
+        /// The ticks passed since the race started,
+        /// including added or subtracted time.
+        pub ticks: GameTickType,
+        /// The last time checkpoint passed in the current run.
+        pub checkpoint: Option<u8>,
+        pub checkpoint_ticks: RaceCheckpointTicks,
+
+        /// The race ticks of the best finished run, if any.
+        pub best_ticks: Option<GameTickType>,
+        pub best_checkpoint_ticks: RaceCheckpointTicks,
+
+        /// Time tiles only apply once while a character touches them.
+        pub(crate) on_add_time: bool,
+        pub(crate) on_subtract_time: bool,
+    }
+
+    impl CharacterRace {
+        /// A new race timer that keeps the best run of this one.
+        pub fn new_run(&self) -> Self {
+            Self {
+                best_ticks: self.best_ticks,
+                best_checkpoint_ticks: self.best_checkpoint_ticks,
+                ..Default::default()
+            }
+        }
+
+        pub fn tick(&mut self) {
+            if self.state == RaceState::Started {
+                self.ticks += 1;
+            }
+        }
+
+        /// (Re-)starts the race timer.
+        pub fn start(&mut self) {
+            *self = Self {
+                state: RaceState::Started,
+                ..self.new_run()
+            };
+        }
+
+        /// Returns the race ticks, if the race was finished by this call.
+        pub fn finish(&mut self) -> Option<GameTickType> {
+            if self.state != RaceState::Started {
+                return None;
+            }
+            self.state = RaceState::Finished;
+            if self
+                .best_ticks
+                .is_none_or(|best_ticks| self.ticks < best_ticks)
+            {
+                self.best_ticks = Some(self.ticks);
+                self.best_checkpoint_ticks = self.checkpoint_ticks;
+            }
+            Some(self.ticks)
+        }
+
+        /// Only checkpoints after the last passed one are considered.
+        pub fn pass_checkpoint(&mut self, checkpoint: u8) {
+            if self.state != RaceState::Started
+                || checkpoint as usize >= NUM_TIME_CHECKPOINTS
+                || self.checkpoint.is_some_and(|last| checkpoint <= last)
+            {
+                return;
+            }
+            self.checkpoint = Some(checkpoint);
+            self.checkpoint_ticks[checkpoint as usize] = Some(self.ticks);
+        }
+
+        pub fn add_time(&mut self, ticks: GameTickType) {
+            if self.state == RaceState::Started {
+                self.ticks += ticks;
+            }
+        }
+
+        pub fn subtract_time(&mut self, ticks: GameTickType) {
+            if self.state == RaceState::Started {
+                self.ticks = self.ticks.saturating_sub(ticks);
+            }
+        }
+
+        /// The last passed checkpoint, the race ticks at that checkpoint and
+        /// the difference in ticks to the best run at that checkpoint.
+        pub fn split(&self) -> Option<(u8, GameTickType, Option<i64>)> {
+            let checkpoint = self.checkpoint?;
+            let ticks = self.checkpoint_ticks[checkpoint as usize]?;
+            let diff = self.best_checkpoint_ticks[checkpoint as usize]
+                .map(|best_ticks| ticks as i64 - best_ticks as i64);
+            Some((checkpoint, ticks, diff))
+        }
+    }
+
+    pub fn race_ticks_to_duration(ticks: GameTickType) -> Duration {
+        Duration::from_micros(ticks * 1_000_000 / TICKS_PER_SECOND)
+    }
+}
@@ -66,6 +66,10 @@ pub mod laser {
         pub can_hit_own: bool,
 
         pub side: Option<MatchSide>,
+
+        /// The laser went through a weapon teleporter
+        /// and continues at this position on the next evaluation.
+        pub tele_pos: Option<vec2>,
     }
 
     #[derive(Debug, Hiarc, Clone)]
@@ -113,6 +117,8 @@ pub mod laser {
                 can_hit_own,
 
                 side,
+
+                tele_pos: None,
             };
 
             Self {
@@ -274,6 +280,10 @@ pub mod laser {
                 return false;
             }
             //self.core.m_PrevPos = self.core.pos;
+            if let Some(tele_pos) = self.core.tele_pos.take() {
+                self.core.from = tele_pos;
+                self.core.pos = tele_pos;
+            }
             let mut col_tile = vec2::default();
 
             let mut to = self.core.pos + self.core.dir * self.core.energy;
@@ -286,7 +296,28 @@ pub mod laser {
                 CollisionTypes::SOLID | CollisionTypes::WEAPON_TELE,
             );
 
-            if !matches!(res, CollisionTile::None) {
+            let tele_out = match &res {
+                CollisionTile::WeaponTele(number) => pipe.collision.tele_out(*number, &col_tile),
+                _ => None,
+            };
+
+            if let Some(tele_out) = tele_out {
+                let cur_pos = self.core.pos;
+                if !self.hit_character(pipe, &cur_pos, &col_tile) {
+                    let core = &mut self.core;
+                    core.from = core.pos;
+                    core.pos = col_tile;
+                    core.tele_pos = Some(tele_out);
+
+                    let tuning = pipe.collision.get_tune_at(&core.pos);
+                    core.energy -= distance(&core.from, &core.pos) + tuning.laser_bounce_cost;
+
+                    core.bounces += 1;
+                    if core.bounces > tuning.laser_bounce_num as usize {
+                        core.energy = -1.0;
+                    }
+                }
+            } else if !matches!(res, CollisionTile::None) {
                 let cur_pos = self.core.pos;
                 if !self.hit_character(pipe, &cur_pos, &to) {
                     let core = &mut self.core;
@@ -244,6 +244,12 @@ pub mod projectile {
 
             self.core.life_span -= 1;
 
+            // weapon teleporters without an out are treated like solid tiles
+            let tele_out = match &collide {
+                CollisionTile::WeaponTele(number) => pipe.collision.tele_out(*number, &cur_pos),
+                _ => None,
+            };
+
             let intersection = GameWorld::intersect_character_on_line(
                 pipe.field,
                 pipe.characters_helper.get_characters_except_owner(),
@@ -253,7 +259,7 @@ pub mod projectile {
             );
 
             let res = if intersection.is_some()
-                || !matches!(collide, CollisionTile::None)
+                || (!matches!(collide, CollisionTile::None) && tele_out.is_none())
                 || self.core.life_span < 0
                 || Entity::<ProjectileId>::outside_of_playfield(&cur_pos, pipe.collision)
             {
@@ -298,7 +304,7 @@ pub mod projectile {
             } else {
                 EntityTickResult::None
             };
-            self.core.pos = cur_pos;
+            self.core.pos = tele_out.unwrap_or(cur_pos);
             res
         }
 
@@ -2,7 +2,10 @@ pub mod events {
     use game_interface::{
         events::GameWorldActionKillWeapon,
         types::{
-            flag::FlagType, game::GameTickCooldown, id_types::CharacterId, pickup::PickupType,
+            flag::FlagType,
+            game::{GameTickCooldown, GameTickType},
+            id_types::CharacterId,
+            pickup::PickupType,
             weapons::WeaponType,
         },
     };
@@ -75,6 +78,11 @@ pub mod events {
             killer_id: Option<CharacterId>,
             weapon: GameWorldActionKillWeapon,
         },
+        /// The character finished the race, `ticks` is the race time.
+        RaceFinish {
+            id: CharacterId,
+            ticks: GameTickType,
+        },
     }
 
     #[derive(Debug, Hiarc, Clone, Copy, Serialize, Deserialize)]
@@ -18,6 +18,7 @@ pub mod spawns;
 pub mod sql;
 pub mod stage;
 pub mod state;
+pub mod switches;
 pub mod types;
 pub mod weapons;
 pub mod world;
@@ -25,6 +26,7 @@ pub mod world;
 #[cfg(test)]
 mod test {
     use std::{
+        borrow::Cow,
         sync::Arc,
         time::{Duration, Instant},
     };
@@ -33,6 +35,7 @@ mod test {
     use base_io::{io::create_runtime, runtime::IoRuntime};
     use game_database::dummy::DummyDb;
     use game_interface::{
+        ghosts::GhostResultPlayer,
         interface::{GameStateCreate, GameStateCreateOptions, GameStateInterface},
         types::{
             character_info::NetworkCharacterInfo,
@@ -40,12 +43,16 @@ mod test {
             input::{cursor::CharacterInputCursor, CharacterInput, CharacterInputInfo},
             network_stats::PlayerNetworkStats,
             player_info::{PlayerClientInfo, PlayerUniqueId},
+            snapshot::SnapshotClientInfo,
         },
     };
     use math::math::{vector::dvec2, Rng};
-    use pool::pool::Pool;
+    use pool::{mt_datatypes::PoolCow as MtPoolCow, pool::Pool};
 
-    use crate::{config::ConfigVanilla, state::state::GameState};
+    use crate::{
+        config::ConfigVanilla, entities::character::race::character_race::RaceState,
+        snapshot::snapshot::Snapshot, state::state::GameState,
+    };
 
     #[test]
     fn benchmark() {
@@ -149,4 +156,80 @@ mod test {
         bench_inner();
         bench_inner();
     }
+
+    fn decode_snapshot(snapshot: &[u8]) -> Snapshot {
+        bincode::serde::decode_from_slice(snapshot, bincode::config::standard())
+            .unwrap()
+            .0
+    }
+
+    fn join(game: &mut GameState) -> PlayerId {
+        game.player_join(&PlayerClientInfo {
+            info: NetworkCharacterInfo::explicit_default(),
+            id: 0,
+            unique_identifier: PlayerUniqueId::Account(0),
+            initial_network_stats: PlayerNetworkStats::default(),
+        })
+    }
+
+    #[test]
+    fn ghosts_of_recorded_snapshots() {
+        let file = include_bytes!("../../../data/map/maps/ctf1.twmap");
+        let (mut game, _) = GameState::new(
+            file.to_vec(),
+            "ctf1".try_into().unwrap(),
+            Default::default(),
+            IoRuntime::new(create_runtime()),
+            Arc::new(DummyDb),
+        )
+        .unwrap();
+        let starting = join(&mut game);
+        let racing = join(&mut game);
+        game.tick(Default::default());
+
+        // nobody races on ctf1, but every character gets a ghost
+        let recorded = game.snapshot_for(SnapshotClientInfo::Everything);
+        let ghosts = game.build_ghosts_from_snapshot(&recorded);
+        assert_eq!(ghosts.players.len(), 2);
+        for (id, ghost) in ghosts.players.iter() {
+            let GhostResultPlayer::GhostInactive { ghost_snapshot } = ghost else {
+                panic!("no character races on ctf1: {ghost:?}");
+            };
+            // a ghost only contains the character itself
+            let ghost = decode_snapshot(ghost_snapshot);
+            assert_eq!(ghost.stages.len(), 1);
+            let stage = ghost.stages.values().next().unwrap();
+            assert_eq!(stage.world.characters.keys().collect::<Vec<_>>(), [id]);
+        }
+
+        let mut snapshot = decode_snapshot(&recorded);
+        for stage in snapshot.stages.values_mut() {
+            for (id, character) in stage.world.characters.iter_mut() {
+                character.core.race.state = RaceState::Started;
+                character.core.race.ticks = if *id == starting { 0 } else { 50 };
+            }
+        }
+        let mut recorded = Vec::new();
+        bincode::serde::encode_into_std_write(
+            &snapshot,
+            &mut recorded,
+            bincode::config::standard(),
+        )
+        .unwrap();
+        let ghosts =
+            game.build_ghosts_from_snapshot(&MtPoolCow::from_without_pool(Cow::Owned(recorded)));
+        assert!(matches!(
+            ghosts.players.get(&starting),
+            Some(GhostResultPlayer::GhostRecordStarted { .. })
+        ));
+        assert!(matches!(
+            ghosts.players.get(&racing),
+            Some(GhostResultPlayer::GhostRecordActive { .. })
+        ));
+
+        // broken snapshots have no ghosts
+        let ghosts =
+            game.build_ghosts_from_snapshot(&MtPoolCow::from_without_pool(Cow::Owned(vec![1])));
+        assert!(ghosts.players.is_empty());
+    }
 }
@@ -61,7 +61,9 @@ pub mod match_manager {
                     match ev {
                         SimulationWorldEvent::Entity(entity_ev) => match &entity_ev.ev {
                             SimulationEventWorldEntityType::Character { ev, .. } => {
-                                let CharacterEvent::Despawn { killer_id, id: victim_id, .. } = ev;
+                                let CharacterEvent::Despawn { killer_id, id: victim_id, .. } = ev else {
+                                    return;
+                                };
                                 if let Some(char) = killer_id.and_then(|killer_id| world.characters.get_mut(&killer_id)) {
                                     if Some(*victim_id) == *killer_id {
                                         char.score.set(char.score.get() - 1);
@@ -25,6 +25,8 @@ pub mod simulation_pipe {
     use crate::events::events::{
         CharacterTickEvent, FlagEvent, LaserEvent, PickupEvent, ProjectileEvent,
     };
+    use crate::spawns::GameSpawns;
+    use crate::switches::switches::Switches;
     use crate::world::world::GameObjectsWorld;
     use crate::{
         entities::character::character::Characters,
@@ -461,6 +463,8 @@ pub mod simulation_pipe {
         pub entity_events: &'a mut Vec<CharacterTickEvent>,
 
         pub collision: &'a Collision,
+        pub switches: &'a mut Switches,
+        pub spawns: &'a GameSpawns,
     }
 
     impl<'a> SimulationPipeCharacter<'a> {
@@ -468,11 +472,15 @@ pub mod simulation_pipe {
             characters: &'a mut dyn SimulationPipeCharactersGetter,
             entity_events: &'a mut Vec<CharacterTickEvent>,
             collision: &'a Collision,
+            switches: &'a mut Switches,
+            spawns: &'a GameSpawns,
         ) -> Self {
             Self {
                 characters,
                 entity_events,
                 collision,
+                switches,
+                spawns,
             }
         }
     }
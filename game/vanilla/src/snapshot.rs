@@ -9,6 +9,7 @@ pub mod snapshot {
     use game_interface::{
         client_commands::MAX_TEAM_NAME_LEN,
         events::GameWorldActionKillWeapon,
+        ghosts::{GhostResult, GhostResultPlayer},
         pooling::GamePooling,
         types::{
             emoticons::EnumCount,
@@ -38,6 +39,7 @@ pub mod snapshot {
                 player::player::{
                     PlayerCharacterInfo, PlayerInfo, Players, SpectatorPlayer, SpectatorPlayers,
                 },
+                race::character_race::RaceState,
             },
             entity::entity::{DropMode, EntityInterface},
             flag::flag::{Flag, FlagCore, FlagReusableCore, Flags, PoolFlagReusableCore},
@@ -52,6 +54,7 @@ pub mod snapshot {
         simulation_pipe::simulation_pipe::GamePendingEvents,
         spawns::GameSpawns,
         stage::stage::Stages,
+        switches::switches::Switches,
         types::types::GameOptions,
         world::world::{GameObjectWorld, WorldPool},
     };
@@ -63,7 +66,7 @@ pub mod snapshot {
         state::state::GameState,
     };
     use pool::{
-        datatypes::{PoolFxLinkedHashMap, PoolVec},
+        datatypes::{PoolCow, PoolFxHashMap, PoolFxLinkedHashMap, PoolVec},
         pool::Pool,
     };
     use serde::{Deserialize, Serialize};
@@ -173,6 +176,8 @@ pub mod snapshot {
         pub blue_flags: SnapshotFlags,
 
         pub inactive_objects: SnapshotInactiveObject,
+
+        pub switches: Switches,
     }
 
     impl SnapshotWorld {
@@ -198,6 +203,7 @@ pub mod snapshot {
                     ],
                     ninjas: world_pool.inactive_objects.new(),
                 },
+                switches: Default::default(),
             }
         }
     }
@@ -496,6 +502,7 @@ pub mod snapshot {
                                 weapons,
                                 ninjas,
                             },
+                            switches: stage.world.switches.clone(),
                         },
                         match_manager: SnapshotMatchManager::new(stage.match_manager.game_match),
                         game_el_id: stage.game_element_id,
@@ -576,6 +583,75 @@ pub mod snapshot {
             res
         }
 
+        /// Splits a snapshot into one ghost snapshot per player character.
+        /// A ghost snapshot only contains the stage of the character
+        /// with the character itself.
+        ///
+        /// The ghost recording is active while the character's race is running.
+        pub(crate) fn build_ghosts(&self, mut snapshot: Snapshot) -> GhostResult {
+            let mut players = PoolFxHashMap::new_without_pool();
+            for (stage_id, mut stage) in snapshot.stages.drain() {
+                for (character_id, character) in stage.world.characters.drain() {
+                    if !matches!(character.ty, SnapshotCharacterPlayerTy::Player(_)) {
+                        continue;
+                    }
+                    let race = character.core.race;
+
+                    let mut ghost = Snapshot::new(
+                        &self.snapshot_pool,
+                        snapshot.id_generator_id,
+                        None,
+                        snapshot.global_tune_zone,
+                    );
+                    let mut world = SnapshotWorld::new(&self.world_pool);
+                    world.characters.insert(character_id, character);
+                    world.switches.copy_from(&stage.world.switches);
+                    ghost.stages.insert(
+                        stage_id,
+                        SnapshotStage {
+                            world,
+                            match_manager: SnapshotMatchManager::new(
+                                stage.match_manager.game_match,
+                            ),
+                            game_el_id: stage_id,
+                            stage_name: {
+                                let mut name = self.snapshot_pool.string_pool.new();
+                                (*name).clone_from(&stage.stage_name);
+                                name
+                            },
+                            stage_color: stage.stage_color,
+                        },
+                    );
+
+                    let mut ghost_snapshot = Vec::new();
+                    bincode::serde::encode_into_std_write(
+                        &ghost,
+                        &mut ghost_snapshot,
+                        bincode::config::standard(),
+                    )
+                    .unwrap();
+                    let ghost_snapshot = PoolCow::from_without_pool(ghost_snapshot.into());
+
+                    players.insert(
+                        character_id,
+                        match race.state {
+                            // the race (re-)started in this tick
+                            RaceState::Started if race.ticks == 0 => {
+                                GhostResultPlayer::GhostRecordStarted { ghost_snapshot }
+                            }
+                            RaceState::Started => {
+                                GhostResultPlayer::GhostRecordActive { ghost_snapshot }
+                            }
+                            RaceState::None | RaceState::Finished => {
+                                GhostResultPlayer::GhostInactive { ghost_snapshot }
+                            }
+                        },
+                    );
+                }
+            }
+            GhostResult { players }
+        }
+
         pub(crate) fn convert_to_game_stages(
             mut snap_stages: PoolFxLinkedHashMap<StageId, SnapshotStage>,
             stages: &mut Stages,
@@ -946,6 +1022,11 @@ pub mod snapshot {
                     .pickups
                     .ninjas
                     .clone_from(&snap_stage.world.inactive_objects.ninjas);
+
+                state_stage
+                    .world
+                    .switches
+                    .copy_from(&snap_stage.world.switches);
             });
         }
 
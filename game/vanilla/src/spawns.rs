@@ -1,3 +1,4 @@
+use game_interface::types::render::game::game_match::MatchSide;
 use hiarc::Hiarc;
 use math::math::vector::vec2;
 
@@ -7,3 +8,56 @@ pub struct GameSpawns {
     pub spawns_red: Vec<vec2>,
     pub spawns_blue: Vec<vec2>,
 }
+
+impl GameSpawns {
+    /// The first spawn of the given side, otherwise the first spawn of any side.
+    ///
+    /// Unlike the spawn selection of the world, this does not depend on
+    /// the positions of other characters.
+    pub fn first_spawn(&self, side: Option<MatchSide>) -> Option<vec2> {
+        let side_spawns = match side {
+            Some(MatchSide::Red) => self.spawns_red.as_slice(),
+            Some(MatchSide::Blue) => self.spawns_blue.as_slice(),
+            None => &[],
+        };
+        side_spawns
+            .iter()
+            .chain(self.spawns.iter())
+            .chain(self.spawns_red.iter())
+            .chain(self.spawns_blue.iter())
+            .next()
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use game_interface::types::render::game::game_match::MatchSide;
+    use math::math::vector::vec2;
+
+    use super::GameSpawns;
+
+    #[test]
+    fn first_spawn_of_side() {
+        let spawns = GameSpawns {
+            spawns: vec![vec2::new(16.0, 16.0)],
+            spawns_red: vec![vec2::new(32.0, 32.0)],
+            spawns_blue: Vec::new(),
+        };
+        assert_eq!(spawns.first_spawn(None), Some(vec2::new(16.0, 16.0)));
+        assert_eq!(
+            spawns.first_spawn(Some(MatchSide::Red)),
+            Some(vec2::new(32.0, 32.0))
+        );
+        assert_eq!(
+            spawns.first_spawn(Some(MatchSide::Blue)),
+            Some(vec2::new(16.0, 16.0))
+        );
+        let spawns = GameSpawns {
+            spawns: Vec::new(),
+            spawns_red: Vec::new(),
+            spawns_blue: Vec::new(),
+        };
+        assert_eq!(spawns.first_spawn(None), None);
+    }
+}
@@ -3,8 +3,9 @@ use std::sync::Arc;
 use anyhow::anyhow;
 use ddnet_accounts_types::account_id::AccountId;
 use game_database::{
-    statement::{Statement, StatementBuilder},
+    statement::{Statement, StatementBuilder, StatementSql},
     traits::{DbInterface, DbKind},
+    types::ExecuteResult,
     StatementArgs,
 };
 use game_interface::types::player_info::Hash;
@@ -19,7 +20,7 @@ type StatementResult = ();
 
 #[derive(Debug, Clone, Copy)]
 pub struct StatementAffected {
-    pub rewrite_saves: u64,
+    pub rewrite_saves: ExecuteResult,
 }
 
 #[derive(Clone)]
@@ -30,13 +31,11 @@ pub struct AccountCreated {
 impl AccountCreated {
     pub async fn new(db: Arc<dyn DbInterface>, account_db: Option<DbKind>) -> anyhow::Result<Self> {
         let kind = account_db.ok_or_else(|| anyhow!("No account db specified"))?;
-        let builder = StatementBuilder::<_, StatementArg, StatementResult>::new(
+        let builder = StatementBuilder::<_, StatementArg, StatementResult>::new_per_kind(
             kind,
-            if matches!(kind, DbKind::MySql(_)) {
-                include_str!("mysql/account_created/rewrite_saves.sql")
-            } else {
-                include_str!("sqlite/account_created/rewrite_saves.sql")
-            },
+            StatementSql::new(include_str!("sqlite/account_created/rewrite_saves.sql"))
+                .with_mysql(include_str!("mysql/account_created/rewrite_saves.sql"))
+                .with_postgres(include_str!("postgres/account_created/rewrite_saves.sql")),
             |arg| vec![arg.account_id, arg.cert_hash],
         );
 
@@ -59,7 +58,14 @@ impl AccountCreated {
                 cert_hash: cert_hash.to_vec(),
             })
             .await
-            .map_err(|err| (err, StatementAffected { rewrite_saves: 0 }))?;
+            .map_err(|err| {
+                (
+                    err,
+                    StatementAffected {
+                        rewrite_saves: ExecuteResult::Executed(0),
+                    },
+                )
+            })?;
 
         Ok(StatementAffected { rewrite_saves })
     }
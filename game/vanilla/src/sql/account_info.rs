@@ -3,7 +3,7 @@ use std::sync::Arc;
 use anyhow::anyhow;
 use ddnet_accounts_types::account_id::AccountId;
 use game_database::{
-    statement::{Statement, StatementBuilder},
+    statement::{Statement, StatementBuilder, StatementSql},
     traits::{DbInterface, DbKind},
     types::UnixUtcTimestamp,
     StatementArgs, StatementResult,
@@ -27,9 +27,10 @@ pub struct AccountInfo(Arc<Statement<StatementArg, StatementResult>>);
 impl AccountInfo {
     pub async fn new(db: Arc<dyn DbInterface>, account_db: Option<DbKind>) -> anyhow::Result<Self> {
         let kind = account_db.ok_or_else(|| anyhow!("No account db specified"))?;
-        let builder = StatementBuilder::<_, StatementArg, StatementResult>::new(
+        let builder = StatementBuilder::<_, StatementArg, StatementResult>::new_per_kind(
             kind,
-            include_str!("generic/account_info/account_info.sql"),
+            StatementSql::new(include_str!("generic/account_info/account_info.sql"))
+                .with_postgres(include_str!("postgres/account_info/account_info.sql")),
             |arg| vec![arg.account_id],
         );
 
@@ -0,0 +1,4 @@
+INSERT INTO
+    race_finish (map_name, player_name, user_hash, finish_time)
+VALUES
+    (?, ?, ?, ?);
@@ -0,0 +1,34 @@
+SELECT
+    best.finish_time,
+    best.finish_count,
+    (
+        SELECT
+            COUNT(*)
+        FROM
+            (
+                SELECT
+                    MIN(other_finish.finish_time) AS finish_time
+                FROM
+                    race_finish AS other_finish
+                WHERE
+                    other_finish.map_name = ?
+                GROUP BY
+                    other_finish.user_id,
+                    other_finish.user_hash
+            ) AS other_best
+        WHERE
+            other_best.finish_time < best.finish_time
+    ) + 1 AS finish_rank
+FROM
+    (
+        SELECT
+            MIN(race_finish.finish_time) AS finish_time,
+            COUNT(*) AS finish_count
+        FROM
+            race_finish
+        WHERE
+            race_finish.map_name = ?
+            AND race_finish.user_hash = ?
+        GROUP BY
+            race_finish.user_hash
+    ) AS best;
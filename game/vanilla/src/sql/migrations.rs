@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use game_database::{
+    migration::Migrations,
+    statement::StatementSql,
+    traits::{DbInterface, DbKind, DbKindExtra},
+};
+
+/// The schema of the vanilla mod.
+///
+/// Existing migrations must never be modified, schema changes
+/// are always added as a new migration.
+pub fn migrations(db: &Arc<dyn DbInterface>) -> Migrations {
+    let kinds = db.kinds();
+    let kinds = [
+        DbKind::MySql(DbKindExtra::Main),
+        DbKind::Sqlite(DbKindExtra::Main),
+        DbKind::Postgres(DbKindExtra::Main),
+    ]
+    .into_iter()
+    .filter(|kind| kinds.contains(kind));
+
+    // the tables might already exist, if they were created
+    // before the mod used migrations.
+    Migrations::new("game-server-vanilla", kinds)
+        .with(
+            1,
+            "user_save",
+            StatementSql::new(include_str!("migrations/0001_user_save/mysql/up.sql"))
+                .with_sqlite(include_str!("migrations/0001_user_save/sqlite/up.sql"))
+                .with_postgres(include_str!("migrations/0001_user_save/postgres/up.sql")),
+            Some(StatementSql::new(include_str!(
+                "migrations/0001_user_save/down.sql"
+            ))),
+        )
+        .with(
+            2,
+            "race_finish",
+            StatementSql::new(include_str!("migrations/0002_race_finish/mysql/up.sql"))
+                .with_sqlite(include_str!("migrations/0002_race_finish/sqlite/up.sql"))
+                .with_postgres(include_str!("migrations/0002_race_finish/postgres/up.sql")),
+            Some(StatementSql::new(include_str!(
+                "migrations/0002_race_finish/down.sql"
+            ))),
+        )
+}
+
+pub async fn migrate(db: Arc<dyn DbInterface>) -> anyhow::Result<()> {
+    db.migrate(migrations(&db)).await
+}
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS user_save (
+    id BIGSERIAL,
+    user_id BIGINT UNIQUE,
+    user_hash BYTEA UNIQUE,
+    score_laser_kills BIGINT NOT NULL DEFAULT 0,
+    score_deaths BIGINT NOT NULL DEFAULT 0,
+    score_hits BIGINT NOT NULL DEFAULT 0,
+    score_teamkills BIGINT NOT NULL DEFAULT 0,
+    score_suicides BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY(id)
+);
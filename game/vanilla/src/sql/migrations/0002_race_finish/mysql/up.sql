@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS race_finish (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    map_name VARCHAR(64) NOT NULL,
+    player_name VARCHAR(32) NOT NULL,
+    user_id BIGINT,
+    user_hash BINARY(32),
+    finish_time DOUBLE NOT NULL,
+    create_time DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id),
+    KEY(map_name, finish_time)
+);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS race_finish (
+    id BIGSERIAL,
+    map_name VARCHAR(64) NOT NULL,
+    player_name VARCHAR(32) NOT NULL,
+    user_id BIGINT,
+    user_hash BYTEA,
+    finish_time DOUBLE PRECISION NOT NULL,
+    create_time TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(id)
+);
+
+CREATE INDEX IF NOT EXISTS race_finish_map_name_finish_time ON race_finish (map_name, finish_time);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS race_finish (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    map_name TEXT NOT NULL,
+    player_name TEXT NOT NULL,
+    user_id INTEGER,
+    user_hash BINARY(32),
+    finish_time REAL NOT NULL,
+    create_time DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
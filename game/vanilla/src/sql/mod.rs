@@ -1,3 +1,5 @@
 pub mod account_created;
 pub mod account_info;
-pub mod save;
+pub mod migrations;
+pub mod race;
+pub mod stats;
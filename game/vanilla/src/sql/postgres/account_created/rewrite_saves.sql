@@ -0,0 +1,7 @@
+UPDATE
+    user_save
+SET
+    user_id = $1
+WHERE
+    user_save.user_hash = $2
+    AND user_save.user_id IS NULL;
@@ -0,0 +1,8 @@
+SELECT
+    "user".id,
+    "user".name,
+    "user".create_time
+FROM
+    "user"
+WHERE
+    "user".account_id = $1;
@@ -0,0 +1,22 @@
+SELECT
+    COALESCE("user".name, best.player_name) AS player_name,
+    best.finish_time
+FROM
+    (
+        SELECT
+            race_finish.user_id,
+            MAX(race_finish.player_name) AS player_name,
+            MIN(race_finish.finish_time) AS finish_time
+        FROM
+            race_finish
+        WHERE
+            race_finish.map_name = $1
+        GROUP BY
+            race_finish.user_id,
+            race_finish.user_hash
+    ) AS best
+    LEFT JOIN "user" ON "user".account_id = best.user_id
+ORDER BY
+    best.finish_time ASC
+LIMIT
+    $2;
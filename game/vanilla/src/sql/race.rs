@@ -0,0 +1,116 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::anyhow;
+use ddnet_accounts_types::account_id::AccountId;
+use game_database::{
+    statement::{Statement, StatementBuilder, StatementSql},
+    traits::{DbInterface, DbKind, DbKindExtra},
+    StatementArgs,
+};
+use game_interface::types::player_info::PlayerUniqueId;
+
+#[derive(Debug, StatementArgs)]
+struct StatementArgAccount {
+    map_name: String,
+    player_name: String,
+    account_id: AccountId,
+    finish_time: f64,
+}
+
+#[derive(Debug, StatementArgs)]
+struct StatementArgHash {
+    map_name: String,
+    player_name: String,
+    cert_hash: Vec<u8>,
+    finish_time: f64,
+}
+
+/// Stores finished races of players.
+#[derive(Clone)]
+pub struct RaceFinishes {
+    insert_account: Arc<Statement<StatementArgAccount, ()>>,
+    insert_hash: Arc<Statement<StatementArgHash, ()>>,
+}
+
+impl RaceFinishes {
+    pub async fn new(db: Arc<dyn DbInterface>) -> anyhow::Result<Self> {
+        let kinds = db.kinds();
+        let kind = if kinds.contains(&DbKind::MySql(DbKindExtra::Main)) {
+            DbKind::MySql(DbKindExtra::Main)
+        } else if kinds.contains(&DbKind::Sqlite(DbKindExtra::Main)) {
+            DbKind::Sqlite(DbKindExtra::Main)
+        } else if kinds.contains(&DbKind::Postgres(DbKindExtra::Main)) {
+            DbKind::Postgres(DbKindExtra::Main)
+        } else {
+            return Err(anyhow!("No main database for race finishes found"));
+        };
+
+        let builder = StatementBuilder::<_, StatementArgAccount, ()>::new_per_kind(
+            kind,
+            StatementSql::new(include_str!("generic/race/insert_finish_account.sql"))
+                .with_postgres(include_str!("postgres/race/insert_finish_account.sql")),
+            |arg| {
+                vec![
+                    arg.map_name,
+                    arg.player_name,
+                    arg.account_id,
+                    arg.finish_time,
+                ]
+            },
+        );
+        let insert_account = Arc::new(Statement::new(db.clone(), builder).await?);
+
+        let builder = StatementBuilder::<_, StatementArgHash, ()>::new_per_kind(
+            kind,
+            StatementSql::new(include_str!("generic/race/insert_finish_hash.sql"))
+                .with_postgres(include_str!("postgres/race/insert_finish_hash.sql")),
+            |arg| {
+                vec![
+                    arg.map_name,
+                    arg.player_name,
+                    arg.cert_hash,
+                    arg.finish_time,
+                ]
+            },
+        );
+        let insert_hash = Arc::new(Statement::new(db.clone(), builder).await?);
+
+        Ok(Self {
+            insert_account,
+            insert_hash,
+        })
+    }
+
+    pub async fn insert(
+        &self,
+        map_name: String,
+        player_name: String,
+        unique_identifier: PlayerUniqueId,
+        finish_time: Duration,
+    ) -> anyhow::Result<()> {
+        let finish_time = finish_time.as_secs_f64();
+        match unique_identifier {
+            PlayerUniqueId::Account(account_id) => {
+                self.insert_account
+                    .execute(StatementArgAccount {
+                        map_name,
+                        player_name,
+                        account_id,
+                        finish_time,
+                    })
+                    .await?;
+            }
+            PlayerUniqueId::CertFingerprint(cert_hash) => {
+                self.insert_hash
+                    .execute(StatementArgHash {
+                        map_name,
+                        player_name,
+                        cert_hash: cert_hash.to_vec(),
+                        finish_time,
+                    })
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -4,4 +4,4 @@ SET
     user_id = ?
 WHERE
     user_save.user_hash = ?
-    AND user_save.user_id = NULL;
+    AND user_save.user_id IS NULL;
@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use ddnet_accounts_types::account_id::AccountId;
+use game_database::{
+    statement::{Statement, StatementBuilder, StatementSql},
+    traits::{DbInterface, DbKind, DbKindExtra},
+    StatementArgs, StatementResult,
+};
+use game_interface::types::player_info::PlayerUniqueId;
+
+#[derive(Debug, StatementArgs)]
+struct StatementArgAccount {
+    map_name: String,
+    account_id: AccountId,
+}
+
+#[derive(Debug, StatementArgs)]
+struct StatementArgHash {
+    map_name: String,
+    cert_hash: Vec<u8>,
+}
+
+#[derive(Debug, StatementArgs)]
+struct StatementArgTop {
+    map_name: String,
+    limit: i64,
+}
+
+#[derive(Debug, StatementResult)]
+pub struct StatementRankResult {
+    /// The best finish time in seconds.
+    pub finish_time: f64,
+    pub finish_count: i64,
+    /// The rank by the best finish time, starting at `1`.
+    pub finish_rank: i64,
+}
+
+#[derive(Debug, StatementResult)]
+pub struct StatementTopResult {
+    /// The name of the account's user or,
+    /// for players without account, the name they finished with.
+    pub player_name: String,
+    /// The best finish time in seconds.
+    pub finish_time: f64,
+}
+
+/// Formats a finish time in seconds as `mm:ss.xx`.
+pub fn fmt_finish_time(finish_time: f64) -> String {
+    let secs = finish_time.max(0.0);
+    format!("{:02}:{:05.2}", (secs / 60.0) as u64, secs % 60.0)
+}
+
+/// Queries the ranks of the race finishes on a map.
+#[derive(Clone)]
+pub struct PlayerStats {
+    rank_account: Arc<Statement<StatementArgAccount, StatementRankResult>>,
+    rank_hash: Arc<Statement<StatementArgHash, StatementRankResult>>,
+    top: Arc<Statement<StatementArgTop, StatementTopResult>>,
+}
+
+impl PlayerStats {
+    pub async fn new(db: Arc<dyn DbInterface>) -> anyhow::Result<Self> {
+        let kinds = db.kinds();
+        let kind = if kinds.contains(&DbKind::MySql(DbKindExtra::Main)) {
+            DbKind::MySql(DbKindExtra::Main)
+        } else if kinds.contains(&DbKind::Sqlite(DbKindExtra::Main)) {
+            DbKind::Sqlite(DbKindExtra::Main)
+        } else if kinds.contains(&DbKind::Postgres(DbKindExtra::Main)) {
+            DbKind::Postgres(DbKindExtra::Main)
+        } else {
+            return Err(anyhow!("No main database for player stats found"));
+        };
+
+        let builder = StatementBuilder::<_, StatementArgAccount, StatementRankResult>::new_per_kind(
+            kind,
+            StatementSql::new(include_str!("generic/stats/rank_account.sql"))
+                .with_postgres(include_str!("postgres/stats/rank_account.sql")),
+            |arg| vec![arg.map_name, arg.map_name, arg.account_id],
+        );
+        let rank_account = Arc::new(Statement::new(db.clone(), builder).await?);
+
+        let builder = StatementBuilder::<_, StatementArgHash, StatementRankResult>::new_per_kind(
+            kind,
+            StatementSql::new(include_str!("generic/stats/rank_hash.sql"))
+                .with_postgres(include_str!("postgres/stats/rank_hash.sql")),
+            |arg| vec![arg.map_name, arg.map_name, arg.cert_hash],
+        );
+        let rank_hash = Arc::new(Statement::new(db.clone(), builder).await?);
+
+        let builder = StatementBuilder::<_, StatementArgTop, StatementTopResult>::new_per_kind(
+            kind,
+            StatementSql::new(include_str!("generic/stats/top.sql"))
+                .with_postgres(include_str!("postgres/stats/top.sql")),
+            |arg| vec![arg.map_name, arg.limit],
+        );
+        let top = Arc::new(Statement::new(db.clone(), builder).await?);
+
+        Ok(Self {
+            rank_account,
+            rank_hash,
+            top,
+        })
+    }
+
+    /// The rank of a single player on the map,
+    /// `None` if the player never finished the map.
+    pub async fn rank(
+        &self,
+        map_name: String,
+        unique_identifier: PlayerUniqueId,
+    ) -> anyhow::Result<Option<StatementRankResult>> {
+        match unique_identifier {
+            PlayerUniqueId::Account(account_id) => {
+                self.rank_account
+                    .fetch_optional(StatementArgAccount {
+                        map_name,
+                        account_id,
+                    })
+                    .await
+            }
+            PlayerUniqueId::CertFingerprint(cert_hash) => {
+                self.rank_hash
+                    .fetch_optional(StatementArgHash {
+                        map_name,
+                        cert_hash: cert_hash.to_vec(),
+                    })
+                    .await
+            }
+        }
+    }
+
+    /// The players with the best finish times on the map.
+    pub async fn top(
+        &self,
+        map_name: String,
+        limit: usize,
+    ) -> anyhow::Result<Vec<StatementTopResult>> {
+        self.top
+            .fetch_all(StatementArgTop {
+                map_name,
+                limit: limit as i64,
+            })
+            .await
+    }
+}
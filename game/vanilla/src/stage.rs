@@ -4,17 +4,25 @@ pub mod stage {
     use base::{linked_hash_map_view::FxLinkedHashMap, network_string::NetworkString};
     use game_interface::{
         client_commands::MAX_TEAM_NAME_LEN,
-        types::{id_gen::IdGenerator, id_types::StageId},
+        types::{
+            game::GameTickType,
+            id_gen::IdGenerator,
+            id_types::{CharacterId, StageId},
+        },
     };
-    use hiarc::Hiarc;
+    use hiarc::{hi_closure, Hiarc};
     use math::math::vector::ubvec4;
 
     use crate::{
         entities::character::pos::character_pos::CharacterPositionPlayfield,
+        events::events::CharacterEvent,
         game_objects::game_objects::GameObjectDefinitions,
         match_manager::match_manager::MatchManager,
         match_state::match_state::MatchState,
-        simulation_pipe::simulation_pipe::{GameStagePendingEventsRaii, SimulationStageEvents},
+        simulation_pipe::simulation_pipe::{
+            GameStagePendingEventsRaii, SimulationEventWorldEntity, SimulationEventWorldEntityType,
+            SimulationStageEvents, SimulationWorldEvent,
+        },
         spawns::GameSpawns,
         types::types::GameOptions,
     };
@@ -38,6 +46,13 @@ pub mod stage {
         pub(crate) game_pending_events: GameStagePendingEventsRaii,
         pub(crate) simulation_events: SimulationStageEvents,
 
+        /// Race finishes of this stage that were not yet handled
+        /// by the game state.
+        pub(crate) race_finishes: Vec<(CharacterId, GameTickType)>,
+        /// The round ended and a new one started,
+        /// not yet handled by the game state.
+        pub(crate) round_ended: bool,
+
         game_object_definitions: Rc<GameObjectDefinitions>,
         pub game_element_id: StageId,
     }
@@ -79,6 +94,9 @@ pub mod stage {
                 game_pending_events,
                 simulation_events,
 
+                race_finishes: Default::default(),
+                round_ended: false,
+
                 game_object_definitions: game_object_definitions.clone(),
 
                 game_element_id,
@@ -117,6 +135,22 @@ pub mod stage {
                 self.world.characters = characters;
                 let game_options = self.match_manager.game_options.clone();
                 self.match_manager = MatchManager::new(game_options, &self.simulation_events);
+                self.round_ended = true;
+            }
+
+            if !pipe.is_prediction {
+                let race_finishes = &mut self.race_finishes;
+                self.simulation_events
+                    .for_each(hi_closure!([race_finishes: &mut Vec<(CharacterId, GameTickType)>], |ev: &SimulationWorldEvent| -> () {
+                        if let SimulationWorldEvent::Entity(SimulationEventWorldEntity {
+                            ev: SimulationEventWorldEntityType::Character {
+                                ev: CharacterEvent::RaceFinish { id, ticks },
+                            },
+                        }) = ev
+                        {
+                            race_finishes.push((*id, *ticks));
+                        }
+                    }));
             }
 
             self.simulation_events.clear();
@@ -13,7 +13,7 @@ pub mod state {
     use config::parsing::parse_conf_values_as_str_list;
     use config::traits::ConfigInterface;
     use ddnet_accounts_types::account_id::AccountId;
-    use game_database::traits::DbInterface;
+    use game_database::{traits::DbInterface, types::ExecuteResult};
     use game_interface::account_info::MAX_ACCOUNT_NAME_LEN;
     use game_interface::chat_commands::ChatCommands;
     use game_interface::client_commands::{
@@ -27,7 +27,7 @@ pub mod state {
     use game_interface::pooling::GamePooling;
     use game_interface::rcon_commands::{AuthLevel, ExecRconCommand, RconCommand, RconCommands};
     use game_interface::settings::GameStateSettings;
-    use game_interface::tick_result::TickResult;
+    use game_interface::tick_result::{TickEvent, TickResult};
     use game_interface::types::character_info::{
         NetworkCharacterInfo, NetworkLaserInfo, NetworkSkinInfo, MAX_ASSET_NAME_LEN,
         MAX_CHARACTER_NAME_LEN,
@@ -70,8 +70,8 @@ pub mod state {
     use game_interface::types::render::character::{
         CharacterBuff, CharacterBuffInfo, CharacterDebuff, CharacterDebuffInfo,
         CharacterHookRenderInfo, CharacterInfo, CharacterPlayerInfo, CharacterRenderInfo,
-        LocalCharacterRenderInfo, LocalCharacterVanilla, PlayerCameraMode, PlayerIngameMode,
-        TeeEye,
+        LocalCharacterRaceSplit, LocalCharacterRenderInfo, LocalCharacterVanilla, PlayerCameraMode,
+        PlayerIngameMode, TeeEye,
     };
     use game_interface::types::render::flag::FlagRenderInfo;
     use game_interface::types::render::laser::LaserRenderInfo;
@@ -82,6 +82,7 @@ pub mod state {
         ScoreboardGameType, ScoreboardGameTypeOptions, ScoreboardPlayerSpectatorInfo,
         ScoreboardScoreType, ScoreboardStageInfo,
     };
+    use game_interface::types::render::switch::SwitchRenderInfo;
     use game_interface::types::snapshot::{SnapshotClientInfo, SnapshotLocalPlayers};
     use pool::rc::PoolRc;
     use rustc_hash::FxHashMap;
@@ -94,6 +95,7 @@ pub mod state {
     use crate::entities::character::player::player::{
         Player, PlayerInfo, Players, SpectatorPlayer, SpectatorPlayers,
     };
+    use crate::entities::character::race::character_race::{race_ticks_to_duration, RaceState};
     use crate::entities::flag::flag::{Flag, Flags};
     use crate::entities::laser::laser::Laser;
     use crate::entities::pickup::pickup::Pickup;
@@ -105,7 +107,11 @@ pub mod state {
     use crate::snapshot::snapshot::{Snapshot, SnapshotFor, SnapshotManager, SnapshotStage};
     use crate::sql::account_created::{self, AccountCreated};
     use crate::sql::account_info::{AccountInfo, StatementResult};
-    use crate::sql::save;
+    use crate::sql::migrations;
+    use crate::sql::race::RaceFinishes;
+    use crate::sql::stats::{
+        fmt_finish_time, PlayerStats, StatementRankResult, StatementTopResult,
+    };
     use crate::stage::stage::Stages;
     use crate::types::types::{GameOptions, GameType};
     use crate::weapons::definitions::weapon_def::Weapon;
@@ -138,6 +144,16 @@ pub mod state {
         Cheats(VanillaRconCommandCheat),
     }
 
+    #[derive(Debug, Clone, Copy)]
+    pub enum VanillaChatCommand {
+        Help,
+        CmdList,
+        Me,
+        Rank,
+        Top5,
+        AccountInfo,
+    }
+
     pub struct Game {
         pub(crate) stages: Stages,
 
@@ -163,6 +179,19 @@ pub mod state {
             affected_rows: account_created::StatementAffected,
             err: Option<anyhow::Error>,
         },
+        RaceFinish {
+            player_name: String,
+            finish_time: Duration,
+            err: Option<anyhow::Error>,
+        },
+        Rank {
+            player_id: PlayerId,
+            rank: anyhow::Result<Option<StatementRankResult>>,
+        },
+        Top {
+            player_id: PlayerId,
+            top: anyhow::Result<Vec<StatementTopResult>>,
+        },
     }
 
     pub struct GameStatements {
@@ -173,6 +202,8 @@ pub mod state {
         pub(crate) io_rt: IoRuntime,
         pub(crate) account_info: Option<AccountInfo>,
         pub(crate) statements: Option<GameStatements>,
+        pub(crate) race_finishes: Option<RaceFinishes>,
+        pub(crate) player_stats: Option<PlayerStats>,
 
         pub(crate) cur_queries: Vec<IoRuntimeTask<GameDbQueries>>,
         pub(crate) cur_queries_helper: Vec<IoRuntimeTask<GameDbQueries>>,
@@ -205,7 +236,7 @@ pub mod state {
         pub(crate) game_options: GameOptions,
         config: ConfigVanilla,
 
-        pub(crate) chat_commands: ChatCommands,
+        pub(crate) chat_chain: CommandChain<VanillaChatCommand>,
         pub(crate) rcon_chain: CommandChain<VanillaRconCommand>,
         cache: ParserCache,
         map_name: NetworkReducedAsciiString<MAX_MAP_NAME_LEN>,
@@ -236,12 +267,13 @@ pub mod state {
         {
             let db_task = io_rt.spawn(async move {
                 if !db.kinds().is_empty() {
-                    if let Err(err) = save::setup(db.clone()).await {
+                    // the schema might still be up to date enough (e.g. if automatic
+                    // migrations are disabled), so the statements are prepared anyway.
+                    if let Err(err) = migrations::migrate(db.clone()).await {
                         log::warn!(
                             target: "sql",
                             "failed to setup databases: {}", err
                         );
-                        return Err(err);
                     }
 
                     let acc_info = AccountInfo::new(db.clone(), options.account_db).await;
@@ -251,6 +283,26 @@ pub mod state {
                         "failed to prepare account info sql: {}", err);
                     }
 
+                    let race_finishes = match RaceFinishes::new(db.clone()).await {
+                        Ok(race_finishes) => Some(race_finishes),
+                        Err(err) => {
+                            log::warn!(
+                            target: "sql",
+                            "failed to prepare race finishes sql: {}", err);
+                            None
+                        }
+                    };
+
+                    let player_stats = match PlayerStats::new(db.clone()).await {
+                        Ok(player_stats) => Some(player_stats),
+                        Err(err) => {
+                            log::warn!(
+                            target: "sql",
+                            "failed to prepare player stats sql: {}", err);
+                            None
+                        }
+                    };
+
                     let account_created = match AccountCreated::new(db, options.account_db).await {
                         Ok(account_created) => Some(account_created),
                         Err(err) => {
@@ -264,7 +316,7 @@ pub mod state {
                     let statements =
                         account_created.map(|account_created| GameStatements { account_created });
 
-                    Ok(statements.zip(acc_info.ok()))
+                    Ok((statements.zip(acc_info.ok()), race_finishes, player_stats))
                 } else {
                     Err(anyhow!("Databases not active."))
                 }
@@ -307,11 +359,112 @@ pub mod state {
                 ConfigGameType::Dm => GameType::Solo,
             };
 
-            let (statements, account_info) = db_task.get_storage().ok().flatten().unzip();
+            let (statements, race_finishes, player_stats) =
+                db_task.get_storage().unwrap_or_default();
+            let (statements, account_info) = statements.unzip();
 
+            let chat_chain = CommandChain::new(
+                vec![
+                    (
+                        "help".try_into().unwrap(),
+                        Command {
+                            rcon: RconCommand {
+                                args: Default::default(),
+                                description: "Lists all commands and how to use them"
+                                    .try_into()
+                                    .unwrap(),
+                                usage: "help".try_into().unwrap(),
+                                auth_level: AuthLevel::None,
+                                groups: Default::default(),
+                            },
+                            cmd: VanillaChatCommand::Help,
+                        },
+                    ),
+                    (
+                        "cmdlist".try_into().unwrap(),
+                        Command {
+                            rcon: RconCommand {
+                                args: Default::default(),
+                                description: "Lists the names of all commands".try_into().unwrap(),
+                                usage: "cmdlist".try_into().unwrap(),
+                                auth_level: AuthLevel::None,
+                                groups: Default::default(),
+                            },
+                            cmd: VanillaChatCommand::CmdList,
+                        },
+                    ),
+                    (
+                        "me".try_into().unwrap(),
+                        Command {
+                            rcon: RconCommand {
+                                args: vec![CommandArg {
+                                    ty: CommandArgType::Text,
+                                    user_ty: None,
+                                }],
+                                description: "Describes what you are doing in the third person"
+                                    .try_into()
+                                    .unwrap(),
+                                usage: "me <text>".try_into().unwrap(),
+                                auth_level: AuthLevel::None,
+                                groups: Default::default(),
+                            },
+                            cmd: VanillaChatCommand::Me,
+                        },
+                    ),
+                    (
+                        "rank".try_into().unwrap(),
+                        Command {
+                            rcon: RconCommand {
+                                args: Default::default(),
+                                description: "Shows your best time and your rank on this map"
+                                    .try_into()
+                                    .unwrap(),
+                                usage: "rank".try_into().unwrap(),
+                                auth_level: AuthLevel::None,
+                                groups: Default::default(),
+                            },
+                            cmd: VanillaChatCommand::Rank,
+                        },
+                    ),
+                    (
+                        "top5".try_into().unwrap(),
+                        Command {
+                            rcon: RconCommand {
+                                args: Default::default(),
+                                description: "Shows the five best times on this map"
+                                    .try_into()
+                                    .unwrap(),
+                                usage: "top5".try_into().unwrap(),
+                                auth_level: AuthLevel::None,
+                                groups: Default::default(),
+                            },
+                            cmd: VanillaChatCommand::Top5,
+                        },
+                    ),
+                    (
+                        "account_info".try_into().unwrap(),
+                        Command {
+                            rcon: RconCommand {
+                                args: Default::default(),
+                                description: "Shows information about your account"
+                                    .try_into()
+                                    .unwrap(),
+                                usage: "account_info".try_into().unwrap(),
+                                auth_level: AuthLevel::None,
+                                groups: Default::default(),
+                            },
+                            cmd: VanillaChatCommand::AccountInfo,
+                        },
+                    ),
+                ]
+                .into_iter()
+                .collect(),
+            );
             let chat_commands = ChatCommands {
-                cmds: vec![("account_info".try_into().unwrap(), vec![])]
-                    .into_iter()
+                cmds: chat_chain
+                    .parser
+                    .iter()
+                    .map(|(name, args)| (name.as_str().try_into().unwrap(), args.clone()))
                     .collect(),
                 prefixes: vec!['/'],
             };
@@ -326,6 +479,8 @@ pub mod state {
                                     .try_into()
                                     .unwrap(),
                                 usage: "".try_into().unwrap(),
+                                auth_level: AuthLevel::Moderator,
+                                groups: Default::default(),
                             },
                             cmd: VanillaRconCommand::Info,
                         },
@@ -339,6 +494,8 @@ pub mod state {
                                     .try_into()
                                     .unwrap(),
                                 usage: "".try_into().unwrap(),
+                                auth_level: AuthLevel::Admin,
+                                groups: vec!["cheats".try_into().unwrap()],
                             },
                             cmd: VanillaRconCommand::Cheats(VanillaRconCommandCheat::WeaponsAll),
                         },
@@ -378,6 +535,8 @@ pub mod state {
                                         user_ty: None,
                                     },
                                 ],
+                                auth_level: AuthLevel::Admin,
+                                groups: vec!["cheats".try_into().unwrap()],
                             },
                             cmd: VanillaRconCommand::Cheats(VanillaRconCommandCheat::Tune),
                         },
@@ -450,7 +609,7 @@ pub mod state {
                     config.laser_hit_self,
                 ),
                 config: config.clone(),
-                chat_commands: chat_commands.clone(),
+                chat_chain,
                 rcon_chain,
                 cache: Default::default(),
                 map_name,
@@ -460,6 +619,8 @@ pub mod state {
                     io_rt,
                     account_info,
                     statements,
+                    race_finishes,
+                    player_stats,
 
                     cur_queries: Default::default(),
                     cur_queries_helper: Default::default(),
@@ -498,7 +659,7 @@ pub mod state {
                         use_account_name: has_accounts,
                         forced_ingame_camera_zoom: Some(FixedZoomLevel::new_lossy(1.0)),
                         allows_voted_player_miniscreen: config.allow_player_vote_cam,
-                        ghosts: false,
+                        ghosts: true,
                         has_ingame_freecam: false,
                     },
                 },
@@ -696,6 +857,56 @@ pub mod state {
             }
         }
 
+        /// Stores the race finishes of all stages in the database.
+        fn race_finish_tick(&mut self) {
+            for stage in self.game.stages.values_mut() {
+                for (character_id, ticks) in stage.race_finishes.drain(..) {
+                    let (Some(race_finishes), Some(character)) = (
+                        &self.game_db.race_finishes,
+                        stage.world.characters.get(&character_id),
+                    ) else {
+                        continue;
+                    };
+                    let race_finishes = race_finishes.clone();
+                    let map_name = self.map_name.to_string();
+                    let player_name = character.player_info.player_info.name.to_string();
+                    let unique_identifier = character.player_info.unique_identifier;
+                    let finish_time = race_ticks_to_duration(ticks);
+                    self.game_db
+                        .cur_queries
+                        .push(self.game_db.io_rt.spawn(async move {
+                            let err = race_finishes
+                                .insert(
+                                    map_name,
+                                    player_name.clone(),
+                                    unique_identifier,
+                                    finish_time,
+                                )
+                                .await
+                                .err();
+                            Ok(GameDbQueries::RaceFinish {
+                                player_name,
+                                finish_time,
+                                err,
+                            })
+                        }));
+                }
+            }
+        }
+
+        /// Whether the round of the main stage ended.
+        ///
+        /// Other stages (ddrace teams) don't have a round that
+        /// ends for the whole server.
+        fn round_end_tick(&mut self) -> bool {
+            let mut round_ended = false;
+            for (stage_id, stage) in self.game.stages.iter_mut() {
+                round_ended |=
+                    std::mem::take(&mut stage.round_ended) && *stage_id == self.stage_0_id;
+            }
+            round_ended
+        }
+
         fn query_tick(&mut self) {
             self.game_db.cur_queries_helper.clear();
             for query in self.game_db.cur_queries.drain(..) {
@@ -768,18 +979,91 @@ pub mod state {
                                 affected_rows,
                                 err,
                             } => {
-                                log::info!(
-                                    "Rewrote {} save for account {} using hash {}",
-                                    affected_rows.rewrite_saves,
-                                    account_id,
-                                    fmt_hash(&cert_fingerprint),
-                                );
+                                match affected_rows.rewrite_saves {
+                                    ExecuteResult::Executed(rewrite_saves) => log::info!(
+                                        "Rewrote {} save for account {} using hash {}",
+                                        rewrite_saves,
+                                        account_id,
+                                        fmt_hash(&cert_fingerprint),
+                                    ),
+                                    ExecuteResult::Journaled => log::info!(
+                                        "Journaled the save rewrite for account {} using hash {}",
+                                        account_id,
+                                        fmt_hash(&cert_fingerprint),
+                                    ),
+                                }
                                 if let Some(err) = err {
                                     log::error!(
                                         "During the rewriting the following error occurred: {err}"
                                     );
                                 }
                             }
+                            GameDbQueries::RaceFinish {
+                                player_name,
+                                finish_time,
+                                err,
+                            } => {
+                                if let Some(err) = err {
+                                    log::error!(
+                                        target: "sql",
+                                        "Failed to store the race finish of {} ({:?}): {err}",
+                                        player_name,
+                                        finish_time
+                                    );
+                                }
+                            }
+                            GameDbQueries::Rank { player_id, rank } => {
+                                let msg = match rank {
+                                    Ok(Some(rank)) => format!(
+                                        "rank #{} with a best time of {} ({} finishes)",
+                                        rank.finish_rank,
+                                        fmt_finish_time(rank.finish_time),
+                                        rank.finish_count
+                                    ),
+                                    Ok(None) => "You did not finish this map yet.".to_string(),
+                                    Err(err) => {
+                                        log::error!(target: "sql", "Failed to fetch a rank: {err}");
+                                        "Failed to fetch your rank.".to_string()
+                                    }
+                                };
+                                Self::push_player_system_msg(
+                                    &mut self.player_events,
+                                    &self.game_pools,
+                                    &player_id,
+                                    &msg,
+                                );
+                            }
+                            GameDbQueries::Top { player_id, top } => {
+                                let msg = match top {
+                                    Ok(top) if top.is_empty() => {
+                                        "Nobody finished this map yet.".to_string()
+                                    }
+                                    Ok(top) => std::iter::once("best times:".to_string())
+                                        .chain(top.into_iter().enumerate().map(|(index, entry)| {
+                                            format!(
+                                                "{}. {}: {}",
+                                                index + 1,
+                                                entry.player_name,
+                                                fmt_finish_time(entry.finish_time)
+                                            )
+                                        }))
+                                        .collect::<Vec<_>>()
+                                        .join("\n"),
+                                    Err(err) => {
+                                        log::error!(
+                                            target: "sql",
+                                            "Failed to fetch the top ranks: {err}"
+                                        );
+                                        "Failed to fetch the top ranks.".to_string()
+                                    }
+                                };
+                                Self::push_player_system_msg(
+                                    &mut self.player_events,
+                                    &self.game_pools,
+                                    &player_id,
+                                    &msg,
+                                );
+                            }
                         }
                     }
                 }
@@ -862,12 +1146,127 @@ pub mod state {
             }
         }
 
-        fn cmd_account_info(game_db: &mut GameDb, player_id: &PlayerId, character: &Character) {
-            Self::push_account_info_task(
-                game_db,
-                player_id,
-                &character.player_info.unique_identifier,
-            )
+        /// Sends a system message that only the given player sees.
+        fn push_player_system_msg(
+            player_events: &mut FxHashMap<PlayerId, GameStagePendingEvents>,
+            game_pools: &GamePooling,
+            player_id: &PlayerId,
+            msg: &str,
+        ) {
+            let mut s = game_pools.mt_network_string_common_pool.new();
+            s.try_set(msg.chars().take(1024).collect::<String>())
+                .unwrap();
+            player_events
+                .entry(*player_id)
+                .or_default()
+                .push(GameWorldEvent::Notification(
+                    GameWorldNotificationEvent::System(GameWorldSystemMessage::Custom(s)),
+                ));
+        }
+
+        fn handle_chat_command(
+            &mut self,
+            player_id: &PlayerId,
+            player_name: &str,
+            unique_identifier: &PlayerUniqueId,
+            cmd: CommandType,
+        ) -> anyhow::Result<Option<String>> {
+            let cmd = match cmd {
+                CommandType::Full(cmd) => cmd,
+                CommandType::Partial(res) => return Err(anyhow!("{res}")),
+            };
+            let Some(chain_cmd) = self.chat_chain.cmds.get(&cmd.ident) else {
+                return Err(anyhow!(
+                    "Command {} was not found, see /cmdlist for all commands",
+                    cmd.ident
+                ));
+            };
+
+            match chain_cmd.cmd {
+                VanillaChatCommand::Help => {
+                    let mut cmds: Vec<_> = self.chat_chain.cmds.values().collect();
+                    cmds.sort_by(|cmd1, cmd2| {
+                        cmd1.rcon.usage.as_str().cmp(cmd2.rcon.usage.as_str())
+                    });
+                    Ok(Some(
+                        cmds.into_iter()
+                            .map(|cmd| format!("/{}: {}", cmd.rcon.usage, cmd.rcon.description))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    ))
+                }
+                VanillaChatCommand::CmdList => {
+                    let mut names: Vec<_> = self
+                        .chat_chain
+                        .cmds
+                        .keys()
+                        .map(|name| name.as_str())
+                        .collect();
+                    names.sort();
+                    Ok(Some(format!("commands: {}", names.join(", "))))
+                }
+                VanillaChatCommand::Me => {
+                    let Some((Syn::Text(text), _)) = cmd.args.first() else {
+                        panic!("Expected a text, this is an implementation bug");
+                    };
+                    for stage in self.game.stages.values() {
+                        stage.game_pending_events.push(GameWorldEvent::Notification(
+                            GameWorldNotificationEvent::System(GameWorldSystemMessage::Custom({
+                                let mut s = self.game_pools.mt_network_string_common_pool.new();
+                                s.try_set(
+                                    format!("* {player_name} {text}")
+                                        .chars()
+                                        .take(1024)
+                                        .collect::<String>(),
+                                )
+                                .unwrap();
+                                s
+                            })),
+                        ));
+                    }
+                    Ok(None)
+                }
+                VanillaChatCommand::Rank => {
+                    let Some(player_stats) = self.game_db.player_stats.clone() else {
+                        return Err(anyhow!("Ranks are not available on this server."));
+                    };
+                    let player_id = *player_id;
+                    let unique_identifier = *unique_identifier;
+                    let map_name = self.map_name.to_string();
+                    self.game_db
+                        .cur_queries
+                        .push(self.game_db.io_rt.spawn(async move {
+                            Ok(GameDbQueries::Rank {
+                                player_id,
+                                rank: player_stats.rank(map_name, unique_identifier).await,
+                            })
+                        }));
+                    Ok(None)
+                }
+                VanillaChatCommand::Top5 => {
+                    let Some(player_stats) = self.game_db.player_stats.clone() else {
+                        return Err(anyhow!("Ranks are not available on this server."));
+                    };
+                    let player_id = *player_id;
+                    let map_name = self.map_name.to_string();
+                    self.game_db
+                        .cur_queries
+                        .push(self.game_db.io_rt.spawn(async move {
+                            Ok(GameDbQueries::Top {
+                                player_id,
+                                top: player_stats.top(map_name, 5).await,
+                            })
+                        }));
+                    Ok(None)
+                }
+                VanillaChatCommand::AccountInfo => {
+                    if !matches!(unique_identifier, PlayerUniqueId::Account(_)) {
+                        return Err(anyhow!("You are not logged in to an account."));
+                    }
+                    Self::push_account_info_task(&mut self.game_db, player_id, unique_identifier);
+                    Ok(None)
+                }
+            }
         }
 
         fn handle_chat_commands(&mut self, player_id: &PlayerId, cmds: Vec<CommandType>) {
@@ -882,22 +1281,25 @@ pub mod state {
             else {
                 return;
             };
+            let player_name = character.player_info.player_info.name.to_string();
+            let unique_identifier = character.player_info.unique_identifier;
             for cmd in cmds {
-                match cmd {
-                    CommandType::Full(cmd) => {
-                        match cmd.ident.as_str() {
-                            "account_info" => {
-                                Self::cmd_account_info(&mut self.game_db, player_id, character);
-                            }
-                            _ => {
-                                // TODO: send command not found text
-                            }
-                        }
-                    }
-                    CommandType::Partial(_) => {
-                        // TODO: ignore for now
-                        // send back feedback to user
-                    }
+                let msg = match self.handle_chat_command(
+                    player_id,
+                    &player_name,
+                    &unique_identifier,
+                    cmd,
+                ) {
+                    Ok(msg) => msg,
+                    Err(err) => Some(err.to_string()),
+                };
+                if let Some(msg) = msg {
+                    Self::push_player_system_msg(
+                        &mut self.player_events,
+                        &self.game_pools,
+                        player_id,
+                        &msg,
+                    );
                 }
             }
         }
@@ -905,7 +1307,7 @@ pub mod state {
         fn handle_rcon_commands(
             &mut self,
             player_id: Option<&PlayerId>,
-            _auth: AuthLevel,
+            auth: AuthLevel,
             cmds: Vec<CommandType>,
         ) -> Vec<NetworkString<65536>> {
             let mut res: Vec<NetworkString<65536>> = Default::default();
@@ -915,6 +1317,12 @@ pub mod state {
                         let Some(chain_cmd) = self.rcon_chain.cmds.get(&cmd.ident) else {
                             return Err(anyhow!("Rcon command {} was not found", cmd.ident));
                         };
+                        if auth < chain_cmd.rcon.auth_level {
+                            return Err(anyhow!(
+                                "Rcon command {} requires a higher auth level",
+                                cmd.ident
+                            ));
+                        }
 
                         match chain_cmd.cmd {
                             VanillaRconCommand::Info => {
@@ -1195,6 +1603,23 @@ pub mod state {
             res
         }
 
+        fn stage_switches(
+            &self,
+            prev_stage: &GameStage,
+        ) -> PoolFxLinkedHashMap<u8, SwitchRenderInfo> {
+            let mut res = self.game_pools.switch_render_info_pool.new();
+            res.extend(prev_stage.world.switches.iter().map(|(number, switch)| {
+                (
+                    number,
+                    SwitchRenderInfo {
+                        active: switch.active,
+                        revert_in: switch.revert_in.get(),
+                    },
+                )
+            }));
+            res
+        }
+
         fn stage_character_render_info(
             &self,
             prev_stage: &GameStage,
@@ -1302,7 +1727,7 @@ pub mod state {
                             CharacterDebuff::Freeze => (
                                 CharacterDebuff::Freeze,
                                 CharacterDebuffInfo {
-                                    remaining_time: None,
+                                    remaining_time: prev_character.freeze_remaining_time(),
                                 },
                             ),
                         },
@@ -1312,6 +1737,8 @@ pub mod state {
 
                 animation_ticks_passed: prev_stage.match_manager.game_match.state.passed_ticks(),
                 game_ticks_passed: prev_stage.match_manager.game_match.state.passed_ticks(),
+                race_ticks_passed: (prev_character.core.race.state != RaceState::None)
+                    .then_some(prev_character.core.race.ticks),
 
                 emoticon: prev_character.core.cur_emoticon.and_then(|emoticon| {
                     prev_character
@@ -1797,6 +2224,7 @@ pub mod state {
                                 stage,
                                 intra_tick_ratio,
                             ),
+                            switches: self.stage_switches(prev_stage),
                         },
                         game: GameRenderInfo::Match {
                             standings: match prev_stage.match_manager.game_match.ty {
@@ -1882,6 +2310,14 @@ pub mod state {
                         .weapons
                         .get(&player_char.core.active_weapon)
                         .and_then(|w| w.cur_ammo),
+                    race_split: player_char.core.race.split().map(
+                        |(checkpoint, ticks, diff_ticks)| LocalCharacterRaceSplit {
+                            checkpoint,
+                            time: race_ticks_to_duration(ticks),
+                            diff_to_best_millis: diff_ticks
+                                .map(|diff| diff * 1000 / TICKS_PER_SECOND as i64),
+                        },
+                    ),
                 })
             } else {
                 // spectators get nothing
@@ -2309,7 +2745,7 @@ pub mod state {
                 ClientCommand::Chat(cmd) => {
                     let cmds = command_parser::parser::parse(
                         &cmd.raw,
-                        &self.chat_commands.cmds,
+                        &self.chat_chain.parser,
                         &mut self.cache,
                     );
                     self.handle_chat_commands(player_id, cmds);
@@ -2561,14 +2997,18 @@ pub mod state {
         fn tick(&mut self, options: TickOptions) -> TickResult {
             self.tick_impl(options.is_future_tick_prediction);
 
+            let mut events = PoolVec::new_without_pool();
             if !options.is_future_tick_prediction {
                 self.player_tick();
+                self.race_finish_tick();
                 self.query_tick();
-            }
 
-            TickResult {
-                events: PoolVec::new_without_pool(),
+                if self.round_end_tick() {
+                    events.push(TickEvent::RoundEnded);
+                }
             }
+
+            TickResult { events }
         }
 
         fn snapshot_for(&self, client: SnapshotClientInfo) -> MtPoolCow<'static, [u8]> {
@@ -2628,10 +3068,16 @@ pub mod state {
             self.build_prev_from_stages(snapshot.stages);
         }
 
-        fn build_ghosts_from_snapshot(&self, _: &MtPoolCow<'static, [u8]>) -> GhostResult {
-            GhostResult {
-                players: PoolFxHashMap::new_without_pool(),
-            }
+        fn build_ghosts_from_snapshot(&self, snapshot: &MtPoolCow<'static, [u8]>) -> GhostResult {
+            let Ok((snapshot, _)) =
+                bincode::serde::decode_from_slice(snapshot, bincode::config::standard())
+            else {
+                return GhostResult {
+                    players: PoolFxHashMap::new_without_pool(),
+                };
+            };
+
+            self.snap_shot_manager.build_ghosts(snapshot)
         }
 
         fn events_for(&self, client: EventClientInfo) -> GameEvents {
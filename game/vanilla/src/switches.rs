@@ -0,0 +1,120 @@
+pub mod switches {
+    use game_base::mapdef_06::DdraceTileNum;
+    use game_interface::types::game::{GameTickCooldown, GameTickType};
+    use hiarc::Hiarc;
+    use num::FromPrimitive;
+    use serde::{Deserialize, Serialize};
+
+    use crate::state::state::TICKS_PER_SECOND;
+
+    /// How a switch tile changes the state of a switch.
+    #[derive(Debug, Hiarc, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub enum SwitchType {
+        #[default]
+        Open,
+        Close,
+        /// Opens the switch, but closes it again after a delay
+        TimedOpen,
+        /// Closes the switch, but opens it again after a delay
+        TimedClose,
+    }
+
+    impl SwitchType {
+        pub fn from_tile(index: u8) -> Option<Self> {
+            match DdraceTileNum::from_u8(index)? {
+                DdraceTileNum::SwitchOpen => Some(Self::Open),
+                DdraceTileNum::SwitchClose => Some(Self::Close),
+                // same tile index as the solo disable tile
+                DdraceTileNum::SoloDisable => Some(Self::TimedOpen),
+                DdraceTileNum::SwitchTimedClose => Some(Self::TimedClose),
+                _ => None,
+            }
+        }
+    }
+
+    #[derive(Debug, Hiarc, Clone, Copy, Serialize, Deserialize)]
+    pub struct Switch {
+        pub active: bool,
+        pub ty: SwitchType,
+        /// For timed switches, the time until the switch
+        /// reverts its state.
+        pub revert_in: GameTickCooldown,
+    }
+
+    impl Default for Switch {
+        fn default() -> Self {
+            Self {
+                active: true,
+                ty: Default::default(),
+                revert_in: Default::default(),
+            }
+        }
+    }
+
+    /// The state of all switches inside a single stage.
+    ///
+    /// A switch is active if doors and other switch-gated tiles
+    /// using its number should take effect.
+    /// Switch numbers that were never touched are active.
+    #[derive(Debug, Hiarc, Default, Clone, Serialize, Deserialize)]
+    pub struct Switches {
+        switches: Vec<Switch>,
+    }
+
+    impl Switches {
+        /// Whether the switch with the given number is active.
+        /// The switch number `0` is always active.
+        pub fn is_active(&self, number: u8) -> bool {
+            self.switches
+                .get(number as usize)
+                .is_none_or(|switch| switch.active)
+        }
+
+        pub fn get(&self, number: u8) -> Option<&Switch> {
+            self.switches.get(number as usize)
+        }
+
+        /// All switches up to the highest switch number that was used,
+        /// together with their number.
+        pub fn iter(&self) -> impl Iterator<Item = (u8, &Switch)> {
+            self.switches
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(number, switch)| (number as u8, switch))
+        }
+
+        /// Applies a switch tile to the switch with the given number.
+        /// `delay` is the time in seconds for timed switches.
+        pub fn activate(&mut self, ty: SwitchType, number: u8, delay: u8) {
+            if number == 0 {
+                return;
+            }
+            if self.switches.len() <= number as usize {
+                self.switches
+                    .resize(number as usize + 1, Default::default());
+            }
+            let switch = &mut self.switches[number as usize];
+            switch.ty = ty;
+            switch.active = matches!(ty, SwitchType::Open | SwitchType::TimedOpen);
+            switch.revert_in = match ty {
+                SwitchType::Open | SwitchType::Close => Default::default(),
+                SwitchType::TimedOpen | SwitchType::TimedClose => {
+                    (1 + delay as GameTickType * TICKS_PER_SECOND).into()
+                }
+            };
+        }
+
+        pub fn tick(&mut self) {
+            for switch in self.switches.iter_mut() {
+                if switch.revert_in.tick().unwrap_or_default() {
+                    switch.active = !switch.active;
+                }
+            }
+        }
+
+        pub fn copy_from(&mut self, other: &Self) {
+            self.switches.clone_from(&other.switches);
+        }
+    }
+}
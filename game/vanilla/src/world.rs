@@ -57,6 +57,7 @@ pub mod world {
         },
         spawns::GameSpawns,
         state::state::TICKS_PER_SECOND,
+        switches::switches::Switches,
         types::types::{GameOptions, GameType},
     };
 
@@ -213,6 +214,8 @@ pub mod world {
         pub(crate) hooks: HookedCharacters,
         pub(crate) scores: CharacterScores,
 
+        pub(crate) switches: Switches,
+
         game_options: GameOptions,
     }
 
@@ -326,6 +329,8 @@ pub mod world {
                 hooks,
                 scores,
 
+                switches: Default::default(),
+
                 game_options,
             }
         }
@@ -741,6 +746,8 @@ pub mod world {
                         .entry(*id)
                         .or_insert_with(|| self.character_tick_helper_pool.new()),
                     pipe.collision,
+                    &mut self.switches,
+                    &self.spawns,
                 ));
             });
             let mut characters = LinkedHashMapIterExt::new(&mut self.characters).rev();
@@ -759,6 +766,8 @@ pub mod world {
                     },
                     events,
                     pipe.collision,
+                    &mut self.switches,
+                    &self.spawns,
                 ));
 
                 // handle the entity events
@@ -851,6 +860,8 @@ pub mod world {
                         .entry(*id)
                         .or_insert_with(|| self.character_tick_helper_pool.new()),
                     pipe.collision,
+                    &mut self.switches,
+                    &self.spawns,
                 ));
             });
         }
@@ -875,6 +886,8 @@ pub mod world {
                         .entry(*id)
                         .or_insert_with(|| self.character_tick_helper_pool.new()),
                     collision,
+                    &mut self.switches,
+                    &self.spawns,
                 ),
                 diff,
             );
@@ -1152,6 +1165,7 @@ pub mod world {
         }
 
         pub fn tick(&mut self, pipe: &mut SimulationPipeStage) {
+            self.switches.tick();
             self.check_character_respawn();
             self.check_inactive_game_objects();
 
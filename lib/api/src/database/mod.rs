@@ -7,20 +7,28 @@ use crate::{read_result_from_host, upload_param};
 use anyhow::anyhow;
 use base_io::yield_now;
 use game_database::{
+    migration::Migrations,
     statement::{QueryProperties, StatementDriverProps},
     traits::{DbInterface, DbKind},
-    types::DbType,
+    types::{DbType, ExecuteResult},
 };
 
 extern "C" {
     fn api_db_kinds();
     fn api_db_setup();
+    fn api_db_migrate();
     fn api_db_prepare_statement();
     fn api_db_drop_statement();
     fn api_db_fetch_optional();
     fn api_db_fetch_one();
     fn api_db_fetch_all();
     fn api_db_execute();
+    fn api_db_begin_transaction();
+    fn api_db_commit_transaction();
+    fn api_db_rollback_transaction();
+    fn api_db_drop_transaction();
+    fn api_db_transaction_fetch_all();
+    fn api_db_transaction_execute();
 }
 
 #[derive(Debug, Default)]
@@ -59,6 +67,25 @@ impl DbInterface for GameDbBackend {
         res.unwrap().map_err(|err| anyhow!(err))
     }
 
+    async fn migrate(&self, migrations: Migrations) -> anyhow::Result<()> {
+        let mut res;
+        let id = self.id.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        loop {
+            upload_param(0, id);
+            upload_param(1, migrations.clone());
+            unsafe {
+                api_db_migrate();
+            }
+            res = read_result_from_host::<Option<Result<(), String>>>();
+            if res.is_some() {
+                break;
+            } else {
+                yield_now::yield_now().await;
+            }
+        }
+        res.unwrap().map_err(|err| anyhow!(err))
+    }
+
     async fn prepare_statement(
         &self,
         query_props: &QueryProperties,
@@ -165,7 +192,7 @@ impl DbInterface for GameDbBackend {
         res.unwrap().map_err(|err| anyhow!(err))
     }
 
-    async fn execute(&self, unique_id: u64, args: Vec<DbType>) -> anyhow::Result<u64> {
+    async fn execute(&self, unique_id: u64, args: Vec<DbType>) -> anyhow::Result<ExecuteResult> {
         let mut res;
         let id = self.id.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
         loop {
@@ -175,6 +202,121 @@ impl DbInterface for GameDbBackend {
             unsafe {
                 api_db_execute();
             }
+            res = read_result_from_host::<Option<Result<ExecuteResult, String>>>();
+            if res.is_some() {
+                break;
+            } else {
+                yield_now::yield_now().await;
+            }
+        }
+        res.unwrap().map_err(|err| anyhow!(err))
+    }
+    async fn begin_transaction(&self, kind: &DbKind) -> anyhow::Result<u64> {
+        let mut res;
+        let id = self.id.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        loop {
+            upload_param(0, id);
+            upload_param(1, kind);
+            unsafe {
+                api_db_begin_transaction();
+            }
+            res = read_result_from_host::<Option<Result<u64, String>>>();
+            if res.is_some() {
+                break;
+            } else {
+                yield_now::yield_now().await;
+            }
+        }
+        res.unwrap().map_err(|err| anyhow!(err))
+    }
+
+    async fn commit_transaction(&self, transaction_id: u64) -> anyhow::Result<()> {
+        let mut res;
+        let id = self.id.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        loop {
+            upload_param(0, id);
+            upload_param(1, transaction_id);
+            unsafe {
+                api_db_commit_transaction();
+            }
+            res = read_result_from_host::<Option<Result<(), String>>>();
+            if res.is_some() {
+                break;
+            } else {
+                yield_now::yield_now().await;
+            }
+        }
+        res.unwrap().map_err(|err| anyhow!(err))
+    }
+
+    async fn rollback_transaction(&self, transaction_id: u64) -> anyhow::Result<()> {
+        let mut res;
+        let id = self.id.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        loop {
+            upload_param(0, id);
+            upload_param(1, transaction_id);
+            unsafe {
+                api_db_rollback_transaction();
+            }
+            res = read_result_from_host::<Option<Result<(), String>>>();
+            if res.is_some() {
+                break;
+            } else {
+                yield_now::yield_now().await;
+            }
+        }
+        res.unwrap().map_err(|err| anyhow!(err))
+    }
+
+    fn drop_transaction(&self, transaction_id: u64) {
+        upload_param(0, transaction_id);
+        unsafe {
+            api_db_drop_transaction();
+        }
+    }
+
+    async fn transaction_fetch_all(
+        &self,
+        transaction_id: u64,
+        unique_id: u64,
+        args: Vec<DbType>,
+    ) -> anyhow::Result<Vec<HashMap<String, DbType>>> {
+        let mut res;
+        let id = self.id.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        loop {
+            upload_param(0, id);
+            upload_param(1, transaction_id);
+            upload_param(2, unique_id);
+            upload_param(3, &args);
+            unsafe {
+                api_db_transaction_fetch_all();
+            }
+            res = read_result_from_host::<Option<Result<Vec<HashMap<String, DbType>>, String>>>();
+            if res.is_some() {
+                break;
+            } else {
+                yield_now::yield_now().await;
+            }
+        }
+        res.unwrap().map_err(|err| anyhow!(err))
+    }
+
+    async fn transaction_execute(
+        &self,
+        transaction_id: u64,
+        unique_id: u64,
+        args: Vec<DbType>,
+    ) -> anyhow::Result<u64> {
+        let mut res;
+        let id = self.id.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        loop {
+            upload_param(0, id);
+            upload_param(1, transaction_id);
+            upload_param(2, unique_id);
+            upload_param(3, &args);
+            unsafe {
+                api_db_transaction_execute();
+            }
             res = read_result_from_host::<Option<Result<u64, String>>>();
             if res.is_some() {
                 break;
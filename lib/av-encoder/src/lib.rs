@@ -3,11 +3,74 @@ pub mod encoder;
 #[cfg(feature = "ffmpeg")]
 mod ffmpeg;
 
+pub mod raw;
 pub mod stub;
 pub mod traits;
 pub mod types;
 
-#[cfg(feature = "ffmpeg")]
-pub type AvEncoder = encoder::FfmpegEncoder;
-#[cfg(not(feature = "ffmpeg"))]
-pub type AvEncoder = stub::StubEncoder;
+use std::{path::Path, rc::Rc};
+
+use graphics_backend::backend::GraphicsBackend;
+use graphics_backend_traits::frame_fetcher_plugin::OffscreenCanvasId;
+use sound::frame_fetcher_plugin::OffairSoundManagerId;
+use sound_backend::sound_backend::SoundBackend;
+
+use crate::{traits::AudioVideoEncoder, types::EncoderSettings};
+
+/// Encodes with ffmpeg if available.
+///
+/// Falls back to the [`raw::RawEncoder`] if ffmpeg support was not compiled in,
+/// ffmpeg failed to create the encoder
+/// or if the file extension of the output is `y4m`.
+pub enum AvEncoder {
+    #[cfg(feature = "ffmpeg")]
+    Ffmpeg(encoder::FfmpegEncoder),
+    Raw(raw::RawEncoder),
+}
+
+impl AudioVideoEncoder for AvEncoder {
+    fn new(
+        video_frame_buffer_id: OffscreenCanvasId,
+        audio_frame_buffer_id: OffairSoundManagerId,
+        file_path: &Path,
+        backend: &Rc<GraphicsBackend>,
+        sound_backend: &Rc<SoundBackend>,
+        encoder_settings: EncoderSettings,
+    ) -> anyhow::Result<Self> {
+        #[cfg(feature = "ffmpeg")]
+        if file_path.extension().is_none_or(|ext| ext != "y4m") {
+            match encoder::FfmpegEncoder::new(
+                video_frame_buffer_id,
+                audio_frame_buffer_id,
+                file_path,
+                backend,
+                sound_backend,
+                encoder_settings.clone(),
+            ) {
+                Ok(enc) => return Ok(Self::Ffmpeg(enc)),
+                Err(err) => {
+                    log::warn!(
+                        "ffmpeg failed to create the encoder, \
+                        writing uncompressed video & audio instead: {err}"
+                    );
+                }
+            }
+        }
+        Ok(Self::Raw(raw::RawEncoder::new(
+            video_frame_buffer_id,
+            audio_frame_buffer_id,
+            file_path,
+            backend,
+            sound_backend,
+            encoder_settings,
+        )?))
+    }
+
+    fn overloaded(&self) -> bool {
+        match self {
+            #[cfg(feature = "ffmpeg")]
+            Self::Ffmpeg(enc) => enc.overloaded(),
+            Self::Raw(enc) => enc.overloaded(),
+        }
+    }
+}
@@ -0,0 +1,477 @@
+//! A pure Rust encoder that does not compress anything.
+//!
+//! The video is written as Y4M (YUV 4:2:0) and the audio
+//! as 32-bit float WAV next to it, both can be read by most
+//! video editors & players or converted later on.
+//! Audio that does not fit into a WAV file (more than 4 GiB)
+//! is written as RF64.
+
+use std::{
+    fmt::Debug,
+    fs::File,
+    io::{BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{atomic::AtomicU64, mpsc, Arc},
+};
+
+use base::join_thread::JoinThread;
+use graphics_backend::backend::GraphicsBackend;
+use graphics_backend_traits::{
+    frame_fetcher_plugin::{
+        BackendFrameFetcher, BackendPresentedImageDataRgba, FetchCanvasError, FetchCanvasIndex,
+        OffscreenCanvasId,
+    },
+    traits::GraphicsBackendInterface,
+};
+use hiarc::{hiarc_safer_arc_mutex, Hiarc};
+use pool::mt_datatypes::PoolVec;
+use sound::backend_types::SoundBackendInterface;
+use sound::frame_fetcher_plugin::{
+    self, BackendAudioFrame, FetchSoundManagerError, FetchSoundManagerIndex, OffairSoundManagerId,
+};
+use sound_backend::sound_backend::SoundBackend;
+
+use crate::{traits::AudioVideoEncoder, types::EncoderSettings};
+
+/// The file the video stream is written to.
+pub fn raw_video_path(file_path: &Path) -> PathBuf {
+    file_path.with_extension("y4m")
+}
+
+/// The file the audio stream is written to.
+pub fn raw_audio_path(file_path: &Path) -> PathBuf {
+    file_path.with_extension("wav")
+}
+
+/// Converts a tightly packed RGBA image to planar YUV 4:2:0
+/// using the full range BT.601 matrix (like JPEG).
+///
+/// Fails if the image is not exactly `width * height` pixels big.
+pub fn rgba_to_yuv420(
+    rgba: &[u8],
+    width: usize,
+    height: usize,
+    yuv: &mut Vec<u8>,
+) -> anyhow::Result<()> {
+    let pixels = width
+        .checked_mul(height)
+        .ok_or_else(|| anyhow::anyhow!("video size of {width}x{height} is too big."))?;
+    anyhow::ensure!(
+        pixels.checked_mul(4) == Some(rgba.len()),
+        "the frame has {} bytes, but a {width}x{height} RGBA image needs {}.",
+        rgba.len(),
+        pixels.saturating_mul(4)
+    );
+    let chroma_width = width.div_ceil(2);
+    let chroma_height = height.div_ceil(2);
+    yuv.clear();
+    yuv.resize(width * height + chroma_width * chroma_height * 2, 0);
+    let (y_plane, chroma) = yuv.split_at_mut(width * height);
+    let (u_plane, v_plane) = chroma.split_at_mut(chroma_width * chroma_height);
+
+    let pixel = |x: usize, y: usize| {
+        let off = (y * width + x) * 4;
+        (rgba[off] as f32, rgba[off + 1] as f32, rgba[off + 2] as f32)
+    };
+
+    for y in 0..height {
+        for x in 0..width {
+            let (r, g, b) = pixel(x, y);
+            y_plane[y * width + x] = (0.299 * r + 0.587 * g + 0.114 * b).round() as u8;
+        }
+    }
+    for cy in 0..chroma_height {
+        for cx in 0..chroma_width {
+            // average the 2x2 block, blocks at the border might be smaller
+            let (mut r, mut g, mut b, mut count) = (0.0, 0.0, 0.0, 0.0);
+            for y in cy * 2..(cy * 2 + 2).min(height) {
+                for x in cx * 2..(cx * 2 + 2).min(width) {
+                    let (pr, pg, pb) = pixel(x, y);
+                    r += pr;
+                    g += pg;
+                    b += pb;
+                    count += 1.0;
+                }
+            }
+            let (r, g, b) = (r / count, g / count, b / count);
+            u_plane[cy * chroma_width + cx] =
+                (128.0 - 0.168736 * r - 0.331264 * g + 0.5 * b).round() as u8;
+            v_plane[cy * chroma_width + cx] =
+                (128.0 + 0.5 * r - 0.418688 * g - 0.081312 * b).round() as u8;
+        }
+    }
+    Ok(())
+}
+
+/// The size of the header written by [`write_wav_header`],
+/// independent of the number of samples.
+pub const WAV_HEADER_SIZE: u64 = 80;
+
+/// Writes a WAV header for 32-bit float stereo samples.
+///
+/// If the data is too big for a WAV file, a RF64 header is written instead.
+/// Both have the same size, so the header can be rewritten
+/// once the number of samples is known.
+pub fn write_wav_header(
+    writer: &mut impl Write,
+    sample_rate: u32,
+    num_samples: u64,
+) -> std::io::Result<()> {
+    const CHANNELS: u16 = 2;
+    const BYTES_PER_SAMPLE: u16 = std::mem::size_of::<f32>() as u16;
+    const BLOCK_ALIGN: u16 = CHANNELS * BYTES_PER_SAMPLE;
+    // the `ds64` (RF64) or `JUNK` (WAV) chunk
+    const DS64_SIZE: u32 = 28;
+    let too_big = || {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "the audio is too big for a RF64 file",
+        )
+    };
+    let data_size = num_samples
+        .checked_mul(BLOCK_ALIGN as u64)
+        .ok_or_else(too_big)?;
+    let riff_size = data_size
+        .checked_add(WAV_HEADER_SIZE - 8)
+        .ok_or_else(too_big)?;
+    let byte_rate = sample_rate.checked_mul(BLOCK_ALIGN as u32).ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "sample rate too high")
+    })?;
+
+    match u32::try_from(riff_size) {
+        Ok(riff_size) => {
+            writer.write_all(b"RIFF")?;
+            writer.write_all(&riff_size.to_le_bytes())?;
+            writer.write_all(b"WAVE")?;
+            // reserves the space for the `ds64` chunk
+            writer.write_all(b"JUNK")?;
+            writer.write_all(&DS64_SIZE.to_le_bytes())?;
+            writer.write_all(&[0; DS64_SIZE as usize])?;
+        }
+        Err(_) => {
+            writer.write_all(b"RF64")?;
+            writer.write_all(&u32::MAX.to_le_bytes())?;
+            writer.write_all(b"WAVE")?;
+            writer.write_all(b"ds64")?;
+            writer.write_all(&DS64_SIZE.to_le_bytes())?;
+            writer.write_all(&riff_size.to_le_bytes())?;
+            writer.write_all(&data_size.to_le_bytes())?;
+            writer.write_all(&num_samples.to_le_bytes())?;
+            // no table entries
+            writer.write_all(&0_u32.to_le_bytes())?;
+        }
+    }
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&16_u32.to_le_bytes())?;
+    // IEEE float
+    writer.write_all(&3_u16.to_le_bytes())?;
+    writer.write_all(&CHANNELS.to_le_bytes())?;
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&byte_rate.to_le_bytes())?;
+    writer.write_all(&BLOCK_ALIGN.to_le_bytes())?;
+    writer.write_all(&(BYTES_PER_SAMPLE * 8).to_le_bytes())?;
+    writer.write_all(b"data")?;
+    writer.write_all(&u32::try_from(data_size).unwrap_or(u32::MAX).to_le_bytes())?;
+    Ok(())
+}
+
+fn write_video(
+    path: &Path,
+    settings: &EncoderSettings,
+    receiver: mpsc::Receiver<PoolVec<u8>>,
+    frames_in_queue: &AtomicU64,
+) -> anyhow::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(
+        file,
+        "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C420jpeg",
+        settings.width, settings.height, settings.fps
+    )?;
+
+    let mut yuv = Vec::new();
+    while let Ok(rgba) = receiver.recv() {
+        frames_in_queue.fetch_sub(1, std::sync::atomic::Ordering::Relaxed);
+        rgba_to_yuv420(
+            &rgba,
+            settings.width as usize,
+            settings.height as usize,
+            &mut yuv,
+        )?;
+        file.write_all(b"FRAME\n")?;
+        file.write_all(&yuv)?;
+    }
+    file.flush()?;
+    Ok(())
+}
+
+fn write_audio(
+    path: &Path,
+    settings: &EncoderSettings,
+    receiver: mpsc::Receiver<BackendAudioFrame>,
+) -> anyhow::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    // the sizes are not known yet, the header is rewritten at the end
+    write_wav_header(&mut file, settings.sample_rate, 0)?;
+
+    let mut num_samples: u64 = 0;
+    while let Ok(frame) = receiver.recv() {
+        file.write_all(&frame.left.to_le_bytes())?;
+        file.write_all(&frame.right.to_le_bytes())?;
+        num_samples += 1;
+    }
+
+    file.seek(SeekFrom::Start(0))?;
+    write_wav_header(&mut file, settings.sample_rate, num_samples)?;
+    file.flush()?;
+    Ok(())
+}
+
+#[hiarc_safer_arc_mutex]
+#[derive(Debug, Hiarc)]
+pub struct RawEncoderImpl {
+    video_sender: mpsc::Sender<PoolVec<u8>>,
+    video_frame_buffer_id: OffscreenCanvasId,
+
+    audio_sender: mpsc::Sender<BackendAudioFrame>,
+    audio_frame_buffer_id: OffairSoundManagerId,
+
+    video_frames_in_queue: Arc<AtomicU64>,
+    max_video_frames_in_queue: u64,
+
+    _video_thread: JoinThread<()>,
+    _audio_thread: JoinThread<()>,
+}
+
+#[hiarc_safer_arc_mutex]
+impl RawEncoderImpl {
+    pub fn new(
+        video_frame_buffer_id: OffscreenCanvasId,
+        audio_frame_buffer_id: OffairSoundManagerId,
+        file_path: &Path,
+        encoder_settings: EncoderSettings,
+    ) -> anyhow::Result<Self> {
+        let (video_sender, video_receiver) = mpsc::channel::<PoolVec<u8>>();
+        let (audio_sender, audio_receiver) = mpsc::channel::<BackendAudioFrame>();
+
+        let video_frames_in_queue: Arc<AtomicU64> = Default::default();
+        let max_video_frames_in_queue: u64 = encoder_settings.max_threads;
+
+        let video_path = raw_video_path(file_path);
+        let audio_path = raw_audio_path(file_path);
+        if let Some(dir) = video_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        let video_settings = encoder_settings.clone();
+        let video_frames_in_queue_thread = video_frames_in_queue.clone();
+        let video_thread = std::thread::Builder::new()
+            .name("raw-video-encoder".to_string())
+            .spawn(move || {
+                if let Err(err) = write_video(
+                    &video_path,
+                    &video_settings,
+                    video_receiver,
+                    &video_frames_in_queue_thread,
+                ) {
+                    log::error!("writing the raw video failed: {err}");
+                    // nothing is written anymore, don't let the queue block the caller
+                    video_frames_in_queue_thread.store(0, std::sync::atomic::Ordering::Relaxed);
+                }
+            })?;
+        let audio_thread = std::thread::Builder::new()
+            .name("raw-audio-encoder".to_string())
+            .spawn(move || {
+                if let Err(err) = write_audio(&audio_path, &encoder_settings, audio_receiver) {
+                    log::error!("writing the raw audio failed: {err}");
+                }
+            })?;
+
+        Ok(Self {
+            video_sender,
+            video_frame_buffer_id,
+
+            audio_sender,
+            audio_frame_buffer_id,
+
+            video_frames_in_queue,
+            max_video_frames_in_queue,
+
+            _video_thread: JoinThread::new(video_thread),
+            _audio_thread: JoinThread::new(audio_thread),
+        })
+    }
+
+    pub fn overloaded(&self) -> bool {
+        self.video_frames_in_queue
+            .load(std::sync::atomic::Ordering::Relaxed)
+            >= self.max_video_frames_in_queue
+    }
+}
+
+#[hiarc_safer_arc_mutex]
+impl BackendFrameFetcher for RawEncoderImpl {
+    #[hiarc_trait_is_immutable_self]
+    fn next_frame(&mut self, frame_data: BackendPresentedImageDataRgba) {
+        if self.video_sender.send(frame_data.dest_data_buffer).is_ok() {
+            self.video_frames_in_queue
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+
+    fn current_fetch_index(&self) -> FetchCanvasIndex {
+        FetchCanvasIndex::Offscreen(self.video_frame_buffer_id)
+    }
+
+    fn fetch_err(&self, err: FetchCanvasError) {
+        match err {
+            FetchCanvasError::CanvasNotFound => {
+                // ignore for now
+            }
+            FetchCanvasError::DriverErr(err) => {
+                panic!("err in video encoding: {err}");
+            }
+        }
+    }
+}
+
+#[hiarc_safer_arc_mutex]
+impl frame_fetcher_plugin::BackendFrameFetcher for RawEncoderImpl {
+    #[hiarc_trait_is_immutable_self]
+    fn next_frame(&mut self, frame_data: BackendAudioFrame) {
+        let _ = self.audio_sender.send(frame_data);
+    }
+
+    fn current_fetch_index(&self) -> FetchSoundManagerIndex {
+        FetchSoundManagerIndex::Offair(self.audio_frame_buffer_id)
+    }
+
+    fn fetch_err(&self, err: FetchSoundManagerError) {
+        match err {
+            FetchSoundManagerError::SoundManagerNotFound => {
+                // ignore for now
+            }
+            FetchSoundManagerError::DriverErr(err) => {
+                panic!("err in audio encoding: {err}");
+            }
+        }
+    }
+}
+
+/// Writes uncompressed video & audio, see the module documentation.
+pub struct RawEncoder {
+    backend: Rc<GraphicsBackend>,
+    sound_backend: Rc<SoundBackend>,
+    encoder: Arc<RawEncoderImpl>,
+}
+
+impl AudioVideoEncoder for RawEncoder {
+    fn new(
+        video_frame_buffer_id: OffscreenCanvasId,
+        audio_frame_buffer_id: OffairSoundManagerId,
+        file_path: &Path,
+        backend: &Rc<GraphicsBackend>,
+        sound_backend: &Rc<SoundBackend>,
+        encoder_settings: EncoderSettings,
+    ) -> anyhow::Result<Self> {
+        let encoder = Arc::new(RawEncoderImpl::new(
+            video_frame_buffer_id,
+            audio_frame_buffer_id,
+            file_path,
+            encoder_settings,
+        )?);
+
+        backend.attach_frame_fetcher("av-encoder".into(), encoder.clone())?;
+        sound_backend.attach_frame_fetcher("av-encoder".into(), encoder.clone())?;
+
+        Ok(Self {
+            backend: backend.clone(),
+            sound_backend: sound_backend.clone(),
+            encoder,
+        })
+    }
+
+    fn overloaded(&self) -> bool {
+        self.encoder.overloaded()
+    }
+}
+
+impl Drop for RawEncoder {
+    fn drop(&mut self) {
+        let _ = self.backend.detach_frame_fetcher("av-encoder".into());
+        let _ = self.sound_backend.detach_frame_fetcher("av-encoder".into());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{rgba_to_yuv420, write_wav_header, WAV_HEADER_SIZE};
+
+    #[test]
+    fn yuv420() {
+        let mut yuv = Vec::new();
+
+        // white & black 2x2 blocks
+        let white = [255; 2 * 2 * 4];
+        rgba_to_yuv420(&white, 2, 2, &mut yuv).unwrap();
+        assert_eq!(yuv, vec![255, 255, 255, 255, 128, 128]);
+        let black = [0; 2 * 2 * 4];
+        rgba_to_yuv420(&black, 2, 2, &mut yuv).unwrap();
+        assert_eq!(yuv, vec![0, 0, 0, 0, 128, 128]);
+
+        // the chroma planes are rounded up for odd sizes
+        let red: Vec<u8> = [255, 0, 0, 255].repeat(3 * 3);
+        rgba_to_yuv420(&red, 3, 3, &mut yuv).unwrap();
+        assert_eq!(yuv.len(), 3 * 3 + 2 * 2 * 2);
+        assert!(yuv[..9].iter().all(|&y| y == 76));
+        assert!(yuv[9..13].iter().all(|&u| u == 85));
+        assert!(yuv[13..].iter().all(|&v| v == 255));
+
+        // the buffer must match the size
+        assert!(rgba_to_yuv420(&red, 4, 3, &mut yuv).is_err());
+        assert!(rgba_to_yuv420(&red, 2, 3, &mut yuv).is_err());
+        assert!(rgba_to_yuv420(&red, usize::MAX, 2, &mut yuv).is_err());
+    }
+
+    fn u32_at(header: &[u8], off: usize) -> u32 {
+        u32::from_le_bytes(header[off..off + 4].try_into().unwrap())
+    }
+
+    fn u64_at(header: &[u8], off: usize) -> u64 {
+        u64::from_le_bytes(header[off..off + 8].try_into().unwrap())
+    }
+
+    #[test]
+    fn wav_header() {
+        let mut header = Vec::new();
+        write_wav_header(&mut header, 48000, 100).unwrap();
+        assert_eq!(header.len() as u64, WAV_HEADER_SIZE);
+        assert_eq!(&header[0..4], b"RIFF");
+        assert_eq!(u32_at(&header, 4), 72 + 800);
+        assert_eq!(&header[8..16], b"WAVEJUNK");
+        assert_eq!(&header[48..52], b"fmt ");
+        assert_eq!(u32_at(&header, 60), 48000);
+        // byte rate
+        assert_eq!(u32_at(&header, 64), 48000 * 8);
+        assert_eq!(&header[72..76], b"data");
+        assert_eq!(u32_at(&header, 76), 800);
+
+        // too big for a WAV file
+        let num_samples = u32::MAX as u64;
+        let mut header = Vec::new();
+        write_wav_header(&mut header, 48000, num_samples).unwrap();
+        assert_eq!(header.len() as u64, WAV_HEADER_SIZE);
+        assert_eq!(&header[0..4], b"RF64");
+        assert_eq!(u32_at(&header, 4), u32::MAX);
+        assert_eq!(&header[8..16], b"WAVEds64");
+        assert_eq!(u64_at(&header, 20), 72 + num_samples * 8);
+        assert_eq!(u64_at(&header, 28), num_samples * 8);
+        assert_eq!(u64_at(&header, 36), num_samples);
+        assert_eq!(&header[72..76], b"data");
+        assert_eq!(u32_at(&header, 76), u32::MAX);
+
+        // too big for any file
+        assert!(write_wav_header(&mut Vec::new(), 48000, u64::MAX / 4).is_err());
+        assert!(write_wav_header(&mut Vec::new(), u32::MAX, 0).is_err());
+    }
+}
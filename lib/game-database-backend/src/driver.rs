@@ -0,0 +1,455 @@
+//! Thin wrappers around the sqlx types of all supported database drivers.
+
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use ddnet_account_sql::any::{AnyPool, AnyQuery};
+use game_database::types::{DbType, UnixUtcTimestamp};
+use sqlx::{
+    mysql::{MySqlArguments, MySqlRow, MySqlStatement},
+    postgres::{PgArguments, PgRow, PgStatement},
+    query::Query,
+    sqlite::{SqliteArguments, SqliteRow, SqliteStatement},
+    Executor, MySql, MySqlPool, PgPool, Postgres, Row, Sqlite, SqlitePool, Statement,
+};
+
+/// Binds all arguments to a sqlx query.
+macro_rules! bind_args {
+    ($qry:expr, $args:expr) => {{
+        let mut qry = $qry;
+        for arg in $args.iter() {
+            match arg {
+                DbType::I16(v) => qry = qry.bind(*v),
+                DbType::I32(v) => qry = qry.bind(*v),
+                DbType::I64(v) => qry = qry.bind(*v),
+                DbType::F32(v) => qry = qry.bind(*v),
+                DbType::F64(v) => qry = qry.bind(*v),
+                DbType::Bool(v) => qry = qry.bind(*v),
+                DbType::String(v) => qry = qry.bind(v),
+                DbType::Vec(v) => qry = qry.bind(v),
+                DbType::DateTime(v) => {
+                    let time_stamp =
+                        <sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc>>::from_timestamp(
+                            v.secs as i64,
+                            v.subsec_nanos,
+                        )
+                        .ok_or_else(|| anyhow!("not a valid utc timestamp"))?;
+                    qry = qry.bind(time_stamp);
+                }
+            }
+        }
+        qry
+    }};
+}
+
+/// Reads a single column of a sqlx row.
+macro_rules! get_column {
+    ($row:expr, $name:expr, $ty:expr) => {
+        match $ty {
+            DbType::I16(_) => DbType::I16($row.try_get::<i16, _>($name)?),
+            DbType::I32(_) => DbType::I32($row.try_get::<i32, _>($name)?),
+            DbType::I64(_) => DbType::I64($row.try_get::<i64, _>($name)?),
+            DbType::F32(_) => DbType::F32($row.try_get::<f32, _>($name)?),
+            DbType::F64(_) => DbType::F64($row.try_get::<f64, _>($name)?),
+            DbType::Bool(_) => DbType::Bool($row.try_get::<bool, _>($name)?),
+            DbType::String(_) => DbType::String($row.try_get::<String, _>($name)?),
+            DbType::Vec(_) => DbType::Vec($row.try_get::<Vec<u8>, _>($name)?),
+            DbType::DateTime(_) => {
+                let time_stamp: sqlx::types::chrono::DateTime<sqlx::types::chrono::Utc> =
+                    $row.try_get($name)?;
+                DbType::DateTime(UnixUtcTimestamp {
+                    secs: time_stamp.timestamp() as u64,
+                    subsec_nanos: time_stamp.timestamp_subsec_nanos(),
+                })
+            }
+        }
+    };
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum DbPool<'a> {
+    MySql(&'a MySqlPool),
+    Sqlite(&'a SqlitePool),
+    Postgres(&'a PgPool),
+}
+
+impl<'a> From<&'a AnyPool> for DbPool<'a> {
+    fn from(pool: &'a AnyPool) -> Self {
+        match pool {
+            AnyPool::MySql(pool) => Self::MySql(pool),
+            AnyPool::Sqlite(pool) => Self::Sqlite(pool),
+        }
+    }
+}
+
+impl DbPool<'_> {
+    pub async fn prepare(&self, sql: &str) -> anyhow::Result<DbStatement> {
+        Ok(match self {
+            Self::MySql(pool) => {
+                let mut connection = pool.acquire().await?;
+                DbStatement::MySql(Statement::to_owned(&(&mut *connection).prepare(sql).await?))
+            }
+            Self::Sqlite(pool) => {
+                let mut connection = pool.acquire().await?;
+                DbStatement::Sqlite(Statement::to_owned(&(&mut *connection).prepare(sql).await?))
+            }
+            Self::Postgres(pool) => {
+                let mut connection = pool.acquire().await?;
+                DbStatement::Postgres(Statement::to_owned(&(&mut *connection).prepare(sql).await?))
+            }
+        })
+    }
+
+    pub async fn begin(&self) -> anyhow::Result<DbTransaction> {
+        Ok(match self {
+            Self::MySql(pool) => DbTransaction::MySql(pool.begin().await?),
+            Self::Sqlite(pool) => DbTransaction::Sqlite(pool.begin().await?),
+            Self::Postgres(pool) => DbTransaction::Postgres(pool.begin().await?),
+        })
+    }
+}
+
+pub enum DbStatement {
+    MySql(MySqlStatement<'static>),
+    Sqlite(SqliteStatement<'static>),
+    Postgres(PgStatement<'static>),
+}
+
+impl DbStatement {
+    pub fn query<'a>(&'a self, args: &'a [DbType]) -> anyhow::Result<DbQuery<'a>> {
+        Ok(match self {
+            Self::MySql(stmt) => DbQuery::MySql(bind_args!(stmt.query(), args)),
+            Self::Sqlite(stmt) => DbQuery::Sqlite(bind_args!(stmt.query(), args)),
+            Self::Postgres(stmt) => DbQuery::Postgres(bind_args!(stmt.query(), args)),
+        })
+    }
+
+    /// The query without arguments for drivers supported by
+    /// [`AnyQuery`], e.g. for database setups.
+    pub fn any_query(&self) -> anyhow::Result<AnyQuery<'_>> {
+        Ok(match self {
+            Self::MySql(stmt) => AnyQuery::MySql(stmt.query()),
+            Self::Sqlite(stmt) => AnyQuery::Sqlite(stmt.query()),
+            Self::Postgres(_) => {
+                return Err(anyhow!(
+                    "postgres statements are not supported by any query"
+                ))
+            }
+        })
+    }
+}
+
+pub enum DbRow {
+    MySql(MySqlRow),
+    Sqlite(SqliteRow),
+    Postgres(PgRow),
+}
+
+impl DbRow {
+    pub fn get(&self, name: &str, ty: &DbType) -> anyhow::Result<DbType> {
+        Ok(match self {
+            Self::MySql(row) => get_column!(row, name, ty),
+            Self::Sqlite(row) => get_column!(row, name, ty),
+            Self::Postgres(row) => get_column!(row, name, ty),
+        })
+    }
+
+    pub fn get_all(
+        &self,
+        result_mapping: &HashMap<String, DbType>,
+    ) -> anyhow::Result<HashMap<String, DbType>> {
+        result_mapping
+            .iter()
+            .map(|(name, ty)| anyhow::Ok((name.clone(), self.get(name, ty)?)))
+            .collect()
+    }
+}
+
+pub enum DbTransaction {
+    MySql(sqlx::Transaction<'static, MySql>),
+    Sqlite(sqlx::Transaction<'static, Sqlite>),
+    Postgres(sqlx::Transaction<'static, Postgres>),
+}
+
+impl DbTransaction {
+    pub async fn commit(self) -> anyhow::Result<()> {
+        match self {
+            Self::MySql(transaction) => transaction.commit().await?,
+            Self::Sqlite(transaction) => transaction.commit().await?,
+            Self::Postgres(transaction) => transaction.commit().await?,
+        }
+        Ok(())
+    }
+
+    pub async fn rollback(self) -> anyhow::Result<()> {
+        match self {
+            Self::MySql(transaction) => transaction.rollback().await?,
+            Self::Sqlite(transaction) => transaction.rollback().await?,
+            Self::Postgres(transaction) => transaction.rollback().await?,
+        }
+        Ok(())
+    }
+}
+
+pub enum DbQuery<'a> {
+    MySql(Query<'a, MySql, MySqlArguments>),
+    Sqlite(Query<'a, Sqlite, SqliteArguments<'a>>),
+    Postgres(Query<'a, Postgres, PgArguments>),
+}
+
+impl DbQuery<'_> {
+    pub async fn fetch_optional(self, pool: DbPool<'_>) -> anyhow::Result<Option<DbRow>> {
+        Ok(match (self, pool) {
+            (Self::MySql(qry), DbPool::MySql(pool)) => {
+                qry.fetch_optional(pool).await?.map(DbRow::MySql)
+            }
+            (Self::Sqlite(qry), DbPool::Sqlite(pool)) => {
+                qry.fetch_optional(pool).await?.map(DbRow::Sqlite)
+            }
+            (Self::Postgres(qry), DbPool::Postgres(pool)) => {
+                qry.fetch_optional(pool).await?.map(DbRow::Postgres)
+            }
+            _ => return Err(anyhow!("statement and database use different drivers")),
+        })
+    }
+
+    pub async fn fetch_one(self, pool: DbPool<'_>) -> anyhow::Result<DbRow> {
+        Ok(match (self, pool) {
+            (Self::MySql(qry), DbPool::MySql(pool)) => DbRow::MySql(qry.fetch_one(pool).await?),
+            (Self::Sqlite(qry), DbPool::Sqlite(pool)) => DbRow::Sqlite(qry.fetch_one(pool).await?),
+            (Self::Postgres(qry), DbPool::Postgres(pool)) => {
+                DbRow::Postgres(qry.fetch_one(pool).await?)
+            }
+            _ => return Err(anyhow!("statement and database use different drivers")),
+        })
+    }
+
+    pub async fn fetch_all(self, pool: DbPool<'_>) -> anyhow::Result<Vec<DbRow>> {
+        Ok(match (self, pool) {
+            (Self::MySql(qry), DbPool::MySql(pool)) => qry
+                .fetch_all(pool)
+                .await?
+                .into_iter()
+                .map(DbRow::MySql)
+                .collect(),
+            (Self::Sqlite(qry), DbPool::Sqlite(pool)) => qry
+                .fetch_all(pool)
+                .await?
+                .into_iter()
+                .map(DbRow::Sqlite)
+                .collect(),
+            (Self::Postgres(qry), DbPool::Postgres(pool)) => qry
+                .fetch_all(pool)
+                .await?
+                .into_iter()
+                .map(DbRow::Postgres)
+                .collect(),
+            _ => return Err(anyhow!("statement and database use different drivers")),
+        })
+    }
+
+    /// Returns the affected rows count
+    pub async fn execute(self, pool: DbPool<'_>) -> anyhow::Result<u64> {
+        Ok(match (self, pool) {
+            (Self::MySql(qry), DbPool::MySql(pool)) => qry.execute(pool).await?.rows_affected(),
+            (Self::Sqlite(qry), DbPool::Sqlite(pool)) => qry.execute(pool).await?.rows_affected(),
+            (Self::Postgres(qry), DbPool::Postgres(pool)) => {
+                qry.execute(pool).await?.rows_affected()
+            }
+            _ => return Err(anyhow!("statement and database use different drivers")),
+        })
+    }
+
+    pub async fn fetch_all_in(self, transaction: &mut DbTransaction) -> anyhow::Result<Vec<DbRow>> {
+        Ok(match (self, transaction) {
+            (Self::MySql(qry), DbTransaction::MySql(transaction)) => qry
+                .fetch_all(&mut **transaction)
+                .await?
+                .into_iter()
+                .map(DbRow::MySql)
+                .collect(),
+            (Self::Sqlite(qry), DbTransaction::Sqlite(transaction)) => qry
+                .fetch_all(&mut **transaction)
+                .await?
+                .into_iter()
+                .map(DbRow::Sqlite)
+                .collect(),
+            (Self::Postgres(qry), DbTransaction::Postgres(transaction)) => qry
+                .fetch_all(&mut **transaction)
+                .await?
+                .into_iter()
+                .map(DbRow::Postgres)
+                .collect(),
+            _ => return Err(anyhow!("statement and transaction use different drivers")),
+        })
+    }
+
+    /// Returns the affected rows count
+    pub async fn execute_in(self, transaction: &mut DbTransaction) -> anyhow::Result<u64> {
+        Ok(match (self, transaction) {
+            (Self::MySql(qry), DbTransaction::MySql(transaction)) => {
+                qry.execute(&mut **transaction).await?.rows_affected()
+            }
+            (Self::Sqlite(qry), DbTransaction::Sqlite(transaction)) => {
+                qry.execute(&mut **transaction).await?.rows_affected()
+            }
+            (Self::Postgres(qry), DbTransaction::Postgres(transaction)) => {
+                qry.execute(&mut **transaction).await?.rows_affected()
+            }
+            _ => return Err(anyhow!("statement and transaction use different drivers")),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use game_database::types::{DbType, UnixUtcTimestamp};
+    use sqlx::{postgres::PgPoolOptions, sqlite::SqlitePoolOptions};
+
+    use super::DbPool;
+
+    fn values() -> Vec<(&'static str, DbType)> {
+        vec![
+            ("i16", DbType::I16(-16)),
+            ("i32", DbType::I32(-32)),
+            ("i64", DbType::I64(-64)),
+            ("f32", DbType::F32(0.5)),
+            ("f64", DbType::F64(0.25)),
+            ("bool", DbType::Bool(true)),
+            ("string", DbType::String("name".into())),
+            ("vec", DbType::Vec(vec![1, 2, 3])),
+            (
+                "date_time",
+                DbType::DateTime(UnixUtcTimestamp {
+                    secs: 1_700_000_000,
+                    subsec_nanos: 0,
+                }),
+            ),
+        ]
+    }
+
+    /// Writes & reads every type through the driver, `create` must create
+    /// the `test_types` table with a column for every type.
+    async fn check_types(
+        pool: DbPool<'_>,
+        create: &str,
+        placeholder: impl Fn(usize) -> String,
+    ) -> anyhow::Result<()> {
+        pool.prepare(create)
+            .await?
+            .query(&[])?
+            .execute(pool)
+            .await?;
+
+        let (names, args): (Vec<_>, Vec<_>) = values().into_iter().unzip();
+        let insert = pool
+            .prepare(&format!(
+                "INSERT INTO test_types ({}) VALUES ({});",
+                names.join(", "),
+                (1..=names.len())
+                    .map(placeholder)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+            .await?;
+        assert_eq!(insert.query(&args)?.execute(pool).await?, 1);
+
+        let select = pool.prepare("SELECT * FROM test_types;").await?;
+        let result_mapping: HashMap<String, DbType> = values()
+            .into_iter()
+            .map(|(name, ty)| (name.to_string(), ty))
+            .collect();
+        let row = select.query(&[])?.fetch_one(pool).await?;
+        let res = row.get_all(&result_mapping)?;
+        for (name, value) in values() {
+            assert_eq!(format!("{:?}", res[name]), format!("{value:?}"));
+        }
+
+        // writes inside a transaction are only visible after the commit
+        let mut transaction = pool.begin().await?;
+        insert.query(&args)?.execute_in(&mut transaction).await?;
+        assert_eq!(
+            select
+                .query(&[])?
+                .fetch_all_in(&mut transaction)
+                .await?
+                .len(),
+            2
+        );
+        transaction.rollback().await?;
+        assert_eq!(select.query(&[])?.fetch_all(pool).await?.len(), 1);
+
+        // a type the column can't be read as
+        assert!(row.get("string", &DbType::I64(0)).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn sqlite_types() -> anyhow::Result<()> {
+        // a single connection, else every connection has its own database
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await?;
+        check_types(
+            DbPool::Sqlite(&pool),
+            "CREATE TABLE test_types (
+                i16 INTEGER, i32 INTEGER, i64 INTEGER, f32 REAL, f64 REAL,
+                bool BOOLEAN, string TEXT, vec BLOB, date_time DATETIME
+            );",
+            |_| "?".to_string(),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a postgres server, e.g. set POSTGRES_TEST_URL"]
+    async fn postgres_types() -> anyhow::Result<()> {
+        let url = std::env::var("POSTGRES_TEST_URL")
+            .unwrap_or_else(|_| "postgres://postgres@localhost/postgres".to_string());
+        // temporary tables only exist for a single connection
+        let pool = PgPoolOptions::new()
+            .max_connections(1)
+            .connect(&url)
+            .await?;
+        let pool = DbPool::Postgres(&pool);
+        check_types(
+            pool,
+            "CREATE TEMPORARY TABLE test_types (
+                i16 SMALLINT, i32 INTEGER, i64 BIGINT, f32 REAL, f64 DOUBLE PRECISION,
+                bool BOOLEAN, string TEXT, vec BYTEA, date_time TIMESTAMPTZ
+            );",
+            |index| format!("${index}"),
+        )
+        .await?;
+
+        // setups run through the any query, which has no postgres driver
+        let stmt = pool.prepare("SELECT 1;").await?;
+        assert!(stmt.any_query().is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn different_drivers() -> anyhow::Result<()> {
+        let sqlite = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await?;
+        let stmt = DbPool::Sqlite(&sqlite).prepare("SELECT 1;").await?;
+        assert!(stmt.any_query().is_ok());
+
+        // never connects, the drivers are compared first
+        let postgres = PgPoolOptions::new().connect_lazy("postgres://localhost/test")?;
+        let postgres = DbPool::Postgres(&postgres);
+        assert!(stmt.query(&[])?.fetch_optional(postgres).await.is_err());
+        assert!(stmt.query(&[])?.fetch_one(postgres).await.is_err());
+        assert!(stmt.query(&[])?.fetch_all(postgres).await.is_err());
+        assert!(stmt.query(&[])?.execute(postgres).await.is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use ddnet_account_sql::any::AnyPool;
+use game_database::{traits::DbKind, types::DbType};
+use parking_lot::Mutex;
+use sqlx::{sqlite::SqlitePool, Row};
+
+/// After a failed replay, the main database is not tried again
+/// for this amount of time.
+const REPLAY_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A single write that failed on a main database.
+#[derive(Debug, Clone)]
+pub struct JournalEntry {
+    /// Increases with every journaled write, which gives the replay order.
+    pub id: i64,
+    pub kind: DbKind,
+    pub sql: String,
+    pub args: Vec<DbType>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WriteJournalStatus {
+    /// Writes that still wait to be replayed into the main database.
+    pub pending: u64,
+    /// Writes that were replayed since the journal was opened.
+    pub replayed: u64,
+    /// Writes that the main database rejected during the replay.
+    /// These are removed from the journal.
+    pub dropped: u64,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct JournalState {
+    /// The amount of journaled writes per statement.
+    pending: HashMap<(DbKind, String), u64>,
+    replayed: u64,
+    dropped: u64,
+    last_error: Option<String>,
+    last_failed_replay: Option<Instant>,
+}
+
+impl JournalState {
+    fn remove_pending(&mut self, kind: DbKind, sql: &str) {
+        let key = (kind, sql.to_string());
+        if let Some(count) = self.pending.get_mut(&key) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                self.pending.remove(&key);
+            }
+        }
+    }
+}
+
+/// Journals writes that failed on a main database into the
+/// sqlite backup database, so they can be replayed later.
+pub struct WriteJournal {
+    pool: SqlitePool,
+    state: Mutex<JournalState>,
+    init: tokio::sync::OnceCell<()>,
+    /// Only one replay at a time, else the order of the writes is lost.
+    pub(crate) replay_lock: tokio::sync::Mutex<()>,
+}
+
+impl WriteJournal {
+    /// `None` if the backup database is not a sqlite database.
+    pub fn new(pool: &AnyPool) -> Option<Self> {
+        match pool {
+            AnyPool::Sqlite(pool) => Some(Self {
+                pool: pool.clone(),
+                state: Default::default(),
+                init: Default::default(),
+                replay_lock: Default::default(),
+            }),
+            AnyPool::MySql(_) => None,
+        }
+    }
+
+    /// Creates the journal table and loads the writes
+    /// of previous runs that were not replayed yet.
+    pub async fn init(&self) -> anyhow::Result<()> {
+        self.init
+            .get_or_try_init(|| async {
+                sqlx::query(include_str!("journal/setup.sql"))
+                    .execute(&self.pool)
+                    .await?;
+
+                let rows = sqlx::query(include_str!("journal/pending.sql"))
+                    .fetch_all(&self.pool)
+                    .await?;
+                let mut pending: HashMap<(DbKind, String), u64> = Default::default();
+                for row in rows {
+                    let kind: DbKind = serde_json::from_str(row.try_get::<&str, _>("kind")?)?;
+                    let sql: String = row.try_get("query")?;
+                    let count: i64 = row.try_get("count")?;
+                    pending.insert((kind, sql), count as u64);
+                }
+                self.state.lock().pending = pending;
+                anyhow::Ok(())
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Whether writes of this statement wait for their replay.
+    /// New writes of such a statement must be journaled too,
+    /// so they are not overtaken by older writes.
+    pub fn has_pending(&self, kind: DbKind, sql: &str) -> bool {
+        self.state
+            .lock()
+            .pending
+            .contains_key(&(kind, sql.to_string()))
+    }
+
+    pub fn should_replay(&self) -> bool {
+        let state = self.state.lock();
+        !state.pending.is_empty()
+            && state
+                .last_failed_replay
+                .is_none_or(|time| time.elapsed() >= REPLAY_RETRY_INTERVAL)
+    }
+
+    pub async fn push(&self, kind: DbKind, sql: &str, args: &[DbType]) -> anyhow::Result<()> {
+        let kind_str = serde_json::to_string(&kind)?;
+        let args_str = serde_json::to_string(args)?;
+
+        // count the write before it is inserted, so concurrent writes
+        // of the same statement are journaled after this one.
+        *self
+            .state
+            .lock()
+            .pending
+            .entry((kind, sql.to_string()))
+            .or_default() += 1;
+
+        let res = sqlx::query(include_str!("journal/push.sql"))
+            .bind(kind_str)
+            .bind(sql)
+            .bind(args_str)
+            .execute(&self.pool)
+            .await;
+        if let Err(err) = res {
+            self.state.lock().remove_pending(kind, sql);
+            return Err(err.into());
+        }
+        Ok(())
+    }
+
+    /// The oldest journaled writes.
+    pub async fn entries(&self, limit: usize) -> anyhow::Result<Vec<JournalEntry>> {
+        sqlx::query(include_str!("journal/entries.sql"))
+            .bind(limit as i64)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|row| {
+                Ok(JournalEntry {
+                    id: row.try_get("id")?,
+                    kind: serde_json::from_str(row.try_get::<&str, _>("kind")?)?,
+                    sql: row.try_get("query")?,
+                    args: serde_json::from_str(row.try_get::<&str, _>("args")?)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn remove(&self, entry: &JournalEntry) -> anyhow::Result<()> {
+        sqlx::query(include_str!("journal/remove.sql"))
+            .bind(entry.id)
+            .execute(&self.pool)
+            .await?;
+        self.state.lock().remove_pending(entry.kind, &entry.sql);
+        Ok(())
+    }
+
+    /// The write was replayed into the main database.
+    pub async fn replayed(&self, entry: &JournalEntry) -> anyhow::Result<()> {
+        self.remove(entry).await?;
+        let mut state = self.state.lock();
+        state.replayed += 1;
+        state.last_failed_replay = None;
+        Ok(())
+    }
+
+    /// The main database rejected the write, replaying it again would
+    /// fail forever.
+    pub async fn dropped(&self, entry: &JournalEntry, err: &anyhow::Error) -> anyhow::Result<()> {
+        self.remove(entry).await?;
+        let mut state = self.state.lock();
+        state.dropped += 1;
+        state.last_error = Some(format!("dropped write \"{}\": {err}", entry.sql.trim()));
+        Ok(())
+    }
+
+    /// The main database is still not reachable.
+    pub fn replay_failed(&self, err: &anyhow::Error) {
+        let mut state = self.state.lock();
+        state.last_error = Some(err.to_string());
+        state.last_failed_replay = Some(Instant::now());
+    }
+
+    pub fn status(&self) -> WriteJournalStatus {
+        let state = self.state.lock();
+        WriteJournalStatus {
+            pending: state.pending.values().sum(),
+            replayed: state.replayed,
+            dropped: state.dropped,
+            last_error: state.last_error.clone(),
+        }
+    }
+}
@@ -0,0 +1,11 @@
+SELECT
+    id,
+    kind,
+    query,
+    args
+FROM
+    game_db_write_journal
+ORDER BY
+    id ASC
+LIMIT
+    ?;
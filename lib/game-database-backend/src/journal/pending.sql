@@ -0,0 +1,9 @@
+SELECT
+    kind,
+    query,
+    COUNT(*) AS count
+FROM
+    game_db_write_journal
+GROUP BY
+    kind,
+    query;
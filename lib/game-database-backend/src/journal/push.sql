@@ -0,0 +1,7 @@
+INSERT INTO game_db_write_journal (
+    kind,
+    query,
+    args
+)
+VALUES
+    (?, ?, ?);
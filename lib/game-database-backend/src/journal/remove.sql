@@ -0,0 +1,4 @@
+DELETE FROM
+    game_db_write_journal
+WHERE
+    id = ?;
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS game_db_write_journal (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    query TEXT NOT NULL,
+    args TEXT NOT NULL
+);
@@ -4,164 +4,397 @@ use std::{
 };
 
 use anyhow::anyhow;
-use ddnet_account_sql::{
-    any::{AnyConnection, AnyQuery, AnyRow, AnyStatement},
-    version::{get_version, set_version},
-};
+use ddnet_account_sql::version::{get_version, set_version};
+use driver::{DbPool, DbRow, DbStatement, DbTransaction};
 use game_database::{
+    migration::{MigrationState, MigrationStatus, Migrations},
     statement::{QueryProperties, StatementDriverProps},
-    traits::{DbInterface, DbKind},
-    types::{DbType, UnixUtcTimestamp},
+    traits::{DbInterface, DbKind, DbKindExtra},
+    types::{DbType, ExecuteResult},
 };
+use journal::{JournalEntry, WriteJournal, WriteJournalStatus};
 use parking_lot::Mutex;
 use sql::database::Database;
-use sqlx::Executor;
-use sqlx::Row;
 use sqlx::Statement;
 
+pub mod driver;
+pub mod journal;
+pub mod migration;
+
+/// How many journaled writes are loaded at once during a replay.
+const REPLAY_BATCH_SIZE: usize = 64;
+
 #[derive(Clone)]
 pub struct CachedStatement {
     kind: DbKind,
-    stmt: Arc<AnyStatement<'static>>,
+    stmt: Arc<DbStatement>,
     qry_props: QueryProperties,
+    sql: String,
+}
+
+#[derive(Clone)]
+struct CachedTransaction {
+    kind: DbKind,
+    transaction: Arc<tokio::sync::Mutex<DbTransaction>>,
+}
+
+/// Whether the error indicates that the database is not reachable,
+/// as opposed to the database rejecting the statement.
+fn is_connection_error(err: &anyhow::Error) -> bool {
+    err.chain().any(|err| {
+        err.downcast_ref::<sqlx::Error>().is_some_and(|err| {
+            matches!(
+                err,
+                sqlx::Error::Io(_)
+                    | sqlx::Error::Tls(_)
+                    | sqlx::Error::Protocol(_)
+                    | sqlx::Error::PoolTimedOut
+                    | sqlx::Error::PoolClosed
+                    | sqlx::Error::WorkerCrashed
+            )
+        })
+    })
 }
 
 pub struct GameDbBackend {
     db: Arc<Database>,
     statements: Mutex<HashMap<u64, CachedStatement>>,
+    transactions: Mutex<HashMap<u64, CachedTransaction>>,
     id_generator: AtomicU64,
+
+    /// Writes to a main database that failed are journaled into the
+    /// sqlite backup database, if one exists.
+    journal: Option<WriteJournal>,
+
+    /// The registered migrations by their name.
+    migrations: Mutex<BTreeMap<String, Migrations>>,
+    /// Only one migration may run at a time.
+    migration_lock: tokio::sync::Mutex<()>,
+    auto_migrate: bool,
 }
 
 impl GameDbBackend {
     pub fn new(db: Arc<Database>) -> anyhow::Result<Self> {
+        let journal = db
+            .pools
+            .get(&DbKind::Sqlite(DbKindExtra::Backup))
+            .and_then(WriteJournal::new);
         Ok(Self {
             db,
             statements: Default::default(),
+            transactions: Default::default(),
             id_generator: Default::default(),
+            journal,
+            migrations: Default::default(),
+            migration_lock: Default::default(),
+            auto_migrate: true,
         })
     }
 
-    fn get_query<'a>(
-        &self,
-        stmt: &'a CachedStatement,
-        args: &'a [DbType],
-    ) -> anyhow::Result<AnyQuery<'a>> {
-        Ok(match stmt.stmt.as_ref() {
-            AnyStatement::MySql(stmt) => {
-                let mut qry = stmt.query();
-                for arg in args.iter() {
-                    match arg {
-                        DbType::I16(v) => qry = qry.bind(*v),
-                        DbType::I32(v) => qry = qry.bind(*v),
-                        DbType::I64(v) => qry = qry.bind(*v),
-                        DbType::F32(v) => qry = qry.bind(*v),
-                        DbType::F64(v) => qry = qry.bind(*v),
-                        DbType::Bool(v) => qry = qry.bind(*v),
-                        DbType::String(v) => qry = qry.bind(v),
-                        DbType::Vec(v) => qry = qry.bind(v),
-                        DbType::DateTime(v) => {
-                            let time_stamp = <sqlx::types::chrono::DateTime<
-                                sqlx::types::chrono::Utc,
-                            >>::from_timestamp(
-                                v.secs as i64, v.subsec_nanos
-                            )
-                            .ok_or_else(|| anyhow!("not a valid utc timestamp"))?;
-                            qry = qry.bind(time_stamp);
-                        }
+    /// Whether pending migrations are applied as soon as a mod registers
+    /// them. If disabled, they are only applied by [`Self::migrate_up`].
+    pub fn with_auto_migrate(mut self, auto_migrate: bool) -> Self {
+        self.auto_migrate = auto_migrate;
+        self
+    }
+
+    /// The status of the write journal, `None` if no sqlite backup
+    /// database exists.
+    pub fn journal_status(&self) -> Option<WriteJournalStatus> {
+        self.journal.as_ref().map(|journal| journal.status())
+    }
+
+    /// Replays the journaled writes into the main databases in the
+    /// order they were journaled.
+    ///
+    /// Stops at the first write that fails because a main database is not
+    /// reachable. Writes the main database rejects are dropped.
+    /// Returns the number of replayed writes, `None` if there is no
+    /// journal or another replay is already running.
+    pub async fn replay_journal(&self) -> anyhow::Result<Option<u64>> {
+        let Some(journal) = &self.journal else {
+            return Ok(None);
+        };
+        journal.init().await?;
+        let Ok(_replay) = journal.replay_lock.try_lock() else {
+            return Ok(None);
+        };
+
+        let mut replayed = 0;
+        let mut stmts: HashMap<(DbKind, String), Arc<DbStatement>> = Default::default();
+        loop {
+            let entries = journal.entries(REPLAY_BATCH_SIZE).await?;
+            if entries.is_empty() {
+                break;
+            }
+            for entry in entries {
+                match self.replay_entry(&mut stmts, &entry).await {
+                    Ok(_) => {
+                        journal.replayed(&entry).await?;
+                        replayed += 1;
+                    }
+                    Err(err) if is_connection_error(&err) => {
+                        journal.replay_failed(&err);
+                        return Err(err);
+                    }
+                    Err(err) => {
+                        journal.dropped(&entry, &err).await?;
                     }
                 }
-                AnyQuery::MySql(qry)
             }
-            AnyStatement::Sqlite(stmt) => {
-                let mut qry = stmt.query();
-                for arg in args.iter() {
-                    match arg {
-                        DbType::I16(v) => qry = qry.bind(*v),
-                        DbType::I32(v) => qry = qry.bind(*v),
-                        DbType::I64(v) => qry = qry.bind(*v),
-                        DbType::F32(v) => qry = qry.bind(*v),
-                        DbType::F64(v) => qry = qry.bind(*v),
-                        DbType::Bool(v) => qry = qry.bind(*v),
-                        DbType::String(v) => qry = qry.bind(v),
-                        DbType::Vec(v) => qry = qry.bind(v),
-                        DbType::DateTime(v) => {
-                            let time_stamp = <sqlx::types::chrono::DateTime<
-                                sqlx::types::chrono::Utc,
-                            >>::from_timestamp(
-                                v.secs as i64, v.subsec_nanos
-                            )
-                            .ok_or_else(|| anyhow!("not a valid utc timestamp"))?;
-                            qry = qry.bind(time_stamp);
-                        }
-                    }
+        }
+        Ok(Some(replayed))
+    }
+
+    /// Registers migrations without applying them,
+    /// e.g. to manage them with [`Self::migrate_up`] & [`Self::migrate_down`] only.
+    pub fn register_migrations(&self, migrations: Migrations) -> anyhow::Result<()> {
+        migrations.validate()?;
+        self.migrations
+            .lock()
+            .insert(migrations.name.clone(), migrations);
+        Ok(())
+    }
+
+    /// The state of all registered migrations on every database.
+    ///
+    /// Unlike [`Self::migrate_up`] this never fails because of modified migrations,
+    /// they are reported as [`MigrationState::ChecksumMismatch`].
+    pub async fn migration_status(&self) -> anyhow::Result<Vec<MigrationStatus>> {
+        let all_migrations: Vec<_> = self.migrations.lock().values().cloned().collect();
+        let mut res = Vec::new();
+        for migrations in all_migrations {
+            for kind in &migrations.kinds {
+                let applied = migration::applied(self.pool(kind)?, &migrations.name).await?;
+                res.extend(migration::status(&migrations, *kind, &applied));
+            }
+        }
+        Ok(res)
+    }
+
+    /// Applies all pending migrations in order of their versions.
+    /// With `dry_run` nothing is applied.
+    ///
+    /// Returns the migrations that were applied.
+    pub async fn migrate_up(&self, dry_run: bool) -> anyhow::Result<Vec<MigrationStatus>> {
+        let _migration = self.migration_lock.lock().await;
+        let all_migrations: Vec<_> = self.migrations.lock().values().cloned().collect();
+        let mut res = Vec::new();
+        for migrations in all_migrations {
+            res.extend(self.migrate_up_impl(&migrations, dry_run).await?);
+        }
+        Ok(res)
+    }
+
+    async fn migrate_up_impl(
+        &self,
+        migrations: &Migrations,
+        dry_run: bool,
+    ) -> anyhow::Result<Vec<MigrationStatus>> {
+        let mut res = Vec::new();
+        for kind in &migrations.kinds {
+            let pool = self.pool(kind)?;
+            let applied = migration::applied(pool, &migrations.name).await?;
+            let status = migration::status(migrations, *kind, &applied);
+            if let Some(modified) = status
+                .iter()
+                .find(|status| status.state == MigrationState::ChecksumMismatch)
+            {
+                return Err(anyhow!(
+                    "migration \"{}\" of \"{}\" was modified after it was applied to {:?}",
+                    modified.name,
+                    migrations.name,
+                    kind
+                ));
+            }
+            for status in status
+                .into_iter()
+                .filter(|status| status.state == MigrationState::Pending)
+            {
+                if !dry_run {
+                    let migration = migrations
+                        .migrations
+                        .iter()
+                        .find(|migration| migration.version == status.version)
+                        .ok_or_else(|| anyhow!("pending migration was not registered"))?;
+                    migration::apply(pool, &migrations.name, kind, migration).await?;
                 }
-                AnyQuery::Sqlite(qry)
+                res.push(status);
             }
-        })
+        }
+        Ok(res)
+    }
+
+    /// Reverts the newest applied migration of the registered migrations
+    /// with the given name on the given database.
+    /// With `dry_run` nothing is reverted.
+    ///
+    /// Returns the migration that was reverted.
+    pub async fn migrate_down(
+        &self,
+        name: &str,
+        kind: DbKind,
+        dry_run: bool,
+    ) -> anyhow::Result<MigrationStatus> {
+        let _migration = self.migration_lock.lock().await;
+        let migrations = self
+            .migrations
+            .lock()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("no migrations named \"{name}\" are registered"))?;
+        anyhow::ensure!(
+            migrations.kinds.contains(&kind),
+            "the migrations \"{name}\" are not applied to {kind:?}"
+        );
+        let pool = self.pool(&kind)?;
+        let applied = migration::applied(pool, name).await?;
+        let newest = migration::status(&migrations, kind, &applied)
+            .into_iter()
+            .rev()
+            .find(|status| status.state != MigrationState::Pending)
+            .ok_or_else(|| anyhow!("no migration of \"{name}\" was applied to {kind:?}"))?;
+        let migration = migrations
+            .migrations
+            .iter()
+            .find(|migration| migration.version == newest.version)
+            .filter(|_| newest.state == MigrationState::Applied)
+            .ok_or_else(|| {
+                anyhow!(
+                    "migration \"{}\" of \"{}\" on {:?} can not be reverted: {:?}",
+                    newest.name,
+                    name,
+                    kind,
+                    newest.state
+                )
+            })?;
+        anyhow::ensure!(
+            migration.down.is_some(),
+            "migration \"{}\" of \"{}\" has no downgrade script",
+            migration.name,
+            name
+        );
+        if !dry_run {
+            migration::revert(pool, name, &kind, migration).await?;
+        }
+        Ok(newest)
+    }
+
+    async fn replay_entry(
+        &self,
+        stmts: &mut HashMap<(DbKind, String), Arc<DbStatement>>,
+        entry: &JournalEntry,
+    ) -> anyhow::Result<u64> {
+        let key = (entry.kind, entry.sql.clone());
+        let stmt = match stmts.get(&key) {
+            Some(stmt) => stmt.clone(),
+            None => {
+                let stmt = Arc::new(self.pool(&entry.kind)?.prepare(&entry.sql).await?);
+                stmts.insert(key, stmt.clone());
+                stmt
+            }
+        };
+        stmt.query(&entry.args)?
+            .execute(self.pool(&entry.kind)?)
+            .await
+    }
+
+    fn pool(&self, kind: &DbKind) -> anyhow::Result<DbPool<'_>> {
+        self.db
+            .pools
+            .get(kind)
+            .map(DbPool::from)
+            .or_else(|| self.db.postgres_pools.get(kind).map(DbPool::Postgres))
+            .ok_or_else(|| anyhow!("database of kind {kind:?} not active."))
+    }
+
+    fn statement(&self, unique_id: u64) -> anyhow::Result<CachedStatement> {
+        self.statements
+            .lock()
+            .get(&unique_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("no statement with id \"{}\" found", unique_id))
+    }
+
+    /// The transaction and the statement that should run inside it.
+    fn transaction_statement(
+        &self,
+        transaction_id: u64,
+        unique_id: u64,
+    ) -> anyhow::Result<(CachedStatement, CachedTransaction)> {
+        let stmt = self.statement(unique_id)?;
+        let transaction = self
+            .transactions
+            .lock()
+            .get(&transaction_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("no transaction with id \"{}\" found", transaction_id))?;
+        anyhow::ensure!(
+            stmt.kind == transaction.kind,
+            "statement of kind {:?} can not run inside a transaction of kind {:?}",
+            stmt.kind,
+            transaction.kind
+        );
+        Ok((stmt, transaction))
+    }
+
+    /// Removes the transaction, so it can be committed or rolled back.
+    fn take_transaction(&self, transaction_id: u64) -> anyhow::Result<DbTransaction> {
+        let transaction = self
+            .transactions
+            .lock()
+            .remove(&transaction_id)
+            .ok_or_else(|| anyhow!("no transaction with id \"{}\" found", transaction_id))?;
+        Arc::try_unwrap(transaction.transaction)
+            .map(|transaction| transaction.into_inner())
+            .map_err(|_| anyhow!("transaction \"{}\" is still in use", transaction_id))
     }
 
     fn get_result(
         stmt: &CachedStatement,
-        rows: impl Iterator<Item = AnyRow>,
+        rows: impl Iterator<Item = DbRow>,
     ) -> anyhow::Result<Vec<HashMap<String, DbType>>> {
-        rows.map(|row| {
-            stmt.qry_props
-                .result_mapping
-                .iter()
-                .map(|(name, ty)| {
-                    let val = match &row {
-                        AnyRow::MySql(row) => match ty {
-                            DbType::I16(_) => DbType::I16(row.try_get::<i16, _>(name.as_str())?),
-                            DbType::I32(_) => DbType::I32(row.try_get::<i32, _>(name.as_str())?),
-                            DbType::I64(_) => DbType::I64(row.try_get::<i64, _>(name.as_str())?),
-                            DbType::F32(_) => DbType::F32(row.try_get::<f32, _>(name.as_str())?),
-                            DbType::F64(_) => DbType::F64(row.try_get::<f64, _>(name.as_str())?),
-                            DbType::Bool(_) => DbType::Bool(row.try_get::<bool, _>(name.as_str())?),
-                            DbType::String(_) => {
-                                DbType::String(row.try_get::<String, _>(name.as_str())?)
-                            }
-                            DbType::Vec(_) => {
-                                DbType::Vec(row.try_get::<Vec<u8>, _>(name.as_str())?)
-                            }
-                            DbType::DateTime(_) => {
-                                let time_stamp: sqlx::types::chrono::DateTime<
-                                    sqlx::types::chrono::Utc,
-                                > = row.try_get(name.as_str())?;
-                                DbType::DateTime(UnixUtcTimestamp {
-                                    secs: time_stamp.timestamp() as u64,
-                                    subsec_nanos: time_stamp.timestamp_subsec_nanos(),
-                                })
-                            }
-                        },
-                        AnyRow::Sqlite(row) => match ty {
-                            DbType::I16(_) => DbType::I16(row.try_get::<i16, _>(name.as_str())?),
-                            DbType::I32(_) => DbType::I32(row.try_get::<i32, _>(name.as_str())?),
-                            DbType::I64(_) => DbType::I64(row.try_get::<i64, _>(name.as_str())?),
-                            DbType::F32(_) => DbType::F32(row.try_get::<f32, _>(name.as_str())?),
-                            DbType::F64(_) => DbType::F64(row.try_get::<f64, _>(name.as_str())?),
-                            DbType::Bool(_) => DbType::Bool(row.try_get::<bool, _>(name.as_str())?),
-                            DbType::String(_) => {
-                                DbType::String(row.try_get::<String, _>(name.as_str())?)
-                            }
-                            DbType::Vec(_) => {
-                                DbType::Vec(row.try_get::<Vec<u8>, _>(name.as_str())?)
-                            }
-                            DbType::DateTime(_) => {
-                                let time_stamp: sqlx::types::chrono::DateTime<
-                                    sqlx::types::chrono::Utc,
-                                > = row.try_get(name.as_str())?;
-                                DbType::DateTime(UnixUtcTimestamp {
-                                    secs: time_stamp.timestamp() as u64,
-                                    subsec_nanos: time_stamp.timestamp_subsec_nanos(),
-                                })
-                            }
-                        },
+        rows.map(|row| row.get_all(&stmt.qry_props.result_mapping))
+            .collect::<anyhow::Result<Vec<_>>>()
+    }
+
+    /// Postgres is not supported by the version helpers of the account system,
+    /// so it tracks the versions of the setups in its own table.
+    async fn setup_postgres(
+        pool: &sqlx::PgPool,
+        version_name: &str,
+        versioned_stmts: BTreeMap<i64, Vec<Arc<DbStatement>>>,
+    ) -> anyhow::Result<()> {
+        let mut transaction = pool.begin().await?;
+        sqlx::query(include_str!("postgres/setup_versions.sql"))
+            .execute(&mut *transaction)
+            .await?;
+        let mut version: i64 = sqlx::query_scalar(include_str!("postgres/get_version.sql"))
+            .bind(version_name)
+            .fetch_optional(&mut *transaction)
+            .await?
+            .unwrap_or_default();
+        for (stmts_version, stmts) in versioned_stmts {
+            if version < stmts_version {
+                for s in stmts {
+                    let DbStatement::Postgres(stmt) = s.as_ref() else {
+                        return Err(anyhow!(
+                            "a postgres database can only be setup by postgres statements"
+                        ));
                     };
-                    anyhow::Ok((name.clone(), val))
-                })
-                .collect::<anyhow::Result<HashMap<_, _>>>()
-        })
-        .collect::<anyhow::Result<Vec<_>>>()
+                    stmt.query().execute(&mut *transaction).await?;
+                }
+
+                sqlx::query(include_str!("postgres/set_version.sql"))
+                    .bind(version_name)
+                    .bind(stmts_version)
+                    .execute(&mut *transaction)
+                    .await?;
+                version = stmts_version;
+            }
+        }
+        transaction.commit().await?;
+        Ok(())
     }
 }
 
@@ -170,6 +403,7 @@ impl DbInterface for GameDbBackend {
     fn kinds(&self) -> HashSet<DbKind> {
         let mut res: HashSet<DbKind> = Default::default();
         res.extend(self.db.pools.keys());
+        res.extend(self.db.postgres_pools.keys());
         res
     }
 
@@ -218,14 +452,7 @@ impl DbInterface for GameDbBackend {
                             for (stmts_version, stmts) in versioned_stmts {
                                 if version < stmts_version {
                                     for s in stmts {
-                                        let qry = match s.as_ref() {
-                                            AnyStatement::MySql(stmt) => {
-                                                AnyQuery::MySql(stmt.query())
-                                            }
-                                            AnyStatement::Sqlite(stmt) => {
-                                                AnyQuery::Sqlite(stmt.query())
-                                            }
-                                        };
+                                        let qry = s.any_query()?;
                                         qry.execute(&mut con.con()).await?;
                                     }
 
@@ -238,9 +465,41 @@ impl DbInterface for GameDbBackend {
                         })
                     })
                     .await?;
+            } else if let Some(pool) = self.db.postgres_pools.get(&kind) {
+                Self::setup_postgres(pool, version_name, versioned_stmts).await?;
             }
         }
 
+        if let Some(journal) = &self.journal {
+            journal.init().await?;
+            // the main database might still be down, the replay is retried
+            // on the next write.
+            let _ = self.replay_journal().await;
+        }
+
+        Ok(())
+    }
+
+    async fn migrate(&self, migrations: Migrations) -> anyhow::Result<()> {
+        migrations.validate()?;
+
+        let _migration = self.migration_lock.lock().await;
+        // fails if applied migrations were modified, these must not be
+        // registered, else they could still be applied or reverted manually.
+        let pending = self.migrate_up_impl(&migrations, true).await?;
+        self.migrations
+            .lock()
+            .insert(migrations.name.clone(), migrations.clone());
+
+        if self.auto_migrate {
+            self.migrate_up_impl(&migrations, false).await?;
+        }
+        anyhow::ensure!(
+            self.auto_migrate || pending.is_empty(),
+            "{} migration(s) of \"{}\" are pending, but automatic migrations are disabled",
+            pending.len(),
+            migrations.name
+        );
         Ok(())
     }
 
@@ -250,22 +509,7 @@ impl DbInterface for GameDbBackend {
         kind: &DbKind,
         driver_props: &StatementDriverProps,
     ) -> anyhow::Result<u64> {
-        let pool = self
-            .db
-            .pools
-            .get(kind)
-            .ok_or_else(|| anyhow!("database of kind {kind:?} not active."))?;
-        let mut connection = pool.acquire().await?;
-        let connection = connection.acquire().await?;
-
-        let stm = match connection {
-            AnyConnection::MySql(con) => AnyStatement::MySql(sqlx::Statement::to_owned(
-                &con.prepare(&driver_props.sql).await?,
-            )),
-            AnyConnection::Sqlite(con) => AnyStatement::Sqlite(sqlx::Statement::to_owned(
-                &con.prepare(&driver_props.sql).await?,
-            )),
-        };
+        let stm = self.pool(kind)?.prepare(&driver_props.sql).await?;
 
         let unique_id = self
             .id_generator
@@ -276,6 +520,7 @@ impl DbInterface for GameDbBackend {
                 kind: *kind,
                 stmt: Arc::new(stm),
                 qry_props: query_props.clone(),
+                sql: driver_props.sql.clone(),
             },
         );
         Ok(unique_id)
@@ -290,32 +535,13 @@ impl DbInterface for GameDbBackend {
         unique_id: u64,
         args: Vec<DbType>,
     ) -> anyhow::Result<Option<HashMap<String, DbType>>> {
-        let stmt = self
-            .statements
-            .lock()
-            .get(&unique_id)
-            .cloned()
-            .ok_or_else(|| anyhow!("no statement with id \"{}\" found", unique_id))?;
-
-        let pool = self
-            .db
-            .pools
-            .get(&stmt.kind)
-            .ok_or_else(|| anyhow!("database of kind {:?} not active.", stmt.kind))?;
-        let mut connection = pool.acquire().await?;
-        let mut connection = connection.acquire().await?;
+        let stmt = self.statement(unique_id)?;
 
-        let qry = self.get_query(&stmt, &args)?;
+        let qry = stmt.stmt.query(&args)?;
 
-        qry.fetch_optional(&mut connection)
+        qry.fetch_optional(self.pool(&stmt.kind)?)
             .await?
-            .map(|res| {
-                Self::get_result(&stmt, [res].into_iter()).and_then(|res| {
-                    res.into_iter()
-                        .next()
-                        .ok_or_else(|| anyhow!("no result fetched"))
-                })
-            })
+            .map(|res| res.get_all(&stmt.qry_props.result_mapping))
             .transpose()
     }
 
@@ -324,30 +550,13 @@ impl DbInterface for GameDbBackend {
         unique_id: u64,
         args: Vec<DbType>,
     ) -> anyhow::Result<HashMap<String, DbType>> {
-        let stmt = self
-            .statements
-            .lock()
-            .get(&unique_id)
-            .cloned()
-            .ok_or_else(|| anyhow!("no statement with id \"{}\" found", unique_id))?;
+        let stmt = self.statement(unique_id)?;
 
-        let pool = self
-            .db
-            .pools
-            .get(&stmt.kind)
-            .ok_or_else(|| anyhow!("database of kind {:?} not active.", stmt.kind))?;
-        let mut connection = pool.acquire().await?;
-        let mut connection = connection.acquire().await?;
-
-        let qry = self.get_query(&stmt, &args)?;
-
-        Self::get_result(&stmt, [qry.fetch_one(&mut connection).await?].into_iter()).and_then(
-            |res| {
-                res.into_iter()
-                    .next()
-                    .ok_or_else(|| anyhow!("no result fetched"))
-            },
-        )
+        let qry = stmt.stmt.query(&args)?;
+
+        qry.fetch_one(self.pool(&stmt.kind)?)
+            .await?
+            .get_all(&stmt.qry_props.result_mapping)
     }
 
     async fn fetch_all(
@@ -355,62 +564,490 @@ impl DbInterface for GameDbBackend {
         unique_id: u64,
         args: Vec<DbType>,
     ) -> anyhow::Result<Vec<HashMap<String, DbType>>> {
-        let stmt = self
-            .statements
-            .lock()
-            .get(&unique_id)
-            .cloned()
-            .ok_or_else(|| anyhow!("no statement with id \"{}\" found", unique_id))?;
+        let stmt = self.statement(unique_id)?;
 
-        let pool = self
-            .db
-            .pools
-            .get(&stmt.kind)
-            .ok_or_else(|| anyhow!("database of kind {:?} not active.", stmt.kind))?;
-        let mut connection = pool.acquire().await?;
-        let mut connection = connection.acquire().await?;
+        let qry = stmt.stmt.query(&args)?;
 
-        let qry = self.get_query(&stmt, &args)?;
+        Self::get_result(
+            &stmt,
+            qry.fetch_all(self.pool(&stmt.kind)?).await?.into_iter(),
+        )
+    }
+
+    async fn execute(&self, unique_id: u64, args: Vec<DbType>) -> anyhow::Result<ExecuteResult> {
+        let stmt = self.statement(unique_id)?;
+        let pool = self.pool(&stmt.kind)?;
 
-        Self::get_result(&stmt, qry.fetch_all(&mut connection).await?.into_iter())
+        let journal = self.journal.as_ref().filter(|_| {
+            matches!(
+                stmt.kind,
+                DbKind::MySql(DbKindExtra::Main)
+                    | DbKind::Sqlite(DbKindExtra::Main)
+                    | DbKind::Postgres(DbKindExtra::Main)
+            )
+        });
+        let Some(journal) = journal else {
+            return stmt
+                .stmt
+                .query(&args)?
+                .execute(pool)
+                .await
+                .map(ExecuteResult::Executed);
+        };
+        journal.init().await?;
+
+        if journal.should_replay() {
+            // failures are remembered by the journal
+            let _ = self.replay_journal().await;
+        }
+
+        // keep the order of writes of the same statement.
+        if journal.has_pending(stmt.kind, &stmt.sql) {
+            journal.push(stmt.kind, &stmt.sql, &args).await?;
+            return Ok(ExecuteResult::Journaled);
+        }
+
+        match stmt.stmt.query(&args)?.execute(pool).await {
+            Ok(rows_affected) => Ok(ExecuteResult::Executed(rows_affected)),
+            Err(err) if is_connection_error(&err) => {
+                journal.push(stmt.kind, &stmt.sql, &args).await?;
+                Ok(ExecuteResult::Journaled)
+            }
+            Err(err) => Err(err),
+        }
     }
 
-    async fn execute(&self, unique_id: u64, args: Vec<DbType>) -> anyhow::Result<u64> {
-        let stmt = self
-            .statements
-            .lock()
-            .get(&unique_id)
-            .cloned()
-            .ok_or_else(|| anyhow!("no statement with id \"{}\" found", unique_id))?;
+    async fn begin_transaction(&self, kind: &DbKind) -> anyhow::Result<u64> {
+        let transaction = self.pool(kind)?.begin().await?;
 
-        let pool = self
-            .db
-            .pools
-            .get(&stmt.kind)
-            .ok_or_else(|| anyhow!("database of kind {:?} not active.", stmt.kind))?;
-        let mut connection = pool.acquire().await?;
-        let mut connection = connection.acquire().await?;
+        let transaction_id = self
+            .id_generator
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.transactions.lock().insert(
+            transaction_id,
+            CachedTransaction {
+                kind: *kind,
+                transaction: Arc::new(tokio::sync::Mutex::new(transaction)),
+            },
+        );
+        Ok(transaction_id)
+    }
+
+    async fn commit_transaction(&self, transaction_id: u64) -> anyhow::Result<()> {
+        self.take_transaction(transaction_id)?.commit().await
+    }
+
+    async fn rollback_transaction(&self, transaction_id: u64) -> anyhow::Result<()> {
+        self.take_transaction(transaction_id)?.rollback().await
+    }
+
+    fn drop_transaction(&self, transaction_id: u64) {
+        // sqlx rolls back transactions that are dropped
+        self.transactions.lock().remove(&transaction_id);
+    }
+
+    async fn transaction_fetch_all(
+        &self,
+        transaction_id: u64,
+        unique_id: u64,
+        args: Vec<DbType>,
+    ) -> anyhow::Result<Vec<HashMap<String, DbType>>> {
+        let (stmt, transaction) = self.transaction_statement(transaction_id, unique_id)?;
+        let mut transaction = transaction.transaction.lock().await;
 
-        let qry = self.get_query(&stmt, &args)?;
+        let qry = stmt.stmt.query(&args)?;
 
-        Ok(qry.execute(&mut connection).await?.rows_affected())
+        Self::get_result(&stmt, qry.fetch_all_in(&mut transaction).await?.into_iter())
+    }
+
+    async fn transaction_execute(
+        &self,
+        transaction_id: u64,
+        unique_id: u64,
+        args: Vec<DbType>,
+    ) -> anyhow::Result<u64> {
+        let (stmt, transaction) = self.transaction_statement(transaction_id, unique_id)?;
+        let mut transaction = transaction.transaction.lock().await;
+
+        let qry = stmt.stmt.query(&args)?;
+
+        qry.execute_in(&mut transaction).await
     }
 }
 
 // TODO: these tests make no sense without having ddnet database
 #[cfg(test)]
 mod test {
-    use std::sync::Arc;
+    use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
+    use anyhow::anyhow;
     use game_database::{
-        statement::{Statement, StatementBuilder},
-        traits::{DbKind, DbKindExtra},
+        migration::{MigrationState, Migrations},
+        statement::{Statement, StatementBuilder, StatementSql},
+        traits::{DbInterface, DbKind, DbKindExtra},
+        transaction::Transaction,
+        types::ExecuteResult,
         StatementArgs, StatementResult,
     };
     use sql::database::{Database, DatabaseDetails};
 
     use crate::GameDbBackend;
 
+    #[derive(StatementArgs)]
+    struct StatementArgInsert {
+        id: i64,
+        name: String,
+    }
+
+    #[derive(Debug, StatementResult)]
+    struct StatementCountResult {
+        count: i64,
+    }
+
+    struct SqliteTestDb {
+        db: Arc<GameDbBackend>,
+        paths: Vec<PathBuf>,
+        insert: Statement<StatementArgInsert, ()>,
+        count: Statement<(), StatementCountResult>,
+    }
+
+    impl Drop for SqliteTestDb {
+        fn drop(&mut self) {
+            for path in &self.paths {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    async fn sqlite_test_db(name: &str) -> anyhow::Result<SqliteTestDb> {
+        sqlite_test_db_with(name, [DbKindExtra::Main]).await
+    }
+
+    /// A sqlite database for every kind, e.g. with a backup database
+    /// for the write journal.
+    async fn sqlite_test_db_with(
+        name: &str,
+        kinds: impl IntoIterator<Item = DbKindExtra>,
+    ) -> anyhow::Result<SqliteTestDb> {
+        let (paths, details): (Vec<_>, HashMap<_, _>) = kinds
+            .into_iter()
+            .map(|kind| {
+                let kind = DbKind::Sqlite(kind);
+                let path = std::env::temp_dir().join(format!(
+                    "game-database-backend-{name}-{}-{}.sqlite",
+                    kind.name(),
+                    std::process::id()
+                ));
+                let _ = std::fs::remove_file(&path);
+                let details = DatabaseDetails {
+                    host: Default::default(),
+                    port: Default::default(),
+                    database: path.to_string_lossy().to_string(),
+                    username: Default::default(),
+                    password: Default::default(),
+                    ca_cert_path: Default::default(),
+                    connection_count: 2,
+                };
+                (path, (kind, details))
+            })
+            .unzip();
+
+        let kind = DbKind::Sqlite(DbKindExtra::Main);
+        let db = Arc::new(GameDbBackend::new(Arc::new(Database::new(details).await?))?);
+
+        let builder = StatementBuilder::<_, (), ()>::new(
+            kind,
+            "CREATE TABLE test_transaction (id INTEGER PRIMARY KEY, name TEXT NOT NULL);",
+            |_| vec![],
+        );
+        Statement::new(db.clone(), builder)
+            .await?
+            .execute(())
+            .await?;
+
+        let builder = StatementBuilder::<_, StatementArgInsert, ()>::new(
+            kind,
+            "INSERT INTO test_transaction (id, name) VALUES (?, ?);",
+            |arg| vec![arg.id, arg.name],
+        );
+        let insert = Statement::new(db.clone(), builder).await?;
+
+        let builder = StatementBuilder::<_, (), StatementCountResult>::new(
+            kind,
+            "SELECT COUNT(*) AS count FROM test_transaction;",
+            |_| vec![],
+        );
+        let count = Statement::new(db.clone(), builder).await?;
+
+        Ok(SqliteTestDb {
+            db,
+            paths,
+            insert,
+            count,
+        })
+    }
+
+    #[tokio::test]
+    async fn sqlite_transaction() -> anyhow::Result<()> {
+        let test_db = sqlite_test_db("transaction").await?;
+        let kind = DbKind::Sqlite(DbKindExtra::Main);
+
+        let transaction = Transaction::begin(test_db.db.clone(), &kind).await?;
+        for (id, name) in [(1, "a"), (2, "b")] {
+            test_db
+                .insert
+                .execute_in(
+                    &transaction,
+                    StatementArgInsert {
+                        id,
+                        name: name.into(),
+                    },
+                )
+                .await?;
+        }
+        // only visible inside the transaction
+        assert_eq!(test_db.count.fetch_one_in(&transaction, ()).await?.count, 2);
+        assert_eq!(test_db.count.fetch_one(()).await?.count, 0);
+        transaction.commit().await?;
+        assert_eq!(test_db.count.fetch_one(()).await?.count, 2);
+
+        let transaction = Transaction::begin(test_db.db.clone(), &kind).await?;
+        test_db
+            .insert
+            .execute_in(
+                &transaction,
+                StatementArgInsert {
+                    id: 3,
+                    name: "c".into(),
+                },
+            )
+            .await?;
+        transaction.rollback().await?;
+        assert_eq!(test_db.count.fetch_one(()).await?.count, 2);
+
+        // dropping rolls back, too
+        let transaction = Transaction::begin(test_db.db.clone(), &kind).await?;
+        test_db
+            .insert
+            .execute_in(
+                &transaction,
+                StatementArgInsert {
+                    id: 4,
+                    name: "d".into(),
+                },
+            )
+            .await?;
+        drop(transaction);
+        assert_eq!(test_db.count.fetch_one(()).await?.count, 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn sqlite_execute_batch() -> anyhow::Result<()> {
+        let test_db = sqlite_test_db("execute_batch").await?;
+        let kind = DbKind::Sqlite(DbKindExtra::Main);
+
+        let insert = |id: i64, name: &str| {
+            anyhow::Ok((
+                test_db.insert.unique_id,
+                test_db.insert.db_args(StatementArgInsert {
+                    id,
+                    name: name.into(),
+                })?,
+            ))
+        };
+
+        // the second insert violates the primary key
+        let res = test_db
+            .db
+            .execute_batch(&kind, vec![insert(1, "a")?, insert(1, "b")?])
+            .await;
+        assert!(res.is_err());
+        assert_eq!(test_db.count.fetch_one(()).await?.count, 0);
+
+        let res = test_db
+            .db
+            .execute_batch(&kind, vec![insert(1, "a")?, insert(2, "b")?])
+            .await?;
+        assert_eq!(res, vec![1, 1]);
+        assert_eq!(test_db.count.fetch_one(()).await?.count, 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn sqlite_migrations() -> anyhow::Result<()> {
+        let test_db = sqlite_test_db("migrations").await?;
+        let kind = DbKind::Sqlite(DbKindExtra::Main);
+
+        let migrations = Migrations::new("test", [kind])
+            .with(
+                1,
+                "create",
+                StatementSql::new("CREATE TABLE test_migration (id INTEGER PRIMARY KEY);"),
+                Some(StatementSql::new("DROP TABLE test_migration;")),
+            )
+            .with(
+                2,
+                "add_name",
+                StatementSql::new(
+                    "ALTER TABLE test_migration ADD COLUMN name TEXT;
+                    INSERT INTO test_migration (id, name) VALUES (1, 'a');",
+                ),
+                Some(StatementSql::new(
+                    "DELETE FROM test_migration WHERE id = 1;
+                    ALTER TABLE test_migration DROP COLUMN name;",
+                )),
+            );
+        test_db.db.migrate(migrations.clone()).await?;
+        let status = test_db.db.migration_status().await?;
+        assert_eq!(status.len(), 2);
+        assert!(status
+            .iter()
+            .all(|status| status.state == MigrationState::Applied));
+
+        // nothing left to apply
+        test_db.db.migrate(migrations.clone()).await?;
+        assert!(test_db.db.migrate_up(false).await?.is_empty());
+
+        assert!(test_db
+            .db
+            .migrate_down("unknown", kind, true)
+            .await
+            .is_err());
+        assert!(test_db
+            .db
+            .migrate_down("test", DbKind::Sqlite(DbKindExtra::Backup), true)
+            .await
+            .is_err());
+
+        let reverted = test_db.db.migrate_down("test", kind, true).await?;
+        assert_eq!(reverted.version, 2);
+        assert_eq!(
+            test_db.db.migration_status().await?[1].state,
+            MigrationState::Applied
+        );
+
+        let reverted = test_db.db.migrate_down("test", kind, false).await?;
+        assert_eq!(reverted.version, 2);
+        assert_eq!(
+            test_db.db.migration_status().await?[1].state,
+            MigrationState::Pending
+        );
+
+        let applied = test_db.db.migrate_up(false).await?;
+        assert_eq!(applied.len(), 1);
+        assert_eq!(applied[0].version, 2);
+
+        // applied migrations must not be modified
+        let mut modified = migrations;
+        modified.migrations[0].up =
+            StatementSql::new("CREATE TABLE test_migration (id INTEGER);").into();
+        modified.migrations[1].down = None;
+        assert!(test_db.db.migrate(modified).await.is_err());
+        // and are not registered, the original ones are still used
+        assert!(test_db.db.migrate_down("test", kind, true).await.is_ok());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn sqlite_journal_replay() -> anyhow::Result<()> {
+        let test_db =
+            sqlite_test_db_with("journal_replay", [DbKindExtra::Main, DbKindExtra::Backup]).await?;
+        let kind = DbKind::Sqlite(DbKindExtra::Main);
+        let journal = test_db.db.journal.as_ref().unwrap();
+        journal.init().await?;
+        let sql = test_db.db.statement(test_db.insert.unique_id)?.sql;
+        let insert = |id: i64, name: &str| StatementArgInsert {
+            id,
+            name: name.into(),
+        };
+
+        // the second write violates the primary key
+        for (id, name) in [(1, "a"), (1, "b"), (2, "c")] {
+            journal
+                .push(kind, &sql, &test_db.insert.db_args(insert(id, name))?)
+                .await?;
+        }
+        // the main database is not reachable for now
+        journal.replay_failed(&anyhow!("main database not reachable"));
+
+        // must not overtake the journaled writes
+        assert_eq!(
+            test_db.insert.execute(insert(3, "d")).await?,
+            ExecuteResult::Journaled
+        );
+        assert_eq!(test_db.count.fetch_one(()).await?.count, 0);
+        assert_eq!(test_db.db.journal_status().unwrap().pending, 4);
+
+        assert_eq!(test_db.db.replay_journal().await?, Some(3));
+        assert_eq!(test_db.count.fetch_one(()).await?.count, 3);
+        let status = test_db.db.journal_status().unwrap();
+        assert_eq!(status.pending, 0);
+        assert_eq!(status.replayed, 3);
+        assert_eq!(status.dropped, 1);
+        assert!(status.last_error.is_some());
+
+        assert_eq!(
+            test_db.insert.execute(insert(4, "e")).await?,
+            ExecuteResult::Executed(1)
+        );
+        assert_eq!(test_db.count.fetch_one(()).await?.count, 4);
+        assert_eq!(test_db.db.replay_journal().await?, Some(0));
+
+        // without a backup database there is no journal
+        let test_db = sqlite_test_db("journal_none").await?;
+        assert!(test_db.db.journal_status().is_none());
+        assert_eq!(test_db.db.replay_journal().await?, None);
+        assert_eq!(
+            test_db.insert.execute(insert(1, "a")).await?,
+            ExecuteResult::Executed(1)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn sqlite_journal_reload() -> anyhow::Result<()> {
+        let test_db =
+            sqlite_test_db_with("journal_reload", [DbKindExtra::Main, DbKindExtra::Backup]).await?;
+        let kind = DbKind::Sqlite(DbKindExtra::Main);
+        let sql = test_db.db.statement(test_db.insert.unique_id)?.sql;
+        let journal = test_db.db.journal.as_ref().unwrap();
+        journal.init().await?;
+        for (id, name) in [(1, "a"), (2, "b")] {
+            journal
+                .push(
+                    kind,
+                    &sql,
+                    &test_db.insert.db_args(StatementArgInsert {
+                        id,
+                        name: name.into(),
+                    })?,
+                )
+                .await?;
+        }
+
+        // e.g. after a restart of the server
+        let db = GameDbBackend::new(test_db.db.db.clone())?;
+        let journal = db.journal.as_ref().unwrap();
+        journal.init().await?;
+        assert_eq!(db.journal_status().unwrap().pending, 2);
+        assert!(journal.has_pending(kind, &sql));
+
+        let entries = journal.entries(10).await?;
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].id < entries[1].id);
+
+        assert_eq!(db.replay_journal().await?, Some(2));
+        assert_eq!(db.journal_status().unwrap().pending, 0);
+        assert_eq!(test_db.count.fetch_one(()).await?.count, 2);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn builder() -> anyhow::Result<()> {
         #[derive(StatementArgs)]
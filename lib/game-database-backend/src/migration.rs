@@ -0,0 +1,193 @@
+//! Applies and reverts the migrations of the mods.
+
+use anyhow::anyhow;
+use base::hash::{fmt_hash, generate_hash_for};
+use game_database::{
+    migration::{Migration, MigrationState, MigrationStatus, Migrations},
+    traits::DbKind,
+};
+use sqlx::Executor;
+
+use crate::driver::DbPool;
+
+struct MigrationQueries {
+    applied: &'static str,
+    insert: &'static str,
+    remove: &'static str,
+}
+
+const GENERIC_QUERIES: MigrationQueries = MigrationQueries {
+    applied: include_str!("migration/applied.sql"),
+    insert: include_str!("migration/insert.sql"),
+    remove: include_str!("migration/remove.sql"),
+};
+
+const POSTGRES_QUERIES: MigrationQueries = MigrationQueries {
+    applied: include_str!("migration/postgres/applied.sql"),
+    insert: include_str!("migration/postgres/insert.sql"),
+    remove: include_str!("migration/postgres/remove.sql"),
+};
+
+/// Runs the same code for the pool of every driver.
+macro_rules! with_pool {
+    ($pool:expr, |$p:ident, $queries:ident| $body:block) => {
+        match $pool {
+            DbPool::MySql($p) => {
+                let $queries = &GENERIC_QUERIES;
+                $body
+            }
+            DbPool::Sqlite($p) => {
+                let $queries = &GENERIC_QUERIES;
+                $body
+            }
+            DbPool::Postgres($p) => {
+                let $queries = &POSTGRES_QUERIES;
+                $body
+            }
+        }
+    };
+}
+
+#[derive(Debug, Clone)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+}
+
+pub fn checksum(sql: &str) -> String {
+    fmt_hash(&generate_hash_for(sql.as_bytes()))
+}
+
+/// Creates the migration table if required and returns the applied
+/// migrations of the given name, ordered by their version.
+pub async fn applied(
+    pool: DbPool<'_>,
+    migrations_name: &str,
+) -> anyhow::Result<Vec<AppliedMigration>> {
+    let rows: Vec<(i64, String, String)> = with_pool!(pool, |pool, queries| {
+        sqlx::query(include_str!("migration/setup.sql"))
+            .execute(pool)
+            .await?;
+        sqlx::query_as(queries.applied)
+            .bind(migrations_name)
+            .fetch_all(pool)
+            .await?
+    });
+    Ok(rows
+        .into_iter()
+        .map(|(version, name, checksum)| AppliedMigration {
+            version,
+            name,
+            checksum,
+        })
+        .collect())
+}
+
+/// The state of every migration on the database of the given kind,
+/// ordered by their version.
+pub fn status(
+    migrations: &Migrations,
+    kind: DbKind,
+    applied: &[AppliedMigration],
+) -> Vec<MigrationStatus> {
+    let mut res: Vec<_> = migrations
+        .migrations
+        .iter()
+        .map(|migration| {
+            let state = match applied.iter().find(|a| a.version == migration.version) {
+                Some(applied) if applied.checksum == checksum(migration.up.get(&kind)) => {
+                    MigrationState::Applied
+                }
+                Some(_) => MigrationState::ChecksumMismatch,
+                None => MigrationState::Pending,
+            };
+            MigrationStatus {
+                migrations: migrations.name.clone(),
+                kind,
+                version: migration.version,
+                name: migration.name.clone(),
+                state,
+            }
+        })
+        .collect();
+    res.extend(
+        applied
+            .iter()
+            .filter(|applied| {
+                !migrations
+                    .migrations
+                    .iter()
+                    .any(|migration| migration.version == applied.version)
+            })
+            .map(|applied| MigrationStatus {
+                migrations: migrations.name.clone(),
+                kind,
+                version: applied.version,
+                name: applied.name.clone(),
+                state: MigrationState::Unknown,
+            }),
+    );
+    res.sort_by_key(|status| status.version);
+    res
+}
+
+/// Runs the up script and records the migration inside a single transaction.
+///
+/// Note that MySQL implicitly commits most schema changes, a failing
+/// script can leave a partially applied migration behind there.
+pub async fn apply(
+    pool: DbPool<'_>,
+    migrations_name: &str,
+    kind: &DbKind,
+    migration: &Migration,
+) -> anyhow::Result<()> {
+    let up = migration.up.get(kind);
+    let checksum = checksum(up);
+    with_pool!(pool, |pool, queries| {
+        let mut transaction = pool.begin().await?;
+        // `RawSql::execute` makes the future of the async trait not `Send`
+        (&mut *transaction).execute(sqlx::raw_sql(up)).await?;
+        sqlx::query(queries.insert)
+            .bind(migrations_name)
+            .bind(migration.version)
+            .bind(&migration.name)
+            .bind(checksum)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+    });
+    Ok(())
+}
+
+/// Runs the down script and removes the migration from the applied ones
+/// inside a single transaction.
+pub async fn revert(
+    pool: DbPool<'_>,
+    migrations_name: &str,
+    kind: &DbKind,
+    migration: &Migration,
+) -> anyhow::Result<()> {
+    let down = migration
+        .down
+        .as_ref()
+        .ok_or_else(|| {
+            anyhow!(
+                "migration \"{}\" of \"{}\" has no downgrade script",
+                migration.name,
+                migrations_name
+            )
+        })?
+        .get(kind);
+    with_pool!(pool, |pool, queries| {
+        let mut transaction = pool.begin().await?;
+        (&mut *transaction).execute(sqlx::raw_sql(down)).await?;
+        sqlx::query(queries.remove)
+            .bind(migrations_name)
+            .bind(migration.version)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+    });
+    Ok(())
+}
@@ -0,0 +1,10 @@
+SELECT
+    version,
+    name,
+    checksum
+FROM
+    game_db_migrations
+WHERE
+    migrations_name = ?
+ORDER BY
+    version ASC;
@@ -0,0 +1,8 @@
+INSERT INTO game_db_migrations (
+    migrations_name,
+    version,
+    name,
+    checksum
+)
+VALUES
+    (?, ?, ?, ?);
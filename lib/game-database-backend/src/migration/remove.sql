@@ -0,0 +1,5 @@
+DELETE FROM
+    game_db_migrations
+WHERE
+    migrations_name = ?
+    AND version = ?;
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS game_db_migrations (
+    migrations_name VARCHAR(64) NOT NULL,
+    version BIGINT NOT NULL,
+    name VARCHAR(256) NOT NULL,
+    checksum VARCHAR(64) NOT NULL,
+    applied_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY(migrations_name, version)
+);
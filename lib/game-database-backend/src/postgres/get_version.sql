@@ -0,0 +1,6 @@
+SELECT
+    version
+FROM
+    game_db_versions
+WHERE
+    name = $1;
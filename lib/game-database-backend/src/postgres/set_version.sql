@@ -0,0 +1,8 @@
+INSERT INTO game_db_versions (
+    name,
+    version
+)
+VALUES
+    ($1, $2)
+ON CONFLICT (name) DO UPDATE SET
+    version = EXCLUDED.version;
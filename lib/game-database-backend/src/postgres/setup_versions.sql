@@ -0,0 +1,4 @@
+CREATE TABLE IF NOT EXISTS game_db_versions (
+    name VARCHAR(64) PRIMARY KEY,
+    version BIGINT NOT NULL
+);
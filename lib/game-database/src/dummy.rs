@@ -3,9 +3,10 @@ use std::collections::{BTreeMap, HashMap, HashSet};
 use anyhow::anyhow;
 
 use crate::{
+    migration::Migrations,
     statement::{QueryProperties, StatementDriverProps},
     traits::{DbInterface, DbKind},
-    types::DbType,
+    types::{DbType, ExecuteResult},
 };
 
 #[derive(Debug)]
@@ -25,6 +26,10 @@ impl DbInterface for DummyDb {
         Err(anyhow!("not implemented for the dummy database"))
     }
 
+    async fn migrate(&self, _migrations: Migrations) -> anyhow::Result<()> {
+        Err(anyhow!("not implemented for the dummy database"))
+    }
+
     async fn prepare_statement(
         &self,
         _query_props: &QueryProperties,
@@ -60,7 +65,39 @@ impl DbInterface for DummyDb {
         Err(anyhow!("not implemented for the dummy database"))
     }
 
-    async fn execute(&self, _unique_id: u64, _args: Vec<DbType>) -> anyhow::Result<u64> {
+    async fn execute(&self, _unique_id: u64, _args: Vec<DbType>) -> anyhow::Result<ExecuteResult> {
+        Err(anyhow!("not implemented for the dummy database"))
+    }
+
+    async fn begin_transaction(&self, _kind: &DbKind) -> anyhow::Result<u64> {
+        Err(anyhow!("not implemented for the dummy database"))
+    }
+
+    async fn commit_transaction(&self, _transaction_id: u64) -> anyhow::Result<()> {
+        Err(anyhow!("not implemented for the dummy database"))
+    }
+
+    async fn rollback_transaction(&self, _transaction_id: u64) -> anyhow::Result<()> {
+        Err(anyhow!("not implemented for the dummy database"))
+    }
+
+    fn drop_transaction(&self, _transaction_id: u64) {}
+
+    async fn transaction_fetch_all(
+        &self,
+        _transaction_id: u64,
+        _unique_id: u64,
+        _args: Vec<DbType>,
+    ) -> anyhow::Result<Vec<HashMap<String, DbType>>> {
+        Err(anyhow!("not implemented for the dummy database"))
+    }
+
+    async fn transaction_execute(
+        &self,
+        _transaction_id: u64,
+        _unique_id: u64,
+        _args: Vec<DbType>,
+    ) -> anyhow::Result<u64> {
         Err(anyhow!("not implemented for the dummy database"))
     }
 }
@@ -3,7 +3,7 @@ use std::{collections::HashMap, sync::Arc};
 use anyhow::anyhow;
 use serde::{Deserialize, Serialize};
 
-use crate::{statement::Statement, traits::DbStatementArgInterface};
+use crate::{statement::Statement, traits::DbStatementArgInterface, types::ExecuteResult};
 
 pub struct ExecutionStatement<A: DbStatementArgInterface> {
     stmt: Arc<Statement<A, ()>>,
@@ -78,15 +78,15 @@ impl<A: DbStatementArgInterface> ExecutionPlanBuilder<A> {
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum ExecutionBranchResult {
     /// The query was executed successfully.
-    Success(u64),
+    Success(ExecuteResult),
     /// The main query failed, but the fallback
     /// was executed successfully.
-    FallbackUsed(u64),
+    FallbackUsed(ExecuteResult),
     /// The main query (and fallback if given) failed,
     /// but the fallback system, that triggers
     /// after all branches executed, executed
     /// successfully.
-    PostRunFallbackUsed(u64),
+    PostRunFallbackUsed(ExecuteResult),
 }
 
 /// A helper to contruct how multiple statements
@@ -1,7 +1,9 @@
 pub mod dummy;
 pub mod execution_plan;
+pub mod migration;
 pub mod statement;
 pub mod traits;
+pub mod transaction;
 pub mod types;
 
 pub use game_database_macros::StatementArgs;
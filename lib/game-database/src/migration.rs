@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{statement::StatementSql, traits::DbKind};
+
+/// The sql script of a migration for different kinds of databases.
+///
+/// Unlike statements, a script can contain multiple statements.
+/// Kinds without their own script use the generic script.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationSql {
+    pub generic: String,
+    pub mysql: Option<String>,
+    pub sqlite: Option<String>,
+    pub postgres: Option<String>,
+}
+
+impl MigrationSql {
+    pub fn get(&self, kind: &DbKind) -> &str {
+        match kind {
+            DbKind::MySql(_) => self.mysql.as_deref(),
+            DbKind::Sqlite(_) => self.sqlite.as_deref(),
+            DbKind::Postgres(_) => self.postgres.as_deref(),
+        }
+        .unwrap_or(&self.generic)
+    }
+}
+
+impl From<StatementSql<'_>> for MigrationSql {
+    fn from(sql: StatementSql<'_>) -> Self {
+        Self {
+            generic: sql.generic.to_string(),
+            mysql: sql.mysql.map(|s| s.to_string()),
+            sqlite: sql.sqlite.map(|s| s.to_string()),
+            postgres: sql.postgres.map(|s| s.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Migration {
+    /// Migrations are applied in the order of their versions.
+    pub version: i64,
+    pub name: String,
+    pub up: MigrationSql,
+    /// Reverts the changes of [`Migration::up`].
+    /// Migrations without a downgrade script can not be reverted.
+    pub down: Option<MigrationSql>,
+}
+
+/// All migrations of a mod.
+///
+/// The applied migrations are recorded together with the checksum of their
+/// up script, modifying a migration after it was applied is an error.
+/// Schema changes should always be added as a new migration instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Migrations {
+    /// The name under which the applied migrations are recorded,
+    /// usually the name of the mod.
+    pub name: String,
+    /// The kinds of databases the migrations are applied to.
+    pub kinds: Vec<DbKind>,
+    pub migrations: Vec<Migration>,
+}
+
+impl Migrations {
+    pub fn new(name: &str, kinds: impl IntoIterator<Item = DbKind>) -> Self {
+        Self {
+            name: name.to_string(),
+            kinds: kinds.into_iter().collect(),
+            migrations: Default::default(),
+        }
+    }
+
+    pub fn with(
+        mut self,
+        version: i64,
+        name: &str,
+        up: StatementSql<'_>,
+        down: Option<StatementSql<'_>>,
+    ) -> Self {
+        self.migrations.push(Migration {
+            version,
+            name: name.to_string(),
+            up: up.into(),
+            down: down.map(|down| down.into()),
+        });
+        self
+    }
+
+    /// Checks that the versions are positive and strictly increasing.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut last_version = 0;
+        for migration in &self.migrations {
+            anyhow::ensure!(
+                migration.version > last_version,
+                "migration \"{}\" of \"{}\" must have a higher version than {}, found {}",
+                migration.name,
+                self.name,
+                last_version,
+                migration.version
+            );
+            last_version = migration.version;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// The migration was modified after it was applied.
+    ChecksumMismatch,
+    /// The migration was applied, but is not known to the mod anymore.
+    Unknown,
+}
+
+/// The state of a single migration on a single database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationStatus {
+    /// The name of the [`Migrations`] this migration belongs to.
+    pub migrations: String,
+    pub kind: DbKind,
+    pub version: i64,
+    pub name: String,
+    pub state: MigrationState,
+}
@@ -8,7 +8,8 @@ use crate::{
         DbInterface, DbKind, DbStatementArgIndexInterface, DbStatementArgInterface,
         DbStatementResultInterface,
     },
-    types::DbType,
+    transaction::Transaction,
+    types::{DbType, ExecuteResult},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +29,52 @@ pub struct QueryProperties {
     pub result_mapping: HashMap<String, DbType>,
 }
 
+/// The sql of a statement for different kinds of databases.
+///
+/// Kinds without their own sql use the generic sql.
+#[derive(Debug, Clone, Copy)]
+pub struct StatementSql<'a> {
+    pub(crate) generic: &'a str,
+    pub(crate) mysql: Option<&'a str>,
+    pub(crate) sqlite: Option<&'a str>,
+    pub(crate) postgres: Option<&'a str>,
+}
+
+impl<'a> StatementSql<'a> {
+    pub fn new(generic: &'a str) -> Self {
+        Self {
+            generic,
+            mysql: None,
+            sqlite: None,
+            postgres: None,
+        }
+    }
+
+    pub fn with_mysql(mut self, sql: &'a str) -> Self {
+        self.mysql = Some(sql);
+        self
+    }
+
+    pub fn with_sqlite(mut self, sql: &'a str) -> Self {
+        self.sqlite = Some(sql);
+        self
+    }
+
+    pub fn with_postgres(mut self, sql: &'a str) -> Self {
+        self.postgres = Some(sql);
+        self
+    }
+
+    pub fn get(&self, kind: &DbKind) -> &'a str {
+        match kind {
+            DbKind::MySql(_) => self.mysql,
+            DbKind::Sqlite(_) => self.sqlite,
+            DbKind::Postgres(_) => self.postgres,
+        }
+        .unwrap_or(self.generic)
+    }
+}
+
 #[derive(Debug)]
 pub struct StatementBuilder<AI, A: DbStatementArgIndexInterface<AI>, R> {
     kind: DbKind,
@@ -59,6 +106,15 @@ impl<AI, A: DbStatementArgIndexInterface<AI>, R: DbStatementResultInterface>
             _res: PhantomData,
         }
     }
+
+    /// Like [`Self::new`], but picks the sql for the given kind.
+    pub fn new_per_kind(
+        kind: DbKind,
+        sql: StatementSql<'_>,
+        arguments_mapping: impl FnOnce(AI) -> Vec<usize>,
+    ) -> Self {
+        Self::new(kind, sql.get(&kind), arguments_mapping)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -102,78 +158,102 @@ impl<A: DbStatementArgInterface, R: DbStatementResultInterface> Statement<A, R>
     }
 
     pub async fn fetch_optional(&self, args: A) -> anyhow::Result<Option<R>> {
-        let db_args = args.to_db_args();
         let res = self
             .db
-            .fetch_optional(
-                self.unique_id,
-                self.inner
-                    .props
-                    .arguments_mapping
-                    .iter()
-                    .map(|arg| db_args.get(*arg).cloned())
-                    .collect::<Option<Vec<_>>>()
-                    .ok_or_else(|| anyhow!("argument index was out of bounds."))?,
-            )
+            .fetch_optional(self.unique_id, self.db_args(args)?)
             .await?;
 
         res.map(|res| R::new(res)).transpose()
     }
 
     pub async fn fetch_one(&self, args: A) -> anyhow::Result<R> {
-        let db_args = args.to_db_args();
         let res = self
             .db
-            .fetch_one(
-                self.unique_id,
-                self.inner
-                    .props
-                    .arguments_mapping
-                    .iter()
-                    .map(|arg| db_args.get(*arg).cloned())
-                    .collect::<Option<Vec<_>>>()
-                    .ok_or_else(|| anyhow!("argument index was out of bounds."))?,
-            )
+            .fetch_one(self.unique_id, self.db_args(args)?)
             .await?;
 
         R::new(res)
     }
 
     pub async fn fetch_all(&self, args: A) -> anyhow::Result<Vec<R>> {
-        let db_args = args.to_db_args();
         let res = self
             .db
-            .fetch_all(
+            .fetch_all(self.unique_id, self.db_args(args)?)
+            .await?;
+
+        res.into_iter().map(|res| R::new(res)).collect()
+    }
+
+    /// Returns the affected rows count, or [`ExecuteResult::Journaled`]
+    /// if the write was journaled to be replayed later.
+    pub async fn execute(&self, args: A) -> anyhow::Result<ExecuteResult> {
+        self.db.execute(self.unique_id, self.db_args(args)?).await
+    }
+
+    /// Same as [`Self::fetch_optional`], but runs inside the transaction.
+    pub async fn fetch_optional_in(
+        &self,
+        transaction: &Transaction,
+        args: A,
+    ) -> anyhow::Result<Option<R>> {
+        let res = self
+            .db
+            .transaction_fetch_all(
+                transaction.transaction_id,
+                self.unique_id,
+                self.db_args(args)?,
+            )
+            .await?;
+
+        res.into_iter().next().map(|res| R::new(res)).transpose()
+    }
+
+    /// Same as [`Self::fetch_one`], but runs inside the transaction.
+    pub async fn fetch_one_in(&self, transaction: &Transaction, args: A) -> anyhow::Result<R> {
+        self.fetch_optional_in(transaction, args)
+            .await?
+            .ok_or_else(|| anyhow!("no result fetched"))
+    }
+
+    /// Same as [`Self::fetch_all`], but runs inside the transaction.
+    pub async fn fetch_all_in(&self, transaction: &Transaction, args: A) -> anyhow::Result<Vec<R>> {
+        let res = self
+            .db
+            .transaction_fetch_all(
+                transaction.transaction_id,
                 self.unique_id,
-                self.inner
-                    .props
-                    .arguments_mapping
-                    .iter()
-                    .map(|arg| db_args.get(*arg).cloned())
-                    .collect::<Option<Vec<_>>>()
-                    .ok_or_else(|| anyhow!("argument index was out of bounds."))?,
+                self.db_args(args)?,
             )
             .await?;
 
         res.into_iter().map(|res| R::new(res)).collect()
     }
 
+    /// Same as [`Self::execute`], but runs inside the transaction.
+    ///
     /// Returns the affected rows count
-    pub async fn execute(&self, args: A) -> anyhow::Result<u64> {
-        let db_args = args.to_db_args();
+    pub async fn execute_in(&self, transaction: &Transaction, args: A) -> anyhow::Result<u64> {
         self.db
-            .execute(
+            .transaction_execute(
+                transaction.transaction_id,
                 self.unique_id,
-                self.inner
-                    .props
-                    .arguments_mapping
-                    .iter()
-                    .map(|arg| db_args.get(*arg).cloned())
-                    .collect::<Option<Vec<_>>>()
-                    .ok_or_else(|| anyhow!("argument index was out of bounds."))?,
+                self.db_args(args)?,
             )
             .await
     }
+
+    /// The arguments in the order of the sql statement,
+    /// e.g. for [`DbInterface::execute_batch`].
+    pub fn db_args(&self, args: A) -> anyhow::Result<Vec<DbType>> {
+        let db_args = args.to_db_args();
+        self.inner
+            .props
+            .arguments_mapping
+            .iter()
+            .map(|arg| db_args.get(*arg).cloned())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| anyhow!("argument index was out of bounds."))
+    }
 }
 
 impl<A: DbStatementArgInterface, R: DbStatementResultInterface> Drop for Statement<A, R> {
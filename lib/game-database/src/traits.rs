@@ -4,8 +4,9 @@ use hiarc::Hiarc;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    migration::Migrations,
     statement::{QueryProperties, StatementDriverProps},
-    types::DbType,
+    types::{DbType, ExecuteResult},
 };
 
 #[derive(
@@ -22,6 +23,21 @@ pub enum DbKindExtra {
 pub enum DbKind {
     MySql(DbKindExtra),
     Sqlite(DbKindExtra),
+    Postgres(DbKindExtra),
+}
+
+impl DbKind {
+    /// The name of the kind as used in configs, e.g. `sqlite_backup`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::MySql(DbKindExtra::Main) => "mysql",
+            Self::MySql(DbKindExtra::Backup) => "mysql_backup",
+            Self::Sqlite(DbKindExtra::Main) => "sqlite",
+            Self::Sqlite(DbKindExtra::Backup) => "sqlite_backup",
+            Self::Postgres(DbKindExtra::Main) => "postgres",
+            Self::Postgres(DbKindExtra::Backup) => "postgres_backup",
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -33,12 +49,22 @@ pub trait DbInterface: Sync + Send {
     /// should only be related to setting up the database.
     /// If altering existing tables or similar stuff is required
     /// it should use a higher version index.
+    ///
+    /// Prefer [`DbInterface::migrate`] for new schemas, it can also
+    /// revert changes.
     async fn setup(
         &self,
         version_name: &str,
         versioned_stmts: BTreeMap<i64, Vec<u64>>,
     ) -> anyhow::Result<()>;
 
+    /// Registers the migrations of a mod and applies the pending ones,
+    /// unless the server only applies migrations on request.
+    ///
+    /// Fails if an applied migration was modified or if migrations
+    /// are still pending afterwards.
+    async fn migrate(&self, migrations: Migrations) -> anyhow::Result<()>;
+
     /// Prepare a new statement.
     /// Returns an unique identifier for that statement
     #[must_use]
@@ -71,8 +97,73 @@ pub trait DbInterface: Sync + Send {
         args: Vec<DbType>,
     ) -> anyhow::Result<Vec<HashMap<String, DbType>>>;
 
+    /// Returns the affected rows count, or [`ExecuteResult::Journaled`]
+    /// if the write to a main database was journaled to be replayed later.
+    async fn execute(&self, unique_id: u64, args: Vec<DbType>) -> anyhow::Result<ExecuteResult>;
+
+    /// Starts a new transaction on the database of the given kind.
+    /// Returns an unique identifier for that transaction.
+    ///
+    /// The transaction is rolled back, if it is dropped before
+    /// it was committed.
+    #[must_use]
+    async fn begin_transaction(&self, kind: &DbKind) -> anyhow::Result<u64>;
+
+    /// Commits a transaction and drops it.
+    async fn commit_transaction(&self, transaction_id: u64) -> anyhow::Result<()>;
+
+    /// Rolls back a transaction and drops it.
+    async fn rollback_transaction(&self, transaction_id: u64) -> anyhow::Result<()>;
+
+    /// Drops a transaction, which rolls it back.
+    /// If the transaction does not exist, nothing happens.
+    fn drop_transaction(&self, transaction_id: u64);
+
+    /// Same as [`DbInterface::fetch_all`], but runs inside a transaction.
+    async fn transaction_fetch_all(
+        &self,
+        transaction_id: u64,
+        unique_id: u64,
+        args: Vec<DbType>,
+    ) -> anyhow::Result<Vec<HashMap<String, DbType>>>;
+
+    /// Same as [`DbInterface::execute`], but runs inside a transaction.
+    ///
     /// Returns the affected rows count
-    async fn execute(&self, unique_id: u64, args: Vec<DbType>) -> anyhow::Result<u64>;
+    async fn transaction_execute(
+        &self,
+        transaction_id: u64,
+        unique_id: u64,
+        args: Vec<DbType>,
+    ) -> anyhow::Result<u64>;
+
+    /// Executes all statements in order inside a single transaction.
+    /// If any of them fails, none of them is applied.
+    ///
+    /// Returns the affected rows count of every statement
+    async fn execute_batch(
+        &self,
+        kind: &DbKind,
+        stmts: Vec<(u64, Vec<DbType>)>,
+    ) -> anyhow::Result<Vec<u64>> {
+        let transaction_id = self.begin_transaction(kind).await?;
+        let mut res = Vec::with_capacity(stmts.len());
+        for (unique_id, args) in stmts {
+            match self
+                .transaction_execute(transaction_id, unique_id, args)
+                .await
+            {
+                Ok(affected_rows) => res.push(affected_rows),
+                Err(err) => {
+                    // the error of the statement is more important
+                    let _ = self.rollback_transaction(transaction_id).await;
+                    return Err(err);
+                }
+            }
+        }
+        self.commit_transaction(transaction_id).await?;
+        Ok(res)
+    }
 }
 
 pub trait DbStatementArgIndexInterface<AI> {
@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use crate::traits::{DbInterface, DbKind};
+
+/// A database transaction.
+///
+/// Statements run inside the transaction using e.g.
+/// [`crate::statement::Statement::execute_in`].
+/// If the transaction is dropped without calling [`Transaction::commit`],
+/// all changes are rolled back.
+pub struct Transaction {
+    db: Arc<dyn DbInterface>,
+    pub transaction_id: u64,
+    finished: bool,
+}
+
+impl Transaction {
+    pub async fn begin(db: Arc<dyn DbInterface>, kind: &DbKind) -> anyhow::Result<Self> {
+        let transaction_id = db.begin_transaction(kind).await?;
+        Ok(Self {
+            db,
+            transaction_id,
+            finished: false,
+        })
+    }
+
+    pub async fn commit(mut self) -> anyhow::Result<()> {
+        self.finished = true;
+        self.db.commit_transaction(self.transaction_id).await
+    }
+
+    pub async fn rollback(mut self) -> anyhow::Result<()> {
+        self.finished = true;
+        self.db.rollback_transaction(self.transaction_id).await
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.db.drop_transaction(self.transaction_id);
+        }
+    }
+}
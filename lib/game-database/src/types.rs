@@ -41,6 +41,27 @@ pub enum DbType {
     DateTime(UnixUtcTimestamp),
 }
 
+/// The result of a statement that does not return any rows.
+#[derive(Debug, Hiarc, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExecuteResult {
+    /// The statement was executed, contains the affected rows count.
+    Executed(u64),
+    /// The main database was not reachable, so the write was journaled
+    /// into the backup database and is replayed later.
+    /// How many rows it affects is not known yet.
+    Journaled,
+}
+
+impl ExecuteResult {
+    /// The affected rows count, `None` if the write was journaled.
+    pub fn affected_rows(&self) -> Option<u64> {
+        match self {
+            Self::Executed(rows) => Some(*rows),
+            Self::Journaled => None,
+        }
+    }
+}
+
 impl TryInto<i16> for DbType {
     type Error = anyhow::Error;
 
@@ -5,6 +5,7 @@ use std::{
 };
 
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 
 use super::{
     connection::NetworkConnectionId,
@@ -12,12 +13,20 @@ use super::{
     plugins::{ConnectionEvent, NetworkPluginConnection},
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Ban {
     pub until: Option<chrono::DateTime<chrono::Utc>>,
     pub ty: BanType,
 }
 
+/// A ban of a whole ip range, a single ip is a range
+/// with the maximal prefix length.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IpRangeBan {
+    pub net: ipnet::IpNet,
+    pub ban: Ban,
+}
+
 #[derive(Debug, Default)]
 pub struct BanState {
     ipv4_bans: prefix_trie::PrefixMap<ipnet::Ipv4Net, Ban>,
@@ -121,22 +130,68 @@ impl ConnectionBans {
         reason: BanType,
         until: Option<chrono::DateTime<chrono::Utc>>,
     ) -> HashSet<NetworkConnectionId> {
+        self.ban_range(ip.into(), reason, until)
+    }
+
+    /// Returns all network ids whose ip is inside the range.
+    #[must_use]
+    pub fn ban_range(
+        &self,
+        net: ipnet::IpNet,
+        reason: BanType,
+        until: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> HashSet<NetworkConnectionId> {
+        let net = net.trunc();
         let mut state = self.state.lock().unwrap();
         let ids = state
             .active_connections
-            .get(&ip)
-            .cloned()
-            .unwrap_or_default();
+            .iter()
+            .filter(|(ip, _)| net.contains(*ip))
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect();
 
-        match ip {
-            IpAddr::V4(ip) => {
-                state.ipv4_bans.insert(ip.into(), Ban { until, ty: reason });
+        match net {
+            ipnet::IpNet::V4(net) => {
+                state.ipv4_bans.insert(net, Ban { until, ty: reason });
             }
-            IpAddr::V6(ip) => {
-                state.ipv6_bans.insert(ip.into(), Ban { until, ty: reason });
+            ipnet::IpNet::V6(net) => {
+                state.ipv6_bans.insert(net, Ban { until, ty: reason });
             }
         }
 
         ids
     }
+
+    /// Removes the ban of exactly this range.
+    /// Returns `true` if such a ban existed.
+    pub fn unban_range(&self, net: ipnet::IpNet) -> bool {
+        let net = net.trunc();
+        let mut state = self.state.lock().unwrap();
+        match net {
+            ipnet::IpNet::V4(net) => state.ipv4_bans.remove(&net).is_some(),
+            ipnet::IpNet::V6(net) => state.ipv6_bans.remove(&net).is_some(),
+        }
+    }
+
+    /// All bans that are not expired yet.
+    pub fn bans(&self) -> Vec<IpRangeBan> {
+        let now = chrono::Utc::now();
+        let state = self.state.lock().unwrap();
+        state
+            .ipv4_bans
+            .iter()
+            .map(|(&net, ban)| (ipnet::IpNet::V4(net), ban))
+            .chain(
+                state
+                    .ipv6_bans
+                    .iter()
+                    .map(|(&net, ban)| (ipnet::IpNet::V6(net), ban)),
+            )
+            .filter(|(_, ban)| ban.until.is_none_or(|until| now < until))
+            .map(|(net, ban)| IpRangeBan {
+                net,
+                ban: ban.clone(),
+            })
+            .collect()
+    }
 }
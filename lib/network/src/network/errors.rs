@@ -8,6 +8,7 @@ pub enum ConnectionErrorCode {
     Kicked = 0x400,
     Banned,
     Shutdown,
+    WrongPassword,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,4 +59,6 @@ impl Display for Banned {
 pub enum KickType {
     Kick(String),
     Ban(Banned),
+    /// The client did not send the correct password.
+    WrongPassword,
 }
@@ -14,6 +14,8 @@ pub enum NetworkEventConnectingClosed {
     Banned(Banned),
     #[error("shutdown was requested {0}")]
     Shutdown(String),
+    #[error("wrong password")]
+    WrongPassword,
     #[error("application restarted")]
     Reset,
     #[error("{0}")]
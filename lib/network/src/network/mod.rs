@@ -12,6 +12,7 @@ pub mod networks;
 pub mod notifier;
 pub mod packet_compressor;
 pub mod packet_dict;
+pub mod ping;
 pub mod plugins;
 pub mod quinn_network;
 pub mod quinnminimal;
@@ -222,14 +222,15 @@ where
         if let Some(conn) = con {
             conn.conn
                 .close(
-                    if matches!(ty, KickType::Ban(_)) {
-                        ConnectionErrorCode::Banned
-                    } else {
-                        ConnectionErrorCode::Kicked
+                    match ty {
+                        KickType::Kick(_) => ConnectionErrorCode::Kicked,
+                        KickType::Ban(_) => ConnectionErrorCode::Banned,
+                        KickType::WrongPassword => ConnectionErrorCode::WrongPassword,
                     },
                     &match ty {
                         KickType::Ban(banned) => serde_json::to_string(&banned).unwrap_or_default(),
                         KickType::Kick(reason) => reason,
+                        KickType::WrongPassword => "wrong password".to_string(),
                     },
                 )
                 .await;
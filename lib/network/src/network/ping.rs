@@ -0,0 +1,249 @@
+//! Measures the round trip time to QUIC servers without connecting to them.
+//!
+//! A long header packet with a reserved QUIC version is sent, which QUIC
+//! servers answer statelessly with a version negotiation packet
+//! (RFC 9000, section 6). The probe never reaches the application
+//! of the server, so it is cheap for both sides.
+
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    num::NonZeroU32,
+    time::Duration,
+};
+
+use rand::RngCore;
+use tokio::{net::UdpSocket, time::Instant};
+
+/// Servers only answer datagrams of at least this size.
+const PROBE_SIZE: usize = 1200;
+/// A reserved version (`0x?a?a?a?a`), that no server supports.
+const PROBE_VERSION: u32 = 0x1a2a_3a4a;
+const CID_LEN: usize = 8;
+/// How often the probes are sent in a second,
+/// the probes per second are split across these.
+const SEND_TICKS_PER_SECOND: u32 = 100;
+
+/// Options for [`ping_servers`].
+#[derive(Debug, Clone, Copy)]
+pub struct PingOptions {
+    /// How many probes are sent per second at most,
+    /// so large server lists don't flood the network.
+    pub probes_per_second: NonZeroU32,
+    /// How long to wait for answers after the last probe was sent.
+    pub timeout: Duration,
+}
+
+impl Default for PingOptions {
+    fn default() -> Self {
+        Self {
+            probes_per_second: NonZeroU32::new(200).unwrap(),
+            timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+fn probe_packet(session: u32, index: u32) -> Vec<u8> {
+    let mut cid = [0; CID_LEN];
+    cid[0..4].copy_from_slice(&session.to_le_bytes());
+    cid[4..8].copy_from_slice(&index.to_le_bytes());
+
+    let mut packet = Vec::with_capacity(PROBE_SIZE);
+    // long header with the fixed bit set
+    packet.push(0xc0);
+    packet.extend(PROBE_VERSION.to_be_bytes());
+    // destination & source connection ids, the answer
+    // uses our source id as its destination id.
+    packet.push(CID_LEN as u8);
+    packet.extend(cid);
+    packet.push(CID_LEN as u8);
+    packet.extend(cid);
+    packet.resize(PROBE_SIZE, 0);
+    packet
+}
+
+/// Returns the index of the probe, if the packet is a version negotiation
+/// packet that answers a probe of this session.
+fn parse_answer(session: u32, packet: &[u8]) -> Option<u32> {
+    let (&first, packet) = packet.split_first()?;
+    if first & 0x80 == 0 {
+        return None;
+    }
+    let (version, packet) = packet.split_at_checked(4)?;
+    if version != [0; 4] {
+        return None;
+    }
+    let (&cid_len, packet) = packet.split_first()?;
+    if cid_len as usize != CID_LEN {
+        return None;
+    }
+    let (cid, _) = packet.split_at_checked(CID_LEN)?;
+    (cid[0..4] == session.to_le_bytes()).then(|| u32::from_le_bytes(cid[4..8].try_into().unwrap()))
+}
+
+/// Sends a probe to every address and measures the round trip time
+/// of the answer.
+///
+/// Addresses that did not answer in time are not part of the result.
+pub async fn ping_servers(
+    addresses: &[SocketAddr],
+    options: PingOptions,
+) -> anyhow::Result<HashMap<SocketAddr, Duration>> {
+    let socket_v4 = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    // ipv6 might not be supported by the system
+    let socket_v6 = UdpSocket::bind((Ipv6Addr::UNSPECIFIED, 0)).await.ok();
+
+    let session = rand::rngs::OsRng.next_u32();
+    let mut sent_at: Vec<Option<Instant>> = vec![None; addresses.len()];
+    let mut res: HashMap<SocketAddr, Duration> = Default::default();
+
+    let per_tick = options
+        .probes_per_second
+        .get()
+        .div_ceil(SEND_TICKS_PER_SECOND) as usize;
+    let mut send_interval = tokio::time::interval(Duration::from_secs(1) / SEND_TICKS_PER_SECOND);
+    let mut next_probe = 0;
+
+    let mut buf_v4 = vec![0; PROBE_SIZE];
+    let mut buf_v6 = vec![0; PROBE_SIZE];
+    let mut deadline = Instant::now() + options.timeout;
+
+    loop {
+        let all_sent = next_probe >= addresses.len();
+        if all_sent && res.len() == addresses.len() {
+            break;
+        }
+        tokio::select! {
+            _ = send_interval.tick(), if !all_sent => {
+                let end = (next_probe + per_tick).min(addresses.len());
+                for (index, addr) in addresses.iter().enumerate().take(end).skip(next_probe) {
+                    let socket = match addr {
+                        SocketAddr::V4(_) => Some(&socket_v4),
+                        SocketAddr::V6(_) => socket_v6.as_ref(),
+                    };
+                    let Some(socket) = socket else {
+                        continue;
+                    };
+                    if let Err(err) = socket.send_to(&probe_packet(session, index as u32), addr).await {
+                        log::debug!(target: "network-ping", "failed to ping {addr}: {err}");
+                        continue;
+                    }
+                    sent_at[index] = Some(Instant::now());
+                }
+                next_probe = end;
+                deadline = Instant::now() + options.timeout;
+            }
+            answer = socket_v4.recv_from(&mut buf_v4) => {
+                if let Ok((size, from)) = answer {
+                    handle_answer(session, &buf_v4[..size], from, addresses, &sent_at, &mut res);
+                }
+            }
+            answer = async { socket_v6.as_ref().unwrap().recv_from(&mut buf_v6).await }, if socket_v6.is_some() => {
+                if let Ok((size, from)) = answer {
+                    handle_answer(session, &buf_v6[..size], from, addresses, &sent_at, &mut res);
+                }
+            }
+            _ = tokio::time::sleep_until(deadline), if all_sent => {
+                break;
+            }
+        }
+    }
+
+    Ok(res)
+}
+
+fn handle_answer(
+    session: u32,
+    packet: &[u8],
+    from: SocketAddr,
+    addresses: &[SocketAddr],
+    sent_at: &[Option<Instant>],
+    res: &mut HashMap<SocketAddr, Duration>,
+) {
+    let Some(index) = parse_answer(session, packet) else {
+        return;
+    };
+    let Some((addr, sent_at)) = addresses
+        .get(index as usize)
+        .zip(sent_at.get(index as usize).copied().flatten())
+    else {
+        return;
+    };
+    // only accept answers of the server the probe was sent to
+    if addr.port() != from.port() || addr.ip().to_canonical() != from.ip().to_canonical() {
+        return;
+    }
+    res.entry(*addr).or_insert_with(|| sent_at.elapsed());
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        net::{Ipv4Addr, SocketAddr},
+        num::NonZeroU32,
+        time::Duration,
+    };
+
+    use tokio::net::UdpSocket;
+
+    use super::{parse_answer, ping_servers, probe_packet, PingOptions, PROBE_SIZE};
+
+    /// Answers probes with a version negotiation packet, like a QUIC server.
+    async fn answer_probes(socket: UdpSocket) {
+        let mut buf = vec![0; PROBE_SIZE];
+        while let Ok((size, from)) = socket.recv_from(&mut buf).await {
+            let probe = &buf[..size];
+            let mut answer = vec![0x80 | 0x2a];
+            answer.extend([0; 4]);
+            answer.extend(&probe[14..23]);
+            answer.extend(&probe[5..14]);
+            answer.extend(1_u32.to_be_bytes());
+            let _ = socket.send_to(&answer, from).await;
+        }
+    }
+
+    #[test]
+    fn answer_of_probe() {
+        let probe = probe_packet(1234, 7);
+        assert_eq!(probe.len(), PROBE_SIZE);
+
+        // version negotiation packet, like a server would send it
+        let mut answer = vec![0x80 | 0x2a];
+        answer.extend([0; 4]);
+        answer.extend(&probe[14..23]);
+        answer.extend(&probe[5..14]);
+        answer.extend(1_u32.to_be_bytes());
+
+        assert_eq!(parse_answer(1234, &answer), Some(7));
+        assert_eq!(parse_answer(4321, &answer), None);
+        assert_eq!(parse_answer(1234, &probe), None);
+        assert_eq!(parse_answer(1234, &answer[..8]), None);
+    }
+
+    #[tokio::test]
+    async fn ping_over_loopback() {
+        let server = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let server_addr = server.local_addr().unwrap();
+        tokio::spawn(answer_probes(server));
+        // bound, but never answers
+        let silent = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let silent_addr = silent.local_addr().unwrap();
+
+        let timeout = Duration::from_millis(500);
+        let pings = ping_servers(
+            &[server_addr, silent_addr],
+            PingOptions {
+                probes_per_second: NonZeroU32::new(200).unwrap(),
+                timeout,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            pings.keys().copied().collect::<Vec<SocketAddr>>(),
+            [server_addr]
+        );
+        assert!(pings[&server_addr] < timeout);
+        drop(silent);
+    }
+}
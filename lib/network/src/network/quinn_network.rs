@@ -305,6 +305,9 @@ impl NetworkConnectionInterface for QuinnNetworkConnectionWrapper {
                             ConnectionErrorCode::Shutdown => {
                                 NetworkEventConnectingClosed::Shutdown(reason)
                             }
+                            ConnectionErrorCode::WrongPassword => {
+                                NetworkEventConnectingClosed::WrongPassword
+                            }
                         },
                         None => NetworkEventConnectingClosed::Other(reason),
                     },
@@ -394,6 +397,9 @@ impl Future for QuinnNetworkConnectingWrapper {
                                 ConnectionErrorCode::Shutdown => {
                                     NetworkEventConnectingClosed::Shutdown(reason)
                                 }
+                                ConnectionErrorCode::WrongPassword => {
+                                    NetworkEventConnectingClosed::WrongPassword
+                                }
                             },
                             None => NetworkEventConnectingClosed::Other(reason),
                         },
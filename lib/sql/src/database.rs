@@ -5,9 +5,10 @@ use game_database::traits::DbKind;
 use sqlx::{
     any::{AnyArguments, AnyRow},
     mysql::{MySqlConnectOptions, MySqlPoolOptions},
+    postgres::{PgConnectOptions, PgPoolOptions, PgSslMode},
     query::QueryAs,
     sqlite::{SqliteConnectOptions, SqlitePoolOptions},
-    Any, FromRow,
+    Any, FromRow, PgPool,
 };
 
 #[derive(Debug, Clone)]
@@ -24,16 +25,42 @@ pub struct DatabaseDetails {
 #[derive(Debug)]
 pub struct Database {
     pub pools: HashMap<DbKind, AnyPool>,
+    /// Postgres is not supported by [`AnyPool`],
+    /// so these pools are kept separately.
+    pub postgres_pools: HashMap<DbKind, PgPool>,
 }
 
 impl Database {
     pub async fn new(connection_details: HashMap<DbKind, DatabaseDetails>) -> anyhow::Result<Self> {
         let mut pools: HashMap<DbKind, AnyPool> = Default::default();
+        let mut postgres_pools: HashMap<DbKind, PgPool> = Default::default();
         for (ty, connection_details) in connection_details {
             let is_localhost = connection_details.host == "localhost"
                 || connection_details.host == "127.0.0.1"
                 || connection_details.host == "::1";
             let pool = match ty {
+                DbKind::Postgres(_) => {
+                    let mut options = PgConnectOptions::new()
+                        .host(&connection_details.host)
+                        .port(connection_details.port)
+                        .database(&connection_details.database)
+                        .username(&connection_details.username)
+                        .password(&connection_details.password)
+                        .ssl_mode(if !is_localhost {
+                            PgSslMode::Require
+                        } else {
+                            PgSslMode::Prefer
+                        });
+                    if !connection_details.ca_cert_path.is_empty() {
+                        options = options.ssl_root_cert(&connection_details.ca_cert_path);
+                    }
+                    let pool = PgPoolOptions::new()
+                        .max_connections(connection_details.connection_count as u32)
+                        .connect_with(options)
+                        .await?;
+                    postgres_pools.insert(ty, pool);
+                    continue;
+                }
                 DbKind::MySql(_) => AnyPool::MySql(
                     MySqlPoolOptions::new()
                         .max_connections(connection_details.connection_count as u32)
@@ -68,7 +95,10 @@ impl Database {
             pools.insert(ty, pool);
         }
 
-        Ok(Self { pools })
+        Ok(Self {
+            pools,
+            postgres_pools,
+        })
     }
 
     pub fn get_query<'a, F>(str: &'a str) -> QueryAs<'a, Any, F, AnyArguments<'a>>
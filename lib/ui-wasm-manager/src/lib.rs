@@ -23,7 +23,7 @@ use wasm_logic_fs::fs::WasmFileSystemLogic;
 use wasm_logic_graphics::WasmGraphicsLogic;
 use wasm_logic_http::http::WasmHttpLogic;
 use wasm_logic_sound::sound::WasmSoundLogic;
-use wasm_runtime::{MemoryLimit, WasmManager, WasmManagerModuleType};
+use wasm_runtime::{MemoryLimit, WasmLimits, WasmManager, WasmManagerModuleType, WasmManifest};
 use wasmer::Module;
 
 pub struct UiWasmPageEntry {
@@ -89,7 +89,7 @@ where
 {
     ui_paths: HashMap<String, UiPageEntry<U>>,
     ui_paths_loading: HashMap<String, anyhow::Result<IoRuntimeTask<Vec<u8>>>>,
-    cache: Arc<Cache<202306060000>>,
+    cache: Arc<Cache<202610171200>>,
     show_cur_page_during_load: bool,
 
     pub ui: UiContainer,
@@ -283,8 +283,14 @@ where
                                         self.id_offset,
                                     );
                                     let sound_logic = WasmSoundLogic::new(self.id_offset, sound);
-                                    let fs_logic = WasmFileSystemLogic::new(io.clone());
-                                    let http_logic = WasmHttpLogic::new(io.clone());
+                                    let fs_logic = WasmFileSystemLogic::new(
+                                        io.clone(),
+                                        WasmManifest::unrestricted(),
+                                    );
+                                    let http_logic = WasmHttpLogic::new(
+                                        io.clone(),
+                                        WasmManifest::unrestricted(),
+                                    );
                                     self.id_offset += u64::MAX as u128;
                                     let wasm_runtime: WasmManager = WasmManager::new(
                                         WasmManagerModuleType::FromClosure(|store| {
@@ -311,6 +317,7 @@ where
                                             Some(imports)
                                         },
                                         MemoryLimit::OneGibiByte,
+                                        WasmLimits::client(),
                                     )
                                     .unwrap();
                                     let mut entry = UiWasmPageEntry { wasm_runtime };
@@ -341,9 +348,12 @@ where
                         cache
                             .load(&path_str, |wasm_bytes| {
                                 Box::pin(async move {
-                                    Ok(WasmManager::compile_module(&wasm_bytes)?
-                                        .serialize()?
-                                        .to_vec())
+                                    Ok(WasmManager::compile_module(
+                                        &wasm_bytes,
+                                        &WasmLimits::client(),
+                                    )?
+                                    .serialize()?
+                                    .to_vec())
                                 })
                             })
                             .await
@@ -3,11 +3,15 @@ use std::sync::Mutex;
 use std::{cell::RefCell, collections::HashMap, sync::Arc};
 
 use base_io::runtime::{IoRuntime, IoRuntimeTask};
+use game_database::migration::Migrations;
 use game_database::statement::StatementDriverProps;
 use game_database::{statement::QueryProperties, traits::DbInterface};
-use game_database::{traits::DbKind, types::DbType};
+use game_database::{
+    traits::DbKind,
+    types::{DbType, ExecuteResult},
+};
 use sendable::SendOption;
-use wasm_runtime_types::{read_param, write_result, RawBytesEnv};
+use wasm_runtime_types::{manifest::WasmManifest, read_param, write_result, RawBytesEnv};
 use wasmer::{imports, AsStoreRef, Function, FunctionEnv, FunctionEnvMut, Imports, Store};
 
 type OptionalFetchTasks = HashMap<u64, IoRuntimeTask<Option<HashMap<String, DbType>>>>;
@@ -16,30 +20,90 @@ type FetchAllTasks = HashMap<u64, IoRuntimeTask<Vec<HashMap<String, DbType>>>>;
 pub struct WasmDatabaseLogicImpl {
     pub io_rt: IoRuntime,
     pub db: Arc<dyn DbInterface>,
+    manifest: WasmManifest,
     setup_tasks: RefCell<HashMap<u64, IoRuntimeTask<()>>>,
+    migrate_tasks: RefCell<HashMap<u64, IoRuntimeTask<()>>>,
     prepare_tasks: RefCell<HashMap<u64, IoRuntimeTask<u64>>>,
     fetch_tasks_optional: RefCell<OptionalFetchTasks>,
     fetch_tasks_one: RefCell<HashMap<u64, IoRuntimeTask<HashMap<String, DbType>>>>,
     fetch_tasks_all: RefCell<FetchAllTasks>,
-    execute_tasks: RefCell<HashMap<u64, IoRuntimeTask<u64>>>,
+    execute_tasks: RefCell<HashMap<u64, IoRuntimeTask<ExecuteResult>>>,
+    begin_transaction_tasks: RefCell<HashMap<u64, IoRuntimeTask<u64>>>,
+    commit_transaction_tasks: RefCell<HashMap<u64, IoRuntimeTask<()>>>,
+    rollback_transaction_tasks: RefCell<HashMap<u64, IoRuntimeTask<()>>>,
+    transaction_fetch_tasks_all: RefCell<FetchAllTasks>,
+    transaction_execute_tasks: RefCell<HashMap<u64, IoRuntimeTask<u64>>>,
+    /// The ids of the statements & transactions are shared by all modules,
+    /// so a module may only use the ones it created itself.
+    statements: RefCell<HashSet<u64>>,
+    transactions: RefCell<HashSet<u64>>,
 }
 
 impl WasmDatabaseLogicImpl {
-    fn new(io_rt: IoRuntime, db: Arc<dyn DbInterface>) -> Self {
+    fn new(io_rt: IoRuntime, db: Arc<dyn DbInterface>, manifest: WasmManifest) -> Self {
         Self {
             io_rt,
             db,
+            manifest,
             setup_tasks: Default::default(),
+            migrate_tasks: Default::default(),
             prepare_tasks: Default::default(),
             fetch_tasks_optional: Default::default(),
             fetch_tasks_one: Default::default(),
             fetch_tasks_all: Default::default(),
             execute_tasks: Default::default(),
+            begin_transaction_tasks: Default::default(),
+            commit_transaction_tasks: Default::default(),
+            rollback_transaction_tasks: Default::default(),
+            transaction_fetch_tasks_all: Default::default(),
+            transaction_execute_tasks: Default::default(),
+            statements: Default::default(),
+            transactions: Default::default(),
         }
     }
 
+    fn check_statement(&self, unique_id: u64) -> Result<(), String> {
+        if self.statements.borrow().contains(&unique_id) {
+            Ok(())
+        } else {
+            Err(format!(
+                "the statement {unique_id} was not prepared by the module"
+            ))
+        }
+    }
+
+    fn check_transaction(&self, transaction_id: u64) -> Result<(), String> {
+        if self.transactions.borrow().contains(&transaction_id) {
+            Ok(())
+        } else {
+            Err(format!(
+                "the transaction {transaction_id} was not started by the module"
+            ))
+        }
+    }
+
+    fn check_kind(&self, kind: &DbKind) -> Result<(), String> {
+        if self.manifest.allows_db_kind(kind.name()) {
+            Ok(())
+        } else {
+            log::warn!(
+                "denied database access of wasm module to {}: kind is not in its manifest",
+                kind.name()
+            );
+            Err(format!(
+                "the database kind {} is not allowed by the manifest of the module",
+                kind.name()
+            ))
+        }
+    }
+
+    /// Only the kinds the module is allowed to use.
     fn kinds(&self) -> HashSet<DbKind> {
-        self.db.kinds()
+        self.db
+            .kinds()
+            .into_iter()
+            .filter(|kind| self.manifest.allows_db_kind(kind.name()))
+            .collect()
     }
 
     fn setup(
@@ -59,6 +123,14 @@ impl WasmDatabaseLogicImpl {
                 }
             }
             None => {
+                // the kinds of the module's own statements were checked when they were prepared
+                if let Err(err) = versioned_stmts
+                    .values()
+                    .flatten()
+                    .try_for_each(|unique_id| self.check_statement(*unique_id))
+                {
+                    return Some(Err(err));
+                }
                 let db = self.db.clone();
                 let task = self
                     .io_rt
@@ -69,6 +141,35 @@ impl WasmDatabaseLogicImpl {
         }
     }
 
+    fn migrate(&self, id: u64, migrations: Migrations) -> Option<Result<(), String>> {
+        let mut tasks = self.migrate_tasks.borrow_mut();
+        match tasks.get(&id) {
+            Some(task) => {
+                if task.is_finished() {
+                    let task = tasks.remove(&id).unwrap();
+                    Some(task.get_storage().map_err(|err| err.to_string()))
+                } else {
+                    None
+                }
+            }
+            None => {
+                if let Err(err) = migrations
+                    .kinds
+                    .iter()
+                    .try_for_each(|kind| self.check_kind(kind))
+                {
+                    return Some(Err(err));
+                }
+                let db = self.db.clone();
+                let task = self
+                    .io_rt
+                    .spawn(async move { db.migrate(migrations).await });
+                tasks.insert(id, task);
+                None
+            }
+        }
+    }
+
     fn prepare_statement(
         &self,
         id: u64,
@@ -81,12 +182,19 @@ impl WasmDatabaseLogicImpl {
             Some(task) => {
                 if task.is_finished() {
                     let task = tasks.remove(&id).unwrap();
-                    Some(task.get_storage().map_err(|err| err.to_string()))
+                    let res = task.get_storage().map_err(|err| err.to_string());
+                    if let Ok(unique_id) = res {
+                        self.statements.borrow_mut().insert(unique_id);
+                    }
+                    Some(res)
                 } else {
                     None
                 }
             }
             None => {
+                if let Err(err) = self.check_kind(&kind) {
+                    return Some(Err(err));
+                }
                 let db = self.db.clone();
                 let task = self.io_rt.spawn(async move {
                     db.prepare_statement(&query_props, &kind, &driver_props)
@@ -99,7 +207,9 @@ impl WasmDatabaseLogicImpl {
     }
 
     fn drop_statement(&self, unique_id: u64) {
-        self.db.drop_statement(unique_id);
+        if self.statements.borrow_mut().remove(&unique_id) {
+            self.db.drop_statement(unique_id);
+        }
     }
 
     fn fetch_optional(
@@ -119,6 +229,9 @@ impl WasmDatabaseLogicImpl {
                 }
             }
             None => {
+                if let Err(err) = self.check_statement(unique_id) {
+                    return Some(Err(err));
+                }
                 let db = self.db.clone();
                 let task = self
                     .io_rt
@@ -146,6 +259,9 @@ impl WasmDatabaseLogicImpl {
                 }
             }
             None => {
+                if let Err(err) = self.check_statement(unique_id) {
+                    return Some(Err(err));
+                }
                 let db = self.db.clone();
                 let task = self
                     .io_rt
@@ -173,6 +289,9 @@ impl WasmDatabaseLogicImpl {
                 }
             }
             None => {
+                if let Err(err) = self.check_statement(unique_id) {
+                    return Some(Err(err));
+                }
                 let db = self.db.clone();
                 let task = self
                     .io_rt
@@ -183,7 +302,12 @@ impl WasmDatabaseLogicImpl {
         }
     }
 
-    fn execute(&self, id: u64, unique_id: u64, args: Vec<DbType>) -> Option<Result<u64, String>> {
+    fn execute(
+        &self,
+        id: u64,
+        unique_id: u64,
+        args: Vec<DbType>,
+    ) -> Option<Result<ExecuteResult, String>> {
         let mut tasks = self.execute_tasks.borrow_mut();
         match tasks.get(&id) {
             Some(task) => {
@@ -195,6 +319,9 @@ impl WasmDatabaseLogicImpl {
                 }
             }
             None => {
+                if let Err(err) = self.check_statement(unique_id) {
+                    return Some(Err(err));
+                }
                 let db = self.db.clone();
                 let task = self
                     .io_rt
@@ -204,14 +331,169 @@ impl WasmDatabaseLogicImpl {
             }
         }
     }
+
+    fn begin_transaction(&self, id: u64, kind: DbKind) -> Option<Result<u64, String>> {
+        let mut tasks = self.begin_transaction_tasks.borrow_mut();
+        match tasks.get(&id) {
+            Some(task) => {
+                if task.is_finished() {
+                    let task = tasks.remove(&id).unwrap();
+                    let res = task.get_storage().map_err(|err| err.to_string());
+                    if let Ok(transaction_id) = res {
+                        self.transactions.borrow_mut().insert(transaction_id);
+                    }
+                    Some(res)
+                } else {
+                    None
+                }
+            }
+            None => {
+                if let Err(err) = self.check_kind(&kind) {
+                    return Some(Err(err));
+                }
+                let db = self.db.clone();
+                let task = self
+                    .io_rt
+                    .spawn(async move { db.begin_transaction(&kind).await });
+                tasks.insert(id, task);
+                None
+            }
+        }
+    }
+
+    fn commit_transaction(&self, id: u64, transaction_id: u64) -> Option<Result<(), String>> {
+        let mut tasks = self.commit_transaction_tasks.borrow_mut();
+        match tasks.get(&id) {
+            Some(task) => {
+                if task.is_finished() {
+                    let task = tasks.remove(&id).unwrap();
+                    Some(task.get_storage().map_err(|err| err.to_string()))
+                } else {
+                    None
+                }
+            }
+            None => {
+                if let Err(err) = self.check_transaction(transaction_id) {
+                    return Some(Err(err));
+                }
+                let db = self.db.clone();
+                let task = self
+                    .io_rt
+                    .spawn(async move { db.commit_transaction(transaction_id).await });
+                tasks.insert(id, task);
+                None
+            }
+        }
+    }
+
+    fn rollback_transaction(&self, id: u64, transaction_id: u64) -> Option<Result<(), String>> {
+        let mut tasks = self.rollback_transaction_tasks.borrow_mut();
+        match tasks.get(&id) {
+            Some(task) => {
+                if task.is_finished() {
+                    let task = tasks.remove(&id).unwrap();
+                    Some(task.get_storage().map_err(|err| err.to_string()))
+                } else {
+                    None
+                }
+            }
+            None => {
+                if let Err(err) = self.check_transaction(transaction_id) {
+                    return Some(Err(err));
+                }
+                let db = self.db.clone();
+                let task = self
+                    .io_rt
+                    .spawn(async move { db.rollback_transaction(transaction_id).await });
+                tasks.insert(id, task);
+                None
+            }
+        }
+    }
+
+    fn drop_transaction(&self, transaction_id: u64) {
+        if self.transactions.borrow_mut().remove(&transaction_id) {
+            self.db.drop_transaction(transaction_id);
+        }
+    }
+
+    fn transaction_fetch_all(
+        &self,
+        id: u64,
+        transaction_id: u64,
+        unique_id: u64,
+        args: Vec<DbType>,
+    ) -> Option<Result<Vec<HashMap<String, DbType>>, String>> {
+        let mut tasks = self.transaction_fetch_tasks_all.borrow_mut();
+        match tasks.get(&id) {
+            Some(task) => {
+                if task.is_finished() {
+                    let task = tasks.remove(&id).unwrap();
+                    Some(task.get_storage().map_err(|err| err.to_string()))
+                } else {
+                    None
+                }
+            }
+            None => {
+                if let Err(err) = self
+                    .check_transaction(transaction_id)
+                    .and_then(|_| self.check_statement(unique_id))
+                {
+                    return Some(Err(err));
+                }
+                let db = self.db.clone();
+                let task = self.io_rt.spawn(async move {
+                    db.transaction_fetch_all(transaction_id, unique_id, args)
+                        .await
+                });
+                tasks.insert(id, task);
+                None
+            }
+        }
+    }
+
+    fn transaction_execute(
+        &self,
+        id: u64,
+        transaction_id: u64,
+        unique_id: u64,
+        args: Vec<DbType>,
+    ) -> Option<Result<u64, String>> {
+        let mut tasks = self.transaction_execute_tasks.borrow_mut();
+        match tasks.get(&id) {
+            Some(task) => {
+                if task.is_finished() {
+                    let task = tasks.remove(&id).unwrap();
+                    Some(task.get_storage().map_err(|err| err.to_string()))
+                } else {
+                    None
+                }
+            }
+            None => {
+                if let Err(err) = self
+                    .check_transaction(transaction_id)
+                    .and_then(|_| self.check_statement(unique_id))
+                {
+                    return Some(Err(err));
+                }
+                let db = self.db.clone();
+                let task = self.io_rt.spawn(async move {
+                    db.transaction_execute(transaction_id, unique_id, args)
+                        .await
+                });
+                tasks.insert(id, task);
+                None
+            }
+        }
+    }
 }
 
 pub struct WasmDatabaseLogic(pub Arc<Mutex<SendOption<WasmDatabaseLogicImpl>>>);
 
 impl WasmDatabaseLogic {
-    pub fn new(io_rt: IoRuntime, db: Arc<dyn DbInterface>) -> Self {
+    pub fn new(io_rt: IoRuntime, db: Arc<dyn DbInterface>, manifest: WasmManifest) -> Self {
         Self(Arc::new(Mutex::new(SendOption::new(Some(
-            WasmDatabaseLogicImpl::new(io_rt, db),
+            WasmDatabaseLogicImpl::new(io_rt, db, manifest),
         )))))
     }
 
@@ -264,6 +546,34 @@ impl WasmDatabaseLogic {
             write_result(instance.as_ref().unwrap(), &mut store, &res);
         }
 
+        fn migrate(
+            logic_clone: &Arc<Mutex<SendOption<WasmDatabaseLogicImpl>>>,
+            mut env: FunctionEnvMut<Arc<RawBytesEnv>>,
+        ) {
+            let (data, mut store) = env.data_and_store_mut();
+            let (mut param0, instance) = data.param_index_mut();
+            let id: u64 = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                0,
+            );
+            let migrations: Migrations = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                1,
+            );
+
+            let res = logic_clone
+                .lock()
+                .unwrap()
+                .as_ref()
+                .unwrap()
+                .migrate(id, migrations);
+            write_result(instance.as_ref().unwrap(), &mut store, &res);
+        }
+
         fn prepare_statement(
             logic_clone: &Arc<Mutex<SendOption<WasmDatabaseLogicImpl>>>,
             mut env: FunctionEnvMut<Arc<RawBytesEnv>>,
@@ -461,6 +771,191 @@ impl WasmDatabaseLogic {
             write_result(instance.as_ref().unwrap(), &mut store, &res);
         }
 
+        fn begin_transaction(
+            logic_clone: &Arc<Mutex<SendOption<WasmDatabaseLogicImpl>>>,
+            mut env: FunctionEnvMut<Arc<RawBytesEnv>>,
+        ) {
+            let (data, mut store) = env.data_and_store_mut();
+            let (mut param0, instance) = data.param_index_mut();
+            let id: u64 = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                0,
+            );
+            let kind: DbKind = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                1,
+            );
+
+            let res = logic_clone
+                .lock()
+                .unwrap()
+                .as_ref()
+                .unwrap()
+                .begin_transaction(id, kind);
+            write_result(instance.as_ref().unwrap(), &mut store, &res);
+        }
+
+        fn commit_transaction(
+            logic_clone: &Arc<Mutex<SendOption<WasmDatabaseLogicImpl>>>,
+            mut env: FunctionEnvMut<Arc<RawBytesEnv>>,
+        ) {
+            let (data, mut store) = env.data_and_store_mut();
+            let (mut param0, instance) = data.param_index_mut();
+            let id: u64 = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                0,
+            );
+            let transaction_id: u64 = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                1,
+            );
+
+            let res = logic_clone
+                .lock()
+                .unwrap()
+                .as_ref()
+                .unwrap()
+                .commit_transaction(id, transaction_id);
+            write_result(instance.as_ref().unwrap(), &mut store, &res);
+        }
+
+        fn rollback_transaction(
+            logic_clone: &Arc<Mutex<SendOption<WasmDatabaseLogicImpl>>>,
+            mut env: FunctionEnvMut<Arc<RawBytesEnv>>,
+        ) {
+            let (data, mut store) = env.data_and_store_mut();
+            let (mut param0, instance) = data.param_index_mut();
+            let id: u64 = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                0,
+            );
+            let transaction_id: u64 = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                1,
+            );
+
+            let res = logic_clone
+                .lock()
+                .unwrap()
+                .as_ref()
+                .unwrap()
+                .rollback_transaction(id, transaction_id);
+            write_result(instance.as_ref().unwrap(), &mut store, &res);
+        }
+
+        fn drop_transaction(
+            logic_clone: &Arc<Mutex<SendOption<WasmDatabaseLogicImpl>>>,
+            mut env: FunctionEnvMut<Arc<RawBytesEnv>>,
+        ) {
+            let (data, store) = env.data_and_store_mut();
+            let (mut param0, instance) = data.param_index_mut();
+            let transaction_id: u64 = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                0,
+            );
+
+            logic_clone
+                .lock()
+                .unwrap()
+                .as_ref()
+                .unwrap()
+                .drop_transaction(transaction_id);
+        }
+
+        fn transaction_fetch_all(
+            logic_clone: &Arc<Mutex<SendOption<WasmDatabaseLogicImpl>>>,
+            mut env: FunctionEnvMut<Arc<RawBytesEnv>>,
+        ) {
+            let (data, mut store) = env.data_and_store_mut();
+            let (mut param0, instance) = data.param_index_mut();
+            let id: u64 = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                0,
+            );
+            let transaction_id: u64 = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                1,
+            );
+            let unique_id: u64 = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                2,
+            );
+            let args: Vec<DbType> = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                3,
+            );
+
+            let res = logic_clone
+                .lock()
+                .unwrap()
+                .as_ref()
+                .unwrap()
+                .transaction_fetch_all(id, transaction_id, unique_id, args);
+            write_result(instance.as_ref().unwrap(), &mut store, &res);
+        }
+
+        fn transaction_execute(
+            logic_clone: &Arc<Mutex<SendOption<WasmDatabaseLogicImpl>>>,
+            mut env: FunctionEnvMut<Arc<RawBytesEnv>>,
+        ) {
+            let (data, mut store) = env.data_and_store_mut();
+            let (mut param0, instance) = data.param_index_mut();
+            let id: u64 = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                0,
+            );
+            let transaction_id: u64 = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                1,
+            );
+            let unique_id: u64 = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                2,
+            );
+            let args: Vec<DbType> = read_param(
+                instance.as_ref().unwrap(),
+                &store.as_store_ref(),
+                &mut param0,
+                3,
+            );
+
+            let res = logic_clone
+                .lock()
+                .unwrap()
+                .as_ref()
+                .unwrap()
+                .transaction_execute(id, transaction_id, unique_id, args);
+            write_result(instance.as_ref().unwrap(), &mut store, &res);
+        }
+
         let logic = self.0.clone();
         let logic1 = self.0.clone();
         let logic2 = self.0.clone();
@@ -469,6 +964,13 @@ impl WasmDatabaseLogic {
         let logic5 = self.0.clone();
         let logic6 = self.0.clone();
         let logic7 = self.0.clone();
+        let logic8 = self.0.clone();
+        let logic9 = self.0.clone();
+        let logic10 = self.0.clone();
+        let logic11 = self.0.clone();
+        let logic12 = self.0.clone();
+        let logic13 = self.0.clone();
+        let logic14 = self.0.clone();
 
         imports! {
             "env" => {
@@ -480,7 +982,110 @@ impl WasmDatabaseLogic {
                 "api_db_fetch_one" => Function::new_typed_with_env(store, raw_bytes_env, move |env: FunctionEnvMut<Arc<RawBytesEnv>>| fetch_one(&logic5, env)),
                 "api_db_fetch_all" => Function::new_typed_with_env(store, raw_bytes_env, move |env: FunctionEnvMut<Arc<RawBytesEnv>>| fetch_all(&logic6, env)),
                 "api_db_execute" => Function::new_typed_with_env(store, raw_bytes_env, move |env: FunctionEnvMut<Arc<RawBytesEnv>>| execute(&logic7, env)),
+                "api_db_begin_transaction" => Function::new_typed_with_env(store, raw_bytes_env, move |env: FunctionEnvMut<Arc<RawBytesEnv>>| begin_transaction(&logic8, env)),
+                "api_db_commit_transaction" => Function::new_typed_with_env(store, raw_bytes_env, move |env: FunctionEnvMut<Arc<RawBytesEnv>>| commit_transaction(&logic9, env)),
+                "api_db_rollback_transaction" => Function::new_typed_with_env(store, raw_bytes_env, move |env: FunctionEnvMut<Arc<RawBytesEnv>>| rollback_transaction(&logic10, env)),
+                "api_db_drop_transaction" => Function::new_typed_with_env(store, raw_bytes_env, move |env: FunctionEnvMut<Arc<RawBytesEnv>>| drop_transaction(&logic11, env)),
+                "api_db_transaction_fetch_all" => Function::new_typed_with_env(store, raw_bytes_env, move |env: FunctionEnvMut<Arc<RawBytesEnv>>| transaction_fetch_all(&logic12, env)),
+                "api_db_transaction_execute" => Function::new_typed_with_env(store, raw_bytes_env, move |env: FunctionEnvMut<Arc<RawBytesEnv>>| transaction_execute(&logic13, env)),
+                "api_db_migrate" => Function::new_typed_with_env(store, raw_bytes_env, move |env: FunctionEnvMut<Arc<RawBytesEnv>>| migrate(&logic14, env)),
             }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+    use base_io::{io::create_runtime, runtime::IoRuntime};
+    use game_database::{
+        dummy::DummyDb,
+        migration::Migrations,
+        statement::{QueryProperties, StatementDriverProps},
+        traits::{DbKind, DbKindExtra},
+    };
+    use wasm_runtime_types::manifest::WasmManifest;
+
+    use super::WasmDatabaseLogicImpl;
+
+    fn logic() -> WasmDatabaseLogicImpl {
+        WasmDatabaseLogicImpl::new(
+            IoRuntime::new(create_runtime()),
+            Arc::new(DummyDb),
+            WasmManifest {
+                db_kinds: vec!["sqlite".to_string()],
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Polls the call until its task finished.
+    fn wait<T>(mut call: impl FnMut() -> Option<T>) -> T {
+        loop {
+            if let Some(res) = call() {
+                return res;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn prepare(logic: &WasmDatabaseLogicImpl, kind: DbKind) -> Option<Result<u64, String>> {
+        logic.prepare_statement(
+            0,
+            QueryProperties {
+                result_mapping: Default::default(),
+            },
+            kind,
+            StatementDriverProps {
+                sql: "SELECT 1".to_string(),
+                arguments_mapping: Default::default(),
+            },
+        )
+    }
+
+    #[test]
+    fn kinds_not_in_manifest_are_denied() {
+        let logic = logic();
+        let denied = DbKind::MySql(DbKindExtra::Main);
+
+        assert!(prepare(&logic, denied).unwrap().is_err());
+        assert!(logic.begin_transaction(0, denied).unwrap().is_err());
+        assert!(logic
+            .migrate(0, Migrations::new("test", [denied]))
+            .unwrap()
+            .is_err());
+
+        // allowed kinds reach the database, which fails on its own
+        let allowed = DbKind::Sqlite(DbKindExtra::Main);
+        assert!(prepare(&logic, allowed).is_none());
+        let err = wait(|| prepare(&logic, allowed)).unwrap_err();
+        assert!(err.contains("dummy database"), "{err}");
+    }
+
+    #[test]
+    fn statements_of_other_modules_are_denied() {
+        let logic = logic();
+
+        assert!(logic.execute(0, 1, vec![]).unwrap().is_err());
+        assert!(logic.fetch_one(0, 1, vec![]).unwrap().is_err());
+        assert!(logic.fetch_all(0, 1, vec![]).unwrap().is_err());
+        assert!(logic.fetch_optional(0, 1, vec![]).unwrap().is_err());
+        assert!(logic
+            .setup(0, "test".to_string(), BTreeMap::from([(0, vec![1])]))
+            .unwrap()
+            .is_err());
+        assert!(logic.commit_transaction(0, 1).unwrap().is_err());
+        assert!(logic.rollback_transaction(0, 1).unwrap().is_err());
+
+        // a statement prepared by the module itself reaches the database
+        logic.statements.borrow_mut().insert(1);
+        let setup = |logic: &WasmDatabaseLogicImpl| {
+            logic.setup(0, "test".to_string(), BTreeMap::from([(0, vec![1])]))
+        };
+        assert!(setup(&logic).is_none());
+        let err = wait(|| setup(&logic)).unwrap_err();
+        assert!(err.contains("dummy database"), "{err}");
+        assert!(logic.execute(1, 1, vec![]).is_none());
+    }
+}
@@ -7,13 +7,14 @@ use std::{
 use base_io::{io::Io, runtime::IoRuntimeTask};
 use base_io_traits::fs_traits::{FileSystemEntryTy, HashMap};
 use sendable::SendOption;
-use wasm_runtime_types::{read_param, write_result, RawBytesEnv};
+use wasm_runtime_types::{manifest::WasmManifest, read_param, write_result, RawBytesEnv};
 use wasmer::{imports, AsStoreRef, Function, FunctionEnv, FunctionEnvMut, Imports, Store};
 
 type DirTasks = HashMap<u64, IoRuntimeTask<HashMap<PathBuf, Vec<u8>>>>;
 
 pub struct WasmFileSystemLogicImpl {
     pub io: Io,
+    manifest: WasmManifest,
     tasks: RefCell<HashMap<u64, IoRuntimeTask<Vec<u8>>>>,
     write_tasks: RefCell<HashMap<u64, IoRuntimeTask<()>>>,
     create_dir_tasks: RefCell<HashMap<u64, IoRuntimeTask<()>>>,
@@ -22,9 +23,10 @@ pub struct WasmFileSystemLogicImpl {
 }
 
 impl WasmFileSystemLogicImpl {
-    fn new(io: Io) -> Self {
+    fn new(io: Io, manifest: WasmManifest) -> Self {
         Self {
             io,
+            manifest,
             tasks: Default::default(),
             write_tasks: Default::default(),
             create_dir_tasks: Default::default(),
@@ -33,6 +35,20 @@ impl WasmFileSystemLogicImpl {
         }
     }
 
+    fn check_path(&self, path: &Path) -> Result<(), String> {
+        if self.manifest.allows_path(path) {
+            Ok(())
+        } else {
+            log::warn!(
+                "denied file system access of wasm module to {path:?}: \
+                path is not in its manifest"
+            );
+            Err(format!(
+                "the path {path:?} is not allowed by the manifest of the module"
+            ))
+        }
+    }
+
     fn read_file(&self, file_id: u64, file_path: &Path) -> Option<Result<Vec<u8>, String>> {
         let mut tasks = self.tasks.borrow_mut();
         match tasks.get(&file_id) {
@@ -45,6 +61,9 @@ impl WasmFileSystemLogicImpl {
                 }
             }
             None => {
+                if let Err(err) = self.check_path(file_path) {
+                    return Some(Err(err));
+                }
                 let fs = self.io.fs.clone();
                 let file_path_str = file_path.to_path_buf();
                 let task = self
@@ -74,6 +93,9 @@ impl WasmFileSystemLogicImpl {
                 }
             }
             None => {
+                if let Err(err) = self.check_path(file_path) {
+                    return Some(Err(err));
+                }
                 let fs = self.io.fs.clone();
                 let file_path_str = file_path.to_path_buf();
                 let task = self
@@ -98,6 +120,9 @@ impl WasmFileSystemLogicImpl {
                 }
             }
             None => {
+                if let Err(err) = self.check_path(dir_path) {
+                    return Some(Err(err));
+                }
                 let fs = self.io.fs.clone();
                 let dir_path_str = dir_path.to_path_buf();
                 let task = self
@@ -126,6 +151,9 @@ impl WasmFileSystemLogicImpl {
                 }
             }
             None => {
+                if let Err(err) = self.check_path(path) {
+                    return Some(Err(err));
+                }
                 let fs = self.io.fs.clone();
                 let path_str = path.to_path_buf();
                 let task = self
@@ -154,6 +182,9 @@ impl WasmFileSystemLogicImpl {
                 }
             }
             None => {
+                if let Err(err) = self.check_path(path) {
+                    return Some(Err(err));
+                }
                 let fs = self.io.fs.clone();
                 let path_str = path.to_path_buf();
                 let task = self
@@ -170,9 +201,9 @@ impl WasmFileSystemLogicImpl {
 pub struct WasmFileSystemLogic(pub Arc<Mutex<SendOption<WasmFileSystemLogicImpl>>>);
 
 impl WasmFileSystemLogic {
-    pub fn new(io: Io) -> Self {
+    pub fn new(io: Io, manifest: WasmManifest) -> Self {
         Self(Arc::new(Mutex::new(SendOption::new(Some(
-            WasmFileSystemLogicImpl::new(io),
+            WasmFileSystemLogicImpl::new(io, manifest),
         )))))
     }
 
@@ -344,3 +375,152 @@ impl WasmFileSystemLogic {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{
+        path::{Path, PathBuf},
+        sync::Arc,
+        time::Duration,
+    };
+
+    use base_io::io::Io;
+    use base_io_traits::{
+        fs_traits::{
+            FileSystemEntryTy, FileSystemInterface, FileSystemPath, FileSystemWatcherItemInterface,
+            HashMap,
+        },
+        http_traits::{HttpClientInterface, HttpError, HttpHeaderValue},
+    };
+    use wasm_runtime_types::manifest::WasmManifest;
+
+    use super::WasmFileSystemLogicImpl;
+
+    /// Returns the path of a file as its content.
+    #[derive(Debug)]
+    struct PathFs;
+
+    #[async_trait::async_trait]
+    impl FileSystemInterface for PathFs {
+        async fn read_file(&self, file_path: &Path) -> std::io::Result<Vec<u8>> {
+            Ok(file_path.to_string_lossy().as_bytes().to_vec())
+        }
+        async fn read_file_in(
+            &self,
+            file_path: &Path,
+            _path: FileSystemPath,
+        ) -> std::io::Result<Vec<u8>> {
+            self.read_file(file_path).await
+        }
+        async fn file_exists(&self, _file_path: &Path) -> bool {
+            true
+        }
+        async fn write_file(&self, _file_path: &Path, _data: Vec<u8>) -> std::io::Result<()> {
+            Ok(())
+        }
+        async fn create_dir(&self, _dir_path: &Path) -> std::io::Result<()> {
+            Ok(())
+        }
+        async fn entries_in_dir(
+            &self,
+            _path: &Path,
+        ) -> anyhow::Result<HashMap<String, FileSystemEntryTy>> {
+            Ok(Default::default())
+        }
+        async fn files_in_dir_recursive(
+            &self,
+            _path: &Path,
+        ) -> anyhow::Result<HashMap<PathBuf, Vec<u8>>> {
+            Ok(Default::default())
+        }
+        fn get_save_path(&self) -> PathBuf {
+            Default::default()
+        }
+        fn get_secure_path(&self) -> PathBuf {
+            Default::default()
+        }
+        fn get_cache_path(&self) -> PathBuf {
+            Default::default()
+        }
+        fn watch_for_change(
+            &self,
+            _path: &Path,
+            _file: Option<&Path>,
+        ) -> Box<dyn FileSystemWatcherItemInterface> {
+            unimplemented!()
+        }
+    }
+
+    #[derive(Debug)]
+    struct NoHttp;
+
+    #[async_trait::async_trait]
+    impl HttpClientInterface for NoHttp {
+        async fn download_text(&self, _url: url::Url) -> Result<String, HttpError> {
+            Err(HttpError::Request)
+        }
+        async fn download_binary_secure(&self, _url: url::Url) -> Result<bytes::Bytes, HttpError> {
+            Err(HttpError::Request)
+        }
+        async fn download_binary(
+            &self,
+            _url: url::Url,
+            _hash: &base::hash::Hash,
+        ) -> Result<bytes::Bytes, HttpError> {
+            Err(HttpError::Request)
+        }
+        async fn post_json(&self, _url: url::Url, _data: Vec<u8>) -> Result<Vec<u8>, HttpError> {
+            Err(HttpError::Request)
+        }
+        async fn custom_request(
+            &self,
+            _url: url::Url,
+            _headers: Vec<HttpHeaderValue>,
+            _content: Option<Vec<u8>>,
+        ) -> Result<bytes::Bytes, HttpError> {
+            Err(HttpError::Request)
+        }
+    }
+
+    /// Polls the call until its task finished.
+    fn wait<T>(mut call: impl FnMut() -> Option<T>) -> T {
+        loop {
+            if let Some(res) = call() {
+                return res;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn paths_not_in_manifest_are_denied() {
+        let logic = WasmFileSystemLogicImpl::new(
+            Io::new(|_| Arc::new(PathFs), Arc::new(NoHttp)),
+            WasmManifest {
+                fs_paths: vec!["mods/my_mod".into()],
+                ..Default::default()
+            },
+        );
+
+        for path in [
+            "mods/other_mod/file.txt",
+            "mods/my_mod/../other_mod/file.txt",
+            "/mods/my_mod/file.txt",
+            "config.json",
+        ] {
+            let path = Path::new(path);
+            assert!(logic.read_file(0, path).unwrap().is_err(), "{path:?}");
+            assert!(logic.write_file(0, path, vec![]).unwrap().is_err());
+            assert!(logic.create_dir(0, path).unwrap().is_err());
+            assert!(logic.files_in_dir_recursive(0, path).unwrap().is_err());
+            assert!(logic.entries_in_dir(0, path).unwrap().is_err());
+        }
+
+        let path = Path::new("mods/my_mod/file.txt");
+        assert!(logic.read_file(0, path).is_none());
+        let file = wait(|| logic.read_file(0, path)).unwrap();
+        assert_eq!(file, b"mods/my_mod/file.txt");
+        assert!(logic.write_file(0, path, vec![]).is_none());
+        assert!(wait(|| logic.write_file(0, path, vec![])).is_ok());
+    }
+}
@@ -10,28 +10,42 @@ use base_io_traits::http_traits::HttpError;
 use bytes::Bytes;
 use sendable::SendOption;
 use url::Url;
-use wasm_runtime_types::{read_param, write_result, RawBytesEnv};
+use wasm_runtime_types::{manifest::WasmManifest, read_param, write_result, RawBytesEnv};
 use wasmer::{imports, AsStoreRef, Function, FunctionEnv, FunctionEnvMut, Imports, Store};
 
 type PostTasks = HashMap<u64, IoRuntimeTask<Result<Vec<u8>, HttpError>>>;
 
 pub struct WasmHttpLogicImpl {
     pub io: Io,
+    manifest: WasmManifest,
     tasks: RefCell<HashMap<u64, IoRuntimeTask<Result<String, HttpError>>>>,
     bin_tasks: RefCell<HashMap<u64, IoRuntimeTask<Result<Bytes, HttpError>>>>,
     post_tasks: RefCell<PostTasks>,
 }
 
 impl WasmHttpLogicImpl {
-    fn new(io: Io) -> Self {
+    fn new(io: Io, manifest: WasmManifest) -> Self {
         Self {
             io,
+            manifest,
             tasks: Default::default(),
             bin_tasks: Default::default(),
             post_tasks: Default::default(),
         }
     }
 
+    fn check_url(&self, url: &Url) -> Result<(), HttpError> {
+        let host = url.host_str().unwrap_or_default();
+        if self.manifest.allows_host(host) {
+            Ok(())
+        } else {
+            log::warn!("denied http request of wasm module to {url}: host is not in its manifest");
+            Err(HttpError::Other(format!(
+                "the host {host} is not allowed by the manifest of the module"
+            )))
+        }
+    }
+
     fn download_text(&self, task_id: u64, url: Url) -> Option<Result<String, HttpError>> {
         let mut tasks = self.tasks.borrow_mut();
         match tasks.get(&task_id) {
@@ -48,6 +62,9 @@ impl WasmHttpLogicImpl {
                 }
             }
             None => {
+                if let Err(err) = self.check_url(&url) {
+                    return Some(Err(err));
+                }
                 let http = self.io.http.clone();
                 let task = self
                     .io
@@ -80,6 +97,9 @@ impl WasmHttpLogicImpl {
                 }
             }
             None => {
+                if let Err(err) = self.check_url(&url) {
+                    return Some(Err(err));
+                }
                 let http = self.io.http.clone();
                 let task = self
                     .io
@@ -112,6 +132,9 @@ impl WasmHttpLogicImpl {
                 }
             }
             None => {
+                if let Err(err) = self.check_url(&url) {
+                    return Some(Err(err));
+                }
                 let http = self.io.http.clone();
                 let task = self
                     .io
@@ -127,9 +150,9 @@ impl WasmHttpLogicImpl {
 pub struct WasmHttpLogic(pub Arc<Mutex<SendOption<WasmHttpLogicImpl>>>);
 
 impl WasmHttpLogic {
-    pub fn new(io: Io) -> Self {
+    pub fn new(io: Io, manifest: WasmManifest) -> Self {
         Self(Arc::new(Mutex::new(SendOption::new(Some(
-            WasmHttpLogicImpl::new(io),
+            WasmHttpLogicImpl::new(io, manifest),
         )))))
     }
 
@@ -245,3 +268,157 @@ impl WasmHttpLogic {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{
+        path::{Path, PathBuf},
+        sync::Arc,
+        time::Duration,
+    };
+
+    use base::hash::Hash;
+    use base_io::io::Io;
+    use base_io_traits::{
+        fs_traits::{
+            FileSystemEntryTy, FileSystemInterface, FileSystemPath, FileSystemWatcherItemInterface,
+            HashMap,
+        },
+        http_traits::{HttpClientInterface, HttpError, HttpHeaderValue},
+    };
+    use bytes::Bytes;
+    use url::Url;
+    use wasm_runtime_types::manifest::WasmManifest;
+
+    use super::WasmHttpLogicImpl;
+
+    #[derive(Debug)]
+    struct NoFs;
+
+    #[async_trait::async_trait]
+    impl FileSystemInterface for NoFs {
+        async fn read_file(&self, _file_path: &Path) -> std::io::Result<Vec<u8>> {
+            unimplemented!()
+        }
+        async fn read_file_in(
+            &self,
+            _file_path: &Path,
+            _path: FileSystemPath,
+        ) -> std::io::Result<Vec<u8>> {
+            unimplemented!()
+        }
+        async fn file_exists(&self, _file_path: &Path) -> bool {
+            unimplemented!()
+        }
+        async fn write_file(&self, _file_path: &Path, _data: Vec<u8>) -> std::io::Result<()> {
+            unimplemented!()
+        }
+        async fn create_dir(&self, _dir_path: &Path) -> std::io::Result<()> {
+            unimplemented!()
+        }
+        async fn entries_in_dir(
+            &self,
+            _path: &Path,
+        ) -> anyhow::Result<HashMap<String, FileSystemEntryTy>> {
+            unimplemented!()
+        }
+        async fn files_in_dir_recursive(
+            &self,
+            _path: &Path,
+        ) -> anyhow::Result<HashMap<PathBuf, Vec<u8>>> {
+            unimplemented!()
+        }
+        fn get_save_path(&self) -> PathBuf {
+            unimplemented!()
+        }
+        fn get_secure_path(&self) -> PathBuf {
+            unimplemented!()
+        }
+        fn get_cache_path(&self) -> PathBuf {
+            unimplemented!()
+        }
+        fn watch_for_change(
+            &self,
+            _path: &Path,
+            _file: Option<&Path>,
+        ) -> Box<dyn FileSystemWatcherItemInterface> {
+            unimplemented!()
+        }
+    }
+
+    /// Answers every request with its url.
+    #[derive(Debug)]
+    struct EchoHttp;
+
+    #[async_trait::async_trait]
+    impl HttpClientInterface for EchoHttp {
+        async fn download_text(&self, url: Url) -> Result<String, HttpError> {
+            Ok(url.to_string())
+        }
+        async fn download_binary_secure(&self, url: Url) -> Result<Bytes, HttpError> {
+            Ok(Bytes::from(url.to_string()))
+        }
+        async fn download_binary(&self, url: Url, _hash: &Hash) -> Result<Bytes, HttpError> {
+            Ok(Bytes::from(url.to_string()))
+        }
+        async fn post_json(&self, url: Url, _data: Vec<u8>) -> Result<Vec<u8>, HttpError> {
+            Ok(url.to_string().into_bytes())
+        }
+        async fn custom_request(
+            &self,
+            url: Url,
+            _headers: Vec<HttpHeaderValue>,
+            _content: Option<Vec<u8>>,
+        ) -> Result<Bytes, HttpError> {
+            Ok(Bytes::from(url.to_string()))
+        }
+    }
+
+    /// Polls the call until its task finished.
+    fn wait<T>(mut call: impl FnMut() -> Option<T>) -> T {
+        loop {
+            if let Some(res) = call() {
+                return res;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn hosts_not_in_manifest_are_denied() {
+        let logic = WasmHttpLogicImpl::new(
+            Io::new(|_| Arc::new(NoFs), Arc::new(EchoHttp)),
+            WasmManifest {
+                http_hosts: vec!["ddnet.org".into()],
+                ..Default::default()
+            },
+        );
+
+        for url in [
+            "https://example.com/",
+            "https://ddnet.org.example.com/",
+            "https://sub.ddnet.org/",
+        ] {
+            let url = Url::parse(url).unwrap();
+            assert!(matches!(
+                logic.download_text(0, url.clone()),
+                Some(Err(HttpError::Other(_)))
+            ));
+            assert!(matches!(
+                logic.download_binary(0, url.clone(), Hash::default()),
+                Some(Err(HttpError::Other(_)))
+            ));
+            assert!(matches!(
+                logic.post_json(0, url, vec![]),
+                Some(Err(HttpError::Other(_)))
+            ));
+        }
+
+        let url = Url::parse("https://ddnet.org/status").unwrap();
+        assert!(logic.download_text(0, url.clone()).is_none());
+        let text = wait(|| logic.download_text(0, url.clone())).unwrap();
+        assert_eq!(text, "https://ddnet.org/status");
+        assert!(logic.post_json(0, url.clone(), vec![]).is_none());
+        assert!(wait(|| logic.post_json(0, url.clone(), vec![])).is_ok());
+    }
+}
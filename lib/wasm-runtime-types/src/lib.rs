@@ -5,6 +5,8 @@ use sendable::SendOption;
 use serde::{de::DeserializeOwned, Serialize};
 use wasmer::{AsStoreRef, Instance, Memory, StoreMut, StoreRef, TypedFunction};
 
+pub mod manifest;
+
 #[derive(Debug, Clone, Copy)]
 pub enum MemoryLimit {
     OneMebiByte,
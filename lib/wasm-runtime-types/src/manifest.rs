@@ -0,0 +1,217 @@
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use wasmer::wasmparser::{Parser, Payload};
+
+/// Allows everything, e.g. every host or every database kind.
+pub const MANIFEST_WILDCARD: &str = "*";
+
+/// The capabilities a WASM module requests from the host.
+///
+/// A manifest is either embedded into the module as custom section
+/// named [`WasmManifest::SECTION_NAME`] or shipped next to the module
+/// as `<name>.manifest.json`, where the latter wins.
+/// The module only gets the requested capabilities that the user or server
+/// operator granted in their config, see [`WasmManifest::granted`].
+/// Everything else is denied.
+///
+/// Modules can embed their manifest using:
+/// ```ignore
+/// #[used]
+/// #[link_section = "ddnet_manifest"]
+/// static MANIFEST: [u8; include_bytes!("../manifest.json").len()] =
+///     *include_bytes!("../manifest.json");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WasmManifest {
+    /// Hosts the module is allowed to send http requests to, e.g. `ddnet.org`.
+    /// A leading `*.` allows all subdomains.
+    pub http_hosts: Vec<String>,
+    /// Path prefixes, relative to the data directory,
+    /// the module is allowed to read and write.
+    pub fs_paths: Vec<PathBuf>,
+    /// Database kinds the module is allowed to use, e.g. `sqlite`.
+    pub db_kinds: Vec<String>,
+}
+
+impl WasmManifest {
+    pub const SECTION_NAME: &'static str = "ddnet_manifest";
+
+    /// A manifest that allows everything, for trusted modules.
+    pub fn unrestricted() -> Self {
+        Self {
+            http_hosts: vec![MANIFEST_WILDCARD.to_string()],
+            fs_paths: vec![MANIFEST_WILDCARD.into()],
+            db_kinds: vec![MANIFEST_WILDCARD.to_string()],
+        }
+    }
+
+    /// Reads the manifest embedded into the WASM module, if any.
+    pub fn from_module(wasm_bytes: &[u8]) -> anyhow::Result<Option<Self>> {
+        for payload in Parser::new(0).parse_all(wasm_bytes) {
+            if let Payload::CustomSection(section) = payload? {
+                if section.name() == Self::SECTION_NAME {
+                    return Ok(Some(serde_json::from_slice(section.data())?));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn from_json(json: &[u8]) -> anyhow::Result<Self> {
+        Ok(serde_json::from_slice(json)?)
+    }
+
+    /// The requested capabilities that are covered by the given grants.
+    ///
+    /// A wildcard request is only kept if the grants contain the wildcard, too,
+    /// and `*.` domains only if they are granted by a wider domain.
+    pub fn granted(&self, grants: &WasmManifest) -> Self {
+        Self {
+            http_hosts: self
+                .http_hosts
+                .iter()
+                .filter(|host| grants.allows_host(host))
+                .cloned()
+                .collect(),
+            fs_paths: self
+                .fs_paths
+                .iter()
+                .filter(|path| grants.allows_path(path))
+                .cloned()
+                .collect(),
+            db_kinds: self
+                .db_kinds
+                .iter()
+                .filter(|kind| grants.allows_db_kind(kind))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    pub fn allows_host(&self, host: &str) -> bool {
+        self.http_hosts.iter().any(|allowed| {
+            if allowed == MANIFEST_WILDCARD {
+                true
+            } else if let Some(domain) = allowed.strip_prefix("*.") {
+                host.strip_suffix(domain)
+                    .is_some_and(|subdomain| subdomain.ends_with('.'))
+            } else {
+                allowed.eq_ignore_ascii_case(host)
+            }
+        })
+    }
+
+    /// Paths must be relative and must not leave their prefix,
+    /// e.g. by using `..`.
+    pub fn allows_path(&self, path: &Path) -> bool {
+        let is_contained = path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        is_contained
+            && self.fs_paths.iter().any(|allowed| {
+                allowed.as_os_str() == MANIFEST_WILDCARD || path.starts_with(allowed)
+            })
+    }
+
+    pub fn allows_db_kind(&self, kind: &str) -> bool {
+        self.db_kinds
+            .iter()
+            .any(|allowed| allowed == MANIFEST_WILDCARD || allowed.eq_ignore_ascii_case(kind))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::WasmManifest;
+
+    #[test]
+    fn manifest_checks() {
+        let manifest = WasmManifest {
+            http_hosts: vec!["ddnet.org".into(), "*.example.com".into()],
+            fs_paths: vec!["mods/my_mod".into()],
+            db_kinds: vec!["sqlite".into()],
+        };
+
+        assert!(manifest.allows_host("ddnet.org"));
+        assert!(manifest.allows_host("api.example.com"));
+        assert!(!manifest.allows_host("example.com"));
+        assert!(!manifest.allows_host("evilexample.com"));
+        assert!(!manifest.allows_host("ddnet.org.evil.com"));
+
+        assert!(manifest.allows_path(Path::new("mods/my_mod/save.json")));
+        assert!(!manifest.allows_path(Path::new("mods/my_mod/../other/save.json")));
+        assert!(!manifest.allows_path(Path::new("/mods/my_mod/save.json")));
+        assert!(!manifest.allows_path(Path::new("mods/my_mod_other/save.json")));
+
+        assert!(manifest.allows_db_kind("sqlite"));
+        assert!(!manifest.allows_db_kind("mysql"));
+
+        let manifest = WasmManifest::default();
+        assert!(!manifest.allows_host("ddnet.org"));
+        assert!(!manifest.allows_path(Path::new("mods")));
+        assert!(!manifest.allows_db_kind("sqlite"));
+
+        let manifest = WasmManifest::unrestricted();
+        assert!(manifest.allows_host("ddnet.org"));
+        assert!(manifest.allows_path(Path::new("mods")));
+        assert!(!manifest.allows_path(Path::new("../mods")));
+        assert!(manifest.allows_db_kind("postgres"));
+    }
+
+    #[test]
+    fn requests_are_only_granted_by_config() {
+        let requested = WasmManifest::unrestricted();
+        assert_eq!(
+            requested.granted(&WasmManifest::default()),
+            WasmManifest::default()
+        );
+        assert_eq!(
+            requested.granted(&WasmManifest {
+                http_hosts: vec!["ddnet.org".into()],
+                fs_paths: vec!["mods".into()],
+                db_kinds: vec!["sqlite".into()],
+            }),
+            WasmManifest::default()
+        );
+        assert_eq!(
+            requested.granted(&WasmManifest::unrestricted()),
+            WasmManifest::unrestricted()
+        );
+
+        let requested = WasmManifest {
+            http_hosts: vec![
+                "ddnet.org".into(),
+                "*.example.com".into(),
+                "other.com".into(),
+            ],
+            fs_paths: vec!["mods/my_mod".into(), "config".into()],
+            db_kinds: vec!["sqlite".into(), "mysql".into()],
+        };
+        let granted = requested.granted(&WasmManifest {
+            http_hosts: vec!["ddnet.org".into(), "*.com".into()],
+            fs_paths: vec!["mods".into()],
+            db_kinds: vec!["*".into()],
+        });
+        assert_eq!(
+            granted,
+            WasmManifest {
+                http_hosts: vec![
+                    "ddnet.org".into(),
+                    "*.example.com".into(),
+                    "other.com".into()
+                ],
+                fs_paths: vec!["mods/my_mod".into()],
+                db_kinds: vec!["sqlite".into(), "mysql".into()],
+            }
+        );
+        let granted = requested.granted(&WasmManifest {
+            http_hosts: vec!["example.com".into(), "api.example.com".into()],
+            ..Default::default()
+        });
+        assert!(granted.http_hosts.is_empty());
+    }
+}
@@ -1,17 +1,26 @@
-use std::{cell::RefCell, sync::Arc};
+use std::{cell::RefCell, sync::Arc, time::Instant};
 
 use arrayvec::ArrayVec;
+use limits::LimitingTunables;
+pub use limits::{WasmCallError, WasmLimits};
 use serde::{de::DeserializeOwned, Serialize};
-pub use wasm_runtime_types::MemoryLimit;
+pub use wasm_runtime_types::{manifest::WasmManifest, MemoryLimit};
 use wasm_runtime_types::{
     read_global, read_global_location, read_param, write_global, InstanceData, RawBytesEnv,
 };
 use wasmer::{
     imports,
-    sys::{EngineBuilder, Features},
-    AsStoreMut, AsStoreRef, Cranelift, Function, FunctionEnv, FunctionEnvMut, Imports, Instance,
-    Module, Store, TypedFunction,
+    sys::{BaseTunables, EngineBuilder, Features, NativeEngineExt, Target},
+    wasmparser::Operator,
+    AsStoreMut, AsStoreRef, CompilerConfig, Cranelift, Engine, Function, FunctionEnv,
+    FunctionEnvMut, Imports, Instance, Module, RuntimeError, Store, TypedFunction,
 };
+use wasmer_middlewares::{
+    metering::{get_remaining_points, set_remaining_points, MeteringPoints},
+    Metering,
+};
+
+pub mod limits;
 
 /// Creates a WASM instance, automatically uses and fills the cache.
 ///
@@ -24,6 +33,10 @@ pub struct WasmManager {
 
     instance_data: InstanceData,
     prepare_param_func: TypedFunction<(u32, u32), ()>,
+
+    limits: WasmLimits,
+    /// The error of the last call that failed.
+    last_error: RefCell<Option<WasmCallError>>,
 }
 
 pub enum WasmManagerModuleType<'a, F: FnOnce(&Store) -> anyhow::Result<Module>> {
@@ -32,30 +45,48 @@ pub enum WasmManagerModuleType<'a, F: FnOnce(&Store) -> anyhow::Result<Module>>
 }
 
 impl WasmManager {
-    fn get_store() -> Store {
+    fn get_store(limits: &WasmLimits) -> Store {
         let mut compiler = Cranelift::new();
         compiler.opt_level(wasmer::CraneliftOptLevel::Speed);
-        Store::new(EngineBuilder::new(compiler).set_features(Some(Features {
-            simd: true,
-            threads: false,
-            ..Default::default()
-        })))
+        // metering slows down the module, so only modules with a fuel limit are metered.
+        if let Some(fuel) = limits.effective_fuel_per_call() {
+            compiler.push_middleware(Arc::new(Metering::new(fuel, |_: &Operator| -> u64 { 1 })));
+        }
+        let mut engine: Engine = EngineBuilder::new(compiler)
+            .set_features(Some(Features {
+                simd: true,
+                threads: false,
+                ..Default::default()
+            }))
+            .engine();
+        if let Some(max_memory_bytes) = limits.max_memory_bytes {
+            engine.set_tunables(LimitingTunables::new(
+                BaseTunables::for_target(&Target::default()),
+                max_memory_bytes,
+            ));
+        }
+        Store::new(engine)
     }
 
-    pub fn compile_module(wasm_bytes: &[u8]) -> anyhow::Result<Module> {
-        Ok(Module::new(&Self::get_store(), wasm_bytes)?)
+    /// Compiles a module for the given limits.
+    ///
+    /// A module compiled without [`WasmLimits::effective_fuel_per_call`] can not be
+    /// instantiated with it and vice versa, because metering is compiled into the module.
+    pub fn compile_module(wasm_bytes: &[u8], limits: &WasmLimits) -> anyhow::Result<Module> {
+        Ok(Module::new(&Self::get_store(limits), wasm_bytes)?)
     }
 
     pub fn new<F, FM>(
         wasm_module: WasmManagerModuleType<FM>,
         create_imports: F,
         memory_read_limit: MemoryLimit,
+        limits: WasmLimits,
     ) -> anyhow::Result<Self>
     where
         F: FnOnce(&mut Store, &FunctionEnv<Arc<RawBytesEnv>>) -> Option<Imports>,
         FM: FnOnce(&Store) -> anyhow::Result<Module>,
     {
-        let mut store = Self::get_store();
+        let mut store = Self::get_store(&limits);
         // We then use our store and Wasm bytes to compile a `Module`.
         // A `Module` is a compiled WebAssembly module that isn't ready to execute yet.
         let module = match wasm_module {
@@ -165,11 +196,59 @@ impl WasmManager {
             instance,
             raw_bytes,
             guest_raw_bytes: Default::default(),
+
+            limits,
+            last_error: Default::default(),
         };
         res.run_by_name::<()>("api_setup")?;
         Ok(res)
     }
 
+    /// Calls into the module while respecting the limits.
+    fn call<R>(
+        &self,
+        func: impl FnOnce(&mut Store) -> Result<R, RuntimeError>,
+    ) -> Result<R, WasmCallError> {
+        let mut store = self.store.borrow_mut();
+        let fuel = self.limits.effective_fuel_per_call();
+        if let Some(fuel) = fuel {
+            set_remaining_points(&mut *store, &self.instance, fuel);
+        }
+        let start = Instant::now();
+        let res = func(&mut *store);
+        let elapsed = start.elapsed();
+
+        let res = match res {
+            Ok(res) => Ok(res),
+            Err(err) => Err(match fuel {
+                Some(fuel)
+                    if matches!(
+                        get_remaining_points(&mut *store, &self.instance),
+                        MeteringPoints::Exhausted
+                    ) =>
+                {
+                    match (self.limits.fuel_per_call, self.limits.call_time_budget) {
+                        (Some(limit), _) if limit == fuel => WasmCallError::FuelExhausted { limit },
+                        // the fuel of the time budget ran out first
+                        (_, Some(budget)) => WasmCallError::TimeBudgetExceeded { budget, elapsed },
+                        _ => WasmCallError::FuelExhausted { limit: fuel },
+                    }
+                }
+                _ => WasmCallError::Trap(err.to_string()),
+            }),
+        }
+        .and_then(|res| match self.limits.call_time_budget {
+            Some(budget) if elapsed > budget => {
+                Err(WasmCallError::TimeBudgetExceeded { budget, elapsed })
+            }
+            _ => Ok(res),
+        });
+        if let Err(err) = &res {
+            *self.last_error.borrow_mut() = Some(err.clone());
+        }
+        res
+    }
+
     pub fn run_by_name<R>(&self, name: &str) -> anyhow::Result<R>
     where
         R: wasmer::WasmTypeList,
@@ -179,14 +258,20 @@ impl WasmManager {
             .instance
             .exports
             .get_typed_function(&self.store.borrow(), name)?;
-        Ok(run_func.call(&mut self.store.borrow_mut())?)
+        Ok(self.call(|store| run_func.call(store))?)
     }
 
     pub fn run_by_ref(&self, func: &TypedFunction<(), ()>) -> anyhow::Result<()> {
-        func.call(&mut self.store.borrow_mut())?;
+        self.call(|store| func.call(store))?;
         Ok(())
     }
 
+    /// The error of the last call into the module that failed,
+    /// e.g. because it exceeded its [`WasmLimits`].
+    pub fn last_error(&self) -> Option<WasmCallError> {
+        self.last_error.borrow().clone()
+    }
+
     pub fn run_func_by_name(&self, name: &str) -> TypedFunction<(), ()> {
         self.instance
             .exports
@@ -267,13 +352,11 @@ impl WasmManager {
                 raw_bytes.len() as i32,
             );
         } else {
-            self.prepare_param_func
-                .call(
-                    &mut self.store.borrow_mut(),
-                    param_index as u32,
-                    raw_bytes.len() as u32,
-                )
-                .unwrap();
+            self.call(|store| {
+                self.prepare_param_func
+                    .call(store, param_index as u32, raw_bytes.len() as u32)
+            })
+            .unwrap();
         }
 
         let ptr = read_global(
@@ -288,3 +371,140 @@ impl WasmManager {
         mem_view.write(ptr as u64, raw_bytes.as_slice()).unwrap();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use wasmer::Module;
+
+    use std::time::Duration;
+
+    use crate::{MemoryLimit, WasmCallError, WasmLimits, WasmManager, WasmManagerModuleType};
+
+    /// A module with the minimal api the runtime expects and
+    /// a few functions that use resources.
+    fn test_module(min_pages: u32) -> String {
+        let params: String = (0..10)
+            .map(|i| {
+                let ptr = 8 + i * 12;
+                format!(
+                    r#"(global (export "PARAM{i}_PTR") i32 (i32.const {}))
+                    (global (export "PARAM{i}_SIZE") i32 (i32.const {}))
+                    (global (export "PARAM{i}_ALLOC_SIZE") i32 (i32.const {}))
+                    "#,
+                    ptr,
+                    ptr + 4,
+                    ptr + 8
+                )
+            })
+            .collect();
+        format!(
+            r#"(module
+                (memory (export "memory") {min_pages})
+                (global (export "RESULT_PTR") i32 (i32.const 0))
+                (global (export "RESULT_SIZE") i32 (i32.const 4))
+                {params}
+                (func (export "prepare_result") (param i32))
+                (func (export "prepare_param") (param i32 i32))
+                (func (export "api_setup"))
+                (func (export "spin") (loop $spin (br $spin)))
+                (func (export "grow") (result i32) (memory.grow (i32.const 1)))
+            )"#
+        )
+    }
+
+    fn new_manager(min_pages: u32, limits: WasmLimits) -> anyhow::Result<WasmManager> {
+        let wasm_bytes = wat::parse_str(test_module(min_pages))?;
+        WasmManager::new(
+            WasmManagerModuleType::FromClosure(|store| Ok(Module::new(store, &wasm_bytes)?)),
+            |_, _| None,
+            MemoryLimit::OneMebiByte,
+            limits,
+        )
+    }
+
+    #[test]
+    fn fuel_limit() {
+        let manager = new_manager(
+            1,
+            WasmLimits {
+                fuel_per_call: Some(10_000),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(manager.last_error().is_none());
+        assert!(manager.run_by_name::<()>("spin").is_err());
+        assert!(matches!(
+            manager.last_error(),
+            Some(WasmCallError::FuelExhausted { limit: 10_000 })
+        ));
+    }
+
+    #[test]
+    fn time_budget() {
+        let budget = Duration::from_millis(10);
+        let manager = new_manager(
+            1,
+            WasmLimits {
+                call_time_budget: Some(budget),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        // stopped while running, even without an explicit fuel limit
+        assert!(manager.run_by_name::<()>("spin").is_err());
+        assert!(matches!(
+            manager.last_error(),
+            Some(WasmCallError::TimeBudgetExceeded { budget: b, .. }) if b == budget
+        ));
+
+        // the smaller limit is reported
+        let manager = new_manager(
+            1,
+            WasmLimits {
+                fuel_per_call: Some(10_000),
+                call_time_budget: Some(budget),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(manager.run_by_name::<()>("spin").is_err());
+        assert!(matches!(
+            manager.last_error(),
+            Some(WasmCallError::FuelExhausted { limit: 10_000 })
+        ));
+    }
+
+    #[test]
+    fn memory_limit() {
+        let page_size = wasmer::WASM_PAGE_SIZE as u64;
+        let manager = new_manager(
+            1,
+            WasmLimits {
+                max_memory_bytes: Some(2 * page_size),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(manager.run_by_name::<i32>("grow").unwrap(), 1);
+        // growing fails without a trap, like in any other out of memory situation
+        assert_eq!(manager.run_by_name::<i32>("grow").unwrap(), -1);
+
+        // a module that requires more than the limit is not instantiated at all
+        assert!(new_manager(
+            3,
+            WasmLimits {
+                max_memory_bytes: Some(2 * page_size),
+                ..Default::default()
+            },
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn unlimited() {
+        let manager = new_manager(1, WasmLimits::default()).unwrap();
+        assert_eq!(manager.run_by_name::<i32>("grow").unwrap(), 1);
+        assert!(manager.last_error().is_none());
+    }
+}
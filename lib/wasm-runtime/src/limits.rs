@@ -0,0 +1,158 @@
+use std::{ptr::NonNull, time::Duration};
+
+use wasmer::{
+    sys::{BaseTunables, Tunables},
+    vm::{
+        MemoryError, MemoryStyle, TableStyle, VMMemory, VMMemoryDefinition, VMTable,
+        VMTableDefinition,
+    },
+    MemoryType, Pages, TableType,
+};
+
+/// Roughly the number of wasm instructions a module executes per millisecond,
+/// used to stop calls that exceed their time budget while they run.
+pub const FUEL_PER_MS: u64 = 1_000_000;
+
+/// The resources a WASM module may use.
+///
+/// `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WasmLimits {
+    /// The maximum number of instructions a single call into
+    /// the module may execute.
+    pub fuel_per_call: Option<u64>,
+    /// The maximum size the linear memory of the module may grow to.
+    pub max_memory_bytes: Option<u64>,
+    /// The maximum time a single call into the module may take.
+    ///
+    /// A running call can not be interrupted by the host, so the budget
+    /// is additionally converted to fuel (see [`FUEL_PER_MS`]), which
+    /// stops the call while it runs. The actual time is checked after the call returned.
+    pub call_time_budget: Option<Duration>,
+}
+
+impl WasmLimits {
+    /// The limits of modules the client runs, e.g. the render mod of a server.
+    ///
+    /// They are not configurable, so they are generous and
+    /// only stop modules that hang or allocate endless memory.
+    pub fn client() -> Self {
+        Self {
+            fuel_per_call: None,
+            max_memory_bytes: Some(1024 * 1024 * 1024),
+            call_time_budget: Some(Duration::from_secs(5)),
+        }
+    }
+
+    /// The fuel a single call may use, the smaller one of
+    /// [`Self::fuel_per_call`] and the fuel of [`Self::call_time_budget`].
+    pub fn effective_fuel_per_call(&self) -> Option<u64> {
+        let budget_fuel = self.call_time_budget.map(|budget| {
+            u64::try_from(budget.as_millis())
+                .unwrap_or(u64::MAX)
+                .saturating_mul(FUEL_PER_MS)
+        });
+        match (self.fuel_per_call, budget_fuel) {
+            (Some(fuel), Some(budget_fuel)) => Some(fuel.min(budget_fuel)),
+            (fuel, budget_fuel) => fuel.or(budget_fuel),
+        }
+    }
+}
+
+/// A call into a WASM module failed.
+///
+/// The module might be in an inconsistent state afterwards and
+/// should not be called again.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum WasmCallError {
+    #[error("the module executed more than {limit} instructions in a single call")]
+    FuelExhausted { limit: u64 },
+    #[error("the call took {elapsed:?}, but only {budget:?} are allowed")]
+    TimeBudgetExceeded { budget: Duration, elapsed: Duration },
+    /// The module trapped, e.g. because it could not grow its memory.
+    #[error("the module trapped: {0}")]
+    Trap(String),
+}
+
+/// Limits the maximum size of the linear memories
+/// a module can create or grow to.
+pub struct LimitingTunables {
+    limit: Pages,
+    base: BaseTunables,
+}
+
+impl LimitingTunables {
+    pub fn new(base: BaseTunables, max_memory_bytes: u64) -> Self {
+        let limit = (max_memory_bytes / wasmer::WASM_PAGE_SIZE as u64).min(u32::MAX as u64);
+        Self {
+            limit: Pages(limit as u32),
+            base,
+        }
+    }
+
+    /// Memories without a maximum get the limit as maximum.
+    fn adjust_memory(&self, requested: &MemoryType) -> MemoryType {
+        let mut adjusted = *requested;
+        if adjusted.maximum.is_none_or(|maximum| maximum > self.limit) {
+            adjusted.maximum = Some(self.limit);
+        }
+        adjusted
+    }
+
+    fn validate_memory(&self, ty: &MemoryType) -> Result<(), MemoryError> {
+        if ty.minimum > self.limit {
+            return Err(MemoryError::Generic(format!(
+                "the module requires at least {} pages of memory, but only {} are allowed",
+                ty.minimum.0, self.limit.0
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Tunables for LimitingTunables {
+    // the memory style is not adjusted, so compiled modules do not
+    // depend on the limit.
+    fn memory_style(&self, memory: &MemoryType) -> MemoryStyle {
+        self.base.memory_style(memory)
+    }
+
+    fn table_style(&self, table: &TableType) -> TableStyle {
+        self.base.table_style(table)
+    }
+
+    fn create_host_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+    ) -> Result<VMMemory, MemoryError> {
+        let adjusted = self.adjust_memory(ty);
+        self.validate_memory(&adjusted)?;
+        self.base.create_host_memory(&adjusted, style)
+    }
+
+    unsafe fn create_vm_memory(
+        &self,
+        ty: &MemoryType,
+        style: &MemoryStyle,
+        vm_definition_location: NonNull<VMMemoryDefinition>,
+    ) -> Result<VMMemory, MemoryError> {
+        let adjusted = self.adjust_memory(ty);
+        self.validate_memory(&adjusted)?;
+        self.base
+            .create_vm_memory(&adjusted, style, vm_definition_location)
+    }
+
+    fn create_host_table(&self, ty: &TableType, style: &TableStyle) -> Result<VMTable, String> {
+        self.base.create_host_table(ty, style)
+    }
+
+    unsafe fn create_vm_table(
+        &self,
+        ty: &TableType,
+        style: &TableStyle,
+        vm_definition_location: NonNull<VMTableDefinition>,
+    ) -> Result<VMTable, String> {
+        self.base.create_vm_table(ty, style, vm_definition_location)
+    }
+}
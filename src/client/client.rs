@@ -12,7 +12,10 @@ use base::{
 use base_fs::filesys::FileSystem;
 
 use base_http::http::HttpClient;
-use base_io::io::{Io, IoFileSys};
+use base_io::{
+    io::{Io, IoFileSys},
+    runtime::IoRuntimeTask,
+};
 use binds::binds::BindActionsHotkey;
 use client_accounts::accounts::{Accounts, AccountsLoading};
 use client_console::console::{
@@ -115,7 +118,10 @@ use native::{
         NativeWindowOptions, PhysicalKey, PhysicalSize, WindowEvent,
     },
 };
-use network::network::types::NetworkInOrderChannel;
+use network::network::{
+    ping::{ping_servers, PingOptions},
+    types::NetworkInOrderChannel,
+};
 use pool::{
     datatypes::{PoolFxLinkedHashMap, StringPool},
     pool::Pool,
@@ -168,6 +174,9 @@ use super::{
 
 type UiManager = UiManagerBase<Config>;
 
+/// How often a server of the server browser is pinged at most.
+const SERVER_PING_INTERVAL: Duration = Duration::from_secs(60);
+
 pub fn ddnet_main(
     start_arguments: Vec<String>,
     sys: System,
@@ -289,6 +298,7 @@ struct ClientNativeImpl {
     player_settings_sync: PlayerSettingsSync,
     raw_input_info: RawInputInfo,
     browser_data: ServerBrowserData,
+    server_ping_task: Option<IoRuntimeTask<Vec<(SocketAddr, Option<Duration>)>>>,
 
     scene: SceneObject,
 
@@ -461,6 +471,37 @@ impl ClientNativeImpl {
             .multi_sampling(self.config.engine.gl.msaa_samples);
     }
 
+    /// Pings the servers of the server browser while no game is active,
+    /// every server is pinged once per [`SERVER_PING_INTERVAL`] at most.
+    fn update_server_pings(&mut self) {
+        if let Some(task) = &self.server_ping_task {
+            if !task.is_finished() {
+                return;
+            }
+            match self.server_ping_task.take().unwrap().get_storage() {
+                Ok(pings) => self.browser_data.set_pings(pings, self.cur_time),
+                Err(err) => log::info!("failed to ping servers: {err}"),
+            }
+        }
+
+        if !matches!(self.game, Game::None) {
+            return;
+        }
+        let addresses = self
+            .browser_data
+            .start_pings(self.cur_time, SERVER_PING_INTERVAL);
+        if addresses.is_empty() {
+            return;
+        }
+        self.server_ping_task = Some(self.io.rt.spawn(async move {
+            let pings = ping_servers(&addresses, PingOptions::default()).await?;
+            Ok(addresses
+                .into_iter()
+                .map(|addr| (addr, pings.get(&addr).copied()))
+                .collect())
+        }));
+    }
+
     fn render_menu_background_map(&mut self) {
         if let Some(map) = self.menu_map.continue_loading() {
             let intra_tick_time = self.sys.time_get();
@@ -1943,6 +1984,15 @@ impl ClientNativeImpl {
             &self.io,
             GameConnect {
                 rcon_secret,
+                password: self
+                    .config
+                    .game
+                    .cl
+                    .server_passwords
+                    .get(&addr.to_string())
+                    .filter(|password| !password.is_empty())
+                    .cloned(),
+                master_servers: self.config.game.cl.master_servers.clone(),
                 addr,
                 mode: self.connect_info.clone(),
                 server_cert,
@@ -2535,6 +2585,7 @@ impl FromNativeLoadingImpl<ClientNativeLoadingImpl> for ClientNativeImpl {
             raw_input_info,
             spatial_chat: spatial_chat::SpatialChat::new(spatial_chat),
             browser_data,
+            server_ping_task: None,
 
             scene,
 
@@ -2674,6 +2725,8 @@ impl FromNativeImpl for ClientNativeImpl {
             &self.local_console.entries,
         );
 
+        self.update_server_pings();
+
         GameEventsClient::update(&mut GameEventPipeline {
             game: &mut self.game,
             msgs: &mut GameMsgPipeline {
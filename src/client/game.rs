@@ -145,6 +145,7 @@ impl Game {
         let server_cert = connect.server_cert.clone();
         let http = io.http.clone();
         let addr = connect.addr;
+        let master_servers = connect.master_servers.clone();
         let server_cert_verify_task = io.rt.spawn(async move {
             // if list didn't refresh for over an hour, do it now
             let outdated = servers.time.is_none_or(|server_time| {
@@ -165,7 +166,7 @@ impl Game {
                     Err(anyhow!("Server was not found in the server list"))
                 }
             } else if should_check {
-                let servers = MainMenuUi::download_server_list(&http).await?;
+                let servers = MainMenuUi::download_server_list(&http, &master_servers).await?;
                 let server = servers
                     .iter()
                     .find(|server| {
@@ -736,6 +737,12 @@ impl Game {
         game_server_info: &GameServerInfo,
         spatial_chat_scene: &SceneObject,
     ) {
+        // notices are also shown while loading, e.g. when the server reloads the game
+        if let ServerToClientMessage::Notice(notice) = &msg {
+            pipe.notifications
+                .add_warn(notice.to_string(), Duration::from_secs(10));
+        }
+
         let mut selfi = Self::None;
         std::mem::swap(&mut selfi, self);
         let mut is_waiting = matches!(&selfi, Game::WaitingForFirstSnapshot(_));
@@ -817,6 +817,9 @@ impl ActiveGame {
                     .client_info
                     .set_local_player_count(self.game_data.local.expected_local_players.len());
             }
+            ServerToClientMessage::Notice(_) => {
+                // shown regardless of the state of the game
+            }
         }
     }
 }
@@ -75,6 +75,10 @@ impl Drop for DisconnectAutoCleanup {
 
 pub struct GameConnect {
     pub rcon_secret: Option<[u8; 32]>,
+    /// The remembered password of the server, if any.
+    pub password: Option<String>,
+    /// Master servers used to verify the server's certificate.
+    pub master_servers: Vec<String>,
     pub addr: SocketAddr,
     pub mode: ConnectMode,
     pub server_cert: ServerCertMode,
@@ -2,7 +2,9 @@ use std::time::Duration;
 
 use chrono::DateTime;
 use client_ui::{connect::user_data::ConnectModes, ingame_menu::server_info::GameServerInfo};
-use game_network::game_event_generator::GameEvents;
+use game_base::network::messages::MsgClPassword;
+use game_config::config::MAX_SERVER_PASSWORD_LEN;
+use game_network::{game_event_generator::GameEvents, messages::ClientToServerMessage};
 use math::math::vector::luffixed;
 
 use network::network::event::{
@@ -55,21 +57,60 @@ impl GameEventsClient {
             for (_, timestamp, event) in events {
                 match event {
                     GameEvents::NetworkEvent(net_ev) => match net_ev {
-                        NetworkEvent::Connected { .. } => {}
+                        NetworkEvent::Connected { .. } => {
+                            if let Game::Connecting(game) = pipe.game {
+                                match game
+                                    .connect
+                                    .password
+                                    .as_deref()
+                                    .map(|password| password.try_into())
+                                    .transpose()
+                                {
+                                    Ok(password) => {
+                                        game.network.send_unordered_to_server(
+                                            &ClientToServerMessage::Password(MsgClPassword {
+                                                password,
+                                            }),
+                                        );
+                                    }
+                                    Err(_) => {
+                                        game.connect.mode.set(ConnectModes::ConnectingErr {
+                                            msg: format!(
+                                                "the password is longer than \
+                                                {MAX_SERVER_PASSWORD_LEN} characters"
+                                            ),
+                                        });
+                                    }
+                                }
+                            }
+                        }
                         NetworkEvent::Disconnected(reason) => {
                             if matches!(reason, NetworkEventDisconnect::Graceful) {
                                 pipe.msgs.config.ui.path.route("");
                             } else {
-                                let connect_info = match pipe.game {
+                                let connect = match pipe.game {
                                     Game::None | Game::Err(_) => None,
-                                    Game::PrepareConnect(game) => Some(&game.connect.mode),
-                                    Game::Connecting(game) => Some(&game.connect.mode),
-                                    Game::Loading(game) => Some(&game.connect.mode),
-                                    Game::WaitingForFirstSnapshot(game) => Some(&game.connect.mode),
-                                    Game::Active(game) => Some(&game.connect.mode),
+                                    Game::PrepareConnect(game) => Some(&game.connect),
+                                    Game::Connecting(game) => Some(&game.connect),
+                                    Game::Loading(game) => Some(&game.connect),
+                                    Game::WaitingForFirstSnapshot(game) => Some(&game.connect),
+                                    Game::Active(game) => Some(&game.connect),
                                 };
-                                if let Some(connect_info) = connect_info {
-                                    connect_info.set(ConnectModes::DisconnectErr {
+                                if let Some(connect) = connect.filter(|_| {
+                                    matches!(
+                                        reason,
+                                        NetworkEventDisconnect::ConnectionClosed(
+                                            NetworkEventConnectingClosed::WrongPassword
+                                        )
+                                    )
+                                }) {
+                                    // let the user enter the password & try again
+                                    connect.mode.set(ConnectModes::Password {
+                                        addr: connect.addr,
+                                        wrong: connect.password.is_some(),
+                                    });
+                                } else if let Some(connect) = connect {
+                                    connect.mode.set(ConnectModes::DisconnectErr {
                                         msg: match reason {
                                             NetworkEventDisconnect::ConnectionClosed(
                                                 NetworkEventConnectingClosed::Banned(ban),
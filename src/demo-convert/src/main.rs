@@ -0,0 +1,125 @@
+use std::{
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::anyhow;
+use base::hash::fmt_hash;
+use base_fs::filesys::FileSystem;
+use base_http::http::HttpClient;
+use base_io::io::Io;
+use clap::Parser;
+use demo_convert_lib::legacy_to_new::legacy_demo_to_new;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// file name of the legacy demo (.demo) to convert. The demo must contain the map it was recorded on.
+    file: String,
+    /// output path (directory), the converted demo, map and map resources are written to it
+    output: String,
+    /// optimize PNGs with oxipng (default: on)
+    #[arg(short, long, default_value_t = true, action = clap::ArgAction::Set)]
+    optimize: bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let io = Io::new(
+        |rt| {
+            Arc::new(
+                FileSystem::new(rt, "org", "", "DDNet-Rs-Alpha", "DDNet-Accounts")
+                    .expect("demo-convert needs the data directory for the legacy map resources."),
+            )
+        },
+        Arc::new(HttpClient::new()),
+    );
+
+    let thread_pool = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(
+                std::thread::available_parallelism()
+                    .unwrap_or(NonZeroUsize::new(2).unwrap())
+                    .get(),
+            )
+            .build()?,
+    );
+
+    let file_path: &Path = args.file.as_ref();
+    let file_path = file_path.to_path_buf();
+    let fs = io.fs.clone();
+    let demo_file = io
+        .rt
+        .spawn(async move {
+            fs.read_file(&file_path)
+                .await
+                .map_err(|err| anyhow!("loading demo file failed: {err}"))
+        })
+        .get_storage()?;
+
+    let output = legacy_demo_to_new(&demo_file, &io, &thread_pool, args.optimize)?;
+
+    let file_path: &Path = args.file.as_ref();
+    let demo_name = file_path
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .unwrap_or("legacy")
+        .to_string();
+    let fs = io.fs.clone();
+    let output_dir = args.output.clone();
+    io.rt
+        .spawn(async move {
+            fs.create_dir(output_dir.as_ref()).await?;
+            // write demo
+            let mut demo_path = PathBuf::from(&output_dir);
+            demo_path.push("demos/");
+            fs.create_dir(&demo_path).await?;
+            demo_path.push(format!("{demo_name}.twdemo"));
+            fs.write_file(&demo_path, output.demo).await?;
+
+            // write map, the demo player looks it up by name & hash
+            let mut map_path = PathBuf::from(&output_dir);
+            map_path.push("downloaded/map/maps/");
+            fs.create_dir(&map_path).await?;
+            map_path.push(format!(
+                "{}_{}.twmap",
+                output.map_name.as_str(),
+                fmt_hash(&output.map_hash)
+            ));
+            fs.write_file(&map_path, output.map_file).await?;
+
+            // write resources
+            let mut res_path = PathBuf::from(&output_dir);
+            res_path.push("downloaded/map/resources/images/");
+            fs.create_dir(&res_path).await?;
+            for (blake3_hash, image) in output.map.resources.images.into_iter() {
+                let mut res_path = res_path.clone();
+                res_path.push(format!(
+                    "{}_{}.{}",
+                    image.name,
+                    fmt_hash(&blake3_hash),
+                    image.ty
+                ));
+                fs.write_file(&res_path, image.buf).await?;
+            }
+
+            let mut res_path = PathBuf::from(&output_dir);
+            res_path.push("downloaded/map/resources/sounds/");
+            fs.create_dir(&res_path).await?;
+            for (blake3_hash, sound) in output.map.resources.sounds.into_iter() {
+                let mut res_path = res_path.clone();
+                res_path.push(format!(
+                    "{}_{}.{}",
+                    sound.name,
+                    fmt_hash(&blake3_hash),
+                    sound.ty
+                ));
+                fs.write_file(&res_path, sound.buf).await?;
+            }
+
+            Ok(())
+        })
+        .get_storage()
+}
@@ -0,0 +1,135 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use demo::{editor::DemoEditor, utils::demo_len};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    cmd: Command,
+}
+
+/// All ticks are relative to the first tick of the demo.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Prints the header information and markers of a demo.
+    Info {
+        /// file name of the demo.
+        file: PathBuf,
+    },
+    /// Only keeps the given tick range of a demo.
+    Cut {
+        /// file name of the demo.
+        file: PathBuf,
+        /// output file.
+        output: PathBuf,
+        /// first tick to keep, defaults to the beginning of the demo.
+        #[arg(long)]
+        from: Option<u64>,
+        /// last tick to keep, defaults to the end of the demo.
+        #[arg(long)]
+        to: Option<u64>,
+    },
+    /// Concatenates demos recorded on the same map with the same modification.
+    Merge {
+        /// output file.
+        output: PathBuf,
+        /// file names of the demos, in the order they should be played.
+        #[arg(required = true, num_args = 2..)]
+        files: Vec<PathBuf>,
+    },
+    /// Adds a marker to a demo.
+    AddMarker {
+        /// file name of the demo.
+        file: PathBuf,
+        tick: u64,
+        /// optional output file, else the demo is overwritten.
+        output: Option<PathBuf>,
+    },
+    /// Removes a marker from a demo.
+    RemoveMarker {
+        /// file name of the demo.
+        file: PathBuf,
+        tick: u64,
+        /// optional output file, else the demo is overwritten.
+        output: Option<PathBuf>,
+    },
+}
+
+fn load(file: &PathBuf) -> anyhow::Result<DemoEditor> {
+    DemoEditor::from_file(&std::fs::read(file)?)
+}
+
+fn save(demo: &DemoEditor, file: &PathBuf) -> anyhow::Result<()> {
+    Ok(std::fs::write(file, demo.to_file()?)?)
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    match args.cmd {
+        Command::Info { file } => {
+            let demo = load(&file)?;
+            let first_tick = demo.first_tick().unwrap_or_default();
+            let last_tick = demo.last_tick().unwrap_or_default();
+            let ticks_per_second = demo.header_ext.ticks_per_second.get();
+            println!("map: {}", demo.header_ext.map.as_str());
+            println!("ticks per second: {ticks_per_second}");
+            println!(
+                "length: {:?} ({} ticks)",
+                demo_len(first_tick, last_tick, ticks_per_second),
+                last_tick - first_tick
+            );
+            println!("snapshots: {}", demo.snapshots.len());
+            for marker in demo.markers() {
+                println!(
+                    "marker at tick {} ({:?})",
+                    marker - first_tick,
+                    demo_len(first_tick, marker, ticks_per_second)
+                );
+            }
+        }
+        Command::Cut {
+            file,
+            output,
+            from,
+            to,
+        } => {
+            let mut demo = load(&file)?;
+            let first_tick = demo.first_tick().unwrap_or_default();
+            let from = first_tick + from.unwrap_or_default();
+            let to = to.map(|to| first_tick + to).unwrap_or(u64::MAX);
+            demo.cut(from..=to)?;
+            save(&demo, &output)?;
+        }
+        Command::Merge { output, files } => {
+            let mut files = files.iter();
+            let mut demo = load(files.next().unwrap())?;
+            for file in files {
+                demo.append(load(file)?)
+                    .map_err(|err| anyhow::anyhow!("{}: {err}", file.display()))?;
+            }
+            save(&demo, &output)?;
+        }
+        Command::AddMarker { file, tick, output } => {
+            let mut demo = load(&file)?;
+            let first_tick = demo.first_tick().unwrap_or_default();
+            if !demo.add_marker(first_tick + tick) {
+                println!("there already is a marker at tick {tick}");
+            }
+            save(&demo, output.as_ref().unwrap_or(&file))?;
+        }
+        Command::RemoveMarker { file, tick, output } => {
+            let mut demo = load(&file)?;
+            let first_tick = demo.first_tick().unwrap_or_default();
+            anyhow::ensure!(
+                demo.remove_marker(first_tick + tick),
+                "there is no marker at tick {tick}"
+            );
+            save(&demo, output.as_ref().unwrap_or(&file))?;
+        }
+    }
+
+    Ok(())
+}
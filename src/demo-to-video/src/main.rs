@@ -0,0 +1,212 @@
+use std::{num::NonZeroUsize, path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::anyhow;
+use base::system::System;
+use base_fs::filesys::FileSystem;
+use base_http::http::HttpClient;
+use base_io::io::{Io, IoFileSys};
+use clap::Parser;
+use client_demo::{DemoVideoEncodeProperties, DemoViewer, DemoViewerSettings, EncoderSettings};
+use config::config::{ConfigDebug, ConfigSound};
+use game_config::config::ConfigDemoRecorder;
+use graphics::graphics::graphics::Graphics;
+use graphics_backend::{
+    backend::{
+        GraphicsBackend, GraphicsBackendBase, GraphicsBackendIoLoading, GraphicsBackendLoading,
+    },
+    window::{BackendRawDisplayHandle, BackendWindow},
+};
+use graphics_backend_traits::traits::GraphicsBackendInterface;
+use graphics_types::types::WindowProps;
+use sound::sound::SoundManager;
+use sound_backend::sound_backend::SoundBackend;
+use ui_base::{
+    font_data::{UiFontData, UiFontDataLoading},
+    ui::UiCreator,
+};
+
+/// The size of the (invisible) window, the video is rendered
+/// to an offscreen canvas of its own size.
+const WINDOW_SIZE: u32 = 64;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// file name of the demo to render.
+    demo: PathBuf,
+    /// output file of the video.
+    /// Without ffmpeg support, or if the extension is `y4m`,
+    /// an uncompressed `.y4m` video & `.wav` audio file are written.
+    output: PathBuf,
+    /// json file containing the recorder config (`cl.recorder`),
+    /// defaults to the one of the client's config.
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// overwrites the width of the video.
+    #[arg(long)]
+    width: Option<u32>,
+    /// overwrites the height of the video.
+    #[arg(long)]
+    height: Option<u32>,
+    /// overwrites the frames per second of the video.
+    #[arg(long)]
+    fps: Option<u32>,
+}
+
+fn main() -> anyhow::Result<()> {
+    if std::env::var("RUST_LOG").is_err() {
+        unsafe { std::env::set_var("RUST_LOG", "info") };
+    }
+    env_logger::init();
+    let args = Args::parse();
+
+    let sys = System::new();
+    let io = IoFileSys::new(|rt| {
+        Arc::new(
+            FileSystem::new(rt, "org", "", "DDNet-Rs-Alpha", "DDNet-Accounts")
+                .expect("most like you are missing a data directory"),
+        )
+    });
+    let config_engine = config_fs::load(&io);
+    let config_game = game_config_fs::fs::load(&io);
+    let io = Io::from(io, Arc::new(HttpClient::new()));
+
+    let mut recorder: ConfigDemoRecorder = match &args.config {
+        Some(path) => serde_json::from_slice(&std::fs::read(path)?)?,
+        None => config_game.cl.recorder.clone(),
+    };
+    recorder.width = args.width.unwrap_or(recorder.width);
+    recorder.height = args.height.unwrap_or(recorder.height);
+    recorder.fps = args.fps.unwrap_or(recorder.fps);
+
+    let thread_pool = Arc::new(
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(
+                std::thread::available_parallelism()
+                    .unwrap_or(NonZeroUsize::new(2).unwrap())
+                    .get(),
+            )
+            .build()?,
+    );
+
+    // headless graphics backend, nothing is ever presented
+    let io_loading = GraphicsBackendIoLoading::new(&config_engine.gfx, &io.clone().into());
+    let backend_loading = GraphicsBackendLoading::new(
+        &config_engine.gfx,
+        &ConfigDebug::default(),
+        &config_engine.gl,
+        BackendRawDisplayHandle::Headless,
+        None,
+        io.clone().into(),
+    )?;
+    let (backend_base, stream_data) = GraphicsBackendBase::new(
+        io_loading,
+        backend_loading,
+        &thread_pool,
+        BackendWindow::Headless {
+            width: WINDOW_SIZE,
+            height: WINDOW_SIZE,
+        },
+    )?;
+    let backend = GraphicsBackend::new(backend_base);
+    let graphics = Graphics::new(
+        backend.clone(),
+        stream_data,
+        WindowProps {
+            canvas_width: WINDOW_SIZE as f64,
+            canvas_height: WINDOW_SIZE as f64,
+            window_width: WINDOW_SIZE,
+            window_height: WINDOW_SIZE,
+        },
+    );
+
+    // sounds are rendered offair, but without any sound device
+    // the backend falls back to the null backend & the video is silent.
+    let sound_backend = SoundBackend::new(&ConfigSound {
+        limits: config_engine.snd.limits,
+        ..Default::default()
+    })?;
+    let sound = SoundManager::new(sound_backend.clone())?;
+
+    let font_data = UiFontData::new(UiFontDataLoading::new(&io))?.into_font_definitions();
+    let mut ui_creator = UiCreator::default();
+    ui_creator.load_font(&font_data);
+
+    let demo_name = args
+        .demo
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .unwrap_or("demo")
+        .to_string();
+    let mut demo_player = DemoViewer::new_from_file(
+        &io,
+        &thread_pool,
+        demo_name.into(),
+        font_data,
+        Some(DemoVideoEncodeProperties {
+            file_name: std::path::absolute(&args.output)?,
+            pixels_per_point: recorder.pixels_per_point,
+            encoder_settings: EncoderSettings {
+                fps: recorder.fps,
+                width: recorder.width,
+                height: recorder.height,
+                hw_accel: recorder.hw_accel.clone(),
+                max_threads: std::thread::available_parallelism()
+                    .map(|v| v.get() + 2)
+                    .unwrap_or_default()
+                    .max(2) as u64,
+                sample_rate: recorder.sample_rate,
+                crf: recorder.crf,
+            },
+            settings: DemoViewerSettings {
+                global_sound_volume: recorder.global_sound_volume,
+                render: recorder.render.clone(),
+                snd: recorder.snd.clone(),
+            },
+        }),
+        std::fs::read(&args.demo)?,
+    );
+
+    log::info!("loading demo & map");
+    loop {
+        if let Some(demo_viewer) = demo_player.try_get_mut() {
+            demo_viewer.render(
+                Default::default(),
+                &recorder.render,
+                &recorder.snd,
+                recorder.global_sound_volume,
+            )?;
+            if demo_viewer.is_closed() {
+                break;
+            }
+        } else {
+            demo_player
+                .continue_loading(
+                    &sound,
+                    &graphics,
+                    &backend,
+                    &sound_backend,
+                    &config_engine,
+                    &config_game,
+                    &sys,
+                    &ui_creator,
+                )
+                .map_err(|err| anyhow!("loading the demo failed: {err}"))?;
+            if demo_player.try_get().is_some() {
+                log::info!("rendering the demo to {}", args.output.display());
+            } else {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
+
+        sound.swap();
+        graphics.swap();
+    }
+
+    // make sure all frames reached the encoder before it is dropped
+    backend.wait_idle()?;
+    drop(demo_player);
+    log::info!("finished rendering the demo");
+
+    Ok(())
+}
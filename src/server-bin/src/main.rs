@@ -4,12 +4,20 @@ use base::system::System;
 use clap::{arg, command, Command};
 use game_config::config::ConfigGame;
 use network::network::utils::create_certifified_keys;
-use server::server::ddnet_server_main;
+use server::server::{ddnet_server_db_migrate, ddnet_server_main, DbMigrateAction};
 use game_base::local_server_info::LocalServerInfo;
 
 fn main() {
     let matches = command!()
         .subcommand(Command::new("default_config").about("Print the default config"))
+        .subcommand(
+            Command::new("db_migrate")
+                .about("Show, apply or revert the schema migrations of the game mod")
+                .arg(arg!(<action> "status, up or down").value_parser(["status", "up", "down"]))
+                .arg(arg!([migrations] "down: the name of the migrations to revert"))
+                .arg(arg!([database] "down: the database type, e.g. sqlite"))
+                .arg(arg!(--confirm "down: revert the migration, which might delete data")),
+        )
         .arg(
             arg!(-c --config <cfg> "A relative path to a config file, used instead of cfg_game.json."),
         )
@@ -34,6 +42,35 @@ fn main() {
 
     let cfg_game = matches.get_one::<String>("config");
 
+    if let Some(("db_migrate", db_migrate)) = matches.subcommand() {
+        let action = match db_migrate.get_one::<String>("action").unwrap().as_str() {
+            "status" => DbMigrateAction::Status,
+            "up" => DbMigrateAction::Up,
+            _ => {
+                let (Some(migrations), Some(database)) = (
+                    db_migrate.get_one::<String>("migrations"),
+                    db_migrate.get_one::<String>("database"),
+                ) else {
+                    eprintln!("db_migrate down requires the migrations and the database");
+                    std::process::exit(1);
+                };
+                DbMigrateAction::Down {
+                    migrations: migrations.clone(),
+                    database: database.clone(),
+                    confirmed: db_migrate.get_flag("confirm"),
+                }
+            }
+        };
+        match ddnet_server_db_migrate(cfg_game.map(|p| p.as_ref()), action.clone()) {
+            Ok(res) => println!("{res}"),
+            Err(err) => {
+                eprintln!("db_migrate {action} failed: {err}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     let cert = create_certifified_keys();
 
     let server_is_open = Arc::new(AtomicBool::new(true));
@@ -93,6 +93,7 @@ pub fn test_hud(
                 health: all,
                 armor: all,
                 ammo_of_weapon: if all > 10 { None } else { Some(all) },
+                race_split: None,
             });
             render(
                 &local_player_info,
@@ -108,6 +109,7 @@ pub fn test_hud(
         health: u32::MAX,
         armor: u32::MAX,
         ammo_of_weapon: Some(u32::MAX),
+        race_split: None,
     });
     render(
         &local_player_info,
@@ -159,6 +161,7 @@ pub fn test_hud(
             } else {
                 Some((counter % u8::MAX as u32) as u8)
             },
+            race_split: None,
         });
         for w in WeaponType::iter() {
             render(
@@ -191,6 +194,7 @@ pub fn test_hud(
         team0_mode: true,
         can_collide: false,
         checkpoint: Some(u8::MAX),
+        race_split: None,
     });
     render(
         &local_player_info,
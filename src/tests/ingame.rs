@@ -92,6 +92,7 @@ pub fn test_ingame(
                 debuffs: PoolFxLinkedHashMap::new_without_pool(),
                 animation_ticks_passed: 0,
                 game_ticks_passed: 0,
+                race_ticks_passed: None,
                 emoticon: None,
                 phased: false,
             },
@@ -120,6 +121,8 @@ pub fn test_ingame(
                 flags: &LinkedHashMap::default(),
                 lasers: &LinkedHashMap::default(),
                 pickups: &LinkedHashMap::default(),
+                doors: &[],
+                switches: &LinkedHashMap::default(),
                 ctf_container: &mut containers.ctf_container,
                 game_container: &mut containers.game_container,
                 ninja_container: &mut containers.ninja_container,
@@ -246,6 +249,7 @@ pub fn test_ingame_skins(
                 debuffs: PoolFxLinkedHashMap::new_without_pool(),
                 animation_ticks_passed: 0,
                 game_ticks_passed: 0,
+                race_ticks_passed: None,
                 emoticon: None,
                 phased: false,
             },